@@ -14,14 +14,18 @@
 use crate::mmtk::MMTKBuilder;
 use crate::mmtk::MMTK;
 use crate::plan::AllocationSemantics;
+use crate::plan::AllocationSite;
+use crate::plan::BarrierFastPath;
 use crate::plan::{Mutator, MutatorContext};
 use crate::scheduler::WorkBucketStage;
 use crate::scheduler::{GCController, GCWork, GCWorker};
-use crate::util::alloc::allocators::AllocatorSelector;
+use crate::util::alloc::allocators::{AllocatorSelector, Allocators};
+use crate::util::alloc::AllocatorFastPath;
 use crate::util::constants::{LOG_BYTES_IN_PAGE, MIN_OBJECT_SIZE};
 use crate::util::heap::layout::vm_layout_constants::HEAP_END;
 use crate::util::heap::layout::vm_layout_constants::HEAP_START;
 use crate::util::opaque_pointer::*;
+use crate::util::options::NurseryZeroingOptions;
 use crate::util::{Address, ObjectReference};
 use crate::vm::edge_shape::MemorySlice;
 use crate::vm::ReferenceGlue;
@@ -128,10 +132,16 @@ pub fn flush_mutator<VM: VMBinding>(mutator: &mut Mutator<VM>) {
 /// Allocate memory for an object. For performance reasons, a VM should
 /// implement the allocation fast-path on their side rather than just calling this function.
 ///
+/// The alignment can be larger than `VM::MAX_ALIGNMENT` (e.g. a page). Such an object is allocated
+/// in the large object space instead if the padding for the alignment makes it too large for the
+/// allocator of `Default` or `NonMoving`. Note that a moving GC copies objects with the alignment
+/// from [`crate::vm::ObjectModel::get_align_when_copied`], so an object that needs to keep a large
+/// alignment should not be moved.
+///
 /// Arguments:
 /// * `mutator`: The mutator to perform this allocation request.
 /// * `size`: The number of bytes required for the object.
-/// * `align`: Required alignment for the object.
+/// * `align`: Required alignment for the object. This needs to be a power of two.
 /// * `offset`: Offset associated with the alignment.
 /// * `semantics`: The allocation semantic required for the allocation.
 pub fn alloc<VM: VMBinding>(
@@ -151,13 +161,81 @@ pub fn alloc<VM: VMBinding>(
     debug_assert!(size >= MIN_OBJECT_SIZE);
     // Assert alignment
     debug_assert!(align >= VM::MIN_ALIGNMENT);
-    debug_assert!(align <= VM::MAX_ALIGNMENT);
+    debug_assert!(align.is_power_of_two());
     // Assert offset
     debug_assert!(VM::USE_ALLOCATION_OFFSET || offset == 0);
 
     mutator.alloc(size, align, offset, semantics)
 }
 
+/// Allocate memory for an object from an allocation site. This is the same as [`alloc`], except
+/// that generational plans use the site to pretenure objects.
+///
+/// A generational plan samples the objects allocated with this function, and tracks the survival
+/// rate of their sites in nursery GCs (see [`get_allocation_site_survival_rate`]). Once a site is
+/// pretenured (see [`is_allocation_site_pretenured`]), its `Default` objects are allocated in the
+/// mature space directly, and are not copied out of the nursery. A binding that allocates in its
+/// own fast-path should call this function for the pretenured sites instead. A pretenured object is
+/// a mature object. [`post_alloc`] remembers it in the write barrier, so the binding may initialize
+/// its fields without the write barrier as long as no GC happens in between. The later stores
+/// need the write barrier.
+///
+/// Arguments:
+/// * `mutator`: The mutator to perform this allocation request.
+/// * `size`: The number of bytes required for the object.
+/// * `align`: Required alignment for the object. This needs to be a power of two.
+/// * `offset`: Offset associated with the alignment.
+/// * `semantics`: The allocation semantic required for the allocation.
+/// * `site`: The allocation site of the object.
+pub fn alloc_with_site<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+    size: usize,
+    align: usize,
+    offset: isize,
+    semantics: AllocationSemantics,
+    site: AllocationSite,
+) -> Address {
+    debug_assert!(size >= MIN_OBJECT_SIZE);
+    debug_assert!(align >= VM::MIN_ALIGNMENT);
+    debug_assert!(align.is_power_of_two());
+    debug_assert!(VM::USE_ALLOCATION_OFFSET || offset == 0);
+
+    mutator.alloc_with_site(size, align, offset, semantics, site)
+}
+
+/// Allocate memory for a number of objects of the same size, alignment and offset. This is faster
+/// than calling [`alloc`] for each object, as the objects are bump allocated in one go if the
+/// allocator for the semantics has a thread local buffer. The objects are not necessarily contiguous,
+/// as the allocation may continue in a new thread local buffer.
+///
+/// The returned vector has fewer than `count` addresses only if the allocation failed (see [`alloc`]).
+///
+/// Arguments:
+/// * `mutator`: The mutator to perform this allocation request.
+/// * `size`: The number of bytes required for each object.
+/// * `align`: Required alignment for the objects.
+/// * `offset`: Offset associated with the alignment.
+/// * `semantics`: The allocation semantic required for the allocation.
+/// * `count`: The number of objects to allocate.
+pub fn alloc_many<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+    size: usize,
+    align: usize,
+    offset: isize,
+    semantics: AllocationSemantics,
+    count: usize,
+) -> Vec<Address> {
+    // See the assertions in alloc().
+    debug_assert!(size >= MIN_OBJECT_SIZE);
+    debug_assert!(align >= VM::MIN_ALIGNMENT);
+    debug_assert!(align.is_power_of_two());
+    debug_assert!(VM::USE_ALLOCATION_OFFSET || offset == 0);
+
+    let mut result = Vec::with_capacity(count);
+    mutator.alloc_many(size, align, offset, semantics, count, &mut result);
+    result
+}
+
 /// Perform post-allocation actions, usually initializing object metadata. For many allocators none are
 /// required. For performance reasons, a VM should implement the post alloc fast-path on their side
 /// rather than just calling this function.
@@ -176,6 +254,224 @@ pub fn post_alloc<VM: VMBinding>(
     mutator.post_alloc(refer, bytes, semantics);
 }
 
+/// Perform post-allocation actions for a number of objects allocated by [`alloc_many`]. This
+/// initializes the object metadata for all the objects in one call. The objects need to be the
+/// result of one `alloc_many` call, in the order it returned them.
+///
+/// Arguments:
+/// * `mutator`: The mutator to perform post-alloc actions.
+/// * `objects`: The newly allocated objects.
+/// * `bytes`: The size of the space allocated for each object (in bytes).
+/// * `semantics`: The allocation semantics used for the allocation.
+pub fn post_alloc_many<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+    objects: &[ObjectReference],
+    bytes: usize,
+    semantics: AllocationSemantics,
+) {
+    mutator.post_alloc_many(objects, bytes, semantics);
+}
+
+/// Return the bytes that a mutator has allocated with the allocation semantics since the mutator
+/// was created. The bytes are counted by the allocators: a bump pointer allocator counts the bytes
+/// it used in a thread local buffer (including the padding for alignment), so the allocation fast
+/// path, including a fast path inlined by the binding, does not pay for the counting. An object that
+/// MMTk redirected to the large object space (e.g. a `NonMoving` object that is too large for the
+/// non-moving space) is counted as `AllocationSemantics::Los`.
+///
+/// This reads the thread local state of the allocators. It must be called by the mutator thread,
+/// or when the mutator is stopped (e.g. in a GC).
+///
+/// Arguments:
+/// * `mutator`: The mutator to query.
+/// * `semantics`: The allocation semantics to query.
+pub fn mutator_allocated_bytes<VM: VMBinding>(
+    mutator: &Mutator<VM>,
+    semantics: AllocationSemantics,
+) -> usize {
+    mutator.allocated_bytes(semantics)
+}
+
+/// Seal the read-only space. The pages of the space are made read-only, so a write to an object in
+/// the space will fault. This is useful for data that should never change after the VM creates it,
+/// such as interned constants and boot image data.
+///
+/// After the space is sealed, an allocation with `AllocationSemantics::ReadOnly` is an error:
+/// MMTk reports it with [`Collection::out_of_memory`](crate::vm::Collection::out_of_memory) and
+/// returns a zero address. A binding that inlines the allocation fast path must not use it for
+/// read-only objects after sealing, as the thread local buffers of the mutators are not
+/// invalidated. The binding should stop allocating read-only objects before calling this.
+///
+/// GCs cannot update the fields of the sealed objects, so they must only refer to objects that are
+/// never moved, such as other read-only objects, or objects in the immortal space, the large
+/// object space or the VM space. Each GC scans the sealed objects as roots, so their referents are
+/// kept alive, and it panics if a referent may be moved. The binding must use the side mark bit
+/// (`ObjectModel::LOCAL_MARK_BIT_SPEC`), as GCs still mark the sealed objects.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+#[cfg(feature = "ro_space")]
+pub fn seal_readonly_space<VM: VMBinding>(mmtk: &MMTK<VM>) {
+    mmtk.plan.base().ro_space.seal();
+}
+
+/// Add a region of memory to the VM space. The VM must have mapped the memory, and populated it with
+/// objects. The objects in the region are immortal and never moved, and GC traces them like the
+/// objects in the immortal space. The region must not overlap with the other regions of the VM
+/// space, or with the address range of the MMTk heap.
+///
+/// MMTk knows the VM space in whole chunks, so [`is_in_mmtk_spaces`] returns true for any address
+/// in the chunks that the region spans. A VM that relies on it to tell its own objects from MMTk
+/// objects should not put its own objects in those chunks, e.g. by aligning the region to chunks.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+/// * `start`: The start address of the region.
+/// * `size`: The size of the region in bytes.
+#[cfg(feature = "vm_space")]
+pub fn register_vm_space_region<VM: VMBinding>(mmtk: &MMTK<VM>, start: Address, size: usize) {
+    mmtk.plan.base().vm_space.add_region(start, size);
+}
+
+/// Map an image file, such as a boot image, and add the mapped memory as a region of the VM space
+/// (see [`register_vm_space_region`]). The file is mapped privately, so the changes to the objects
+/// are not written back to the file. Return the start address and the size of the region.
+///
+/// If `start` is given, the file is mapped at the address, and this returns an error if the memory
+/// is already mapped. Otherwise, the image is relocatable, and the OS chooses the address. The VM
+/// is responsible for relocating the objects in the image in that case.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+/// * `path`: The path of the image file.
+/// * `start`: The address to map the image at, or `None` to let the OS choose it.
+#[cfg(feature = "vm_space")]
+pub fn map_vm_space_image<VM: VMBinding>(
+    mmtk: &MMTK<VM>,
+    path: &std::path::Path,
+    start: Option<Address>,
+) -> std::io::Result<(Address, usize)> {
+    let file = std::fs::File::open(path)?;
+    let size = file.metadata()?.len() as usize;
+    if size == 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "The VM space image is empty",
+        ));
+    }
+    let start = crate::util::memory::mmap_file(&file, start, size)?;
+    mmtk.plan.base().vm_space.add_region(start, size);
+    Ok((start, size))
+}
+
+/// Start writing to `[start, start + bytes)` in a code space (for `AllocationSemantics::Code` and
+/// `AllocationSemantics::LargeCode`), and return the address where the binding writes the byte at
+/// `start`. The pages of the code spaces are executable but not writable. A binding writes code
+/// objects (including their headers after allocation) and patches code at the returned address, and
+/// calls [`code_space_end_write`] when it is done.
+///
+/// On Linux, the memory of the code spaces is also mapped at another address, where it is writable
+/// but not executable, and the returned address is in that mapping. The protection of the pages does
+/// not change, so other threads can run the code while it is written. On the other systems, the
+/// returned address is `start`, and the pages that overlap the range are writable but not executable
+/// until all the threads that write to them call [`code_space_end_write`]. The code in those pages
+/// must not run in between.
+///
+/// The GC does not write to the code objects, and the code objects are not in the nursery. The
+/// binding must use the write barrier when it stores a reference into a code object. On Linux, the
+/// binding reports the reference fields of a code object at their writable addresses in
+/// `Scanning::scan_object()`, so a GC that moves the referents can update the fields. On the other
+/// systems, a code object can only refer to objects that do not move. On architectures that need
+/// it, the binding is responsible for flushing the instruction cache after writing code.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+/// * `start`: The start of the range, which is in the memory allocated to a code space.
+/// * `bytes`: The size of the range.
+#[cfg(feature = "code_space")]
+pub fn code_space_begin_write<VM: VMBinding>(
+    mmtk: &MMTK<VM>,
+    start: Address,
+    bytes: usize,
+) -> Address {
+    let base = mmtk.plan.base();
+    base.code_space
+        .begin_write(start, bytes)
+        .or_else(|| base.code_lo_space.begin_write(start, bytes))
+        .unwrap_or_else(|| panic!("{} ({} bytes) is not in a code space", start, bytes))
+}
+
+/// Finish writing to `[start, start + bytes)` in a code space, which was started by
+/// [`code_space_begin_write`] with the same range.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+/// * `start`: The start of the range.
+/// * `bytes`: The size of the range.
+#[cfg(feature = "code_space")]
+pub fn code_space_end_write<VM: VMBinding>(mmtk: &MMTK<VM>, start: Address, bytes: usize) {
+    use crate::policy::space::Space;
+    let base = mmtk.plan.base();
+    if base.code_space.address_in_space(start) {
+        base.code_space.end_write(start, bytes)
+    } else {
+        base.code_lo_space.end_write(start, bytes)
+    }
+}
+
+/// Resize an object in the large object space to `new_size` bytes. This is useful for growable
+/// arrays and buffers, which would otherwise allocate a new object and copy the contents.
+///
+/// The object is resized in place if possible: it gives up its tail pages when it shrinks, and takes
+/// the free pages right after it when it grows. Otherwise the object is moved to new pages. On Linux,
+/// the pages are moved with `mremap()` instead of copying the bytes. The bytes of the object are
+/// kept, and the grown part is zeroed if the space zeroes new memory.
+///
+/// Return the object after it is resized, which is the same object if it is resized in place.
+/// If the object is moved, the binding must update the references to the object, and the object
+/// is treated as a newly allocated object. Return `None` if the object is not in the large object
+/// space, or it cannot be resized (e.g. a GC is needed, or the object is aligned to more than a
+/// page). The object is not changed in this case, and the binding can allocate a new object and copy
+/// the contents instead.
+///
+/// Arguments:
+/// * `mutator`: The mutator that resizes the object.
+/// * `object`: The object to resize. It must have been allocated with `AllocationSemantics::Los`, or
+///   redirected to the large object space.
+/// * `new_size`: The new size of the object in bytes. The object keeps at least the page where it
+///   starts, even if this is zero.
+pub fn resize_large_object<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+    object: ObjectReference,
+    new_size: usize,
+) -> Option<ObjectReference> {
+    match mutator.config.allocator_mapping[AllocationSemantics::Los] {
+        AllocatorSelector::LargeObject(index) => {
+            unsafe { mutator.allocators.large_object[index as usize].assume_init_mut() }
+                .resize(object, new_size)
+        }
+        // The plan does not use a large object space for the semantics.
+        _ => None,
+    }
+}
+
+/// Free an object in the immortal space, so its memory can be reused. This is useful for immortal
+/// objects that the VM knows to be dead, such as the metadata of an unloaded class. GC never
+/// reclaims immortal objects. The memory of the object is reused for immortal objects after the
+/// next GC.
+///
+/// This is only available with the feature `immortal_free_list`, which allocates immortal objects
+/// with a free list allocator. The binding must not access the object after freeing it.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+/// * `object`: The object to free. It must have been allocated with `AllocationSemantics::Immortal`
+///   in a plan that uses the common plan (all the plans except NoGC).
+#[cfg(feature = "immortal_free_list")]
+pub fn free_immortal<VM: VMBinding>(mmtk: &MMTK<VM>, object: ObjectReference) {
+    mmtk.plan.common().get_immortal().free_object(object)
+}
+
 /// The *subsuming* write barrier by MMTk. For performance reasons, a VM should implement the write barrier
 /// fast-path on their side rather than just calling this function.
 ///
@@ -325,6 +621,113 @@ pub fn memory_region_copy_post<VM: VMBinding>(
     mutator.barrier().memory_region_copy_post(src, dst);
 }
 
+/// The *subsuming* read barrier by MMTk. The binding only needs to call this if the plan selects a barrier
+/// that needs reference load hooks (see [`crate::plan::BarrierSelector::needs_object_reference_read_barrier`]).
+/// For performance reasons, a VM should implement the read barrier fast-path on their side rather than
+/// just calling this function.
+///
+/// For a correct barrier implementation, a VM binding needs to choose one of the following options:
+/// * Use subsuming barrier `object_reference_read`
+/// * Load the reference by itself, and call `object_reference_read_post` with the loaded reference.
+/// * Implement fast-path on the VM side, and do a specialized slow-path call.
+///
+/// Arguments:
+/// * `mutator`: The mutator for the current thread.
+/// * `src`: The source object that holds the field.
+/// * `slot`: The location of the field to be read.
+///
+/// Returns the reference that the mutator should use.
+pub fn object_reference_read<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+    src: ObjectReference,
+    slot: VM::VMEdge,
+) -> ObjectReference {
+    mutator.barrier().object_reference_read(src, slot)
+}
+
+/// The read barrier by MMTk. This is a *post* read barrier, which we expect a binding to call
+/// *after* it loads a reference from an object. For performance reasons, a VM should implement the
+/// read barrier fast-path on their side rather than just calling this function.
+///
+/// Arguments:
+/// * `mutator`: The mutator for the current thread.
+/// * `src`: The source object that holds the field.
+/// * `slot`: The location of the field that was read.
+/// * `target`: The reference loaded from the slot.
+///
+/// Returns the reference that the mutator should use.
+pub fn object_reference_read_post<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+    src: ObjectReference,
+    slot: VM::VMEdge,
+    target: ObjectReference,
+) -> ObjectReference {
+    mutator
+        .barrier()
+        .object_reference_read_post(src, slot, target)
+}
+
+/// The weak reference read barrier by MMTk. The binding needs to call this when the mutator reads the referent
+/// of a weak reference (e.g. `Reference.get()` in Java), if the plan selects a barrier that needs the hook
+/// (see [`crate::plan::BarrierSelector::needs_weak_reference_read_barrier`]). For example, a concurrent marking
+/// plan needs to keep the referent alive if the mutator gets a strong reference to it during marking.
+///
+/// Arguments:
+/// * `mutator`: The mutator for the current thread.
+/// * `referent`: The referent loaded from the weak reference.
+///
+/// Returns the referent that the mutator should use.
+pub fn weak_reference_read<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+    referent: ObjectReference,
+) -> ObjectReference {
+    mutator.barrier().weak_reference_read(referent)
+}
+
+/// Return a description of the write barrier fast-path for the plan, including the metadata layout
+/// for the log bit or the card table. This method is provided so that VM compilers may inline the
+/// barrier fast-path without depending on the side metadata layout in mmtk-core.
+///
+/// Arguments:
+/// * `mmtk`: The reference to an MMTk instance.
+pub fn get_barrier_fast_path<VM: VMBinding>(mmtk: &MMTK<VM>) -> BarrierFastPath {
+    BarrierFastPath::new::<VM>(mmtk.plan.constraints().barrier)
+}
+
+/// Get the fraction of the objects from an allocation site that survived nursery GCs. The
+/// survival rate is estimated with the objects sampled in [`alloc_with_site`]. Return `None` if no
+/// object from the site has been checked in a nursery GC, or the plan is not a generational plan
+/// that tracks allocation sites.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+/// * `site`: The allocation site.
+pub fn get_allocation_site_survival_rate<VM: VMBinding>(
+    mmtk: &MMTK<VM>,
+    site: AllocationSite,
+) -> Option<f64> {
+    mmtk.plan
+        .generational()?
+        .allocation_sites()?
+        .survival_rate(site)
+}
+
+/// Is an allocation site pretenured? The `Default` objects from a pretenured site are allocated in
+/// the mature space by [`alloc_with_site`]. A site is pretenured once its survival rate is higher
+/// than the option `pretenure_threshold`, and it stays pretenured afterwards.
+///
+/// Arguments:
+/// * `mmtk`: A reference to an MMTk instance.
+/// * `site`: The allocation site.
+pub fn is_allocation_site_pretenured<VM: VMBinding>(mmtk: &MMTK<VM>, site: AllocationSite) -> bool {
+    mmtk.plan.generational().map_or(false, |gen| {
+        gen.pretenure_allocator().is_some()
+            && gen
+                .allocation_sites()
+                .map_or(false, |sites| sites.is_pretenured(site))
+    })
+}
+
 /// Return an AllocatorSelector for the given allocation semantic. This method is provided
 /// so that VM compilers may call it to help generate allocation fast-path.
 ///
@@ -338,6 +741,24 @@ pub fn get_allocator_mapping<VM: VMBinding>(
     mmtk.plan.get_allocator_mapping()[semantics]
 }
 
+/// Return a description of the bump pointer allocation fast-path for the given allocation semantic,
+/// including the byte offsets of the cursor and the limit in the mutator. This method is provided so
+/// that VM compilers may inline the allocation fast-path without depending on the field layout of
+/// the allocators. Returns `None` if the allocator for the semantic does not have a fast-path that
+/// can be inlined, in which case the binding should call [`alloc`]. If the option `nursery_zeroing`
+/// is `Temporal`, the returned fast-path needs to zero the objects it allocates.
+///
+/// Arguments:
+/// * `mmtk`: The reference to an MMTk instance.
+/// * `semantics`: The allocation semantic to query.
+pub fn get_allocator_fast_path<VM: VMBinding>(
+    mmtk: &MMTK<VM>,
+    semantics: AllocationSemantics,
+) -> Option<AllocatorFastPath> {
+    let needs_zeroing = *mmtk.options.nursery_zeroing == NurseryZeroingOptions::Temporal;
+    Allocators::<VM>::get_fast_path(get_allocator_mapping(mmtk, semantics), needs_zeroing)
+}
+
 /// The standard malloc. MMTk either uses its own allocator, or forward the call to a
 /// library malloc.
 pub fn malloc(size: usize) -> Address {
@@ -726,7 +1147,9 @@ pub fn harness_begin<VM: VMBinding>(mmtk: &MMTK<VM>, tls: VMMutatorThread) {
 }
 
 /// Generic hook to allow benchmarks to be harnessed. We stop collecting
-/// statistics, and print stats values.
+/// statistics, and print stats values. The stats include the bytes allocated by each
+/// mutator (see [`mutator_allocated_bytes`]) as of the last GC, or as of when the mutator
+/// was destroyed.
 ///
 /// Arguments:
 /// * `mmtk`: A reference to an MMTk instance.
@@ -1,5 +1,8 @@
 //! Read/Write barrier implementations.
 
+use crate::policy::region::HeapRegion;
+use crate::util::linear_scan::Region;
+use crate::util::metadata::side_metadata::SideMetadataSpec;
 use crate::vm::edge_shape::{Edge, MemorySlice};
 use crate::vm::ObjectModel;
 use crate::{
@@ -8,6 +11,8 @@ use crate::{
 };
 use atomic::Ordering;
 use downcast_rs::Downcast;
+use spin::Mutex;
+use std::sync::atomic::AtomicBool;
 
 /// BarrierSelector describes which barrier to use.
 ///
@@ -19,6 +24,23 @@ use downcast_rs::Downcast;
 pub enum BarrierSelector {
     NoBarrier,
     ObjectBarrier,
+    /// An object barrier whose slow-path is invoked *before* the store. The slow-path can still
+    /// observe the old values of the fields of the object. See [`SnapshotObjectBarrier`].
+    SnapshotObjectBarrier,
+    /// A deletion barrier for snapshot-at-the-beginning marking. The slow-path is invoked *before*
+    /// the store when concurrent marking is in progress. The binding also needs to call
+    /// `weak_reference_read` when it reads the referent of a weak reference. See [`SATBBarrier`].
+    SATBBarrier,
+    /// A post-write barrier for region-based plans. The slow-path is invoked *after* the store if the
+    /// new reference points to a different region. See [`RegionBarrier`].
+    RegionBarrier,
+    /// A load barrier. The binding needs to call `object_reference_read` for every reference it
+    /// loads from the heap, and `weak_reference_read` when it reads the referent of a weak reference.
+    /// See [`LoadBarrier`].
+    LoadBarrier,
+    /// A card marking barrier. Every reference store marks the card of the source object in the
+    /// card table as dirty, without any check. See [`CardTableBarrier`].
+    CardTableBarrier,
 }
 
 impl BarrierSelector {
@@ -26,6 +48,138 @@ impl BarrierSelector {
         // cast enum to u8 then compare. Otherwise, we cannot do it in a const fn.
         *self as u8 == other as u8
     }
+
+    /// Does the binding need to call `object_reference_read` (or `object_reference_read_post`) when
+    /// it loads a reference from the heap?
+    pub const fn needs_object_reference_read_barrier(&self) -> bool {
+        self.equals(BarrierSelector::LoadBarrier)
+    }
+
+    /// Does the binding need to call `weak_reference_read` when it reads the referent of a weak
+    /// reference?
+    pub const fn needs_weak_reference_read_barrier(&self) -> bool {
+        self.equals(BarrierSelector::LoadBarrier) || self.equals(BarrierSelector::SATBBarrier)
+    }
+}
+
+/// Describes the fast-path of a barrier, so a VM compiler can inline the fast-path without knowing the
+/// metadata layout in mmtk-core. See [`crate::memory_manager::get_barrier_fast_path`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BarrierFastPath {
+    /// There is no barrier. The binding can store references directly.
+    NoBarrier,
+    /// The fast-path checks the per-object log bit in side metadata, and calls `object_reference_write_slow`
+    /// if the object is unlogged.
+    LogBitOnSide {
+        /// The layout of the log bit.
+        layout: SideMetadataBitLayout,
+        /// The value of the log bit that means the object is unlogged.
+        unlogged_value: u8,
+        /// Should the slow-path be called before the store? Otherwise it is called after the store.
+        before_store: bool,
+    },
+    /// The fast-path checks the per-object log bit in the object header, and calls `object_reference_write_slow`
+    /// if the object is unlogged.
+    LogBitInHeader {
+        /// The bit offset of the log bit from the header address (see [`crate::vm::ObjectModel::ref_to_header`]).
+        bit_offset: isize,
+        /// The number of bits of the log bit.
+        num_of_bits: usize,
+        /// The value of the log bit that means the object is unlogged.
+        unlogged_value: u8,
+        /// Should the slow-path be called before the store? Otherwise it is called after the store.
+        before_store: bool,
+    },
+    /// The fast-path stores `dirty_value` to the card of the source object after the store. There is no slow-path
+    /// for reference stores.
+    CardTable {
+        /// The layout of the card table.
+        layout: SideMetadataBitLayout,
+        /// The value for a dirty card.
+        dirty_value: u8,
+    },
+    /// The fast-path cannot be described by this type. The binding should call the barrier functions in
+    /// [`crate::memory_manager`].
+    Opaque,
+}
+
+/// The layout of a piece of global side metadata for the barrier fast-path. For an address `addr`
+/// (which is `ObjectReference::to_address()` for an object), the metadata byte is at
+/// `base + (addr >> addr_rshift)`, and the metadata value is
+/// `(byte >> bit_shift(addr)) & value_mask`, where
+/// `bit_shift(addr) = ((addr >> log_bytes_in_region) & bit_index_mask) << log_num_of_bits`.
+/// If the metadata takes a whole byte, `bit_index_mask` is 0, and the byte is the value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SideMetadataBitLayout {
+    /// The base address of the side metadata.
+    pub base: Address,
+    /// The right shift from an address to the offset of its metadata byte.
+    pub addr_rshift: usize,
+    /// The log of the number of bytes covered by one metadata value.
+    pub log_bytes_in_region: usize,
+    /// The log of the number of bits of one metadata value.
+    pub log_num_of_bits: usize,
+    /// The mask for the index of the metadata value in the byte.
+    pub bit_index_mask: usize,
+    /// The mask for the metadata value after shifting.
+    pub value_mask: u8,
+}
+
+impl SideMetadataBitLayout {
+    /// Get the layout of a side metadata spec. The spec must be global, and its values must not be
+    /// larger than a byte.
+    pub const fn new(spec: &SideMetadataSpec) -> Self {
+        assert!(spec.is_global);
+        assert!(spec.log_num_of_bits <= constants::LOG_BITS_IN_BYTE as usize);
+        let log_values_in_byte = constants::LOG_BITS_IN_BYTE as usize - spec.log_num_of_bits;
+        Self {
+            base: spec.get_absolute_offset(),
+            addr_rshift: spec.log_bytes_in_region + log_values_in_byte,
+            log_bytes_in_region: spec.log_bytes_in_region,
+            log_num_of_bits: spec.log_num_of_bits,
+            bit_index_mask: (1 << log_values_in_byte) - 1,
+            value_mask: ((1usize << (1 << spec.log_num_of_bits)) - 1) as u8,
+        }
+    }
+
+    /// The address of the metadata byte for the address.
+    pub fn meta_byte_address(&self, addr: Address) -> Address {
+        self.base + (addr >> self.addr_rshift)
+    }
+
+    /// The shift of the metadata value in the metadata byte for the address.
+    pub fn bit_shift(&self, addr: Address) -> usize {
+        ((addr >> self.log_bytes_in_region) & self.bit_index_mask) << self.log_num_of_bits
+    }
+}
+
+impl BarrierFastPath {
+    /// Describe the fast-path of the barrier. The log bit is the global log bit spec of the binding.
+    pub fn new<VM: VMBinding>(selector: BarrierSelector) -> Self {
+        let log_bit = |before_store: bool| match *VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC.as_spec() {
+            MetadataSpec::OnSide(spec) => BarrierFastPath::LogBitOnSide {
+                layout: SideMetadataBitLayout::new(&spec),
+                unlogged_value: 1,
+                before_store,
+            },
+            MetadataSpec::InHeader(spec) => BarrierFastPath::LogBitInHeader {
+                bit_offset: spec.bit_offset,
+                num_of_bits: spec.num_of_bits,
+                unlogged_value: 1,
+                before_store,
+            },
+        };
+        match selector {
+            BarrierSelector::NoBarrier => BarrierFastPath::NoBarrier,
+            BarrierSelector::ObjectBarrier => log_bit(false),
+            BarrierSelector::SnapshotObjectBarrier => log_bit(true),
+            BarrierSelector::CardTableBarrier => BarrierFastPath::CardTable {
+                layout: SideMetadataBitLayout::new(&card_table::CARD_TABLE_SIDE_METADATA_SPEC),
+                dirty_value: card_table::CARD_DIRTY,
+            },
+            _ => BarrierFastPath::Opaque,
+        }
+    }
 }
 
 /// A barrier is a combination of fast-path behaviour + slow-path semantics.
@@ -39,6 +193,11 @@ impl BarrierSelector {
 ///
 /// As a performance optimization, the binding may also choose to port the fast-path to the VM side,
 /// and call the slow-path (`object_reference_write_slow`) only if necessary.
+///
+/// Some barriers also need to be called when references are loaded. The binding only needs to call the
+/// read barrier interfaces (`object_reference_read` and `weak_reference_read`) if the plan selects
+/// such a barrier. See [`BarrierSelector::needs_object_reference_read_barrier`] and
+/// [`BarrierSelector::needs_weak_reference_read_barrier`].
 pub trait Barrier<VM: VMBinding>: 'static + Send + Downcast {
     fn flush(&mut self) {}
 
@@ -94,6 +253,46 @@ pub trait Barrier<VM: VMBinding>: 'static + Send + Downcast {
 
     /// Full post-barrier for array copy
     fn memory_region_copy_post(&mut self, _src: VM::VMMemorySlice, _dst: VM::VMMemorySlice) {}
+
+    /// Subsuming barrier for object reference read. Returns the reference that the mutator should use,
+    /// which may be different from the value in the slot.
+    fn object_reference_read(&mut self, src: ObjectReference, slot: VM::VMEdge) -> ObjectReference {
+        let target = slot.load();
+        self.object_reference_read_post(src, slot, target)
+    }
+
+    /// Full post-barrier for object reference read. `target` is the reference loaded from the slot.
+    /// Returns the reference that the mutator should use.
+    fn object_reference_read_post(
+        &mut self,
+        _src: ObjectReference,
+        _slot: VM::VMEdge,
+        target: ObjectReference,
+    ) -> ObjectReference {
+        target
+    }
+
+    /// Object reference read slow-path call. This is called after the load.
+    /// Returns the reference that the mutator should use.
+    fn object_reference_read_slow(
+        &mut self,
+        _src: ObjectReference,
+        _slot: VM::VMEdge,
+        target: ObjectReference,
+    ) -> ObjectReference {
+        target
+    }
+
+    /// Barrier for reading the referent of a weak reference, such as `Reference.get()` in Java.
+    /// Returns the referent that the mutator should use.
+    fn weak_reference_read(&mut self, referent: ObjectReference) -> ObjectReference {
+        referent
+    }
+
+    /// A barrier indicating that some fields of the object will probably be written without the
+    /// write barrier, e.g. a newly allocated object that is initialized. The barrier remembers the
+    /// whole object. There must be no GC between this call and the writes.
+    fn object_probable_write(&mut self, _obj: ObjectReference) {}
 }
 
 impl_downcast!(Barrier<VM> where VM: VMBinding);
@@ -136,6 +335,27 @@ pub trait BarrierSemantics: 'static + Send {
         src: <Self::VM as VMBinding>::VMMemorySlice,
         dst: <Self::VM as VMBinding>::VMMemorySlice,
     );
+
+    /// Slow-path call for object field read operations. Returns the reference that the mutator
+    /// should use. Only barriers with read hooks call this.
+    fn object_reference_read_slow(
+        &mut self,
+        _src: ObjectReference,
+        _slot: <Self::VM as VMBinding>::VMEdge,
+        target: ObjectReference,
+    ) -> ObjectReference {
+        target
+    }
+
+    /// Slow-path call for reading the referent of a weak reference. Returns the referent that the
+    /// mutator should use. Only barriers with read hooks call this.
+    fn weak_reference_read_slow(&mut self, referent: ObjectReference) -> ObjectReference {
+        referent
+    }
+
+    /// Slow-path call for an object whose fields will probably be written without the write
+    /// barrier. See [`Barrier::object_probable_write`].
+    fn object_probable_write_slow(&mut self, _obj: ObjectReference) {}
 }
 
 /// Generic object barrier with a type argument defining it's slow-path behaviour.
@@ -215,4 +435,360 @@ impl<S: BarrierSemantics> Barrier<S::VM> for ObjectBarrier<S> {
     ) {
         self.semantics.memory_region_copy_slow(src, dst);
     }
+
+    fn object_probable_write(&mut self, obj: ObjectReference) {
+        if self.object_is_unlogged(obj) && self.log_object(obj) {
+            self.semantics.object_probable_write_slow(obj);
+        }
+    }
+}
+
+/// Number of locks used by [`SnapshotObjectBarrier`]. Objects are mapped to the locks by their addresses.
+const SNAPSHOT_LOCKS: usize = 256;
+
+/// Locks that serialize the slow-paths of [`SnapshotObjectBarrier`] for the same object.
+static SNAPSHOT_LOCK_TABLE: [Mutex<()>; SNAPSHOT_LOCKS] = {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const LOCK: Mutex<()> = Mutex::new(());
+    [LOCK; SNAPSHOT_LOCKS]
+};
+
+/// Generic object barrier that invokes its slow-path before the store, with a type argument
+/// defining it's slow-path behaviour.
+///
+/// The first write to an unlogged object calls the slow-path of the barrier semantics before the
+/// object is logged. As the store has not happened yet, the slow-path can take a snapshot of the old
+/// values of the object fields (e.g. for reference counting decrements, or for snapshot-at-the-beginning marking).
+/// Other mutators that write to the same object are blocked until the slow-path finishes,
+/// so no field can be overwritten before it is snapshotted.
+///
+/// A binding that implements the fast-path on its side must call `object_reference_write_slow` *before* the store.
+pub struct SnapshotObjectBarrier<S: BarrierSemantics> {
+    semantics: S,
+}
+
+impl<S: BarrierSemantics> SnapshotObjectBarrier<S> {
+    pub fn new(semantics: S) -> Self {
+        Self { semantics }
+    }
+
+    /// Check if an object is unlogged (i.e. not yet written to since it was unlogged).
+    fn object_is_unlogged(&self, object: ObjectReference) -> bool {
+        S::UNLOG_BIT_SPEC.load_atomic::<S::VM, u8>(object, None, Ordering::SeqCst) != 0
+    }
+
+    /// Get the lock that guards the slow-path for the given object.
+    fn lock_for(object: ObjectReference) -> &'static Mutex<()> {
+        let index = (object.to_address::<S::VM>().as_usize() >> constants::LOG_MIN_OBJECT_SIZE)
+            % SNAPSHOT_LOCKS;
+        &SNAPSHOT_LOCK_TABLE[index]
+    }
+}
+
+impl<S: BarrierSemantics> Barrier<S::VM> for SnapshotObjectBarrier<S> {
+    fn flush(&mut self) {
+        self.semantics.flush();
+    }
+
+    fn object_reference_write_pre(
+        &mut self,
+        src: ObjectReference,
+        slot: <S::VM as VMBinding>::VMEdge,
+        target: ObjectReference,
+    ) {
+        if self.object_is_unlogged(src) {
+            self.object_reference_write_slow(src, slot, target);
+        }
+    }
+
+    fn object_reference_write_slow(
+        &mut self,
+        src: ObjectReference,
+        slot: <S::VM as VMBinding>::VMEdge,
+        target: ObjectReference,
+    ) {
+        let _guard = Self::lock_for(src).lock();
+        // Check again. Another mutator may have logged the object while we were waiting for the lock.
+        if self.object_is_unlogged(src) {
+            self.semantics
+                .object_reference_write_slow(src, slot, target);
+            // Only log the object after the slow-path is done. Other mutators will not bypass the
+            // barrier and write to the object before that.
+            S::UNLOG_BIT_SPEC.store_atomic::<S::VM, u8>(src, 0, None, Ordering::SeqCst);
+        }
+    }
+
+    fn memory_region_copy_pre(
+        &mut self,
+        src: <S::VM as VMBinding>::VMMemorySlice,
+        dst: <S::VM as VMBinding>::VMMemorySlice,
+    ) {
+        self.semantics.memory_region_copy_slow(src, dst);
+    }
+}
+
+/// Generic snapshot-at-the-beginning (deletion) barrier with a type argument defining it's
+/// slow-path behaviour.
+///
+/// When concurrent marking is in progress, the slow-path is called before every reference store and
+/// every array copy, so the barrier semantics can record the references that are about to be
+/// overwritten. The slow-path is also called when the mutator reads the referent of a weak reference,
+/// as the referent may not be reachable in the snapshot. Otherwise the barrier does nothing.
+///
+/// A binding that implements the fast-path on its side must call `object_reference_write_slow` *before* the store.
+pub struct SATBBarrier<S: BarrierSemantics> {
+    semantics: S,
+    /// Is concurrent marking in progress? This is set by the plan in a pause.
+    marking: &'static AtomicBool,
+}
+
+impl<S: BarrierSemantics> SATBBarrier<S> {
+    pub fn new(semantics: S, marking: &'static AtomicBool) -> Self {
+        Self { semantics, marking }
+    }
+
+    fn is_marking(&self) -> bool {
+        // The flag only changes when mutators are stopped.
+        self.marking.load(Ordering::Relaxed)
+    }
+}
+
+impl<S: BarrierSemantics> Barrier<S::VM> for SATBBarrier<S> {
+    fn flush(&mut self) {
+        self.semantics.flush();
+    }
+
+    fn object_reference_write_pre(
+        &mut self,
+        src: ObjectReference,
+        slot: <S::VM as VMBinding>::VMEdge,
+        target: ObjectReference,
+    ) {
+        if self.is_marking() {
+            self.object_reference_write_slow(src, slot, target);
+        }
+    }
+
+    fn object_reference_write_slow(
+        &mut self,
+        src: ObjectReference,
+        slot: <S::VM as VMBinding>::VMEdge,
+        target: ObjectReference,
+    ) {
+        self.semantics
+            .object_reference_write_slow(src, slot, target);
+    }
+
+    fn memory_region_copy_pre(
+        &mut self,
+        src: <S::VM as VMBinding>::VMMemorySlice,
+        dst: <S::VM as VMBinding>::VMMemorySlice,
+    ) {
+        if self.is_marking() {
+            self.semantics.memory_region_copy_slow(src, dst);
+        }
+    }
+
+    fn weak_reference_read(&mut self, referent: ObjectReference) -> ObjectReference {
+        if self.is_marking() && !referent.is_null() {
+            self.semantics.weak_reference_read_slow(referent)
+        } else {
+            referent
+        }
+    }
+}
+
+/// Generic region barrier with a type argument defining it's slow-path behaviour.
+///
+/// The slow-path is called after a reference store if the target is in a different region from
+/// the source object, so the barrier semantics can record the source in the remembered set of the
+/// target region. The slow-path is always called after an array copy.
+///
+/// A binding that implements the fast-path on its side must call `object_reference_write_slow` *after* the store.
+pub struct RegionBarrier<S: BarrierSemantics> {
+    semantics: S,
+}
+
+impl<S: BarrierSemantics> RegionBarrier<S> {
+    pub fn new(semantics: S) -> Self {
+        Self { semantics }
+    }
+
+    /// Does the reference from `src` to `target` cross regions?
+    fn crosses_regions(src: ObjectReference, target: ObjectReference) -> bool {
+        !target.is_null()
+            && HeapRegion::containing::<S::VM>(src) != HeapRegion::containing::<S::VM>(target)
+    }
+}
+
+impl<S: BarrierSemantics> Barrier<S::VM> for RegionBarrier<S> {
+    fn flush(&mut self) {
+        self.semantics.flush();
+    }
+
+    fn object_reference_write_post(
+        &mut self,
+        src: ObjectReference,
+        slot: <S::VM as VMBinding>::VMEdge,
+        target: ObjectReference,
+    ) {
+        if Self::crosses_regions(src, target) {
+            self.object_reference_write_slow(src, slot, target);
+        }
+    }
+
+    fn object_reference_write_slow(
+        &mut self,
+        src: ObjectReference,
+        slot: <S::VM as VMBinding>::VMEdge,
+        target: ObjectReference,
+    ) {
+        self.semantics
+            .object_reference_write_slow(src, slot, target);
+    }
+
+    fn memory_region_copy_post(
+        &mut self,
+        src: <S::VM as VMBinding>::VMMemorySlice,
+        dst: <S::VM as VMBinding>::VMMemorySlice,
+    ) {
+        self.semantics.memory_region_copy_slow(src, dst);
+    }
+}
+
+/// Generic card marking barrier with a type argument defining it's slow-path behaviour.
+///
+/// The fast-path marks the card that covers the source object as dirty after every reference store.
+/// Unlike [`ObjectBarrier`], there is no branch in the fast-path, and no per-object log bit. The
+/// GC finds the objects that may have been modified by scanning the dirty cards
+/// (see [`crate::util::card_table`]). The slow-path is always called after an array copy.
+///
+/// A binding that implements the fast-path on its side must store
+/// [`crate::util::card_table::CARD_DIRTY`] to the card of the source object *after* the store.
+pub struct CardTableBarrier<S: BarrierSemantics> {
+    semantics: S,
+}
+
+impl<S: BarrierSemantics> CardTableBarrier<S> {
+    pub fn new(semantics: S) -> Self {
+        Self { semantics }
+    }
+}
+
+impl<S: BarrierSemantics> Barrier<S::VM> for CardTableBarrier<S> {
+    fn flush(&mut self) {
+        self.semantics.flush();
+    }
+
+    fn object_reference_write_post(
+        &mut self,
+        src: ObjectReference,
+        _slot: <S::VM as VMBinding>::VMEdge,
+        _target: ObjectReference,
+    ) {
+        card_table::mark_card(src.to_address::<S::VM>());
+    }
+
+    fn object_reference_write_slow(
+        &mut self,
+        src: ObjectReference,
+        slot: <S::VM as VMBinding>::VMEdge,
+        target: ObjectReference,
+    ) {
+        self.semantics
+            .object_reference_write_slow(src, slot, target);
+    }
+
+    fn memory_region_copy_post(
+        &mut self,
+        src: <S::VM as VMBinding>::VMMemorySlice,
+        dst: <S::VM as VMBinding>::VMMemorySlice,
+    ) {
+        self.semantics.memory_region_copy_slow(src, dst);
+    }
+
+    fn object_probable_write(&mut self, obj: ObjectReference) {
+        card_table::mark_card(obj.to_address::<S::VM>());
+    }
+}
+
+/// Generic load barrier with a type argument defining it's slow-path behaviour.
+///
+/// The slow-path is called after every non-null reference load, and every read of the referent of a
+/// weak reference. The barrier semantics may return a different reference for the mutator to use,
+/// e.g. the new copy of an object that a concurrent copying collector has moved.
+///
+/// A binding that implements the fast-path on its side must call `object_reference_read_slow` *after* the load.
+pub struct LoadBarrier<S: BarrierSemantics> {
+    semantics: S,
+}
+
+impl<S: BarrierSemantics> LoadBarrier<S> {
+    pub fn new(semantics: S) -> Self {
+        Self { semantics }
+    }
+}
+
+impl<S: BarrierSemantics> Barrier<S::VM> for LoadBarrier<S> {
+    fn flush(&mut self) {
+        self.semantics.flush();
+    }
+
+    fn object_reference_read_post(
+        &mut self,
+        src: ObjectReference,
+        slot: <S::VM as VMBinding>::VMEdge,
+        target: ObjectReference,
+    ) -> ObjectReference {
+        if target.is_null() {
+            target
+        } else {
+            self.object_reference_read_slow(src, slot, target)
+        }
+    }
+
+    fn object_reference_read_slow(
+        &mut self,
+        src: ObjectReference,
+        slot: <S::VM as VMBinding>::VMEdge,
+        target: ObjectReference,
+    ) -> ObjectReference {
+        self.semantics.object_reference_read_slow(src, slot, target)
+    }
+
+    fn weak_reference_read(&mut self, referent: ObjectReference) -> ObjectReference {
+        if referent.is_null() {
+            referent
+        } else {
+            self.semantics.weak_reference_read_slow(referent)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::metadata::side_metadata::spec_defs::{ALLOC_BIT, CARD_TABLE, RC_COUNT};
+    use crate::util::metadata::side_metadata::{
+        address_to_meta_address, meta_byte_lshift, meta_byte_mask,
+    };
+
+    #[test]
+    fn side_metadata_bit_layout_matches_side_metadata() {
+        for spec in [ALLOC_BIT, RC_COUNT, CARD_TABLE] {
+            let layout = SideMetadataBitLayout::new(&spec);
+            assert_eq!(layout.value_mask, meta_byte_mask(&spec));
+            for offset in [0usize, 8, 16, 24, 56, 512, 4096 + 40] {
+                let addr = unsafe { Address::from_usize(0x4000_0000usize + offset) };
+                assert_eq!(
+                    layout.meta_byte_address(addr),
+                    address_to_meta_address(&spec, addr)
+                );
+                assert_eq!(
+                    layout.bit_shift(addr),
+                    meta_byte_lshift(&spec, addr) as usize
+                );
+            }
+        }
+    }
 }
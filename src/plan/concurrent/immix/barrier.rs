@@ -0,0 +1,75 @@
+//! The snapshot-at-the-beginning barrier for concurrent Immix.
+
+use super::gc_work::ProcessSATBBuffer;
+use crate::plan::barriers::BarrierSemantics;
+use crate::plan::VectorQueue;
+use crate::scheduler::WorkBucketStage;
+use crate::util::*;
+use crate::vm::edge_shape::{Edge, MemorySlice};
+use crate::vm::VMBinding;
+use crate::MMTK;
+
+/// The slow-path semantics for concurrent Immix. This is used with
+/// [`SATBBarrier`](crate::plan::barriers::SATBBarrier), so the slow-path is invoked before a
+/// reference is overwritten, or when a weak referent is read, while concurrent marking is in progress.
+pub struct SATBBarrierSemantics<VM: VMBinding> {
+    /// MMTk instance
+    mmtk: &'static MMTK<VM>,
+    /// The references that are about to be overwritten. They will be marked.
+    satb: VectorQueue<ObjectReference>,
+}
+
+impl<VM: VMBinding> SATBBarrierSemantics<VM> {
+    pub fn new(mmtk: &'static MMTK<VM>) -> Self {
+        Self {
+            mmtk,
+            satb: VectorQueue::new(),
+        }
+    }
+
+    fn enqueue(&mut self, object: ObjectReference) {
+        if !object.is_null() {
+            self.satb.push(object);
+            self.satb.is_full().then(|| self.flush_satb());
+        }
+    }
+
+    fn flush_satb(&mut self) {
+        let buf = self.satb.take();
+        if !buf.is_empty() {
+            let work = ProcessSATBBuffer::<VM>::new(buf);
+            self.mmtk.scheduler.work_buckets[WorkBucketStage::Concurrent].add(work);
+        }
+    }
+}
+
+impl<VM: VMBinding> BarrierSemantics for SATBBarrierSemantics<VM> {
+    type VM = VM;
+
+    fn flush(&mut self) {
+        self.flush_satb();
+    }
+
+    fn object_reference_write_slow(
+        &mut self,
+        _src: ObjectReference,
+        slot: VM::VMEdge,
+        _target: ObjectReference,
+    ) {
+        // The store has not happened yet. The slot still holds the reference in the snapshot.
+        self.enqueue(slot.load());
+    }
+
+    fn memory_region_copy_slow(&mut self, _src: VM::VMMemorySlice, dst: VM::VMMemorySlice) {
+        // The references in the destination slice are about to be overwritten.
+        for edge in dst.iter_edges() {
+            self.enqueue(edge.load());
+        }
+    }
+
+    fn weak_reference_read_slow(&mut self, referent: ObjectReference) -> ObjectReference {
+        // The mutator may store the referent in an object that has been scanned. Keep it alive.
+        self.enqueue(referent);
+        referent
+    }
+}
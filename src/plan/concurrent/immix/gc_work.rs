@@ -0,0 +1,203 @@
+use super::global::ConcurrentImmix;
+use crate::plan::PlanTraceObject;
+use crate::plan::VectorObjectQueue;
+use crate::policy::immix::TRACE_KIND_FAST;
+use crate::scheduler::gc_work::*;
+use crate::scheduler::{GCWork, GCWorker, WorkBucketStage};
+use crate::util::ObjectReference;
+use crate::vm::edge_shape::Edge;
+use crate::vm::*;
+use crate::MMTK;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+pub(super) struct ConcurrentImmixGCWorkContext<VM: VMBinding>(PhantomData<VM>);
+impl<VM: VMBinding> crate::scheduler::GCWorkContext for ConcurrentImmixGCWorkContext<VM> {
+    type VM = VM;
+    type PlanType = ConcurrentImmix<VM>;
+    type ProcessEdgesWorkType = PlanProcessEdges<VM, ConcurrentImmix<VM>, TRACE_KIND_FAST>;
+}
+
+pub(super) struct InitialMarkGCWorkContext<VM: VMBinding>(PhantomData<VM>);
+impl<VM: VMBinding> crate::scheduler::GCWorkContext for InitialMarkGCWorkContext<VM> {
+    type VM = VM;
+    type PlanType = ConcurrentImmix<VM>;
+    type ProcessEdgesWorkType = InitialMarkEdges<VM>;
+}
+
+/// Process root edges in an initial mark pause. The objects reached from the roots are marked in
+/// the pause, but they are scanned by concurrent work packets after the pause.
+pub struct InitialMarkEdges<VM: VMBinding> {
+    plan: &'static ConcurrentImmix<VM>,
+    base: ProcessEdgesBase<VM>,
+}
+
+impl<VM: VMBinding> ProcessEdgesWork for InitialMarkEdges<VM> {
+    type VM = VM;
+    type ScanObjectsWorkType = PlanScanObjects<Self, ConcurrentImmix<VM>>;
+    const OVERWRITE_REFERENCE: bool = false;
+
+    fn new(edges: Vec<EdgeOf<Self>>, roots: bool, mmtk: &'static MMTK<VM>) -> Self {
+        let base = ProcessEdgesBase::new(edges, roots, mmtk);
+        let plan = base.plan().downcast_ref::<ConcurrentImmix<VM>>().unwrap();
+        Self { plan, base }
+    }
+
+    fn create_scan_work(
+        &self,
+        nodes: Vec<ObjectReference>,
+        roots: bool,
+    ) -> Self::ScanObjectsWorkType {
+        PlanScanObjects::<Self, ConcurrentImmix<VM>>::new(self.plan, nodes, false, roots)
+    }
+
+    fn trace_object(&mut self, object: ObjectReference) -> ObjectReference {
+        if object.is_null() {
+            return object;
+        }
+        // We cannot borrow `self` twice in a call, so we extract `worker` as a local variable.
+        let worker = self.worker();
+        self.plan
+            .trace_object::<VectorObjectQueue, TRACE_KIND_FAST>(
+                &mut self.base.nodes,
+                object,
+                worker,
+            )
+    }
+
+    fn flush(&mut self) {
+        let nodes = self.pop_nodes();
+        if !nodes.is_empty() {
+            // The concurrent bucket is opened when the pause is finished.
+            self.mmtk().scheduler.work_buckets[WorkBucketStage::Concurrent]
+                .add(ConcurrentTraceObjects::<VM>::new(nodes));
+        }
+    }
+}
+
+impl<VM: VMBinding> Deref for InitialMarkEdges<VM> {
+    type Target = ProcessEdgesBase<VM>;
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl<VM: VMBinding> DerefMut for InitialMarkEdges<VM> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+/// Scan marked objects, and mark the objects they point to. The newly marked objects are scanned
+/// by more work packets in the concurrent bucket. This may run while mutators are running.
+pub struct ConcurrentTraceObjects<VM: VMBinding> {
+    objects: Vec<ObjectReference>,
+    phantom: PhantomData<VM>,
+}
+
+impl<VM: VMBinding> ConcurrentTraceObjects<VM> {
+    pub fn new(objects: Vec<ObjectReference>) -> Self {
+        Self {
+            objects,
+            phantom: PhantomData,
+        }
+    }
+
+    fn flush(mmtk: &'static MMTK<VM>, objects: &mut VectorObjectQueue) {
+        if !objects.is_empty() {
+            let work = ConcurrentTraceObjects::<VM>::new(objects.take());
+            mmtk.scheduler.work_buckets[WorkBucketStage::Concurrent].add(work);
+        }
+    }
+}
+
+impl<VM: VMBinding> GCWork<VM> for ConcurrentTraceObjects<VM> {
+    fn do_work(&mut self, worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let plan = mmtk.plan.downcast_ref::<ConcurrentImmix<VM>>().unwrap();
+        let tls = worker.tls;
+        let mut next_objects = VectorObjectQueue::new();
+        for object in std::mem::take(&mut self.objects) {
+            // Mutators may write to the fields while we scan them. We either read the reference
+            // in the snapshot, or a new reference, in which case the barrier has recorded the
+            // reference in the snapshot. We must not write to the fields, so the binding has to
+            // enqueue the edges.
+            assert!(
+                VM::VMScanning::support_edge_enqueuing(tls, object),
+                "Concurrent marking requires enqueuing the edges of object {}",
+                object
+            );
+            VM::VMScanning::scan_object(tls, object, &mut |edge: VM::VMEdge| {
+                let child = edge.load();
+                if !child.is_null() {
+                    plan.trace_object::<VectorObjectQueue, TRACE_KIND_FAST>(
+                        &mut next_objects,
+                        child,
+                        worker,
+                    );
+                }
+            });
+            plan.post_scan_object(object);
+            if next_objects.is_full() {
+                Self::flush(mmtk, &mut next_objects);
+            }
+        }
+        Self::flush(mmtk, &mut next_objects);
+    }
+}
+
+/// The references recorded by the snapshot-at-the-beginning barrier. They were in the snapshot
+/// before they were overwritten by mutators. This work packet marks them, and scans the newly
+/// marked objects.
+pub struct ProcessSATBBuffer<VM: VMBinding> {
+    buffer: Vec<ObjectReference>,
+    phantom: PhantomData<VM>,
+}
+
+impl<VM: VMBinding> ProcessSATBBuffer<VM> {
+    pub fn new(buffer: Vec<ObjectReference>) -> Self {
+        debug_assert!(!buffer.is_empty());
+        Self {
+            buffer,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<VM: VMBinding> GCWork<VM> for ProcessSATBBuffer<VM> {
+    fn do_work(&mut self, worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let plan = mmtk.plan.downcast_ref::<ConcurrentImmix<VM>>().unwrap();
+        let mut marked = VectorObjectQueue::new();
+        for object in std::mem::take(&mut self.buffer) {
+            plan.trace_object::<VectorObjectQueue, TRACE_KIND_FAST>(&mut marked, object, worker);
+        }
+        if !marked.is_empty() {
+            GCWork::do_work(
+                &mut ConcurrentTraceObjects::<VM>::new(marked.take()),
+                worker,
+                mmtk,
+            )
+        }
+    }
+}
+
+/// The sentinel of the concurrent bucket. When there is no more concurrent work, this work packet
+/// requests a final mark pause.
+#[derive(Default)]
+pub struct ConcurrentMarkingEnd<VM: VMBinding>(PhantomData<VM>);
+
+impl<VM: VMBinding> ConcurrentMarkingEnd<VM> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<VM: VMBinding> GCWork<VM> for ConcurrentMarkingEnd<VM> {
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let plan = mmtk.plan.downcast_ref::<ConcurrentImmix<VM>>().unwrap();
+        // The bucket may also be drained in a final mark pause that was triggered before the
+        // concurrent work was done. Marking is already finished in that case.
+        if plan.is_marking() {
+            mmtk.plan.base().gc_requester.request();
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use super::gc_work::{
+    ConcurrentImmixGCWorkContext, ConcurrentMarkingEnd, InitialMarkEdges, InitialMarkGCWorkContext,
+};
+use crate::plan::concurrent::Pause;
+use crate::plan::global::BasePlan;
+use crate::plan::global::CommonPlan;
+use crate::plan::global::CreateGeneralPlanArgs;
+use crate::plan::global::CreateSpecificPlanArgs;
+use crate::plan::global::GcStatus;
+use crate::plan::immix;
+use crate::plan::AllocationSemantics;
+use crate::plan::BarrierSelector;
+use crate::plan::Plan;
+use crate::plan::PlanConstraints;
+use crate::policy::immix::ImmixSpace;
+use crate::policy::immix::ImmixSpaceArgs;
+use crate::policy::space::Space;
+use crate::scheduler::gc_work::{Prepare, Release, StopMutators};
+use crate::scheduler::*;
+use crate::util::alloc::allocators::AllocatorSelector;
+use crate::util::copy::*;
+use crate::util::heap::VMRequest;
+use crate::util::metadata::side_metadata::SideMetadataContext;
+use crate::util::metadata::side_metadata::SideMetadataSanity;
+use crate::util::opaque_pointer::VMWorkerThread;
+use crate::vm::VMBinding;
+
+use atomic::{Atomic, Ordering};
+use enum_map::EnumMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use mmtk_macros::PlanTraceObject;
+
+/// Start concurrent marking when the heap is this full (in percentage), so marking may finish
+/// before the mutators run out of memory.
+const CONCURRENT_MARKING_THRESHOLD_PERCENT: usize = 70;
+
+#[derive(PlanTraceObject)]
+pub struct ConcurrentImmix<VM: VMBinding> {
+    #[post_scan]
+    #[trace(CopySemantics::DefaultCopy)]
+    pub immix_space: ImmixSpace<VM>,
+    #[fallback_trace]
+    pub common: CommonPlan<VM>,
+    /// The kind of the current pause.
+    current_pause: Atomic<Pause>,
+    /// Is concurrent marking in progress? This is set from the end of an initial mark pause to
+    /// the start of the next final mark pause. The barrier is only active when this is set.
+    marking: AtomicBool,
+    scheduler: Arc<GCWorkScheduler<VM>>,
+}
+
+pub const CONCURRENT_IMMIX_CONSTRAINTS: PlanConstraints = PlanConstraints {
+    // Objects are never moved, as mutators may access them while they are marked.
+    moves_objects: false,
+    barrier: BarrierSelector::SATBBarrier,
+    needs_concurrent_workers: true,
+    ..immix::IMMIX_CONSTRAINTS
+};
+
+impl<VM: VMBinding> Plan for ConcurrentImmix<VM> {
+    type VM = VM;
+
+    fn collection_required(&self, space_full: bool, _space: Option<&dyn Space<Self::VM>>) -> bool {
+        if self.base().collection_required(self, space_full) {
+            return true;
+        }
+        // Start concurrent marking before the heap is full.
+        !self.is_marking()
+            && self.get_reserved_pages() * 100
+                >= self.get_total_pages() * CONCURRENT_MARKING_THRESHOLD_PERCENT
+    }
+
+    fn last_collection_was_exhaustive(&self) -> bool {
+        // An initial mark pause does not reclaim any memory.
+        self.current_pause() != Pause::InitialMark
+    }
+
+    fn constraints(&self) -> &'static PlanConstraints {
+        &CONCURRENT_IMMIX_CONSTRAINTS
+    }
+
+    fn create_copy_config(&'static self) -> CopyConfig<Self::VM> {
+        use enum_map::enum_map;
+        CopyConfig {
+            copy_mapping: enum_map! {
+                CopySemantics::DefaultCopy => CopySelector::Immix(0),
+                _ => CopySelector::Unused,
+            },
+            space_mapping: vec![(CopySelector::Immix(0), &self.immix_space)],
+            constraints: &CONCURRENT_IMMIX_CONSTRAINTS,
+        }
+    }
+
+    fn get_spaces(&self) -> Vec<&dyn Space<Self::VM>> {
+        let mut ret = self.common.get_spaces();
+        ret.push(&self.immix_space);
+        ret
+    }
+
+    fn schedule_collection(&'static self, scheduler: &GCWorkScheduler<VM>) {
+        self.base().set_collection_kind::<Self>(self);
+        self.base().set_gc_status(GcStatus::GcPrepare);
+
+        let pause = self.select_pause();
+        self.current_pause.store(pause, Ordering::SeqCst);
+
+        match pause {
+            Pause::Full => {
+                info!("Full pause");
+                scheduler.schedule_common_work::<ConcurrentImmixGCWorkContext<VM>>(self);
+            }
+            Pause::InitialMark => {
+                info!("Initial mark pause");
+                // Only scan the roots. Reference processing and sweeping are done in the final
+                // mark pause.
+                scheduler.work_buckets[WorkBucketStage::Unconstrained]
+                    .add(StopMutators::<InitialMarkEdges<VM>>::new());
+                scheduler.work_buckets[WorkBucketStage::Prepare]
+                    .add(Prepare::<InitialMarkGCWorkContext<VM>>::new(self));
+                scheduler.work_buckets[WorkBucketStage::Release]
+                    .add(Release::<InitialMarkGCWorkContext<VM>>::new(self));
+            }
+            Pause::FinalMark => {
+                info!("Final mark pause");
+                // The remaining concurrent work is done in this pause before the closure stage.
+                scheduler.schedule_common_work::<ConcurrentImmixGCWorkContext<VM>>(self);
+            }
+        }
+    }
+
+    fn get_allocator_mapping(&self) -> &'static EnumMap<AllocationSemantics, AllocatorSelector> {
+        &immix::mutator::ALLOCATOR_MAPPING
+    }
+
+    fn prepare(&mut self, tls: VMWorkerThread) {
+        match self.current_pause() {
+            Pause::Full | Pause::InitialMark => {
+                self.common.prepare(tls, true);
+                self.immix_space.prepare(true);
+            }
+            Pause::FinalMark => {
+                // Mutators are stopped. Marking will be finished in this pause.
+                self.set_marking(false);
+            }
+        }
+    }
+
+    fn release(&mut self, tls: VMWorkerThread) {
+        match self.current_pause() {
+            Pause::Full => {
+                self.common.release(tls, true);
+                self.immix_space.release(true);
+            }
+            Pause::InitialMark => {
+                // Marking starts when the mutators resume.
+                self.set_marking(true);
+            }
+            Pause::FinalMark => {
+                self.scheduler.deactivate_concurrent_bucket();
+                self.common.release(tls, true);
+                self.immix_space.release(true);
+            }
+        }
+    }
+
+    fn end_of_gc(&mut self, _tls: VMWorkerThread) {
+        if self.current_pause() == Pause::InitialMark {
+            // Mark the objects reached from the roots concurrently. When there is no more
+            // concurrent work, the sentinel requests a final mark pause.
+            self.scheduler.work_buckets[WorkBucketStage::Concurrent]
+                .set_sentinel(Box::new(ConcurrentMarkingEnd::<VM>::new()));
+            self.scheduler.activate_concurrent_bucket();
+        }
+    }
+
+    fn get_used_pages(&self) -> usize {
+        self.immix_space.reserved_pages() + self.common.get_used_pages()
+    }
+
+    fn base(&self) -> &BasePlan<VM> {
+        &self.common.base
+    }
+
+    fn common(&self) -> &CommonPlan<VM> {
+        &self.common
+    }
+}
+
+impl<VM: VMBinding> ConcurrentImmix<VM> {
+    pub fn new(args: CreateGeneralPlanArgs<VM>) -> Self {
+        let scheduler = args.scheduler.clone();
+        let mut plan_args = CreateSpecificPlanArgs {
+            global_args: args,
+            constraints: &CONCURRENT_IMMIX_CONSTRAINTS,
+            global_side_metadata_specs: SideMetadataContext::new_global_specs(&[]),
+        };
+
+        let immix_space = ImmixSpace::new(
+            plan_args.get_space_args("immix", true, VMRequest::discontiguous()),
+            ImmixSpaceArgs {
+                unlog_object_when_traced: false,
+                reset_log_bit_in_major_gc: false,
+                rc_enabled: false,
+            },
+        );
+        let common = CommonPlan::new(plan_args);
+
+        let plan = ConcurrentImmix {
+            immix_space,
+            common,
+            current_pause: Atomic::new(Pause::Full),
+            marking: AtomicBool::new(false),
+            scheduler,
+        };
+
+        {
+            let mut side_metadata_sanity_checker = SideMetadataSanity::new();
+            plan.common
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+            plan.immix_space
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+        }
+
+        plan
+    }
+
+    /// Get the kind of the current (or the last) pause.
+    pub fn current_pause(&self) -> Pause {
+        self.current_pause.load(Ordering::SeqCst)
+    }
+
+    /// Is concurrent marking in progress?
+    pub fn is_marking(&self) -> bool {
+        self.marking.load(Ordering::SeqCst)
+    }
+
+    /// The flag that tells the barrier whether concurrent marking is in progress.
+    pub(super) fn marking_flag(&self) -> &AtomicBool {
+        &self.marking
+    }
+
+    /// Start or stop concurrent marking. This should only be called when mutators are stopped.
+    fn set_marking(&self, marking: bool) {
+        self.marking.store(marking, Ordering::SeqCst);
+        // Objects allocated during marking are not in the snapshot. They are live.
+        self.immix_space.set_allocate_as_live(marking);
+    }
+
+    fn select_pause(&self) -> Pause {
+        // Separate each condition so the code is clear
+        #[allow(clippy::if_same_then_else)]
+        if self.is_marking() {
+            // Any pause during concurrent marking finishes the marking.
+            Pause::FinalMark
+        } else if self.base().is_user_triggered_collection() {
+            // User triggered collection should reclaim memory before it returns.
+            Pause::Full
+        } else if self.is_emergency_collection()
+            || self.base().cur_collection_attempts.load(Ordering::SeqCst) > 1
+        {
+            // We are running out of memory. There is no time for concurrent marking.
+            Pause::Full
+        } else {
+            Pause::InitialMark
+        }
+    }
+}
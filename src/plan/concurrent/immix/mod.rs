@@ -0,0 +1,19 @@
+//! Plan: concurrent Immix, an Immix plan that marks the heap while mutators are running.
+//!
+//! An initial mark pause scans the roots. Then GC workers mark the rest of the heap concurrently,
+//! and a snapshot-at-the-beginning barrier records the references that mutators overwrite while
+//! marking is in progress. New objects are allocated as live. A final mark pause processes the
+//! remaining references recorded by the barrier, and sweeps the heap.
+//!
+//! Reference objects and finalizable objects are processed in the final mark pause, after the
+//! marking is finished. The barrier keeps the objects in the snapshot alive, and the mutators
+//! must read referents with [`weak_reference_read`](crate::memory_manager::weak_reference_read),
+//! so a referent that is not marked by then is dead.
+
+pub(in crate::plan) mod barrier;
+pub(in crate::plan) mod gc_work;
+pub(in crate::plan) mod global;
+pub(in crate::plan) mod mutator;
+
+pub use global::ConcurrentImmix;
+pub use global::CONCURRENT_IMMIX_CONSTRAINTS;
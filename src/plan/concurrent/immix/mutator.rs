@@ -0,0 +1,58 @@
+use super::barrier::SATBBarrierSemantics;
+use super::ConcurrentImmix;
+use crate::plan::barriers::SATBBarrier;
+use crate::plan::immix;
+use crate::plan::mutator_context::{create_space_mapping, MutatorConfig};
+use crate::util::alloc::allocators::Allocators;
+use crate::util::alloc::AllocatorSelector;
+use crate::util::opaque_pointer::VMWorkerThread;
+use crate::util::VMMutatorThread;
+use crate::vm::VMBinding;
+use crate::{Mutator, MMTK};
+
+pub fn concurrent_immix_mutator_prepare<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+    tls: VMWorkerThread,
+) {
+    immix::mutator::immix_mutator_prepare(mutator, tls)
+}
+
+pub fn concurrent_immix_mutator_release<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+    tls: VMWorkerThread,
+) {
+    immix::mutator::immix_mutator_release(mutator, tls)
+}
+
+pub use immix::mutator::ALLOCATOR_MAPPING;
+
+pub fn create_concurrent_immix_mutator<VM: VMBinding>(
+    mutator_tls: VMMutatorThread,
+    mmtk: &'static MMTK<VM>,
+) -> Mutator<VM> {
+    let cimmix = mmtk.plan.downcast_ref::<ConcurrentImmix<VM>>().unwrap();
+    let config = MutatorConfig {
+        allocator_mapping: &ALLOCATOR_MAPPING,
+        space_mapping: Box::new({
+            let mut vec =
+                create_space_mapping(immix::mutator::RESERVED_ALLOCATORS, true, &*mmtk.plan);
+            vec.push((AllocatorSelector::Immix(0), &cimmix.immix_space));
+            vec
+        }),
+        prepare_func: &concurrent_immix_mutator_prepare,
+        release_func: &concurrent_immix_mutator_release,
+    };
+
+    Mutator {
+        allocators: Allocators::<VM>::new(mutator_tls, &*mmtk.plan, &config.space_mapping),
+        barrier: Box::new(SATBBarrier::new(
+            SATBBarrierSemantics::new(mmtk),
+            cimmix.marking_flag(),
+        )),
+        mutator_tls,
+        config,
+        los_redirects: 0,
+        pretenured_objects: 0,
+        plan: &*mmtk.plan,
+    }
+}
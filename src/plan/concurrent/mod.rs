@@ -0,0 +1,13 @@
+pub mod immix;
+
+/// The kinds of pauses in a concurrent plan.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Pause {
+    /// A stop-the-world collection that marks and reclaims the whole heap in one pause.
+    Full,
+    /// Scan the roots, and start concurrent marking after the pause.
+    InitialMark,
+    /// Finish concurrent marking, and reclaim the dead objects.
+    FinalMark,
+}
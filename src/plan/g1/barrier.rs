@@ -0,0 +1,92 @@
+//! The remembered set barrier for G1.
+
+use super::global::G1;
+use crate::plan::barriers::BarrierSemantics;
+use crate::plan::VectorQueue;
+use crate::policy::region::HeapRegion;
+use crate::util::*;
+use crate::vm::edge_shape::{Edge, MemorySlice};
+use crate::vm::VMBinding;
+
+/// The slow-path semantics for G1. This is used with
+/// [`RegionBarrier`](crate::plan::barriers::RegionBarrier), so the slow-path is invoked after a
+/// store that creates a reference across regions.
+pub struct G1BarrierSemantics<VM: VMBinding> {
+    /// G1 plan
+    g1: &'static G1<VM>,
+    /// Objects that may point into other regions, paired with the regions they point into.
+    objects: VectorQueue<(HeapRegion, ObjectReference)>,
+    /// Array slices written by array copying, paired with the regions they point into.
+    slices: VectorQueue<(HeapRegion, VM::VMMemorySlice)>,
+}
+
+impl<VM: VMBinding> G1BarrierSemantics<VM> {
+    pub fn new(g1: &'static G1<VM>) -> Self {
+        Self {
+            g1,
+            objects: VectorQueue::new(),
+            slices: VectorQueue::new(),
+        }
+    }
+
+    fn flush_objects(&mut self) {
+        let buf = self.objects.take();
+        if !buf.is_empty() {
+            self.g1.region_space.remset.add_objects(buf);
+        }
+    }
+
+    fn flush_slices(&mut self) {
+        let buf = self.slices.take();
+        if !buf.is_empty() {
+            self.g1.region_space.remset.add_slices(buf);
+        }
+    }
+}
+
+impl<VM: VMBinding> BarrierSemantics for G1BarrierSemantics<VM> {
+    type VM = VM;
+
+    fn flush(&mut self) {
+        self.flush_objects();
+        self.flush_slices();
+    }
+
+    fn object_reference_write_slow(
+        &mut self,
+        src: ObjectReference,
+        _slot: VM::VMEdge,
+        target: ObjectReference,
+    ) {
+        let space = &self.g1.region_space;
+        let src_addr = src.to_address::<VM>();
+        if space.is_in_young_region(src_addr) {
+            // The source will be scanned in the next pause, if it is alive.
+            return;
+        }
+        if let Some(region) = space.crossing_region(src_addr, target) {
+            self.objects.push((region, src));
+            self.objects.is_full().then(|| self.flush_objects());
+        }
+    }
+
+    fn memory_region_copy_slow(&mut self, _src: VM::VMMemorySlice, dst: VM::VMMemorySlice) {
+        let space = &self.g1.region_space;
+        if space.is_in_young_region(dst.start()) {
+            return;
+        }
+        // Remember the slice once for each region that it points into.
+        let mut regions = vec![];
+        for edge in dst.iter_edges() {
+            if let Some(region) = space.crossing_region(dst.start(), edge.load()) {
+                if !regions.contains(&region) {
+                    regions.push(region);
+                }
+            }
+        }
+        for region in regions {
+            self.slices.push((region, dst.clone()));
+        }
+        self.slices.is_full().then(|| self.flush_slices());
+    }
+}
@@ -0,0 +1,274 @@
+use super::global::G1;
+use crate::plan::PlanTraceObject;
+use crate::plan::VectorObjectQueue;
+use crate::policy::gc_work::TraceKind;
+use crate::policy::region::TRACE_KIND_EVACUATE;
+use crate::scheduler::gc_work::*;
+use crate::scheduler::{GCWork, GCWorker, WorkBucketStage};
+use crate::util::ObjectReference;
+use crate::vm::edge_shape::{Edge, MemorySlice};
+use crate::vm::*;
+use crate::MMTK;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+pub(super) struct G1GCWorkContext<VM: VMBinding, const KIND: TraceKind>(PhantomData<VM>);
+impl<VM: VMBinding, const KIND: TraceKind> crate::scheduler::GCWorkContext
+    for G1GCWorkContext<VM, KIND>
+{
+    type VM = VM;
+    type PlanType = G1<VM>;
+    type ProcessEdgesWorkType = G1ProcessEdges<VM, KIND>;
+}
+
+/// Process edges for G1. This is the same as [`PlanProcessEdges`], except that the objects are
+/// scanned by [`G1ScanObjects`].
+pub struct G1ProcessEdges<VM: VMBinding, const KIND: TraceKind> {
+    plan: &'static G1<VM>,
+    base: ProcessEdgesBase<VM>,
+}
+
+impl<VM: VMBinding, const KIND: TraceKind> ProcessEdgesWork for G1ProcessEdges<VM, KIND> {
+    type VM = VM;
+    type ScanObjectsWorkType = G1ScanObjects<VM, KIND>;
+
+    fn new(edges: Vec<EdgeOf<Self>>, roots: bool, mmtk: &'static MMTK<VM>) -> Self {
+        let base = ProcessEdgesBase::new(edges, roots, mmtk);
+        let plan = base.plan().downcast_ref::<G1<VM>>().unwrap();
+        Self { plan, base }
+    }
+
+    fn create_scan_work(
+        &self,
+        nodes: Vec<ObjectReference>,
+        roots: bool,
+    ) -> Self::ScanObjectsWorkType {
+        G1ScanObjects::<VM, KIND>::new(self.plan, nodes, roots)
+    }
+
+    fn trace_object(&mut self, object: ObjectReference) -> ObjectReference {
+        if object.is_null() {
+            return object;
+        }
+        // We cannot borrow `self` twice in a call, so we extract `worker` as a local variable.
+        let worker = self.worker();
+        self.plan
+            .trace_object::<VectorObjectQueue, KIND>(&mut self.base.nodes, object, worker)
+    }
+
+    fn process_edge(&mut self, slot: EdgeOf<Self>) {
+        let object = slot.load();
+        let new_object = self.trace_object(object);
+        if G1::<VM>::may_move_objects::<KIND>() {
+            slot.store(new_object);
+        }
+    }
+}
+
+impl<VM: VMBinding, const KIND: TraceKind> Deref for G1ProcessEdges<VM, KIND> {
+    type Target = ProcessEdgesBase<VM>;
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl<VM: VMBinding, const KIND: TraceKind> DerefMut for G1ProcessEdges<VM, KIND> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+/// Scan objects and trace their fields in the same packet. Unlike [`ScanObjects`], this knows the
+/// source object of each reference it traces, so it can record the references across regions in
+/// the remembered sets. The objects reached for the first time are scanned by new packets.
+pub struct G1ScanObjects<VM: VMBinding, const KIND: TraceKind> {
+    plan: &'static G1<VM>,
+    buffer: Vec<ObjectReference>,
+    roots: bool,
+}
+
+impl<VM: VMBinding, const KIND: TraceKind> G1ScanObjects<VM, KIND> {
+    pub fn new(plan: &'static G1<VM>, buffer: Vec<ObjectReference>, roots: bool) -> Self {
+        Self {
+            plan,
+            buffer,
+            roots,
+        }
+    }
+
+    /// Scan the newly traced objects in another packet.
+    fn flush(&self, closure: &mut G1ProcessEdges<VM, KIND>, mmtk: &'static MMTK<VM>) {
+        let nodes = closure.pop_nodes();
+        if !nodes.is_empty() {
+            mmtk.scheduler.work_buckets[WorkBucketStage::Closure].add(self.make_another(nodes));
+        }
+    }
+}
+
+impl<VM: VMBinding, const KIND: TraceKind> ScanObjectsWork<VM> for G1ScanObjects<VM, KIND> {
+    type E = G1ProcessEdges<VM, KIND>;
+
+    fn roots(&self) -> bool {
+        self.roots
+    }
+
+    fn post_scan_object(&self, object: ObjectReference) {
+        self.plan.post_scan_object(object);
+    }
+
+    fn make_another(&self, buffer: Vec<ObjectReference>) -> Self {
+        Self::new(self.plan, buffer, false)
+    }
+}
+
+impl<VM: VMBinding, const KIND: TraceKind> GCWork<VM> for G1ScanObjects<VM, KIND> {
+    fn do_work(&mut self, worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        trace!("G1ScanObjects");
+        let tls = worker.tls;
+        let plan = self.plan;
+        let space = &plan.region_space;
+        let buffer = std::mem::take(&mut self.buffer);
+
+        #[cfg(feature = "sanity")]
+        {
+            if self.roots {
+                mmtk.sanity_checker
+                    .lock()
+                    .unwrap()
+                    .add_root_nodes(buffer.clone());
+            }
+        }
+
+        // We use an instance of the ProcessEdgesWork for its `trace_object` method and its object queue.
+        let mut closure = G1ProcessEdges::<VM, KIND>::new(vec![], false, mmtk);
+        closure.set_worker(worker);
+
+        // If this is a root packet, the objects have not been traced yet. Trace them, and only scan
+        // the objects that are traced for the first time.
+        let objects = if self.roots {
+            for object in buffer {
+                let new_object = closure.trace_object(object);
+                debug_assert_eq!(
+                    object, new_object,
+                    "Object moved while tracing root unmovable root object: {} -> {}",
+                    object, new_object
+                );
+            }
+            closure.pop_nodes()
+        } else {
+            buffer
+        };
+
+        let mut remembered = vec![];
+        for object in objects {
+            let src = object.to_address::<VM>();
+            let mut trace_field = |child: ObjectReference| {
+                let new_child = closure.trace_object(child);
+                if let Some(region) = space.crossing_region(src, new_child) {
+                    if remembered.last() != Some(&(region, object)) {
+                        remembered.push((region, object));
+                    }
+                }
+                new_child
+            };
+            if VM::VMScanning::support_edge_enqueuing(tls, object) {
+                VM::VMScanning::scan_object(tls, object, &mut |edge: VM::VMEdge| {
+                    let child = edge.load();
+                    let new_child = trace_field(child);
+                    if new_child != child {
+                        edge.store(new_child);
+                    }
+                });
+            } else {
+                VM::VMScanning::scan_object_and_trace_edges(tls, object, &mut trace_field);
+            }
+            self.post_scan_object(object);
+            if closure.nodes.is_full() {
+                self.flush(&mut closure, mmtk);
+            }
+        }
+        self.flush(&mut closure, mmtk);
+
+        if !remembered.is_empty() {
+            space.remset.add_objects(remembered);
+        }
+        trace!("G1ScanObjects End");
+    }
+}
+
+/// Scan the remembered sets of the collection set in an evacuating pause. The objects and the
+/// slices in them are outside the collection set, and they may point into the collection set.
+///
+/// This packet is added to the closure bucket, after all the mutators have flushed their barriers.
+pub struct ScanRemSets<VM: VMBinding>(PhantomData<VM>);
+
+impl<VM: VMBinding> ScanRemSets<VM> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<VM: VMBinding> GCWork<VM> for ScanRemSets<VM> {
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let g1 = mmtk.plan.downcast_ref::<G1<VM>>().unwrap();
+        let remset = g1.region_space.take_collection_set_remsets();
+        let objects: Vec<ObjectReference> = remset.objects.into_iter().collect();
+        let mut packets: Vec<Box<dyn GCWork<VM>>> = vec![];
+        for chunk in objects.chunks(G1ProcessEdges::<VM, TRACE_KIND_EVACUATE>::CAPACITY) {
+            packets.push(Box::new(G1ScanObjects::<VM, TRACE_KIND_EVACUATE>::new(
+                g1,
+                chunk.to_vec(),
+                false,
+            )));
+        }
+        for slice in remset.slices {
+            packets.push(Box::new(ScanRemSetSlice::<VM>::new(g1, slice)));
+        }
+        mmtk.scheduler.work_buckets[WorkBucketStage::Closure].bulk_add(packets);
+    }
+}
+
+/// Trace the edges in a remembered array slice in an evacuating pause, and remember the slice again
+/// for the regions that it points into after the evacuation.
+pub struct ScanRemSetSlice<VM: VMBinding> {
+    plan: &'static G1<VM>,
+    slice: VM::VMMemorySlice,
+}
+
+impl<VM: VMBinding> ScanRemSetSlice<VM> {
+    pub fn new(plan: &'static G1<VM>, slice: VM::VMMemorySlice) -> Self {
+        Self { plan, slice }
+    }
+}
+
+impl<VM: VMBinding> GCWork<VM> for ScanRemSetSlice<VM> {
+    fn do_work(&mut self, worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let plan = self.plan;
+        let space = &plan.region_space;
+        let mut closure = G1ProcessEdges::<VM, TRACE_KIND_EVACUATE>::new(vec![], false, mmtk);
+        closure.set_worker(worker);
+        let mut regions = vec![];
+        for edge in self.slice.iter_edges() {
+            let object = edge.load();
+            let new_object = closure.trace_object(object);
+            if new_object != object {
+                edge.store(new_object);
+            }
+            if let Some(region) = space.crossing_region(self.slice.start(), new_object) {
+                if !regions.contains(&region) {
+                    regions.push(region);
+                }
+            }
+        }
+        space.remset.add_slices(
+            regions
+                .into_iter()
+                .map(|region| (region, self.slice.clone())),
+        );
+        let nodes = closure.pop_nodes();
+        if !nodes.is_empty() {
+            let work = G1ScanObjects::<VM, TRACE_KIND_EVACUATE>::new(self.plan, nodes, false);
+            mmtk.scheduler.work_buckets[WorkBucketStage::Closure].add(work);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use super::gc_work::{G1GCWorkContext, ScanRemSets};
+use super::mutator::ALLOCATOR_MAPPING;
+use crate::plan::global::BasePlan;
+use crate::plan::global::CommonPlan;
+use crate::plan::global::CreateGeneralPlanArgs;
+use crate::plan::global::CreateSpecificPlanArgs;
+use crate::plan::global::GcStatus;
+use crate::plan::AllocationSemantics;
+use crate::plan::BarrierSelector;
+use crate::plan::Plan;
+use crate::plan::PlanConstraints;
+use crate::policy::region::{RegionSpace, TRACE_KIND_EVACUATE, TRACE_KIND_MARK};
+use crate::policy::space::Space;
+use crate::scheduler::*;
+use crate::util::alloc::allocators::AllocatorSelector;
+use crate::util::copy::*;
+use crate::util::heap::VMRequest;
+use crate::util::metadata::side_metadata::SideMetadataContext;
+use crate::util::metadata::side_metadata::SideMetadataSanity;
+use crate::util::opaque_pointer::VMWorkerThread;
+use crate::util::statistics::counter::EventCounter;
+use crate::vm::VMBinding;
+
+use atomic::Ordering;
+use enum_map::EnumMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+
+use mmtk_macros::PlanTraceObject;
+
+/// If the heap is still this full (in percentage) after a pause, and there are no candidate regions
+/// left to evacuate, the next pause will mark the heap to find more candidates.
+const MARK_THRESHOLD_PERCENT: usize = 45;
+
+#[derive(PlanTraceObject)]
+pub struct G1<VM: VMBinding> {
+    #[trace(CopySemantics::DefaultCopy)]
+    pub region_space: RegionSpace<VM>,
+    #[fallback_trace]
+    pub common: CommonPlan<VM>,
+    /// Is the current pause a full heap marking pause?
+    current_pause_marks: AtomicBool,
+    /// Should the next pause be a full heap marking pause?
+    next_pause_marks: AtomicBool,
+    mark_pause_count: Arc<Mutex<EventCounter>>,
+}
+
+pub const G1_CONSTRAINTS: PlanConstraints = PlanConstraints {
+    moves_objects: true,
+    gc_header_bits: 2,
+    gc_header_words: 0,
+    num_specialized_scans: 1,
+    max_non_los_default_alloc_bytes:
+        crate::plan::plan_constraints::MAX_NON_LOS_ALLOC_BYTES_COPYING_PLAN,
+    max_non_los_copy_bytes: crate::plan::plan_constraints::MAX_NON_LOS_ALLOC_BYTES_COPYING_PLAN,
+    barrier: BarrierSelector::RegionBarrier,
+    ..PlanConstraints::default()
+};
+
+impl<VM: VMBinding> Plan for G1<VM> {
+    type VM = VM;
+
+    fn collection_required(&self, space_full: bool, _space: Option<&dyn Space<Self::VM>>) -> bool {
+        // The young regions are always evacuated. Collect when they use up the nursery size, so the
+        // pause time stays bounded.
+        let nursery_full = self.region_space.young_pages() > self.options().get_max_nursery_pages();
+        self.base().collection_required(self, space_full) || nursery_full
+    }
+
+    fn last_collection_was_exhaustive(&self) -> bool {
+        self.current_pause_marks()
+    }
+
+    fn constraints(&self) -> &'static PlanConstraints {
+        &G1_CONSTRAINTS
+    }
+
+    fn create_copy_config(&'static self) -> CopyConfig<Self::VM> {
+        use enum_map::enum_map;
+        CopyConfig {
+            copy_mapping: enum_map! {
+                CopySemantics::DefaultCopy => CopySelector::Region(0),
+                _ => CopySelector::Unused,
+            },
+            space_mapping: vec![(CopySelector::Region(0), &self.region_space)],
+            constraints: &G1_CONSTRAINTS,
+        }
+    }
+
+    fn get_spaces(&self) -> Vec<&dyn Space<Self::VM>> {
+        let mut ret = self.common.get_spaces();
+        ret.push(&self.region_space);
+        ret
+    }
+
+    fn schedule_collection(&'static self, scheduler: &GCWorkScheduler<VM>) {
+        self.base().set_collection_kind::<Self>(self);
+        self.base().set_gc_status(GcStatus::GcPrepare);
+
+        let mark = self.requires_mark();
+        self.current_pause_marks.store(mark, Ordering::SeqCst);
+
+        if mark {
+            info!("Full heap marking pause");
+            scheduler.schedule_common_work::<G1GCWorkContext<VM, TRACE_KIND_MARK>>(self);
+        } else {
+            info!("Evacuating pause");
+            scheduler.schedule_common_work::<G1GCWorkContext<VM, TRACE_KIND_EVACUATE>>(self);
+            // The remembered sets are complete after the mutators flush their barriers in the
+            // prepare stage.
+            scheduler.work_buckets[WorkBucketStage::Closure].add(ScanRemSets::<VM>::new());
+        }
+    }
+
+    fn get_allocator_mapping(&self) -> &'static EnumMap<AllocationSemantics, AllocatorSelector> {
+        &ALLOCATOR_MAPPING
+    }
+
+    fn prepare(&mut self, tls: VMWorkerThread) {
+        if self.current_pause_marks() {
+            self.mark_pause_count.lock().unwrap().inc();
+            self.common.prepare(tls, true);
+            self.region_space.prepare_mark();
+        } else {
+            // An evacuating pause does not trace the objects outside the collection set. The
+            // other spaces are left untouched, and their references into the collection set are
+            // found through the remembered sets.
+            self.region_space
+                .select_collection_set(self.region_space.candidates_per_pause());
+        }
+    }
+
+    fn release(&mut self, tls: VMWorkerThread) {
+        if self.current_pause_marks() {
+            self.common.release(tls, true);
+            self.region_space.release_mark();
+        } else {
+            self.region_space.release_collection_set();
+        }
+    }
+
+    fn end_of_gc(&mut self, _tls: VMWorkerThread) {
+        let next_pause_marks = !self.region_space.has_candidates()
+            && self.get_reserved_pages() * 100 >= self.get_total_pages() * MARK_THRESHOLD_PERCENT;
+        self.next_pause_marks
+            .store(next_pause_marks, Ordering::Relaxed);
+    }
+
+    fn get_collection_reserved_pages(&self) -> usize {
+        self.region_space.evacuation_reserve_pages()
+    }
+
+    fn get_used_pages(&self) -> usize {
+        self.region_space.reserved_pages() + self.common.get_used_pages()
+    }
+
+    fn base(&self) -> &BasePlan<VM> {
+        &self.common.base
+    }
+
+    fn common(&self) -> &CommonPlan<VM> {
+        &self.common
+    }
+}
+
+impl<VM: VMBinding> G1<VM> {
+    pub fn new(args: CreateGeneralPlanArgs<VM>) -> Self {
+        let mut plan_args = CreateSpecificPlanArgs {
+            global_args: args,
+            constraints: &G1_CONSTRAINTS,
+            global_side_metadata_specs: SideMetadataContext::new_global_specs(&[]),
+        };
+
+        let region_space =
+            RegionSpace::new(plan_args.get_space_args("region", true, VMRequest::discontiguous()));
+        let common = CommonPlan::new(plan_args);
+        let mark_pause_count = common.base.stats.new_event_counter("markPause", true, true);
+
+        let g1 = G1 {
+            region_space,
+            common,
+            current_pause_marks: AtomicBool::new(false),
+            next_pause_marks: AtomicBool::new(false),
+            mark_pause_count,
+        };
+
+        {
+            let mut side_metadata_sanity_checker = SideMetadataSanity::new();
+            g1.common
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+            g1.region_space
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+        }
+
+        g1
+    }
+
+    /// Is the current pause a full heap marking pause?
+    pub fn current_pause_marks(&self) -> bool {
+        self.current_pause_marks.load(Ordering::SeqCst)
+    }
+
+    fn requires_mark(&self) -> bool {
+        // Separate each condition so the code is clear
+        #[allow(clippy::if_same_then_else, clippy::needless_bool)]
+        if self.base().is_user_triggered_collection() && *self.base().options.full_heap_system_gc {
+            // User triggered collection, and we mark for user triggered collection
+            true
+        } else if self.is_emergency_collection()
+            || self.next_pause_marks.load(Ordering::SeqCst)
+            || self.base().cur_collection_attempts.load(Ordering::SeqCst) > 1
+        {
+            // Evacuating the collection set did not reclaim enough memory
+            true
+        } else {
+            false
+        }
+    }
+}
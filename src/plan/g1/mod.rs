@@ -0,0 +1,26 @@
+//! Plan: G1, a region-based evacuating plan.
+//!
+//! The heap is divided into fixed-size regions. Each region keeps a remembered set of the objects
+//! outside the region that may point into it, maintained by a post-write barrier and by the GC.
+//!
+//! Most pauses evacuate a collection set: all the regions allocated since the last pause, plus a few
+//! old regions with the least live bytes. The rest of the heap is not traced. The liveness of old
+//! regions is estimated by occasional full heap marking pauses, which also reclaim the regions
+//! without live objects and rebuild the remembered sets.
+//!
+//! Reference objects and finalizable objects are processed in every pause. An evacuating pause
+//! treats the objects outside the collection set as live, so only the referents and finalizable
+//! objects in the collection set can be found dead: those that were not evacuated by the trace from
+//! the roots and the remembered sets. A reference object stays registered while its referent is
+//! alive, so it is processed in later pauses even if it is outside their collection sets. A
+//! reference object that the binding has not registered yet is found through the remembered set of
+//! its referent's region and registered when it is scanned, if the binding stores the referent
+//! with the write barrier.
+
+pub(super) mod barrier;
+pub(super) mod gc_work;
+pub(super) mod global;
+pub(super) mod mutator;
+
+pub use self::global::G1;
+pub use self::global::G1_CONSTRAINTS;
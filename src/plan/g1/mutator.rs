@@ -0,0 +1,78 @@
+use super::barrier::G1BarrierSemantics;
+use super::G1;
+use crate::plan::barriers::RegionBarrier;
+use crate::plan::mutator_context::create_allocator_mapping;
+use crate::plan::mutator_context::create_space_mapping;
+use crate::plan::mutator_context::Mutator;
+use crate::plan::mutator_context::MutatorConfig;
+use crate::plan::mutator_context::ReservedAllocators;
+use crate::plan::AllocationSemantics;
+use crate::util::alloc::allocators::{AllocatorSelector, Allocators};
+use crate::util::alloc::RegionAllocator;
+use crate::util::opaque_pointer::{VMMutatorThread, VMWorkerThread};
+use crate::vm::VMBinding;
+use crate::MMTK;
+use enum_map::EnumMap;
+
+pub fn g1_mutator_prepare<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
+    let region_allocator = unsafe {
+        mutator
+            .allocators
+            .get_allocator_mut(mutator.config.allocator_mapping[AllocationSemantics::Default])
+    }
+    .downcast_mut::<RegionAllocator<VM>>()
+    .unwrap();
+    region_allocator.reset();
+}
+
+pub fn g1_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
+    // The region that the mutator was allocating into is either released, or becomes an old region.
+    let region_allocator = unsafe {
+        mutator
+            .allocators
+            .get_allocator_mut(mutator.config.allocator_mapping[AllocationSemantics::Default])
+    }
+    .downcast_mut::<RegionAllocator<VM>>()
+    .unwrap();
+    region_allocator.reset();
+}
+
+const RESERVED_ALLOCATORS: ReservedAllocators = ReservedAllocators {
+    n_region: 1,
+    ..ReservedAllocators::DEFAULT
+};
+
+lazy_static! {
+    pub static ref ALLOCATOR_MAPPING: EnumMap<AllocationSemantics, AllocatorSelector> = {
+        let mut map = create_allocator_mapping(RESERVED_ALLOCATORS, true);
+        map[AllocationSemantics::Default] = AllocatorSelector::Region(0);
+        map
+    };
+}
+
+pub fn create_g1_mutator<VM: VMBinding>(
+    mutator_tls: VMMutatorThread,
+    mmtk: &'static MMTK<VM>,
+) -> Mutator<VM> {
+    let g1 = mmtk.plan.downcast_ref::<G1<VM>>().unwrap();
+    let config = MutatorConfig {
+        allocator_mapping: &ALLOCATOR_MAPPING,
+        space_mapping: Box::new({
+            let mut vec = create_space_mapping(RESERVED_ALLOCATORS, true, &*mmtk.plan);
+            vec.push((AllocatorSelector::Region(0), &g1.region_space));
+            vec
+        }),
+        prepare_func: &g1_mutator_prepare,
+        release_func: &g1_mutator_release,
+    };
+
+    Mutator {
+        allocators: Allocators::<VM>::new(mutator_tls, &*mmtk.plan, &config.space_mapping),
+        barrier: Box::new(RegionBarrier::new(G1BarrierSemantics::new(g1))),
+        mutator_tls,
+        config,
+        los_redirects: 0,
+        pretenured_objects: 0,
+        plan: &*mmtk.plan,
+    }
+}
@@ -81,6 +81,12 @@ impl<VM: VMBinding, P: GenerationalPlanExt<VM> + PlanTraceObject<VM>> BarrierSem
         self.modbuf.is_full().then(|| self.flush_modbuf());
     }
 
+    fn object_probable_write_slow(&mut self, obj: ObjectReference) {
+        // enqueue the object
+        self.modbuf.push(obj);
+        self.modbuf.is_full().then(|| self.flush_modbuf());
+    }
+
     fn memory_region_copy_slow(&mut self, _src: VM::VMMemorySlice, dst: VM::VMMemorySlice) {
         // Only enqueue array slices in mature spaces
         if !self.plan.is_address_in_nursery(dst.start()) {
@@ -97,3 +103,74 @@ impl<VM: VMBinding, P: GenerationalPlanExt<VM> + PlanTraceObject<VM>> BarrierSem
         }
     }
 }
+
+/// The barrier semantics for generational plans that use [`crate::plan::barriers::CardTableBarrier`].
+/// The objects that may contain pointers to the nursery are found by scanning the dirty cards in a
+/// nursery GC, so the object modbuf is not needed. The array-copy modbuf is the same as
+/// [`GenObjectBarrierSemantics`].
+pub struct GenCardTableBarrierSemantics<
+    VM: VMBinding,
+    P: GenerationalPlanExt<VM> + PlanTraceObject<VM>,
+> {
+    /// MMTk instance
+    mmtk: &'static MMTK<VM>,
+    /// Generational plan
+    plan: &'static P,
+    /// Array-copy modbuf. Contains a list of sub-arrays or array slices that may contain pointers to the nursery space.
+    region_modbuf: VectorQueue<VM::VMMemorySlice>,
+}
+
+impl<VM: VMBinding, P: GenerationalPlanExt<VM> + PlanTraceObject<VM>>
+    GenCardTableBarrierSemantics<VM, P>
+{
+    pub fn new(mmtk: &'static MMTK<VM>, plan: &'static P) -> Self {
+        Self {
+            mmtk,
+            plan,
+            region_modbuf: VectorQueue::new(),
+        }
+    }
+
+    fn flush_region_modbuf(&mut self) {
+        let buf = self.region_modbuf.take();
+        if !buf.is_empty() {
+            self.mmtk.scheduler.work_buckets[WorkBucketStage::Closure].add(ProcessRegionModBuf::<
+                GenNurseryProcessEdges<VM, P>,
+            >::new(buf));
+        }
+    }
+}
+
+impl<VM: VMBinding, P: GenerationalPlanExt<VM> + PlanTraceObject<VM>> BarrierSemantics
+    for GenCardTableBarrierSemantics<VM, P>
+{
+    type VM = VM;
+
+    fn flush(&mut self) {
+        self.flush_region_modbuf();
+    }
+
+    fn object_reference_write_slow(
+        &mut self,
+        src: ObjectReference,
+        _slot: VM::VMEdge,
+        _target: ObjectReference,
+    ) {
+        card_table::mark_card(src.to_address::<VM>());
+    }
+
+    fn memory_region_copy_slow(&mut self, _src: VM::VMMemorySlice, dst: VM::VMMemorySlice) {
+        // Only enqueue array slices in mature spaces
+        if !self.plan.is_address_in_nursery(dst.start()) {
+            debug_assert_eq!(
+                dst.bytes() & (BYTES_IN_ADDRESS - 1),
+                0,
+                "bytes should be a multiple of words"
+            );
+            self.region_modbuf.push(dst);
+            self.region_modbuf
+                .is_full()
+                .then(|| self.flush_region_modbuf());
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use super::gc_work::GenCopyGCWorkContext;
 use super::gc_work::GenCopyNurseryGCWorkContext;
 use super::mutator::ALLOCATOR_MAPPING;
+use crate::plan::generational::gc_work::GenNurseryProcessEdges;
 use crate::plan::generational::global::CommonGenPlan;
 use crate::plan::generational::global::GenerationalPlan;
 use crate::plan::generational::global::GenerationalPlanExt;
+use crate::plan::generational::pretenuring::AllocationSites;
 use crate::plan::global::BasePlan;
 use crate::plan::global::CommonPlan;
 use crate::plan::global::CreateGeneralPlanArgs;
@@ -88,6 +90,8 @@ impl<VM: VMBinding> Plan for GenCopy<VM> {
         } else {
             scheduler.schedule_common_work::<GenCopyNurseryGCWorkContext<VM>>(self);
         }
+        self.gen
+            .schedule_card_scanning::<GenNurseryProcessEdges<VM, Self>>(scheduler);
     }
 
     fn get_allocator_mapping(&self) -> &'static EnumMap<AllocationSemantics, AllocatorSelector> {
@@ -185,6 +189,14 @@ impl<VM: VMBinding> GenerationalPlan for GenCopy<VM> {
     fn last_collection_full_heap(&self) -> bool {
         self.gen.last_collection_full_heap()
     }
+
+    fn allocation_sites(&self) -> Option<&AllocationSites> {
+        Some(&self.gen.allocation_sites)
+    }
+
+    fn pretenure_allocator(&self) -> Option<AllocatorSelector> {
+        Some(super::mutator::PRETENURE_ALLOCATOR)
+    }
 }
 
 impl<VM: VMBinding> GenerationalPlanExt<VM> for GenCopy<VM> {
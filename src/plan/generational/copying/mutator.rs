@@ -1,17 +1,19 @@
 pub(super) use super::super::ALLOCATOR_MAPPING;
 use super::GenCopy;
-use crate::plan::barriers::ObjectBarrier;
-use crate::plan::generational::barrier::GenObjectBarrierSemantics;
+use crate::plan::generational::create_gen_barrier;
 use crate::plan::generational::create_gen_space_mapping;
 use crate::plan::mutator_context::Mutator;
 use crate::plan::mutator_context::MutatorConfig;
 use crate::plan::AllocationSemantics;
-use crate::util::alloc::allocators::Allocators;
+use crate::util::alloc::allocators::{AllocatorSelector, Allocators};
 use crate::util::alloc::BumpAllocator;
 use crate::util::{VMMutatorThread, VMWorkerThread};
 use crate::vm::VMBinding;
 use crate::MMTK;
 
+/// The allocator for pretenured objects, which allocates into the to-space.
+pub(super) const PRETENURE_ALLOCATOR: AllocatorSelector = AllocatorSelector::BumpPointer(1);
+
 pub fn gencopy_mutator_prepare<VM: VMBinding>(_mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
     // Do nothing
 }
@@ -26,6 +28,18 @@ pub fn gencopy_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: V
     .downcast_mut::<BumpAllocator<VM>>()
     .unwrap();
     bump_allocator.reset();
+
+    // rebind the pretenure allocator to the to-space, which may have been flipped
+    let pretenure_allocator = unsafe { mutator.allocators.get_allocator_mut(PRETENURE_ALLOCATOR) }
+        .downcast_mut::<BumpAllocator<VM>>()
+        .unwrap();
+    pretenure_allocator.rebind(
+        mutator
+            .plan
+            .downcast_ref::<GenCopy<VM>>()
+            .unwrap()
+            .tospace(),
+    );
 }
 
 pub fn create_gencopy_mutator<VM: VMBinding>(
@@ -35,18 +49,22 @@ pub fn create_gencopy_mutator<VM: VMBinding>(
     let gencopy = mmtk.plan.downcast_ref::<GenCopy<VM>>().unwrap();
     let config = MutatorConfig {
         allocator_mapping: &ALLOCATOR_MAPPING,
-        space_mapping: Box::new(create_gen_space_mapping(&*mmtk.plan, &gencopy.gen.nursery)),
+        space_mapping: Box::new({
+            let mut vec = create_gen_space_mapping(&*mmtk.plan, &gencopy.gen.nursery);
+            vec.push((PRETENURE_ALLOCATOR, gencopy.tospace()));
+            vec
+        }),
         prepare_func: &gencopy_mutator_prepare,
         release_func: &gencopy_mutator_release,
     };
 
     Mutator {
         allocators: Allocators::<VM>::new(mutator_tls, &*mmtk.plan, &config.space_mapping),
-        barrier: Box::new(ObjectBarrier::new(GenObjectBarrierSemantics::new(
-            mmtk, gencopy,
-        ))),
+        barrier: create_gen_barrier(mmtk, gencopy),
         mutator_tls,
         config,
+        los_redirects: 0,
+        pretenured_objects: 0,
         plan: gencopy,
     }
 }
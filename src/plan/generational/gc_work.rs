@@ -1,7 +1,11 @@
 use atomic::Ordering;
 
+use crate::mmtk::SFT_MAP;
 use crate::plan::PlanTraceObject;
-use crate::scheduler::{gc_work::*, GCWork, GCWorker};
+use crate::scheduler::{gc_work::*, GCWork, GCWorker, WorkBucketStage};
+use crate::util::card_table;
+use crate::util::heap::chunk_map::{Chunk, ChunkState};
+use crate::util::linear_scan::{DefaultObjectSize, ObjectIterator, Region};
 use crate::util::ObjectReference;
 use crate::vm::edge_shape::{Edge, MemorySlice};
 use crate::vm::*;
@@ -92,6 +96,16 @@ impl<E: ProcessEdgesWork> ProcessModBuf<E> {
 
 impl<E: ProcessEdgesWork> GCWork<E::VM> for ProcessModBuf<E> {
     fn do_work(&mut self, worker: &mut GCWorker<E::VM>, mmtk: &'static MMTK<E::VM>) {
+        // An object may be logged before the read-only space is sealed. The sealed objects are
+        // scanned as roots, and their pages cannot be written.
+        #[cfg(feature = "ro_space")]
+        {
+            use crate::policy::space::Space;
+            let ro_space = &mmtk.plan.base().ro_space;
+            if ro_space.is_sealed() {
+                self.modbuf.retain(|obj| !ro_space.in_space(*obj));
+            }
+        }
         // Flip the per-object unlogged bits to "unlogged" state.
         for obj in &self.modbuf {
             <E::VM as VMBinding>::VMObjectModel::GLOBAL_LOG_BIT_SPEC.store_atomic::<E::VM, u8>(
@@ -148,3 +162,90 @@ impl<E: ProcessEdgesWork> GCWork<E::VM> for ProcessRegionModBuf<E> {
         }
     }
 }
+
+/// Find the chunks that are covered by the card table in the spaces of the plan. In a nursery GC,
+/// scan each chunk that is not in the nursery in a separate [`ScanCardsInChunk`] packet, as the
+/// whole nursery is traced anyway. In a full heap GC, clean the cards in all the chunks, as no
+/// mature object points to the nursery after the GC.
+pub struct ScanDirtyCards<E: ProcessEdgesWork>(PhantomData<E>);
+
+impl<E: ProcessEdgesWork> ScanDirtyCards<E> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<E: ProcessEdgesWork> GCWork<E::VM> for ScanDirtyCards<E> {
+    fn do_work(&mut self, _worker: &mut GCWorker<E::VM>, mmtk: &'static MMTK<E::VM>) {
+        let plan = mmtk.plan.generational().unwrap();
+        let mut chunks = vec![];
+        for space in mmtk.plan.get_spaces() {
+            if let Some(chunk_map) = space.common().card_table_chunks.as_ref() {
+                chunks.extend(
+                    chunk_map
+                        .all_chunks()
+                        // The chunk map of a space is only mapped for the chunks the space owns.
+                        .filter(|chunk| {
+                            SFT_MAP.get_checked(chunk.start()).name() == space.get_name()
+                        })
+                        .filter(|chunk| chunk_map.get(*chunk) == ChunkState::Allocated),
+                );
+            }
+        }
+        if !plan.is_current_gc_nursery() {
+            chunks.into_iter().for_each(card_table::clear_chunk);
+            return;
+        }
+        let packets: Vec<Box<dyn GCWork<E::VM>>> = chunks
+            .into_iter()
+            .filter(|chunk| !plan.is_address_in_nursery(chunk.start()))
+            .map(|chunk| Box::new(ScanCardsInChunk::<E>::new(chunk)) as Box<dyn GCWork<E::VM>>)
+            .collect();
+        mmtk.scheduler.work_buckets[WorkBucketStage::Closure].bulk_add(packets);
+    }
+}
+
+/// Clean the dirty cards in a chunk, and scan the live objects that start in those cards. The
+/// objects are found by their alloc bits.
+pub struct ScanCardsInChunk<E: ProcessEdgesWork> {
+    chunk: Chunk,
+    phantom: PhantomData<E>,
+}
+
+impl<E: ProcessEdgesWork> ScanCardsInChunk<E> {
+    pub fn new(chunk: Chunk) -> Self {
+        Self {
+            chunk,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<E: ProcessEdgesWork> GCWork<E::VM> for ScanCardsInChunk<E> {
+    fn do_work(&mut self, worker: &mut GCWorker<E::VM>, mmtk: &'static MMTK<E::VM>) {
+        let mut objects = vec![];
+        let mut card = self.chunk.start();
+        while card < self.chunk.end() {
+            if card_table::is_dirty(card) {
+                card_table::clear(card);
+                // The dead objects in the mature spaces may point to the memory that has been
+                // reclaimed. Do not scan them.
+                objects.extend(
+                    ObjectIterator::<E::VM, DefaultObjectSize<E::VM>, true>::new(
+                        card,
+                        card + card_table::BYTES_IN_CARD,
+                    )
+                    .filter(|object| object.is_live()),
+                );
+            }
+            card += card_table::BYTES_IN_CARD;
+        }
+        if !objects.is_empty() {
+            GCWork::do_work(
+                &mut ScanObjects::<E>::new(objects, false, false),
+                worker,
+                mmtk,
+            )
+        }
+    }
+}
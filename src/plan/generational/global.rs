@@ -1,3 +1,6 @@
+use super::gc_work::ScanDirtyCards;
+use super::pretenuring::AllocationSites;
+use crate::plan::barriers::BarrierSelector;
 use crate::plan::global::CommonPlan;
 use crate::plan::global::CreateSpecificPlanArgs;
 use crate::plan::ObjectQueue;
@@ -5,6 +8,7 @@ use crate::plan::Plan;
 use crate::policy::copyspace::CopySpace;
 use crate::policy::space::Space;
 use crate::scheduler::*;
+use crate::util::alloc::AllocatorSelector;
 use crate::util::copy::CopySemantics;
 use crate::util::heap::VMRequest;
 use crate::util::metadata::side_metadata::SideMetadataSanity;
@@ -34,6 +38,8 @@ pub struct CommonGenPlan<VM: VMBinding> {
     /// Is next GC full heap?
     pub next_gc_full_heap: AtomicBool,
     pub full_heap_gc_count: Arc<Mutex<EventCounter>>,
+    /// The survival rates of allocation sites, which decide the sites to pretenure.
+    pub allocation_sites: AllocationSites,
 }
 
 impl<VM: VMBinding> CommonGenPlan<VM> {
@@ -46,6 +52,7 @@ impl<VM: VMBinding> CommonGenPlan<VM> {
             ),
             true,
         );
+        let allocation_sites = AllocationSites::new(&args.global_args.options);
         let common = CommonPlan::new(args);
 
         let full_heap_gc_count = common.base.stats.new_event_counter("majorGC", true, true);
@@ -56,6 +63,7 @@ impl<VM: VMBinding> CommonGenPlan<VM> {
             gc_full_heap: AtomicBool::default(),
             next_gc_full_heap: AtomicBool::new(false),
             full_heap_gc_count,
+            allocation_sites,
         }
     }
 
@@ -77,8 +85,13 @@ impl<VM: VMBinding> CommonGenPlan<VM> {
         let full_heap = !self.is_current_gc_nursery();
         if full_heap {
             self.full_heap_gc_count.lock().unwrap().inc();
+        } else {
+            self.common.base.pause_time.set_collected_pages(
+                self.nursery.reserved_pages() + self.common.los.nursery_pages(),
+            );
         }
         self.common.prepare(tls, full_heap);
+        self.allocation_sites.prepare(!full_heap);
         self.nursery.prepare(true);
         self.nursery
             .set_copy_for_sft_trace(Some(CopySemantics::PromoteToMature));
@@ -88,6 +101,7 @@ impl<VM: VMBinding> CommonGenPlan<VM> {
     pub fn release(&mut self, tls: VMWorkerThread) {
         let full_heap = !self.is_current_gc_nursery();
         self.common.release(tls, full_heap);
+        self.allocation_sites.release();
         self.nursery.release();
     }
 
@@ -112,7 +126,11 @@ impl<VM: VMBinding> CommonGenPlan<VM> {
         space: Option<&dyn Space<VM>>,
     ) -> bool {
         let cur_nursery = self.nursery.reserved_pages();
-        let max_nursery = self.common.base.options.get_max_nursery_pages();
+        let max_nursery = self
+            .common
+            .base
+            .pause_time
+            .nursery_pages(&self.common.base.options);
         let nursery_full = cur_nursery >= max_nursery;
         trace!(
             "nursery_full = {:?} (nursery = {}, max_nursery = {})",
@@ -243,6 +261,8 @@ impl<VM: VMBinding> CommonGenPlan<VM> {
     ) -> ObjectReference {
         // Evacuate nursery objects
         if self.nursery.in_space(object) {
+            self.allocation_sites
+                .trace_nursery_object(object.to_object_start::<VM>());
             return self.nursery.trace_object::<Q>(
                 queue,
                 object,
@@ -262,6 +282,18 @@ impl<VM: VMBinding> CommonGenPlan<VM> {
         !self.gc_full_heap.load(Ordering::SeqCst)
     }
 
+    /// Schedule the work to scan the dirty cards in a nursery GC, or to clean the cards in a full
+    /// heap GC, if the plan uses the card table barrier. This should be called for both kinds of
+    /// GCs. `E` should be the process edges work for the nursery GC.
+    pub fn schedule_card_scanning<E: ProcessEdgesWork<VM = VM>>(
+        &self,
+        scheduler: &GCWorkScheduler<VM>,
+    ) {
+        if super::ACTIVE_BARRIER == BarrierSelector::CardTableBarrier {
+            scheduler.work_buckets[WorkBucketStage::Closure].add(ScanDirtyCards::<E>::new());
+        }
+    }
+
     /// Check a plan to see if the next GC should be a full heap GC.
     ///
     /// Note that this function should be called after all spaces have been released. This is
@@ -328,6 +360,17 @@ pub trait GenerationalPlan: Plan {
 
     /// Force the next collection to be full heap.
     fn force_full_heap_collection(&self);
+
+    /// Return the survival rates of allocation sites, if the plan tracks them for pretenuring.
+    fn allocation_sites(&self) -> Option<&AllocationSites> {
+        None
+    }
+
+    /// Return the mutator allocator for the objects from pretenured allocation sites, which
+    /// allocates into the mature space. `None` if the plan does not pretenure objects.
+    fn pretenure_allocator(&self) -> Option<AllocatorSelector> {
+        None
+    }
 }
 
 /// This trait is the extension trait for [`GenerationalPlan`] (see Rust's extension trait pattern).
@@ -1,7 +1,9 @@
 use super::gc_work::GenImmixMatureGCWorkContext;
 use super::gc_work::GenImmixNurseryGCWorkContext;
+use crate::plan::generational::gc_work::GenNurseryProcessEdges;
 use crate::plan::generational::global::CommonGenPlan;
 use crate::plan::generational::global::GenerationalPlan;
+use crate::plan::generational::pretenuring::AllocationSites;
 use crate::plan::global::BasePlan;
 use crate::plan::global::CommonPlan;
 use crate::plan::global::CreateGeneralPlanArgs;
@@ -125,6 +127,8 @@ impl<VM: VMBinding> Plan for GenImmix<VM> {
                 GenImmixMatureGCWorkContext<VM, TRACE_KIND_DEFRAG>,
             >(self, &self.immix, scheduler);
         }
+        self.gen
+            .schedule_card_scanning::<GenNurseryProcessEdges<VM, Self>>(scheduler);
     }
 
     fn get_allocator_mapping(&self) -> &'static EnumMap<AllocationSemantics, AllocatorSelector> {
@@ -136,6 +140,11 @@ impl<VM: VMBinding> Plan for GenImmix<VM> {
         self.gen.prepare(tls);
         if full_heap {
             self.immix.prepare(full_heap);
+            if self.immix.in_defrag() {
+                self.base()
+                    .pause_time
+                    .set_evacuated_pages(self.immix.defrag_evacuated_pages());
+            }
         }
     }
 
@@ -215,6 +224,14 @@ impl<VM: VMBinding> GenerationalPlan for GenImmix<VM> {
     fn last_collection_full_heap(&self) -> bool {
         self.gen.last_collection_full_heap()
     }
+
+    fn allocation_sites(&self) -> Option<&AllocationSites> {
+        Some(&self.gen.allocation_sites)
+    }
+
+    fn pretenure_allocator(&self) -> Option<AllocatorSelector> {
+        Some(super::mutator::PRETENURE_ALLOCATOR)
+    }
 }
 
 impl<VM: VMBinding> crate::plan::generational::global::GenerationalPlanExt<VM> for GenImmix<VM> {
@@ -243,6 +260,7 @@ impl<VM: VMBinding> GenImmix<VM> {
                 // We don't need to unlog objects at tracing. Instead, we unlog objects at copying.
                 // Any object is moved into the mature space, or is copied inside the mature space. We will unlog it.
                 unlog_object_when_traced: false,
+                rc_enabled: false,
             },
         );
 
@@ -1,18 +1,26 @@
 pub(super) use super::super::ALLOCATOR_MAPPING;
-use crate::plan::barriers::ObjectBarrier;
-use crate::plan::generational::barrier::GenObjectBarrierSemantics;
+use crate::plan::generational::create_gen_barrier;
 use crate::plan::generational::create_gen_space_mapping;
 use crate::plan::generational::immix::GenImmix;
 use crate::plan::mutator_context::Mutator;
 use crate::plan::mutator_context::MutatorConfig;
 use crate::plan::AllocationSemantics;
-use crate::util::alloc::allocators::Allocators;
-use crate::util::alloc::BumpAllocator;
+use crate::util::alloc::allocators::{AllocatorSelector, Allocators};
+use crate::util::alloc::{BumpAllocator, ImmixAllocator};
 use crate::util::{VMMutatorThread, VMWorkerThread};
 use crate::vm::VMBinding;
 use crate::MMTK;
 
-pub fn genimmix_mutator_prepare<VM: VMBinding>(_mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {}
+/// The allocator for pretenured objects, which allocates into the immix space.
+pub(super) const PRETENURE_ALLOCATOR: AllocatorSelector = AllocatorSelector::Immix(0);
+
+pub fn genimmix_mutator_prepare<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
+    // return the block that the pretenure allocator zeroed ahead before the immix space is swept
+    unsafe { mutator.allocators.get_allocator_mut(PRETENURE_ALLOCATOR) }
+        .downcast_mut::<ImmixAllocator<VM>>()
+        .unwrap()
+        .prepare();
+}
 
 pub fn genimmix_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
     // reset nursery allocator
@@ -24,6 +32,12 @@ pub fn genimmix_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls:
     .downcast_mut::<BumpAllocator<VM>>()
     .unwrap();
     bump_allocator.reset();
+
+    // reset the pretenure allocator, as a full heap GC may reclaim or defragment its block
+    unsafe { mutator.allocators.get_allocator_mut(PRETENURE_ALLOCATOR) }
+        .downcast_mut::<ImmixAllocator<VM>>()
+        .unwrap()
+        .reset();
 }
 
 pub fn create_genimmix_mutator<VM: VMBinding>(
@@ -33,18 +47,22 @@ pub fn create_genimmix_mutator<VM: VMBinding>(
     let genimmix = mmtk.plan.downcast_ref::<GenImmix<VM>>().unwrap();
     let config = MutatorConfig {
         allocator_mapping: &ALLOCATOR_MAPPING,
-        space_mapping: Box::new(create_gen_space_mapping(&*mmtk.plan, &genimmix.gen.nursery)),
+        space_mapping: Box::new({
+            let mut vec = create_gen_space_mapping(&*mmtk.plan, &genimmix.gen.nursery);
+            vec.push((PRETENURE_ALLOCATOR, &genimmix.immix));
+            vec
+        }),
         prepare_func: &genimmix_mutator_prepare,
         release_func: &genimmix_mutator_release,
     };
 
     Mutator {
         allocators: Allocators::<VM>::new(mutator_tls, &*mmtk.plan, &config.space_mapping),
-        barrier: Box::new(ObjectBarrier::new(GenObjectBarrierSemantics::new(
-            mmtk, genimmix,
-        ))),
+        barrier: create_gen_barrier(mmtk, genimmix),
         mutator_tls,
         config,
+        los_redirects: 0,
+        pretenured_objects: 0,
         plan: genimmix,
     }
 }
@@ -0,0 +1,260 @@
+use super::gc_work::GenMarkSweepMatureGCWorkContext;
+use super::gc_work::GenMarkSweepNurseryGCWorkContext;
+use crate::plan::generational::gc_work::GenNurseryProcessEdges;
+use crate::plan::generational::global::CommonGenPlan;
+use crate::plan::generational::global::GenerationalPlan;
+use crate::plan::generational::pretenuring::AllocationSites;
+use crate::plan::global::BasePlan;
+use crate::plan::global::CommonPlan;
+use crate::plan::global::CreateGeneralPlanArgs;
+use crate::plan::global::CreateSpecificPlanArgs;
+use crate::plan::global::GcStatus;
+use crate::plan::AllocationSemantics;
+use crate::plan::Plan;
+use crate::plan::PlanConstraints;
+use crate::policy::marksweepspace::native_ms::MarkSweepSpace;
+use crate::policy::space::Space;
+use crate::scheduler::GCWorkScheduler;
+use crate::scheduler::GCWorker;
+use crate::util::alloc::allocators::AllocatorSelector;
+use crate::util::copy::*;
+use crate::util::heap::VMRequest;
+use crate::util::Address;
+use crate::util::ObjectReference;
+use crate::util::VMWorkerThread;
+use crate::vm::*;
+use crate::ObjectQueue;
+
+use enum_map::EnumMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use mmtk_macros::PlanTraceObject;
+
+/// Generational mark sweep. This implements a two-generation collector with a copying nursery
+/// and a non-moving mark sweep mature space. Objects that survive a nursery GC are promoted into
+/// the free list of the mark sweep space, so the mature space keeps the allocation locality of
+/// size-segregated free lists.
+#[derive(PlanTraceObject)]
+pub struct GenMarkSweep<VM: VMBinding> {
+    /// Generational plan, which includes a nursery space and operations related with nursery.
+    #[fallback_trace]
+    pub gen: CommonGenPlan<VM>,
+    /// A mark sweep space as the mature space.
+    #[trace]
+    pub ms: MarkSweepSpace<VM>,
+    /// Whether the last GC was a full heap GC
+    pub last_gc_was_full_heap: AtomicBool,
+}
+
+pub const GENMS_CONSTRAINTS: PlanConstraints = PlanConstraints {
+    // Objects promoted from the nursery have to fit in a cell of the mark sweep space.
+    max_non_los_default_alloc_bytes: crate::util::rust_util::min_of_usize(
+        crate::policy::marksweepspace::native_ms::MAX_OBJECT_SIZE,
+        crate::plan::generational::GEN_CONSTRAINTS.max_non_los_default_alloc_bytes,
+    ),
+    ..crate::plan::generational::GEN_CONSTRAINTS
+};
+
+impl<VM: VMBinding> Plan for GenMarkSweep<VM> {
+    type VM = VM;
+
+    fn constraints(&self) -> &'static PlanConstraints {
+        &GENMS_CONSTRAINTS
+    }
+
+    fn create_copy_config(&'static self) -> CopyConfig<Self::VM> {
+        use enum_map::enum_map;
+        CopyConfig {
+            copy_mapping: enum_map! {
+                CopySemantics::PromoteToMature => CopySelector::MarkSweep(0),
+                _ => CopySelector::Unused,
+            },
+            space_mapping: vec![(CopySelector::MarkSweep(0), &self.ms)],
+            constraints: &GENMS_CONSTRAINTS,
+        }
+    }
+
+    fn last_collection_was_exhaustive(&self) -> bool {
+        self.last_gc_was_full_heap.load(Ordering::Relaxed)
+    }
+
+    fn collection_required(&self, space_full: bool, space: Option<&dyn Space<Self::VM>>) -> bool
+    where
+        Self: Sized,
+    {
+        self.gen.collection_required(self, space_full, space)
+    }
+
+    fn get_spaces(&self) -> Vec<&dyn Space<Self::VM>> {
+        let mut ret = self.gen.get_spaces();
+        ret.push(&self.ms);
+        ret
+    }
+
+    fn schedule_collection(&'static self, scheduler: &GCWorkScheduler<Self::VM>) {
+        let is_full_heap = self.requires_full_heap_collection();
+        self.base().set_collection_kind::<Self>(self);
+        self.base().set_gc_status(GcStatus::GcPrepare);
+        if is_full_heap {
+            debug!("Full heap GC");
+            scheduler.schedule_common_work::<GenMarkSweepMatureGCWorkContext<VM>>(self);
+        } else {
+            debug!("Nursery GC");
+            scheduler.schedule_common_work::<GenMarkSweepNurseryGCWorkContext<VM>>(self);
+        }
+        self.gen
+            .schedule_card_scanning::<GenNurseryProcessEdges<VM, Self>>(scheduler);
+    }
+
+    fn get_allocator_mapping(&self) -> &'static EnumMap<AllocationSemantics, AllocatorSelector> {
+        &super::mutator::ALLOCATOR_MAPPING
+    }
+
+    fn prepare(&mut self, tls: VMWorkerThread) {
+        let full_heap = !self.gen.is_current_gc_nursery();
+        self.gen.prepare(tls);
+        if full_heap {
+            self.ms.prepare();
+        }
+    }
+
+    fn release(&mut self, tls: VMWorkerThread) {
+        let full_heap = !self.gen.is_current_gc_nursery();
+        self.gen.release(tls);
+        if full_heap {
+            self.ms.release();
+        }
+        self.last_gc_was_full_heap
+            .store(full_heap, Ordering::Relaxed);
+    }
+
+    fn end_of_gc(&mut self, _tls: VMWorkerThread) {
+        self.gen
+            .set_next_gc_full_heap(CommonGenPlan::should_next_gc_be_full_heap(self));
+    }
+
+    fn get_collection_reserved_pages(&self) -> usize {
+        self.gen.get_collection_reserved_pages()
+    }
+
+    fn get_used_pages(&self) -> usize {
+        self.gen.get_used_pages() + self.ms.reserved_pages()
+    }
+
+    /// Return the number of pages available for allocation. Assuming all future allocations goes to nursery.
+    fn get_available_pages(&self) -> usize {
+        // super.get_available_pages() / 2 to reserve pages for copying
+        (self
+            .get_total_pages()
+            .saturating_sub(self.get_reserved_pages()))
+            >> 1
+    }
+
+    fn base(&self) -> &BasePlan<VM> {
+        &self.gen.common.base
+    }
+
+    fn common(&self) -> &CommonPlan<VM> {
+        &self.gen.common
+    }
+
+    fn generational(&self) -> Option<&dyn GenerationalPlan<VM = VM>> {
+        Some(self)
+    }
+}
+
+impl<VM: VMBinding> GenerationalPlan for GenMarkSweep<VM> {
+    fn is_current_gc_nursery(&self) -> bool {
+        self.gen.is_current_gc_nursery()
+    }
+
+    fn is_object_in_nursery(&self, object: ObjectReference) -> bool {
+        self.gen.nursery.in_space(object)
+    }
+
+    fn is_address_in_nursery(&self, addr: Address) -> bool {
+        self.gen.nursery.address_in_space(addr)
+    }
+
+    fn get_mature_physical_pages_available(&self) -> usize {
+        self.ms.available_physical_pages()
+    }
+
+    fn get_mature_reserved_pages(&self) -> usize {
+        self.ms.reserved_pages()
+    }
+
+    fn force_full_heap_collection(&self) {
+        self.gen.force_full_heap_collection()
+    }
+
+    fn last_collection_full_heap(&self) -> bool {
+        self.gen.last_collection_full_heap()
+    }
+
+    fn allocation_sites(&self) -> Option<&AllocationSites> {
+        Some(&self.gen.allocation_sites)
+    }
+
+    fn pretenure_allocator(&self) -> Option<AllocatorSelector> {
+        Some(super::mutator::PRETENURE_ALLOCATOR)
+    }
+}
+
+impl<VM: VMBinding> crate::plan::generational::global::GenerationalPlanExt<VM>
+    for GenMarkSweep<VM>
+{
+    fn trace_object_nursery<Q: ObjectQueue>(
+        &self,
+        queue: &mut Q,
+        object: ObjectReference,
+        worker: &mut GCWorker<VM>,
+    ) -> ObjectReference {
+        self.gen.trace_object_nursery(queue, object, worker)
+    }
+}
+
+impl<VM: VMBinding> GenMarkSweep<VM> {
+    pub fn new(args: CreateGeneralPlanArgs<VM>) -> Self {
+        let mut global_side_metadata_specs =
+            crate::plan::generational::new_generational_global_metadata_specs::<VM>();
+        MarkSweepSpace::<VM>::extend_global_side_metadata_specs(&mut global_side_metadata_specs);
+
+        let mut plan_args = CreateSpecificPlanArgs {
+            global_args: args,
+            constraints: &GENMS_CONSTRAINTS,
+            global_side_metadata_specs,
+        };
+        let ms = MarkSweepSpace::new(plan_args.get_space_args(
+            "ms_mature",
+            true,
+            VMRequest::discontiguous(),
+        ));
+
+        let genms = GenMarkSweep {
+            gen: CommonGenPlan::new(plan_args),
+            ms,
+            last_gc_was_full_heap: AtomicBool::new(false),
+        };
+
+        // Use SideMetadataSanity to check if each spec is valid. This is also needed for check
+        // side metadata in extreme_assertions.
+        {
+            use crate::util::metadata::side_metadata::SideMetadataSanity;
+            let mut side_metadata_sanity_checker = SideMetadataSanity::new();
+            genms
+                .gen
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+            genms
+                .ms
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+        }
+
+        genms
+    }
+
+    fn requires_full_heap_collection(&self) -> bool {
+        self.gen.requires_full_heap_collection(self)
+    }
+}
@@ -0,0 +1,77 @@
+pub(super) use super::super::ALLOCATOR_MAPPING;
+use super::GenMarkSweep;
+use crate::plan::generational::create_gen_barrier;
+use crate::plan::generational::create_gen_space_mapping;
+use crate::plan::mutator_context::Mutator;
+use crate::plan::mutator_context::MutatorConfig;
+use crate::plan::AllocationSemantics;
+use crate::util::alloc::allocators::{AllocatorSelector, Allocators};
+use crate::util::alloc::{BumpAllocator, FreeListAllocator};
+use crate::util::{VMMutatorThread, VMWorkerThread};
+use crate::vm::VMBinding;
+use crate::MMTK;
+
+/// The allocator for pretenured objects, which allocates into the mark sweep space.
+pub(super) const PRETENURE_ALLOCATOR: AllocatorSelector = AllocatorSelector::FreeList(0);
+
+fn get_pretenure_allocator_mut<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+) -> &mut FreeListAllocator<VM> {
+    unsafe { mutator.allocators.get_allocator_mut(PRETENURE_ALLOCATOR) }
+        .downcast_mut::<FreeListAllocator<VM>>()
+        .unwrap()
+}
+
+/// The mark sweep space is only prepared and released in a full heap GC, and so is the pretenure allocator.
+fn is_full_heap_gc<VM: VMBinding>(mutator: &Mutator<VM>) -> bool {
+    !mutator.plan.generational().unwrap().is_current_gc_nursery()
+}
+
+pub fn genms_mutator_prepare<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
+    if is_full_heap_gc(mutator) {
+        get_pretenure_allocator_mut(mutator).prepare();
+    }
+}
+
+pub fn genms_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
+    // reset nursery allocator
+    let bump_allocator = unsafe {
+        mutator
+            .allocators
+            .get_allocator_mut(mutator.config.allocator_mapping[AllocationSemantics::Default])
+    }
+    .downcast_mut::<BumpAllocator<VM>>()
+    .unwrap();
+    bump_allocator.reset();
+
+    if is_full_heap_gc(mutator) {
+        get_pretenure_allocator_mut(mutator).release();
+    }
+}
+
+pub fn create_genms_mutator<VM: VMBinding>(
+    mutator_tls: VMMutatorThread,
+    mmtk: &'static MMTK<VM>,
+) -> Mutator<VM> {
+    let genms = mmtk.plan.downcast_ref::<GenMarkSweep<VM>>().unwrap();
+    let config = MutatorConfig {
+        allocator_mapping: &ALLOCATOR_MAPPING,
+        space_mapping: Box::new({
+            let mut vec = create_gen_space_mapping(&*mmtk.plan, &genms.gen.nursery);
+            vec.push((PRETENURE_ALLOCATOR, &genms.ms));
+            vec
+        }),
+        prepare_func: &genms_mutator_prepare,
+        release_func: &genms_mutator_release,
+    };
+
+    Mutator {
+        allocators: Allocators::<VM>::new(mutator_tls, &*mmtk.plan, &config.space_mapping),
+        barrier: create_gen_barrier(mmtk, genms),
+        mutator_tls,
+        config,
+        los_redirects: 0,
+        pretenured_objects: 0,
+        plan: genms,
+    }
+}
@@ -1,10 +1,11 @@
 use enum_map::EnumMap;
 
 ///! Generational plans
-use crate::plan::barriers::BarrierSelector;
+use crate::plan::barriers::{Barrier, BarrierSelector, CardTableBarrier, NoBarrier, ObjectBarrier};
 use crate::plan::mutator_context::create_allocator_mapping;
 use crate::plan::AllocationSemantics;
 use crate::plan::PlanConstraints;
+use crate::plan::PlanTraceObject;
 use crate::policy::copyspace::CopySpace;
 use crate::policy::space::Space;
 use crate::util::alloc::AllocatorSelector;
@@ -13,6 +14,10 @@ use crate::util::metadata::side_metadata::SideMetadataSpec;
 use crate::vm::ObjectModel;
 use crate::vm::VMBinding;
 use crate::Plan;
+use crate::MMTK;
+
+use barrier::{GenCardTableBarrierSemantics, GenObjectBarrierSemantics};
+use global::GenerationalPlanExt;
 
 use super::mutator_context::create_space_mapping;
 use super::mutator_context::ReservedAllocators;
@@ -24,19 +29,38 @@ pub mod barrier;
 pub mod copying;
 /// Generational immix (GenImmix)
 pub mod immix;
+/// Generational mark sweep (GenMarkSweep)
+pub mod marksweep;
 
 // Common generational code
 
 pub(super) mod gc_work;
 pub(super) mod global;
+pub(super) mod pretenuring;
 
+/// The barrier for generational plans. This is the object barrier, or the card table barrier with
+/// the feature `card_table_barrier`.
+///
 /// # Barrier overhead measurement:
 ///  - Set `FULL_NURSERY_GC` to `true`.
 /// ## 1. Baseline: No barrier
 ///  - Set `ACTIVE_BARRIER` to `BarrierSelector::NoBarrier`.
 /// ## 2. Object barrier
-///  - Set `ACTIVE_BARRIER` to `BarrierSelector::ObjectBarrier`.
-pub const ACTIVE_BARRIER: BarrierSelector = BarrierSelector::ObjectBarrier;
+///  - Build without the feature `card_table_barrier`.
+/// ## 3. Card table barrier
+///  - Build with the feature `card_table_barrier`.
+pub const ACTIVE_BARRIER: BarrierSelector = if cfg!(feature = "card_table_barrier") {
+    BarrierSelector::CardTableBarrier
+} else {
+    BarrierSelector::ObjectBarrier
+};
+// Generational plans only support the following barriers.
+const _: () = assert!(
+    ACTIVE_BARRIER.equals(BarrierSelector::NoBarrier)
+        || ACTIVE_BARRIER.equals(BarrierSelector::ObjectBarrier)
+        || ACTIVE_BARRIER.equals(BarrierSelector::CardTableBarrier),
+    "Generational plans do not support the selected barrier"
+);
 /// Full heap collection as nursery GC.
 pub const FULL_NURSERY_GC: bool = false;
 
@@ -49,7 +73,9 @@ pub const GEN_CONSTRAINTS: PlanConstraints = PlanConstraints {
     needs_log_bit: ACTIVE_BARRIER.equals(BarrierSelector::ObjectBarrier),
     barrier: ACTIVE_BARRIER,
     // We may trace duplicate edges in sticky immix (or any plan that uses object remembering barrier). See https://github.com/mmtk/mmtk-core/issues/743.
-    may_trace_duplicate_edges: ACTIVE_BARRIER.equals(BarrierSelector::ObjectBarrier),
+    // The card table barrier may scan a mature object more than once as well.
+    may_trace_duplicate_edges: ACTIVE_BARRIER.equals(BarrierSelector::ObjectBarrier)
+        || ACTIVE_BARRIER.equals(BarrierSelector::CardTableBarrier),
     max_non_los_default_alloc_bytes: crate::util::rust_util::min_of_usize(
         crate::plan::plan_constraints::MAX_NON_LOS_ALLOC_BYTES_COPYING_PLAN,
         crate::util::options::NURSERY_SIZE,
@@ -62,14 +88,43 @@ pub const GEN_CONSTRAINTS: PlanConstraints = PlanConstraints {
 pub fn new_generational_global_metadata_specs<VM: VMBinding>() -> Vec<SideMetadataSpec> {
     let specs = if ACTIVE_BARRIER == BarrierSelector::ObjectBarrier {
         crate::util::metadata::extract_side_metadata(&[*VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC])
+    } else if ACTIVE_BARRIER == BarrierSelector::CardTableBarrier {
+        // The feature `card_table_barrier` enables `global_alloc_bit`, so we can find the objects
+        // in dirty cards.
+        vec![
+            crate::util::card_table::CARD_TABLE_SIDE_METADATA_SPEC,
+            crate::util::card_table::CARD_TABLE_CHUNK_MAP_SIDE_METADATA_SPEC,
+        ]
     } else {
         vec![]
     };
     SideMetadataContext::new_global_specs(&specs)
 }
 
+/// Create the barrier for a generational plan based on `ACTIVE_BARRIER`.
+fn create_gen_barrier<VM: VMBinding, P: GenerationalPlanExt<VM> + PlanTraceObject<VM>>(
+    mmtk: &'static MMTK<VM>,
+    plan: &'static P,
+) -> Box<dyn Barrier<VM>> {
+    if ACTIVE_BARRIER.equals(BarrierSelector::CardTableBarrier) {
+        Box::new(CardTableBarrier::new(GenCardTableBarrierSemantics::new(
+            mmtk, plan,
+        )))
+    } else if ACTIVE_BARRIER.equals(BarrierSelector::NoBarrier) {
+        Box::new(NoBarrier)
+    } else {
+        Box::new(ObjectBarrier::new(GenObjectBarrierSemantics::new(
+            mmtk, plan,
+        )))
+    }
+}
+
+// Each plan uses BumpPointer(0) for the nursery. The other allocators are for pretenured objects in the
+// mature space, and each plan uses the one that matches its mature space.
 const RESERVED_ALLOCATORS: ReservedAllocators = ReservedAllocators {
-    n_bump_pointer: 1,
+    n_bump_pointer: 2,
+    n_immix: 1,
+    n_free_list: 1,
     ..ReservedAllocators::DEFAULT
 };
 
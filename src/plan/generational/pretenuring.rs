@@ -0,0 +1,206 @@
+//! Allocation-site pretenuring for generational plans.
+//!
+//! A binding may identify the allocation site of an object when it allocates with
+//! [`alloc_with_site`](crate::memory_manager::alloc_with_site). A generational plan samples such
+//! allocations (one every `pretenure_sample_bytes` bytes), and checks in the next nursery GC
+//! whether the sampled objects survived. A site whose objects survive at a rate higher than
+//! `pretenure_threshold` is pretenured: its `Default` allocations go to the mature space directly,
+//! so they are no longer copied out of the nursery.
+//!
+//! Samples are only checked in nursery GCs. A full heap GC traces the nursery in the same way as the
+//! other spaces, and the samples taken before a full heap GC are discarded. A site stays pretenured
+//! once it is pretenured, as its objects are no longer allocated in the nursery.
+
+use crate::util::options::Options;
+use crate::util::Address;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicIsize, Ordering};
+use std::sync::Mutex;
+
+/// An identifier of an allocation site, e.g. a bytecode index in a method. It is opaque to MMTk,
+/// and the binding decides how sites are numbered.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AllocationSite(pub u32);
+
+/// A site is not pretenured before it has this many samples.
+const MIN_SAMPLES: usize = 32;
+/// When a site has this many samples, its counts are halved, so the recent GCs weigh more.
+const DECAY_SAMPLES: usize = 1024;
+
+/// The survival statistics of an allocation site.
+#[derive(Default)]
+struct SiteStats {
+    sampled: usize,
+    survived: usize,
+    pretenured: bool,
+}
+
+/// An object sampled since the last GC.
+struct SampledObject {
+    start: Address,
+    site: AllocationSite,
+    survived: AtomicBool,
+}
+
+/// The allocation sites seen by a generational plan, and their survival rates in nursery GCs.
+pub struct AllocationSites {
+    sample_bytes: isize,
+    threshold: usize,
+    /// The bytes to allocate with sites before the next sample.
+    bytes_until_sample: AtomicIsize,
+    /// The objects sampled since the last GC, and their allocation sites.
+    samples: Mutex<Vec<(Address, AllocationSite)>>,
+    /// The objects sampled before the current nursery GC, sorted by their addresses.
+    tracing: Vec<SampledObject>,
+    /// The statistics of the sites. They are only updated in `release()` while the mutators are
+    /// stopped, so the mutators look up whether a site is pretenured without a lock.
+    stats: HashMap<AllocationSite, SiteStats>,
+}
+
+impl AllocationSites {
+    pub fn new(options: &Options) -> Self {
+        let sample_bytes = *options.pretenure_sample_bytes as isize;
+        AllocationSites {
+            sample_bytes,
+            threshold: *options.pretenure_threshold,
+            bytes_until_sample: AtomicIsize::new(sample_bytes),
+            samples: Mutex::new(vec![]),
+            tracing: vec![],
+            stats: HashMap::new(),
+        }
+    }
+
+    /// Record an object of `bytes` allocated in the nursery from `site`. The object may be sampled.
+    pub fn record_allocation(&self, start: Address, bytes: usize, site: AllocationSite) {
+        let bytes = bytes as isize;
+        if self.bytes_until_sample.fetch_sub(bytes, Ordering::Relaxed) > bytes {
+            return;
+        }
+        self.bytes_until_sample
+            .store(self.sample_bytes, Ordering::Relaxed);
+        self.samples.lock().unwrap().push((start, site));
+    }
+
+    /// Should the objects from `site` be allocated in the mature space?
+    pub fn is_pretenured(&self, site: AllocationSite) -> bool {
+        self.stats
+            .get(&site)
+            .map_or(false, |stats| stats.pretenured)
+    }
+
+    /// The fraction of the sampled objects from `site` that survived nursery GCs. Return `None` if
+    /// no object from the site has been checked in a nursery GC.
+    pub fn survival_rate(&self, site: AllocationSite) -> Option<f64> {
+        self.stats
+            .get(&site)
+            .filter(|stats| stats.sampled > 0)
+            .map(|stats| stats.survived as f64 / stats.sampled as f64)
+    }
+
+    /// Prepare for a GC. The objects sampled since the last GC are checked if this is a nursery GC,
+    /// and discarded otherwise.
+    pub fn prepare(&mut self, nursery: bool) {
+        let samples = std::mem::take(self.samples.get_mut().unwrap());
+        if !nursery {
+            return;
+        }
+        self.tracing = samples
+            .into_iter()
+            .map(|(start, site)| SampledObject {
+                start,
+                site,
+                survived: AtomicBool::new(false),
+            })
+            .collect();
+        self.tracing.sort_unstable_by_key(|object| object.start);
+    }
+
+    /// Note that the nursery object that starts at `start` is reached in a nursery GC.
+    pub fn trace_nursery_object(&self, start: Address) {
+        if self.tracing.is_empty() {
+            return;
+        }
+        if let Ok(i) = self
+            .tracing
+            .binary_search_by_key(&start, |object| object.start)
+        {
+            self.tracing[i].survived.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Release after a GC. Update the survival rates of the sites with the objects sampled before a
+    /// nursery GC, and decide whether the sites are pretenured.
+    pub fn release(&mut self) {
+        for object in self.tracing.drain(..) {
+            let site = self.stats.entry(object.site).or_default();
+            site.sampled += 1;
+            if *object.survived.get_mut() {
+                site.survived += 1;
+            }
+            if site.sampled >= DECAY_SAMPLES {
+                site.sampled /= 2;
+                site.survived /= 2;
+            }
+            site.pretenured =
+                site.sampled >= MIN_SAMPLES && site.survived * 100 > site.sampled * self.threshold;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sites() -> AllocationSites {
+        let mut options = Options::default();
+        options.pretenure_sample_bytes.set(64);
+        options.pretenure_threshold.set(50);
+        AllocationSites::new(&options)
+    }
+
+    #[test]
+    fn samples_every_sample_bytes() {
+        let sites = sites();
+        let base = unsafe { Address::from_usize(0x1000_0000) };
+        for i in 0..16 {
+            sites.record_allocation(base + i * 16usize, 16, AllocationSite(0));
+        }
+        assert_eq!(sites.samples.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn pretenure_surviving_site() {
+        let mut sites = sites();
+        let base = unsafe { Address::from_usize(0x1000_0000) };
+        let long_lived = AllocationSite(1);
+        let short_lived = AllocationSite(2);
+        for _ in 0..MIN_SAMPLES {
+            for i in 0..8usize {
+                let site = if i % 2 == 0 { long_lived } else { short_lived };
+                sites.record_allocation(base + i * 64, 64, site);
+            }
+            sites.prepare(true);
+            for i in (0..8usize).step_by(2) {
+                sites.trace_nursery_object(base + i * 64);
+            }
+            sites.release();
+        }
+        assert_eq!(sites.survival_rate(long_lived), Some(1.0));
+        assert_eq!(sites.survival_rate(short_lived), Some(0.0));
+        assert!(sites.is_pretenured(long_lived));
+        assert!(!sites.is_pretenured(short_lived));
+        assert_eq!(sites.survival_rate(AllocationSite(3)), None);
+    }
+
+    #[test]
+    fn full_heap_gc_discards_samples() {
+        let mut sites = sites();
+        let base = unsafe { Address::from_usize(0x1000_0000) };
+        sites.record_allocation(base, 64, AllocationSite(1));
+        sites.prepare(false);
+        sites.trace_nursery_object(base);
+        sites.release();
+        assert_eq!(sites.survival_rate(AllocationSite(1)), None);
+    }
+}
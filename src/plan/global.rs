@@ -5,9 +5,19 @@ use super::PlanConstraints;
 use crate::mmtk::MMTK;
 use crate::plan::tracing::ObjectQueue;
 use crate::plan::Mutator;
+#[cfg(feature = "code_space")]
+use crate::policy::codespace::CodeSpace;
+#[cfg(any(not(feature = "immortal_free_list"), feature = "ro_space"))]
 use crate::policy::immortalspace::ImmortalSpace;
+#[cfg(not(feature = "immortal_free_list"))]
+use crate::policy::immortalspace::ImmortalSpace as CommonImmortalSpace;
 use crate::policy::largeobjectspace::LargeObjectSpace;
+#[cfg(feature = "immortal_free_list")]
+use crate::policy::marksweepspace::native_ms::MarkSweepSpace as CommonImmortalSpace;
+use crate::policy::marksweepspace::native_ms::{MarkSweepSpace, MarkSweepSpaceArgs};
 use crate::policy::space::{PlanCreateSpaceArgs, Space};
+#[cfg(feature = "vm_space")]
+use crate::policy::vmspace::VMSpace;
 use crate::scheduler::*;
 use crate::util::alloc::allocators::AllocatorSelector;
 #[cfg(feature = "analysis")]
@@ -48,6 +58,9 @@ pub fn create_mutator<VM: VMBinding>(
         PlanSelector::GenImmix => {
             crate::plan::generational::immix::mutator::create_genimmix_mutator(tls, mmtk)
         }
+        PlanSelector::GenMarkSweep => {
+            crate::plan::generational::marksweep::mutator::create_genms_mutator(tls, mmtk)
+        }
         PlanSelector::MarkSweep => {
             crate::plan::marksweep::mutator::create_ms_mutator(tls, &*mmtk.plan)
         }
@@ -61,6 +74,14 @@ pub fn create_mutator<VM: VMBinding>(
         PlanSelector::StickyImmix => {
             crate::plan::sticky::immix::mutator::create_stickyimmix_mutator(tls, mmtk)
         }
+        PlanSelector::StickyMarkSweep => {
+            crate::plan::sticky::marksweep::mutator::create_stickyms_mutator(tls, mmtk)
+        }
+        PlanSelector::LXR => crate::plan::lxr::mutator::create_lxr_mutator(tls, mmtk),
+        PlanSelector::ConcurrentImmix => {
+            crate::plan::concurrent::immix::mutator::create_concurrent_immix_mutator(tls, mmtk)
+        }
+        PlanSelector::G1 => crate::plan::g1::mutator::create_g1_mutator(tls, mmtk),
     })
 }
 
@@ -92,6 +113,9 @@ pub fn create_plan<VM: VMBinding>(
             as Box<dyn Plan<VM = VM>>,
         PlanSelector::GenImmix => Box::new(crate::plan::generational::immix::GenImmix::new(args))
             as Box<dyn Plan<VM = VM>>,
+        PlanSelector::GenMarkSweep => Box::new(
+            crate::plan::generational::marksweep::GenMarkSweep::new(args),
+        ) as Box<dyn Plan<VM = VM>>,
         PlanSelector::MarkSweep => {
             Box::new(crate::plan::marksweep::MarkSweep::new(args)) as Box<dyn Plan<VM = VM>>
         }
@@ -107,6 +131,16 @@ pub fn create_plan<VM: VMBinding>(
         PlanSelector::StickyImmix => {
             Box::new(crate::plan::sticky::immix::StickyImmix::new(args)) as Box<dyn Plan<VM = VM>>
         }
+        PlanSelector::StickyMarkSweep => {
+            Box::new(crate::plan::sticky::marksweep::StickyMarkSweep::new(args))
+                as Box<dyn Plan<VM = VM>>
+        }
+        PlanSelector::LXR => Box::new(crate::plan::lxr::LXR::new(args)) as Box<dyn Plan<VM = VM>>,
+        PlanSelector::ConcurrentImmix => {
+            Box::new(crate::plan::concurrent::immix::ConcurrentImmix::new(args))
+                as Box<dyn Plan<VM = VM>>
+        }
+        PlanSelector::G1 => Box::new(crate::plan::g1::G1::new(args)) as Box<dyn Plan<VM = VM>>,
     };
 
     // We have created Plan in the heap, and we won't explicitly move it.
@@ -386,6 +420,8 @@ pub struct BasePlan<VM: VMBinding> {
     pub options: Arc<Options>,
     pub heap: HeapMeta,
     pub gc_trigger: Arc<GCTrigger<VM>>,
+    /// Predict how much a partial GC can collect within the pause time target.
+    pub pause_time: PauseTimeModel,
     #[cfg(feature = "sanity")]
     pub inside_sanity: AtomicBool,
     /// A counter for per-mutator stack scanning
@@ -405,16 +441,19 @@ pub struct BasePlan<VM: VMBinding> {
     // Spaces in base plan
     #[cfg(feature = "code_space")]
     #[trace]
-    pub code_space: ImmortalSpace<VM>,
+    pub code_space: CodeSpace<VM>,
     #[cfg(feature = "code_space")]
     #[trace]
-    pub code_lo_space: ImmortalSpace<VM>,
+    pub code_lo_space: CodeSpace<VM>,
     #[cfg(feature = "ro_space")]
     #[trace]
     pub ro_space: ImmortalSpace<VM>,
 
-    /// A VM space is a space allocated and populated by the VM.  Currently it is used by JikesRVM
-    /// for boot image.
+    /// A VM space is a space allocated and populated by the VM, such as a boot image. It consists
+    /// of the regions registered by the VM (see `memory_manager::register_vm_space_region` and
+    /// `memory_manager::map_vm_space_image`), and the region specified by the `vm_space_start` and
+    /// `vm_space_size` options. If `vm_space_start` is not set, the region is a fixed address range
+    /// in the heap range, as used by JikesRVM for its boot image.
     ///
     /// If VM space is present, it has some special interaction with the
     /// `memory_manager::is_mmtk_object` and the `memory_manager::is_in_mmtk_spaces` functions.
@@ -424,32 +463,36 @@ pub struct BasePlan<VM: VMBinding> {
     ///     address argument is in the VM space.
     ///
     /// -   The `is_in_mmtk_spaces` currently returns `true` if the given object reference is in
-    ///     the VM space.
+    ///     the VM space, including anywhere in the chunks that the regions of the VM space span.
     #[cfg(feature = "vm_space")]
     #[trace]
-    pub vm_space: ImmortalSpace<VM>,
+    pub vm_space: VMSpace<VM>,
 }
 
 #[cfg(feature = "vm_space")]
-pub fn create_vm_space<VM: VMBinding>(args: &mut CreateSpecificPlanArgs<VM>) -> ImmortalSpace<VM> {
+pub fn create_vm_space<VM: VMBinding>(args: &mut CreateSpecificPlanArgs<VM>) -> VMSpace<VM> {
     use crate::util::constants::LOG_BYTES_IN_MBYTE;
-    let boot_segment_bytes = *args.global_args.options.vm_space_size;
-    debug_assert!(boot_segment_bytes > 0);
-
     use crate::util::conversions::raw_align_up;
     use crate::util::heap::layout::vm_layout_constants::BYTES_IN_CHUNK;
-    let boot_segment_mb = raw_align_up(boot_segment_bytes, BYTES_IN_CHUNK) >> LOG_BYTES_IN_MBYTE;
-
-    let space = ImmortalSpace::new(args.get_space_args(
-        "boot",
-        false,
-        VMRequest::fixed_size(boot_segment_mb),
-    ));
 
-    // The space is mapped externally by the VM. We need to update our mmapper to mark the range as mapped.
-    space.ensure_mapped();
+    let start = *args.global_args.options.vm_space_start;
+    let size = *args.global_args.options.vm_space_size;
+    // The region is mapped externally by the VM, and it is added to the space when the SFT is initialized.
+    let (vmrequest, initial_region) = if size == 0 {
+        (VMRequest::discontiguous(), None)
+    } else if start != 0 {
+        let start = unsafe { crate::util::Address::from_usize(start) };
+        (VMRequest::discontiguous(), Some((start, size)))
+    } else {
+        // The region is the address range of the space in the heap range.
+        let boot_segment_mb = raw_align_up(size, BYTES_IN_CHUNK) >> LOG_BYTES_IN_MBYTE;
+        (VMRequest::fixed_size(boot_segment_mb), None)
+    };
 
-    space
+    VMSpace::new(
+        args.get_space_args("boot", false, vmrequest),
+        initial_region,
+    )
 }
 
 /// Args needed for creating any plan. This includes a set of contexts from MMTK or global. This
@@ -505,16 +548,18 @@ impl<VM: VMBinding> BasePlan<VM> {
         #[cfg(feature = "analysis")]
         let analysis_manager = AnalysisManager::new(&stats);
         BasePlan {
+            // The pages of the code spaces are fresh memory from the OS when they are handed out,
+            // and MMTk cannot write to them, so the code spaces are not zeroed by MMTk.
             #[cfg(feature = "code_space")]
-            code_space: ImmortalSpace::new(args.get_space_args(
+            code_space: CodeSpace::new(args.get_space_args(
                 "code_space",
-                true,
+                false,
                 VMRequest::discontiguous(),
             )),
             #[cfg(feature = "code_space")]
-            code_lo_space: ImmortalSpace::new(args.get_space_args(
+            code_lo_space: CodeSpace::new(args.get_space_args(
                 "code_lo_space",
-                true,
+                false,
                 VMRequest::discontiguous(),
             )),
             #[cfg(feature = "ro_space")]
@@ -543,6 +588,7 @@ impl<VM: VMBinding> BasePlan<VM> {
             mmapper: args.global_args.mmapper,
             heap: args.global_args.heap,
             gc_trigger: args.global_args.gc_trigger,
+            pause_time: PauseTimeModel::new(*args.global_args.options.pause_target_ms),
             vm_map: args.global_args.vm_map,
             options: args.global_args.options.clone(),
             #[cfg(feature = "sanity")]
@@ -666,22 +712,24 @@ impl<VM: VMBinding> BasePlan<VM> {
         VM::VMActivePlan::vm_trace_object::<Q>(queue, object, worker)
     }
 
-    pub fn prepare(&mut self, _tls: VMWorkerThread, _full_heap: bool) {
+    #[allow(unused_variables)] // depending on the enabled features, full_heap may not be used.
+    pub fn prepare(&mut self, _tls: VMWorkerThread, full_heap: bool) {
         #[cfg(feature = "code_space")]
-        self.code_space.prepare();
+        self.code_space.prepare(full_heap);
         #[cfg(feature = "code_space")]
-        self.code_lo_space.prepare();
+        self.code_lo_space.prepare(full_heap);
         #[cfg(feature = "ro_space")]
         self.ro_space.prepare();
         #[cfg(feature = "vm_space")]
         self.vm_space.prepare();
     }
 
-    pub fn release(&mut self, _tls: VMWorkerThread, _full_heap: bool) {
+    #[allow(unused_variables)] // depending on the enabled features, full_heap may not be used.
+    pub fn release(&mut self, _tls: VMWorkerThread, full_heap: bool) {
         #[cfg(feature = "code_space")]
-        self.code_space.release();
+        self.code_space.release(full_heap);
         #[cfg(feature = "code_space")]
-        self.code_lo_space.release();
+        self.code_lo_space.release(full_heap);
         #[cfg(feature = "ro_space")]
         self.ro_space.release();
         #[cfg(feature = "vm_space")]
@@ -889,33 +937,46 @@ CommonPlan is for representing state and features used by _many_ plans, but that
 #[derive(PlanTraceObject)]
 pub struct CommonPlan<VM: VMBinding> {
     #[trace]
-    pub immortal: ImmortalSpace<VM>,
+    pub immortal: CommonImmortalSpace<VM>,
     #[trace]
     pub los: LargeObjectSpace<VM>,
-    // TODO: We should use a marksweep space for nonmoving.
+    /// The space for `AllocationSemantics::NonMoving`. It is a mark sweep space, so its objects
+    /// are never moved, and they are reclaimed when they are dead.
     #[trace]
-    pub nonmoving: ImmortalSpace<VM>,
+    pub nonmoving: MarkSweepSpace<VM>,
     #[fallback_trace]
     pub base: BasePlan<VM>,
 }
 
 impl<VM: VMBinding> CommonPlan<VM> {
     pub fn new(mut args: CreateSpecificPlanArgs<VM>) -> CommonPlan<VM> {
+        let immortal_args = args.get_space_args("immortal", true, VMRequest::discontiguous());
+        #[cfg(not(feature = "immortal_free_list"))]
+        let immortal = ImmortalSpace::new(immortal_args);
+        #[cfg(feature = "immortal_free_list")]
+        let immortal = CommonImmortalSpace::new_with_args(
+            immortal_args,
+            MarkSweepSpaceArgs {
+                immortal: true,
+                ..Default::default()
+            },
+        );
+        let needs_log_bit = args.constraints.needs_log_bit;
         CommonPlan {
-            immortal: ImmortalSpace::new(args.get_space_args(
-                "immortal",
-                true,
-                VMRequest::discontiguous(),
-            )),
+            immortal,
             los: LargeObjectSpace::new(
                 args.get_space_args("los", true, VMRequest::discontiguous()),
                 false,
             ),
-            nonmoving: ImmortalSpace::new(args.get_space_args(
-                "nonmoving",
-                true,
-                VMRequest::discontiguous(),
-            )),
+            nonmoving: MarkSweepSpace::new_with_args(
+                args.get_space_args("nonmoving", true, VMRequest::discontiguous()),
+                MarkSweepSpaceArgs {
+                    nonmoving: true,
+                    // The objects stay unlogged after a GC that traces them.
+                    unlog_object_when_traced: needs_log_bit,
+                    ..Default::default()
+                },
+            ),
             base: BasePlan::new(args),
         }
     }
@@ -959,14 +1020,20 @@ impl<VM: VMBinding> CommonPlan<VM> {
     pub fn prepare(&mut self, tls: VMWorkerThread, full_heap: bool) {
         self.immortal.prepare();
         self.los.prepare(full_heap);
-        self.nonmoving.prepare();
+        // The non-moving space is only collected in full heap GCs. Its objects are allocated
+        // marked, so the other GCs keep them.
+        if full_heap {
+            self.nonmoving.prepare();
+        }
         self.base.prepare(tls, full_heap)
     }
 
     pub fn release(&mut self, tls: VMWorkerThread, full_heap: bool) {
         self.immortal.release();
         self.los.release(full_heap);
-        self.nonmoving.release();
+        if full_heap {
+            self.nonmoving.release();
+        }
         self.base.release(tls, full_heap)
     }
 
@@ -974,7 +1041,7 @@ impl<VM: VMBinding> CommonPlan<VM> {
         self.base.stacks_prepared()
     }
 
-    pub fn get_immortal(&self) -> &ImmortalSpace<VM> {
+    pub fn get_immortal(&self) -> &CommonImmortalSpace<VM> {
         &self.immortal
     }
 
@@ -982,7 +1049,7 @@ impl<VM: VMBinding> CommonPlan<VM> {
         &self.los
     }
 
-    pub fn get_nonmoving(&self) -> &ImmortalSpace<VM> {
+    pub fn get_nonmoving(&self) -> &MarkSweepSpace<VM> {
         &self.nonmoving
     }
 
@@ -1065,6 +1132,10 @@ pub enum AllocationSemantics {
     ReadOnly = 4,
     /// Los + Code.
     LargeCode = 5,
-    /// Non moving objects will not be moved by GC.
+    /// Non moving objects will never be moved by GC, and they are reclaimed when they are dead. This is
+    /// useful for objects whose addresses are held by native code.
+    /// The objects are allocated in the non-moving mark sweep space of the common plan. An object that is
+    /// too large for a mark sweep space is allocated in the large object space, so a binding does not need
+    /// to check the size.
     NonMoving = 6,
 }
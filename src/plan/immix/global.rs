@@ -96,6 +96,11 @@ impl<VM: VMBinding> Plan for Immix<VM> {
     fn prepare(&mut self, tls: VMWorkerThread) {
         self.common.prepare(tls, true);
         self.immix_space.prepare(true);
+        if self.immix_space.in_defrag() {
+            self.base()
+                .pause_time
+                .set_evacuated_pages(self.immix_space.defrag_evacuated_pages());
+        }
     }
 
     fn release(&mut self, tls: VMWorkerThread) {
@@ -134,6 +139,7 @@ impl<VM: VMBinding> Immix<VM> {
             ImmixSpaceArgs {
                 reset_log_bit_in_major_gc: false,
                 unlog_object_when_traced: false,
+                rc_enabled: false,
             },
         )
     }
@@ -181,6 +187,12 @@ impl<VM: VMBinding> Immix<VM> {
             plan.base().cur_collection_attempts.load(Ordering::SeqCst),
             plan.base().is_user_triggered_collection(),
             *plan.base().options.full_heap_system_gc,
+            // An emergency GC evacuates as much as it can, regardless of the pause time target.
+            if plan.is_emergency_collection() {
+                None
+            } else {
+                plan.base().pause_time.evacuation_target_pages()
+            },
         );
 
         if in_defrag {
@@ -23,7 +23,7 @@ pub fn immix_mutator_prepare<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMW
     }
     .downcast_mut::<ImmixAllocator<VM>>()
     .unwrap();
-    immix_allocator.reset();
+    immix_allocator.prepare();
 }
 
 pub fn immix_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
@@ -71,6 +71,8 @@ pub fn create_immix_mutator<VM: VMBinding>(
         barrier: Box::new(NoBarrier),
         mutator_tls,
         config,
+        los_redirects: 0,
+        pretenured_objects: 0,
         plan,
     }
 }
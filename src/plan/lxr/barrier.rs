@@ -0,0 +1,129 @@
+//! The reference counting barrier for LXR.
+
+use super::gc_work::{ProcessIncBuf, ProcessRegionIncBuf};
+use super::global::LXR;
+use crate::plan::barriers::BarrierSemantics;
+use crate::plan::VectorQueue;
+use crate::scheduler::WorkBucketStage;
+use crate::util::constants::BYTES_IN_ADDRESS;
+use crate::util::ref_count;
+use crate::util::*;
+use crate::vm::edge_shape::{Edge, MemorySlice};
+use crate::vm::Scanning;
+use crate::vm::VMBinding;
+use crate::MMTK;
+
+/// The slow-path semantics for LXR. This is used with
+/// [`SnapshotObjectBarrier`](crate::plan::barriers::SnapshotObjectBarrier), so the slow-path
+/// is invoked before the first write to an object since the last pause.
+pub struct LXRBarrierSemantics<VM: VMBinding> {
+    /// MMTk instance
+    mmtk: &'static MMTK<VM>,
+    /// LXR plan
+    lxr: &'static LXR<VM>,
+    /// The mutator thread that owns this barrier.
+    tls: VMMutatorThread,
+    /// Logged objects that were counted. The references in their fields will be incremented.
+    incs: VectorQueue<ObjectReference>,
+    /// Logged objects that were not counted. They will be unlogged again.
+    unlogs: VectorQueue<ObjectReference>,
+    /// The references in the fields of the logged objects before they were overwritten.
+    /// They will be decremented.
+    decs: VectorQueue<ObjectReference>,
+    /// Array slices written by array copying. The references in them will be incremented.
+    region_incs: VectorQueue<VM::VMMemorySlice>,
+}
+
+impl<VM: VMBinding> LXRBarrierSemantics<VM> {
+    pub fn new(mmtk: &'static MMTK<VM>, lxr: &'static LXR<VM>, tls: VMMutatorThread) -> Self {
+        Self {
+            mmtk,
+            lxr,
+            tls,
+            incs: VectorQueue::new(),
+            unlogs: VectorQueue::new(),
+            decs: VectorQueue::new(),
+            region_incs: VectorQueue::new(),
+        }
+    }
+
+    fn flush_incs(&mut self) {
+        let buf = self.incs.take();
+        if !buf.is_empty() {
+            self.mmtk.scheduler.work_buckets[WorkBucketStage::Closure]
+                .add(ProcessIncBuf::<VM>::new(buf, true));
+        }
+    }
+
+    fn flush_unlogs(&mut self) {
+        let buf = self.unlogs.take();
+        if !buf.is_empty() {
+            self.mmtk.scheduler.work_buckets[WorkBucketStage::Closure]
+                .add(ProcessIncBuf::<VM>::new(buf, false));
+        }
+    }
+
+    fn flush_decs(&mut self) {
+        let buf = self.decs.take();
+        if !buf.is_empty() {
+            self.lxr.add_dec_buffer(buf);
+        }
+    }
+
+    fn flush_region_incs(&mut self) {
+        let buf = self.region_incs.take();
+        if !buf.is_empty() {
+            self.mmtk.scheduler.work_buckets[WorkBucketStage::Closure]
+                .add(ProcessRegionIncBuf::<VM>::new(buf));
+        }
+    }
+}
+
+impl<VM: VMBinding> BarrierSemantics for LXRBarrierSemantics<VM> {
+    type VM = VM;
+
+    fn flush(&mut self) {
+        self.flush_incs();
+        self.flush_unlogs();
+        self.flush_decs();
+        self.flush_region_incs();
+    }
+
+    fn object_reference_write_slow(
+        &mut self,
+        src: ObjectReference,
+        _slot: VM::VMEdge,
+        _target: ObjectReference,
+    ) {
+        if ref_count::is_counted::<VM>(src) {
+            // The fields have not been overwritten yet. Take a snapshot of the old references.
+            let decs = &mut self.decs;
+            VM::VMScanning::scan_object_for_mutator(self.tls, src, &mut |edge: VM::VMEdge| {
+                let object = edge.load();
+                if !object.is_null() {
+                    decs.push(object);
+                }
+            });
+            self.decs.is_full().then(|| self.flush_decs());
+            self.incs.push(src);
+            self.incs.is_full().then(|| self.flush_incs());
+        } else {
+            // The object is not counted yet, and neither are its fields. When the object gets counted,
+            // its fields will be counted at that time.
+            self.unlogs.push(src);
+            self.unlogs.is_full().then(|| self.flush_unlogs());
+        }
+    }
+
+    fn memory_region_copy_slow(&mut self, _src: VM::VMMemorySlice, dst: VM::VMMemorySlice) {
+        // We do not decrement the old references in the slice. Missing a decrement only delays
+        // the reclamation of an object until the next tracing pause.
+        debug_assert_eq!(
+            dst.bytes() & (BYTES_IN_ADDRESS - 1),
+            0,
+            "bytes should be a multiple of words"
+        );
+        self.region_incs.push(dst);
+        self.region_incs.is_full().then(|| self.flush_region_incs());
+    }
+}
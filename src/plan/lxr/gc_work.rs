@@ -0,0 +1,419 @@
+use super::global::LXR;
+use crate::plan::ObjectsClosure;
+use crate::plan::PlanTraceObject;
+use crate::plan::VectorObjectQueue;
+use crate::plan::VectorQueue;
+use crate::policy::immix::TRACE_KIND_FAST;
+use crate::policy::space::Space;
+use crate::scheduler::gc_work::*;
+use crate::scheduler::{GCWork, GCWorker, WorkBucketStage};
+use crate::util::ref_count;
+use crate::util::{ObjectReference, VMWorkerThread};
+use crate::vm::edge_shape::{Edge, MemorySlice};
+use crate::vm::*;
+use crate::MMTK;
+use atomic::Ordering;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+pub(super) struct LXRRCGCWorkContext<VM: VMBinding>(PhantomData<VM>);
+impl<VM: VMBinding> crate::scheduler::GCWorkContext for LXRRCGCWorkContext<VM> {
+    type VM = VM;
+    type PlanType = LXR<VM>;
+    type ProcessEdgesWorkType = RCIncEdges<VM>;
+}
+
+pub(super) struct LXRTraceGCWorkContext<VM: VMBinding>(PhantomData<VM>);
+impl<VM: VMBinding> crate::scheduler::GCWorkContext for LXRTraceGCWorkContext<VM> {
+    type VM = VM;
+    type PlanType = LXR<VM>;
+    type ProcessEdgesWorkType = LXRTraceEdges<VM>;
+}
+
+/// Call `f` for each reference in the fields of an object.
+pub(super) fn scan_fields<VM: VMBinding>(
+    tls: VMWorkerThread,
+    object: ObjectReference,
+    mut f: impl FnMut(ObjectReference),
+) {
+    if VM::VMScanning::support_edge_enqueuing(tls, object) {
+        VM::VMScanning::scan_object(tls, object, &mut |edge: VM::VMEdge| f(edge.load()));
+    } else {
+        VM::VMScanning::scan_object_and_trace_edges(tls, object, &mut |child| {
+            f(child);
+            child
+        });
+    }
+}
+
+/// Record the objects pointed by root edges, so we can undo their increments in the next pause.
+fn record_root_edges<VM: VMBinding>(plan: &LXR<VM>, edges: &[VM::VMEdge]) {
+    plan.add_root_buffer(
+        edges
+            .iter()
+            .map(|edge| edge.load())
+            .filter(|object| !object.is_null())
+            .collect(),
+    );
+}
+
+/// Process edges in a reference counting pause. Each edge increments the count of the object it points to.
+///
+/// Only the processing of reference objects, finalizable objects and the weak references of the
+/// binding calls `trace_object()` directly. It runs after the increments and the decrements of the
+/// pause, and keeps an object alive without counting a reference to it. See [`LXR::retain`].
+pub struct RCIncEdges<VM: VMBinding> {
+    plan: &'static LXR<VM>,
+    base: ProcessEdgesBase<VM>,
+}
+
+impl<VM: VMBinding> ProcessEdgesWork for RCIncEdges<VM> {
+    type VM = VM;
+    type ScanObjectsWorkType = RCScanObjects<VM>;
+    const OVERWRITE_REFERENCE: bool = false;
+
+    fn new(edges: Vec<EdgeOf<Self>>, roots: bool, mmtk: &'static MMTK<VM>) -> Self {
+        let base = ProcessEdgesBase::new(edges, roots, mmtk);
+        let plan = base.plan().downcast_ref::<LXR<VM>>().unwrap();
+        Self { plan, base }
+    }
+
+    fn create_scan_work(
+        &self,
+        nodes: Vec<ObjectReference>,
+        roots: bool,
+    ) -> Self::ScanObjectsWorkType {
+        if roots {
+            self.plan.add_root_buffer(nodes.clone());
+        }
+        RCScanObjects::new(nodes, roots)
+    }
+
+    fn trace_object(&mut self, object: ObjectReference) -> ObjectReference {
+        if object.is_null() {
+            return object;
+        }
+        // We cannot borrow `self` twice in a call, so we extract `worker` as a local variable.
+        let worker = self.worker();
+        self.plan.retain(&mut self.base.nodes, object, worker)
+    }
+
+    fn process_edges(&mut self) {
+        if self.roots {
+            record_root_edges(self.plan, &self.edges);
+        }
+        let worker = self.worker();
+        for i in 0..self.base.edges.len() {
+            let object = self.base.edges[i].load();
+            if !object.is_null() {
+                self.plan.increment(&mut self.base.nodes, object, worker);
+            }
+        }
+    }
+}
+
+impl<VM: VMBinding> Deref for RCIncEdges<VM> {
+    type Target = ProcessEdgesBase<VM>;
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl<VM: VMBinding> DerefMut for RCIncEdges<VM> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+/// Scan objects in a reference counting pause, and increment the counts of the objects in their
+/// fields. The objects in a root packet are incremented first, and only the objects that get their
+/// first count are scanned.
+pub struct RCScanObjects<VM: VMBinding> {
+    buffer: Vec<ObjectReference>,
+    roots: bool,
+    phantom: PhantomData<VM>,
+}
+
+impl<VM: VMBinding> RCScanObjects<VM> {
+    pub fn new(buffer: Vec<ObjectReference>, roots: bool) -> Self {
+        Self {
+            buffer,
+            roots,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<VM: VMBinding> ScanObjectsWork<VM> for RCScanObjects<VM> {
+    type E = RCIncEdges<VM>;
+
+    fn roots(&self) -> bool {
+        self.roots
+    }
+
+    fn post_scan_object(&self, _object: ObjectReference) {
+        // Do nothing.
+    }
+
+    fn make_another(&self, buffer: Vec<ObjectReference>) -> Self {
+        Self::new(buffer, false)
+    }
+}
+
+impl<VM: VMBinding> GCWork<VM> for RCScanObjects<VM> {
+    fn do_work(&mut self, worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let lxr = mmtk.plan.downcast_ref::<LXR<VM>>().unwrap();
+        let tls = worker.tls;
+        let mut queue = VectorObjectQueue::new();
+        let objects = if self.roots {
+            for object in self.buffer.iter().copied() {
+                lxr.increment(&mut queue, object, worker);
+            }
+            queue.take()
+        } else {
+            std::mem::take(&mut self.buffer)
+        };
+
+        // The edges are processed by `RCIncEdges` packets. The fields of the objects that do not
+        // support edge enqueuing are incremented here. Unlike `ScanObjects`, we do not call
+        // `trace_object()` for them, as it does not count the references.
+        let mut scan_later = vec![];
+        {
+            let mut closure = ObjectsClosure::<RCIncEdges<VM>>::new(worker);
+            for object in objects {
+                if VM::VMScanning::support_edge_enqueuing(tls, object) {
+                    VM::VMScanning::scan_object(tls, object, &mut closure);
+                } else {
+                    scan_later.push(object);
+                }
+            }
+        }
+        for object in scan_later {
+            scan_fields::<VM>(tls, object, |child| {
+                if !child.is_null() {
+                    lxr.increment(&mut queue, child, worker);
+                    if queue.is_full() {
+                        mmtk.scheduler.work_buckets[WorkBucketStage::Closure].add(RCScanObjects::<
+                            VM,
+                        >::new(
+                            queue.take(),
+                            false,
+                        ));
+                    }
+                }
+            });
+        }
+        if !queue.is_empty() {
+            mmtk.scheduler.work_buckets[WorkBucketStage::Closure]
+                .add(RCScanObjects::<VM>::new(queue.take(), false));
+        }
+    }
+}
+
+/// Process edges in a tracing pause. Each edge we trace increments the count of the object it
+/// points to, so the reference counts are rebuilt when the trace finishes.
+pub struct LXRTraceEdges<VM: VMBinding> {
+    plan: &'static LXR<VM>,
+    base: ProcessEdgesBase<VM>,
+}
+
+impl<VM: VMBinding> ProcessEdgesWork for LXRTraceEdges<VM> {
+    type VM = VM;
+    type ScanObjectsWorkType = PlanScanObjects<Self, LXR<VM>>;
+    const OVERWRITE_REFERENCE: bool = false;
+
+    fn new(edges: Vec<EdgeOf<Self>>, roots: bool, mmtk: &'static MMTK<VM>) -> Self {
+        let base = ProcessEdgesBase::new(edges, roots, mmtk);
+        let plan = base.plan().downcast_ref::<LXR<VM>>().unwrap();
+        Self { plan, base }
+    }
+
+    fn create_scan_work(
+        &self,
+        nodes: Vec<ObjectReference>,
+        roots: bool,
+    ) -> Self::ScanObjectsWorkType {
+        if roots {
+            self.plan.add_root_buffer(nodes.clone());
+        }
+        PlanScanObjects::<Self, LXR<VM>>::new(self.plan, nodes, false, roots)
+    }
+
+    fn trace_object(&mut self, object: ObjectReference) -> ObjectReference {
+        if object.is_null() {
+            return object;
+        }
+        if crate::memory_manager::is_in_mmtk_spaces::<VM>(object) {
+            ref_count::inc::<VM>(object);
+        }
+        // We cannot borrow `self` twice in a call, so we extract `worker` as a local variable.
+        let worker = self.worker();
+        self.plan
+            .trace_object::<VectorObjectQueue, TRACE_KIND_FAST>(
+                &mut self.base.nodes,
+                object,
+                worker,
+            )
+    }
+
+    fn process_edges(&mut self) {
+        if self.roots {
+            record_root_edges(self.plan, &self.edges);
+        }
+        for i in 0..self.edges.len() {
+            self.process_edge(self.edges[i])
+        }
+    }
+}
+
+impl<VM: VMBinding> Deref for LXRTraceEdges<VM> {
+    type Target = ProcessEdgesBase<VM>;
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl<VM: VMBinding> DerefMut for LXRTraceEdges<VM> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+/// The objects logged by the barrier since the last pause.
+/// This work packet unlogs them again, so the barrier will capture their next mutations.
+/// In a reference counting pause, it also increments the references in the fields of the objects
+/// that had been counted when they were logged.
+pub struct ProcessIncBuf<VM: VMBinding> {
+    modbuf: Vec<ObjectReference>,
+    /// Were the objects counted when they were logged? The fields of an object that is not
+    /// counted are not counted either.
+    counted: bool,
+    phantom: PhantomData<VM>,
+}
+
+impl<VM: VMBinding> ProcessIncBuf<VM> {
+    pub fn new(modbuf: Vec<ObjectReference>, counted: bool) -> Self {
+        debug_assert!(!modbuf.is_empty());
+        Self {
+            modbuf,
+            counted,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<VM: VMBinding> GCWork<VM> for ProcessIncBuf<VM> {
+    fn do_work(&mut self, worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        for object in &self.modbuf {
+            VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC
+                .mark_as_unlogged::<VM>(*object, Ordering::SeqCst);
+        }
+        let lxr = mmtk.plan.downcast_ref::<LXR<VM>>().unwrap();
+        // A tracing pause counts the current fields of all the live objects.
+        if self.counted && !lxr.current_pause_traces() {
+            let modbuf = std::mem::take(&mut self.modbuf);
+            GCWork::do_work(&mut RCScanObjects::<VM>::new(modbuf, false), worker, mmtk)
+        }
+    }
+}
+
+/// The array slices written by array copying since the last pause. In a reference counting pause,
+/// this work packet increments the references in the slices.
+pub struct ProcessRegionIncBuf<VM: VMBinding> {
+    modbuf: Vec<VM::VMMemorySlice>,
+}
+
+impl<VM: VMBinding> ProcessRegionIncBuf<VM> {
+    pub fn new(modbuf: Vec<VM::VMMemorySlice>) -> Self {
+        debug_assert!(!modbuf.is_empty());
+        Self { modbuf }
+    }
+}
+
+impl<VM: VMBinding> GCWork<VM> for ProcessRegionIncBuf<VM> {
+    fn do_work(&mut self, worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let lxr = mmtk.plan.downcast_ref::<LXR<VM>>().unwrap();
+        if !lxr.current_pause_traces() {
+            // Collect all the entries in all the slices
+            let mut edges = vec![];
+            for slice in &self.modbuf {
+                for edge in slice.iter_edges() {
+                    edges.push(edge);
+                }
+            }
+            GCWork::do_work(&mut RCIncEdges::<VM>::new(edges, false, mmtk), worker, mmtk)
+        }
+    }
+}
+
+/// The sentinel of the closure bucket in a reference counting pause. When all the increments are
+/// done, this work packet schedules the decrements captured by the barrier, and the deferred
+/// decrements for the roots of the last pause.
+#[derive(Default)]
+pub struct ProcessDecBuffers<VM: VMBinding>(PhantomData<VM>);
+
+impl<VM: VMBinding> ProcessDecBuffers<VM> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<VM: VMBinding> GCWork<VM> for ProcessDecBuffers<VM> {
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let lxr = mmtk.plan.downcast_ref::<LXR<VM>>().unwrap();
+        let work_packets = lxr
+            .take_dec_buffers()
+            .into_iter()
+            .filter(|decs| !decs.is_empty())
+            .map(|decs| Box::new(ProcessDecs::<VM>::new(decs)) as Box<dyn GCWork<VM>>)
+            .collect();
+        mmtk.scheduler.work_buckets[WorkBucketStage::Closure].bulk_add(work_packets);
+    }
+}
+
+/// Decrement the reference counts of a list of objects. An object whose count drops to zero is
+/// dead, and the references in its fields are decremented as well.
+///
+/// Only the objects in the immix space are reclaimed by reference counting. The counts of other
+/// objects are never decremented, and those objects are reclaimed by tracing.
+pub struct ProcessDecs<VM: VMBinding> {
+    decs: Vec<ObjectReference>,
+    phantom: PhantomData<VM>,
+}
+
+impl<VM: VMBinding> ProcessDecs<VM> {
+    pub fn new(decs: Vec<ObjectReference>) -> Self {
+        Self {
+            decs,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<VM: VMBinding> GCWork<VM> for ProcessDecs<VM> {
+    fn do_work(&mut self, worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let lxr = mmtk.plan.downcast_ref::<LXR<VM>>().unwrap();
+        let mut new_decs = VectorQueue::new();
+        for object in std::mem::take(&mut self.decs) {
+            if !lxr.immix_space.in_space(object) {
+                continue;
+            }
+            if ref_count::dec::<VM>(object) == 1 {
+                // The object is dead. The references in its fields are gone with it.
+                scan_fields::<VM>(worker.tls, object, |child| {
+                    if !child.is_null() {
+                        new_decs.push(child);
+                        if new_decs.is_full() {
+                            mmtk.scheduler.work_buckets[WorkBucketStage::Closure]
+                                .add(ProcessDecs::<VM>::new(new_decs.take()));
+                        }
+                    }
+                });
+            }
+        }
+        if !new_decs.is_empty() {
+            mmtk.scheduler.work_buckets[WorkBucketStage::Closure]
+                .add(ProcessDecs::<VM>::new(new_decs.take()));
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use super::gc_work::{LXRRCGCWorkContext, LXRTraceGCWorkContext, ProcessDecBuffers};
+use crate::plan::global::BasePlan;
+use crate::plan::global::CommonPlan;
+use crate::plan::global::CreateGeneralPlanArgs;
+use crate::plan::global::CreateSpecificPlanArgs;
+use crate::plan::global::GcStatus;
+use crate::plan::immix;
+use crate::plan::AllocationSemantics;
+use crate::plan::BarrierSelector;
+use crate::plan::ObjectQueue;
+use crate::plan::Plan;
+use crate::plan::PlanConstraints;
+use crate::policy::immix::ImmixSpace;
+use crate::policy::immix::ImmixSpaceArgs;
+use crate::policy::space::Space;
+use crate::scheduler::*;
+use crate::util::alloc::allocators::AllocatorSelector;
+use crate::util::copy::*;
+use crate::util::heap::VMRequest;
+use crate::util::metadata::side_metadata::SideMetadataContext;
+use crate::util::metadata::side_metadata::SideMetadataSanity;
+use crate::util::opaque_pointer::VMWorkerThread;
+use crate::util::ref_count;
+use crate::util::statistics::counter::EventCounter;
+use crate::util::ObjectReference;
+use crate::vm::{ActivePlan, ObjectModel, VMBinding};
+
+use atomic::Ordering;
+use enum_map::EnumMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+
+use mmtk_macros::PlanTraceObject;
+
+/// If the heap is still this full (in percentage) after a pause, the next pause will trace the heap.
+/// Reference counting cannot reclaim cyclic garbage, or objects whose counts are stuck.
+const TRACE_THRESHOLD_PERCENT: usize = 90;
+
+#[derive(PlanTraceObject)]
+pub struct LXR<VM: VMBinding> {
+    #[post_scan]
+    #[trace(CopySemantics::DefaultCopy)]
+    pub immix_space: ImmixSpace<VM>,
+    #[fallback_trace]
+    pub common: CommonPlan<VM>,
+    /// Is the current pause a tracing pause?
+    current_pause_traces: AtomicBool,
+    /// Should the next pause be a tracing pause?
+    next_pause_traces: AtomicBool,
+    /// Decrement buffers flushed by the mutators since the last pause.
+    dec_buffers: Mutex<Vec<Vec<ObjectReference>>>,
+    /// Root objects found in the current pause.
+    root_buffers: Mutex<Vec<Vec<ObjectReference>>>,
+    /// Root objects found in the last pause. Their increments are undone in the current pause.
+    prev_root_buffers: Mutex<Vec<Vec<ObjectReference>>>,
+    trace_pause_count: Arc<Mutex<EventCounter>>,
+}
+
+pub const LXR_CONSTRAINTS: PlanConstraints = PlanConstraints {
+    moves_objects: false,
+    needs_log_bit: true,
+    barrier: BarrierSelector::SnapshotObjectBarrier,
+    // An object may be reached from the fields of multiple logged objects.
+    may_trace_duplicate_edges: true,
+    ..immix::IMMIX_CONSTRAINTS
+};
+
+impl<VM: VMBinding> Plan for LXR<VM> {
+    type VM = VM;
+
+    fn collection_required(&self, space_full: bool, _space: Option<&dyn Space<Self::VM>>) -> bool {
+        // Like sticky immix, we also collect when young objects use up the nursery size. The cost
+        // of a reference counting pause is proportional to the young objects and the mutations.
+        let nursery_full =
+            self.immix_space.get_pages_allocated() > self.options().get_max_nursery_pages();
+        self.base().collection_required(self, space_full) || nursery_full
+    }
+
+    fn last_collection_was_exhaustive(&self) -> bool {
+        self.current_pause_traces()
+    }
+
+    fn constraints(&self) -> &'static PlanConstraints {
+        &LXR_CONSTRAINTS
+    }
+
+    fn create_copy_config(&'static self) -> CopyConfig<Self::VM> {
+        use enum_map::enum_map;
+        CopyConfig {
+            copy_mapping: enum_map! {
+                CopySemantics::DefaultCopy => CopySelector::Immix(0),
+                _ => CopySelector::Unused,
+            },
+            space_mapping: vec![(CopySelector::Immix(0), &self.immix_space)],
+            constraints: &LXR_CONSTRAINTS,
+        }
+    }
+
+    fn get_spaces(&self) -> Vec<&dyn Space<Self::VM>> {
+        let mut ret = self.common.get_spaces();
+        ret.push(&self.immix_space);
+        ret
+    }
+
+    fn schedule_collection(&'static self, scheduler: &GCWorkScheduler<VM>) {
+        self.base().set_collection_kind::<Self>(self);
+        self.base().set_gc_status(GcStatus::GcPrepare);
+
+        let trace = self.requires_trace();
+        self.current_pause_traces.store(trace, Ordering::SeqCst);
+
+        if trace {
+            info!("Tracing pause");
+            scheduler.schedule_common_work::<LXRTraceGCWorkContext<VM>>(self);
+        } else {
+            info!("Reference counting pause");
+            scheduler.schedule_common_work::<LXRRCGCWorkContext<VM>>(self);
+            // Decrements are applied after all the increments in this pause are done.
+            scheduler.work_buckets[WorkBucketStage::Closure]
+                .set_sentinel(Box::new(ProcessDecBuffers::<VM>::new()));
+        }
+    }
+
+    fn get_allocator_mapping(&self) -> &'static EnumMap<AllocationSemantics, AllocatorSelector> {
+        &immix::mutator::ALLOCATOR_MAPPING
+    }
+
+    fn prepare(&mut self, tls: VMWorkerThread) {
+        let trace = self.current_pause_traces();
+        if trace {
+            self.trace_pause_count.lock().unwrap().inc();
+        }
+        // A reference counting pause only reclaims young objects in the large object space.
+        self.common.prepare(tls, trace);
+        // A tracing pause clears the reference counts in the immix space, and rebuilds them.
+        self.immix_space.prepare(trace);
+    }
+
+    fn release(&mut self, tls: VMWorkerThread) {
+        let trace = self.current_pause_traces();
+        self.common.release(tls, trace);
+        self.immix_space.release(trace);
+        if trace {
+            // The counts are rebuilt by the trace. The decrements recorded before the pause are stale.
+            self.dec_buffers.lock().unwrap().clear();
+        }
+        // The roots found in this pause are decremented in the next pause.
+        let roots = std::mem::take(&mut *self.root_buffers.lock().unwrap());
+        *self.prev_root_buffers.lock().unwrap() = roots;
+    }
+
+    fn end_of_gc(&mut self, _tls: VMWorkerThread) {
+        let next_pause_traces =
+            self.get_reserved_pages() * 100 >= self.get_total_pages() * TRACE_THRESHOLD_PERCENT;
+        self.next_pause_traces
+            .store(next_pause_traces, Ordering::Relaxed);
+    }
+
+    fn get_collection_reserved_pages(&self) -> usize {
+        self.immix_space.defrag_headroom_pages()
+    }
+
+    fn get_used_pages(&self) -> usize {
+        self.immix_space.reserved_pages() + self.common.get_used_pages()
+    }
+
+    fn base(&self) -> &BasePlan<VM> {
+        &self.common.base
+    }
+
+    fn common(&self) -> &CommonPlan<VM> {
+        &self.common
+    }
+}
+
+impl<VM: VMBinding> LXR<VM> {
+    pub fn new(args: CreateGeneralPlanArgs<VM>) -> Self {
+        let mut global_specs = crate::util::metadata::extract_side_metadata(&[
+            *VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC,
+        ]);
+        global_specs.push(ref_count::RC_SIDE_METADATA_SPEC);
+        let mut plan_args = CreateSpecificPlanArgs {
+            global_args: args,
+            constraints: &LXR_CONSTRAINTS,
+            global_side_metadata_specs: SideMetadataContext::new_global_specs(&global_specs),
+        };
+
+        let immix_space = ImmixSpace::new(
+            plan_args.get_space_args("immix", true, VMRequest::discontiguous()),
+            ImmixSpaceArgs {
+                // Every object we trace in a tracing pause is counted, and should be unlogged.
+                unlog_object_when_traced: true,
+                // Dead objects may be unlogged. Clear the log bits in a tracing pause, and unlog
+                // the traced objects again.
+                reset_log_bit_in_major_gc: true,
+                rc_enabled: true,
+            },
+        );
+        let common = CommonPlan::new(plan_args);
+        let trace_pause_count = common
+            .base
+            .stats
+            .new_event_counter("tracePause", true, true);
+
+        let lxr = LXR {
+            immix_space,
+            common,
+            current_pause_traces: AtomicBool::new(false),
+            next_pause_traces: AtomicBool::new(false),
+            dec_buffers: Mutex::new(vec![]),
+            root_buffers: Mutex::new(vec![]),
+            prev_root_buffers: Mutex::new(vec![]),
+            trace_pause_count,
+        };
+
+        {
+            let mut side_metadata_sanity_checker = SideMetadataSanity::new();
+            lxr.common
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+            lxr.immix_space
+                .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+        }
+
+        lxr
+    }
+
+    /// Is the current pause a tracing pause?
+    pub fn current_pause_traces(&self) -> bool {
+        self.current_pause_traces.load(Ordering::SeqCst)
+    }
+
+    fn requires_trace(&self) -> bool {
+        // Separate each condition so the code is clear
+        #[allow(clippy::if_same_then_else, clippy::needless_bool)]
+        if self.base().is_user_triggered_collection() && *self.base().options.full_heap_system_gc {
+            // User triggered collection, and we trace for user triggered collection
+            true
+        } else if self.is_emergency_collection()
+            || self.next_pause_traces.load(Ordering::SeqCst)
+            || self.base().cur_collection_attempts.load(Ordering::SeqCst) > 1
+        {
+            // Reference counting did not reclaim enough memory
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Increment the reference count of an object for a new reference in a reference counting pause.
+    /// If this is the first reference to the object, the object is enqueued so that its fields will
+    /// be counted, and it is unlogged so that the barrier will capture changes to its fields.
+    pub(super) fn increment<Q: ObjectQueue>(
+        &self,
+        queue: &mut Q,
+        object: ObjectReference,
+        worker: &mut GCWorker<VM>,
+    ) -> ObjectReference {
+        if !crate::memory_manager::is_in_mmtk_spaces::<VM>(object) {
+            // We cannot count objects outside MMTk spaces. The binding treats them like roots.
+            return VM::VMActivePlan::vm_trace_object::<Q>(queue, object, worker);
+        }
+        if ref_count::inc::<VM>(object) == 0 {
+            if self.common.get_los().in_space(object) {
+                // A large object that is not counted is a young object. Tracing it moves it out
+                // of the nursery, unlogs it and enqueues it.
+                self.common.get_los().trace_object::<Q>(queue, object);
+            } else {
+                VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC
+                    .mark_as_unlogged::<VM>(object, Ordering::SeqCst);
+                queue.enqueue(object);
+            }
+        }
+        object
+    }
+
+    /// Keep an object alive in a reference counting pause without counting a reference to it. This is
+    /// called for the referents of reference objects, the finalizable objects and the weak
+    /// references of the binding, after the increments and the decrements of the pause are done.
+    /// An object with a count is alive already. An object without a count (a young object that is
+    /// not reachable, or an object whose count dropped to zero in this pause) is counted like a
+    /// root of this pause, so its fields are counted again, and the count is undone in the next
+    /// pause. Its memory is not reclaimed before the lines are swept in the release.
+    pub(super) fn retain<Q: ObjectQueue>(
+        &self,
+        queue: &mut Q,
+        object: ObjectReference,
+        worker: &mut GCWorker<VM>,
+    ) -> ObjectReference {
+        if crate::memory_manager::is_in_mmtk_spaces::<VM>(object) {
+            if object.is_live() {
+                return object;
+            }
+            self.add_root_buffer(vec![object]);
+        }
+        self.increment(queue, object, worker)
+    }
+
+    /// Record the decrements captured by a mutator barrier.
+    pub(super) fn add_dec_buffer(&self, decs: Vec<ObjectReference>) {
+        self.dec_buffers.lock().unwrap().push(decs);
+    }
+
+    /// Record the root objects found in the current pause.
+    pub(super) fn add_root_buffer(&self, roots: Vec<ObjectReference>) {
+        if !roots.is_empty() {
+            self.root_buffers.lock().unwrap().push(roots);
+        }
+    }
+
+    /// Take all the decrements for the current reference counting pause, including the deferred
+    /// decrements for the roots of the last pause.
+    pub(super) fn take_dec_buffers(&self) -> Vec<Vec<ObjectReference>> {
+        let mut buffers = std::mem::take(&mut *self.dec_buffers.lock().unwrap());
+        buffers.append(&mut self.prev_root_buffers.lock().unwrap());
+        buffers
+    }
+}
@@ -0,0 +1,27 @@
+//! Plan: LXR, a reference counting Immix plan.
+//!
+//! Most pauses are reference counting pauses. Objects are counted only when they get their
+//! first reference from a counted object or a root. Unreachable young objects are never counted,
+//! and the lines they occupy are reclaimed without being traced. The increments for the roots
+//! are deferred: they are undone in the next pause.
+//!
+//! Cyclic garbage and objects with stuck counts are reclaimed by occasional tracing pauses,
+//! which also rebuild the reference counts for the Immix space.
+//!
+//! Reference objects and finalizable objects are processed in every pause. In a reference counting
+//! pause, they are processed after the decrements, so an object is dead if it has no count. A
+//! referent that is kept alive, or a finalizable object that is ready for finalization, is counted
+//! like a root of the pause. A referent in a dead cycle keeps its count, and is only found dead by
+//! a tracing pause.
+//!
+//! The barrier takes a snapshot of the fields of an object in the mutator thread before the first
+//! write to the object, with
+//! [`Scanning::scan_object_for_mutator`](crate::vm::Scanning::scan_object_for_mutator).
+
+pub(super) mod barrier;
+pub(super) mod gc_work;
+pub(super) mod global;
+pub(super) mod mutator;
+
+pub use self::global::LXR;
+pub use self::global::LXR_CONSTRAINTS;
@@ -0,0 +1,53 @@
+use super::barrier::LXRBarrierSemantics;
+use super::LXR;
+use crate::plan::barriers::SnapshotObjectBarrier;
+use crate::plan::immix;
+use crate::plan::mutator_context::{create_space_mapping, MutatorConfig};
+use crate::util::alloc::allocators::Allocators;
+use crate::util::alloc::AllocatorSelector;
+use crate::util::opaque_pointer::VMWorkerThread;
+use crate::util::VMMutatorThread;
+use crate::vm::VMBinding;
+use crate::{Mutator, MMTK};
+
+pub fn lxr_mutator_prepare<VM: VMBinding>(mutator: &mut Mutator<VM>, tls: VMWorkerThread) {
+    immix::mutator::immix_mutator_prepare(mutator, tls)
+}
+
+pub fn lxr_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, tls: VMWorkerThread) {
+    immix::mutator::immix_mutator_release(mutator, tls)
+}
+
+pub use immix::mutator::ALLOCATOR_MAPPING;
+
+pub fn create_lxr_mutator<VM: VMBinding>(
+    mutator_tls: VMMutatorThread,
+    mmtk: &'static MMTK<VM>,
+) -> Mutator<VM> {
+    let lxr = mmtk.plan.downcast_ref::<LXR<VM>>().unwrap();
+    let config = MutatorConfig {
+        allocator_mapping: &ALLOCATOR_MAPPING,
+        space_mapping: Box::new({
+            let mut vec =
+                create_space_mapping(immix::mutator::RESERVED_ALLOCATORS, true, &*mmtk.plan);
+            vec.push((AllocatorSelector::Immix(0), &lxr.immix_space));
+            vec
+        }),
+        prepare_func: &lxr_mutator_prepare,
+        release_func: &lxr_mutator_release,
+    };
+
+    Mutator {
+        allocators: Allocators::<VM>::new(mutator_tls, &*mmtk.plan, &config.space_mapping),
+        barrier: Box::new(SnapshotObjectBarrier::new(LXRBarrierSemantics::new(
+            mmtk,
+            lxr,
+            mutator_tls,
+        ))),
+        mutator_tls,
+        config,
+        los_redirects: 0,
+        pretenured_objects: 0,
+        plan: &*mmtk.plan,
+    }
+}
@@ -13,13 +13,20 @@ use crate::MMTK;
 use std::marker::PhantomData;
 
 /// iterate through the heap and calculate the new location of live objects
+///
+/// This sets up the compaction regions, and counts the live bytes in each region in parallel. The
+/// forwarding addresses are then calculated for each region in parallel.
 pub struct CalculateForwardingAddress<VM: VMBinding> {
     mc_space: &'static MarkCompactSpace<VM>,
 }
 
 impl<VM: VMBinding> GCWork<VM> for CalculateForwardingAddress<VM> {
-    fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
-        self.mc_space.calculate_forwarding_pointer();
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let num_regions = self.mc_space.prepare_compaction_regions();
+        let packets = (0..num_regions)
+            .map(|index| Box::new(CountLiveBytes::new(self.mc_space, index)) as Box<dyn GCWork<VM>>)
+            .collect();
+        mmtk.scheduler.work_buckets[WorkBucketStage::CalculateForwarding].bulk_add(packets);
     }
 }
 
@@ -29,6 +36,54 @@ impl<VM: VMBinding> CalculateForwardingAddress<VM> {
     }
 }
 
+/// count the live bytes in a compaction region. The packet for the last region computes the
+/// destinations of all the regions, and calculates the forwarding addresses in each region.
+pub struct CountLiveBytes<VM: VMBinding> {
+    mc_space: &'static MarkCompactSpace<VM>,
+    index: usize,
+}
+
+impl<VM: VMBinding> GCWork<VM> for CountLiveBytes<VM> {
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        if self.mc_space.count_live_bytes(self.index) {
+            let packets = self
+                .mc_space
+                .compute_region_destinations()
+                .into_iter()
+                .map(|index| {
+                    Box::new(CalculateRegionForwarding::new(self.mc_space, index))
+                        as Box<dyn GCWork<VM>>
+                })
+                .collect();
+            mmtk.scheduler.work_buckets[WorkBucketStage::CalculateForwarding].bulk_add(packets);
+        }
+    }
+}
+
+impl<VM: VMBinding> CountLiveBytes<VM> {
+    pub fn new(mc_space: &'static MarkCompactSpace<VM>, index: usize) -> Self {
+        Self { mc_space, index }
+    }
+}
+
+/// calculate the new location of live objects in a compaction region
+pub struct CalculateRegionForwarding<VM: VMBinding> {
+    mc_space: &'static MarkCompactSpace<VM>,
+    index: usize,
+}
+
+impl<VM: VMBinding> GCWork<VM> for CalculateRegionForwarding<VM> {
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
+        self.mc_space.calculate_forwarding(self.index);
+    }
+}
+
+impl<VM: VMBinding> CalculateRegionForwarding<VM> {
+    pub fn new(mc_space: &'static MarkCompactSpace<VM>, index: usize) -> Self {
+        Self { mc_space, index }
+    }
+}
+
 /// create another round of root scanning work packets
 /// to update object references
 pub struct UpdateReferences<VM: VMBinding> {
@@ -63,13 +118,17 @@ impl<VM: VMBinding> UpdateReferences<VM> {
 }
 
 /// compact live objects based on forwarding pointers calculated before
+///
+/// This starts compacting the regions that do not overwrite the live objects in other regions.
+/// Each region then starts compacting the regions that wait for it.
 pub struct Compact<VM: VMBinding> {
     mc_space: &'static MarkCompactSpace<VM>,
 }
 
 impl<VM: VMBinding> GCWork<VM> for Compact<VM> {
-    fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
-        self.mc_space.compact();
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let regions = self.mc_space.start_compaction();
+        CompactRegion::schedule(self.mc_space, regions, mmtk);
     }
 }
 
@@ -79,6 +138,37 @@ impl<VM: VMBinding> Compact<VM> {
     }
 }
 
+/// compact live objects in a compaction region
+pub struct CompactRegion<VM: VMBinding> {
+    mc_space: &'static MarkCompactSpace<VM>,
+    index: usize,
+}
+
+impl<VM: VMBinding> GCWork<VM> for CompactRegion<VM> {
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, mmtk: &'static MMTK<VM>) {
+        let ready = self.mc_space.compact_region(self.index);
+        Self::schedule(self.mc_space, ready, mmtk);
+    }
+}
+
+impl<VM: VMBinding> CompactRegion<VM> {
+    pub fn new(mc_space: &'static MarkCompactSpace<VM>, index: usize) -> Self {
+        Self { mc_space, index }
+    }
+
+    fn schedule(
+        mc_space: &'static MarkCompactSpace<VM>,
+        regions: Vec<usize>,
+        mmtk: &'static MMTK<VM>,
+    ) {
+        let packets = regions
+            .into_iter()
+            .map(|index| Box::new(CompactRegion::new(mc_space, index)) as Box<dyn GCWork<VM>>)
+            .collect();
+        mmtk.scheduler.work_buckets[WorkBucketStage::Compact].bulk_add(packets);
+    }
+}
+
 /// Marking trace
 pub type MarkingProcessEdges<VM> = PlanProcessEdges<VM, MarkCompact<VM>, TRACE_KIND_MARK>;
 /// Forwarding trace
@@ -38,7 +38,8 @@ pub struct MarkCompact<VM: VMBinding> {
 pub const MARKCOMPACT_CONSTRAINTS: PlanConstraints = PlanConstraints {
     moves_objects: true,
     gc_header_bits: 2,
-    gc_header_words: 1,
+    // The forwarding addresses are calculated from side metadata, so no header word is needed.
+    gc_header_words: 0,
     num_specialized_scans: 2,
     needs_forward_after_liveness: true,
     ..PlanConstraints::default()
@@ -1,11 +1,13 @@
 use super::MarkSweep;
-use crate::policy::gc_work::DEFAULT_TRACE;
+use crate::policy::gc_work::TraceKind;
 use crate::scheduler::gc_work::*;
 use crate::vm::VMBinding;
 
-pub struct MSGCWorkContext<VM: VMBinding>(std::marker::PhantomData<VM>);
-impl<VM: VMBinding> crate::scheduler::GCWorkContext for MSGCWorkContext<VM> {
+pub struct MSGCWorkContext<VM: VMBinding, const KIND: TraceKind>(std::marker::PhantomData<VM>);
+impl<VM: VMBinding, const KIND: TraceKind> crate::scheduler::GCWorkContext
+    for MSGCWorkContext<VM, KIND>
+{
     type VM = VM;
     type PlanType = MarkSweep<VM>;
-    type ProcessEdgesWorkType = PlanProcessEdges<Self::VM, MarkSweep<VM>, DEFAULT_TRACE>;
+    type ProcessEdgesWorkType = PlanProcessEdges<Self::VM, MarkSweep<VM>, KIND>;
 }
@@ -11,12 +11,15 @@ use crate::plan::PlanConstraints;
 use crate::policy::space::Space;
 use crate::scheduler::GCWorkScheduler;
 use crate::util::alloc::allocators::AllocatorSelector;
+use crate::util::copy::*;
 use crate::util::heap::VMRequest;
 use crate::util::metadata::side_metadata::{SideMetadataContext, SideMetadataSanity};
 use crate::util::VMWorkerThread;
 use crate::vm::VMBinding;
 use enum_map::EnumMap;
 use mmtk_macros::PlanTraceObject;
+#[cfg(not(feature = "malloc_mark_sweep"))]
+use std::sync::atomic::Ordering;
 
 #[cfg(feature = "malloc_mark_sweep")]
 pub type MarkSweepSpace<VM> = crate::policy::marksweepspace::malloc_ms::MallocSpace<VM>;
@@ -28,16 +31,28 @@ pub type MarkSweepSpace<VM> = crate::policy::marksweepspace::native_ms::MarkSwee
 #[cfg(not(feature = "malloc_mark_sweep"))]
 use crate::policy::marksweepspace::native_ms::MAX_OBJECT_SIZE;
 
+// The malloc mark sweep space does not move objects, so it never does a defrag GC.
+#[cfg(feature = "malloc_mark_sweep")]
+use crate::policy::gc_work::{
+    DEFAULT_TRACE as TRACE_KIND_FAST, DEFAULT_TRACE as TRACE_KIND_DEFRAG,
+};
+#[cfg(not(feature = "malloc_mark_sweep"))]
+use crate::policy::marksweepspace::native_ms::{TRACE_KIND_DEFRAG, TRACE_KIND_FAST};
+
 #[derive(PlanTraceObject)]
 pub struct MarkSweep<VM: VMBinding> {
     #[fallback_trace]
     common: CommonPlan<VM>,
-    #[trace]
+    #[trace(CopySemantics::DefaultCopy)]
     ms: MarkSweepSpace<VM>,
 }
 
 pub const MS_CONSTRAINTS: PlanConstraints = PlanConstraints {
-    moves_objects: false,
+    // The native mark sweep space evacuates the objects in sparse blocks with `marksweep_defrag`.
+    moves_objects: cfg!(all(
+        feature = "marksweep_defrag",
+        not(feature = "malloc_mark_sweep")
+    )),
     gc_header_bits: 2,
     gc_header_words: 0,
     num_specialized_scans: 1,
@@ -49,6 +64,19 @@ pub const MS_CONSTRAINTS: PlanConstraints = PlanConstraints {
 impl<VM: VMBinding> Plan for MarkSweep<VM> {
     type VM = VM;
 
+    #[cfg(not(feature = "malloc_mark_sweep"))]
+    fn create_copy_config(&'static self) -> CopyConfig<Self::VM> {
+        use enum_map::enum_map;
+        CopyConfig {
+            copy_mapping: enum_map! {
+                CopySemantics::DefaultCopy => CopySelector::MarkSweep(0),
+                _ => CopySelector::Unused,
+            },
+            space_mapping: vec![(CopySelector::MarkSweep(0), &self.ms)],
+            constraints: &MS_CONSTRAINTS,
+        }
+    }
+
     fn get_spaces(&self) -> Vec<&dyn Space<Self::VM>> {
         let mut ret = self.common.get_spaces();
         ret.push(&self.ms);
@@ -58,7 +86,11 @@ impl<VM: VMBinding> Plan for MarkSweep<VM> {
     fn schedule_collection(&'static self, scheduler: &GCWorkScheduler<VM>) {
         self.base().set_collection_kind::<Self>(self);
         self.base().set_gc_status(GcStatus::GcPrepare);
-        scheduler.schedule_common_work::<MSGCWorkContext<VM>>(self);
+        if self.decide_whether_to_defrag() {
+            scheduler.schedule_common_work::<MSGCWorkContext<VM, TRACE_KIND_DEFRAG>>(self);
+        } else {
+            scheduler.schedule_common_work::<MSGCWorkContext<VM, TRACE_KIND_FAST>>(self);
+        }
     }
 
     fn get_allocator_mapping(&self) -> &'static EnumMap<AllocationSemantics, AllocatorSelector> {
@@ -68,6 +100,12 @@ impl<VM: VMBinding> Plan for MarkSweep<VM> {
     fn prepare(&mut self, tls: VMWorkerThread) {
         self.common.prepare(tls, true);
         self.ms.prepare();
+        #[cfg(not(feature = "malloc_mark_sweep"))]
+        if self.ms.in_defrag() {
+            self.base()
+                .pause_time
+                .set_evacuated_pages(self.ms.defrag_evacuated_pages());
+        }
     }
 
     fn release(&mut self, tls: VMWorkerThread) {
@@ -83,6 +121,11 @@ impl<VM: VMBinding> Plan for MarkSweep<VM> {
         self.common.get_used_pages() + self.ms.reserved_pages()
     }
 
+    #[cfg(not(feature = "malloc_mark_sweep"))]
+    fn get_collection_reserved_pages(&self) -> usize {
+        self.ms.defrag_headroom_pages()
+    }
+
     fn base(&self) -> &BasePlan<VM> {
         &self.common.base
     }
@@ -107,12 +150,20 @@ impl<VM: VMBinding> MarkSweep<VM> {
             global_side_metadata_specs,
         };
 
+        #[cfg(feature = "malloc_mark_sweep")]
+        let ms =
+            MarkSweepSpace::new(plan_args.get_space_args("ms", true, VMRequest::discontiguous()));
+        #[cfg(not(feature = "malloc_mark_sweep"))]
+        let ms = MarkSweepSpace::new_with_args(
+            plan_args.get_space_args("ms", true, VMRequest::discontiguous()),
+            crate::policy::marksweepspace::native_ms::MarkSweepSpaceArgs {
+                defrag: cfg!(feature = "marksweep_defrag"),
+                ..Default::default()
+            },
+        );
+
         let res = MarkSweep {
-            ms: MarkSweepSpace::new(plan_args.get_space_args(
-                "ms",
-                true,
-                VMRequest::discontiguous(),
-            )),
+            ms,
             common: CommonPlan::new(plan_args),
         };
 
@@ -124,6 +175,29 @@ impl<VM: VMBinding> MarkSweep<VM> {
         res
     }
 
+    /// Decide whether the current GC evacuates the sparse blocks in the mark sweep space.
+    #[cfg(not(feature = "malloc_mark_sweep"))]
+    fn decide_whether_to_defrag(&self) -> bool {
+        self.ms.decide_whether_to_defrag(
+            self.is_emergency_collection(),
+            self.base().cur_collection_attempts.load(Ordering::SeqCst),
+            self.base().is_user_triggered_collection(),
+            *self.base().options.full_heap_system_gc,
+            // An emergency GC evacuates as much as it can, regardless of the pause time target.
+            if self.is_emergency_collection() {
+                None
+            } else {
+                self.base().pause_time.evacuation_target_pages()
+            },
+        )
+    }
+
+    /// The malloc mark sweep space never moves objects.
+    #[cfg(feature = "malloc_mark_sweep")]
+    fn decide_whether_to_defrag(&self) -> bool {
+        false
+    }
+
     pub fn ms_space(&self) -> &MarkSweepSpace<VM> {
         &self.ms
     }
@@ -14,7 +14,9 @@
 //! For more about implementing a plan, it is recommended to read the [MMTk tutorial](/docs/tutorial/Tutorial.md).
 
 mod barriers;
+pub use barriers::BarrierFastPath;
 pub use barriers::BarrierSelector;
+pub use barriers::SideMetadataBitLayout;
 
 pub(crate) mod gc_requester;
 
@@ -38,12 +40,16 @@ pub use plan_constraints::DEFAULT_PLAN_CONSTRAINTS;
 mod tracing;
 pub use tracing::{ObjectQueue, ObjectsClosure, VectorObjectQueue, VectorQueue};
 
+/// Concurrent plans (marking the heap while mutators are running)
+mod concurrent;
 /// Generational plans (with a copying nursery)
 mod generational;
 /// Sticky plans (using sticky marks for generational behaviors without a copying nursery)
 mod sticky;
 
+mod g1;
 mod immix;
+mod lxr;
 mod markcompact;
 mod marksweep;
 mod nogc;
@@ -52,16 +58,22 @@ mod semispace;
 
 pub(crate) use generational::global::is_nursery_gc;
 pub(crate) use generational::global::GenerationalPlan;
+pub use generational::pretenuring::AllocationSite;
 
 // Expose plan constraints as public. Though a binding can get them from plan.constraints(),
 // it is possible for performance reasons that they want the constraints as constants.
 
+pub use concurrent::immix::CONCURRENT_IMMIX_CONSTRAINTS;
+pub use g1::G1_CONSTRAINTS;
 pub use generational::copying::GENCOPY_CONSTRAINTS;
 pub use generational::immix::GENIMMIX_CONSTRAINTS;
+pub use generational::marksweep::GENMS_CONSTRAINTS;
 pub use immix::IMMIX_CONSTRAINTS;
+pub use lxr::LXR_CONSTRAINTS;
 pub use markcompact::MARKCOMPACT_CONSTRAINTS;
 pub use marksweep::MS_CONSTRAINTS;
 pub use nogc::NOGC_CONSTRAINTS;
 pub use pageprotect::PP_CONSTRAINTS;
 pub use semispace::SS_CONSTRAINTS;
 pub use sticky::immix::STICKY_IMMIX_CONSTRAINTS;
+pub use sticky::marksweep::STICKY_MS_CONSTRAINTS;
@@ -3,11 +3,17 @@
 use crate::plan::barriers::Barrier;
 use crate::plan::global::Plan;
 use crate::plan::AllocationSemantics;
+use crate::plan::AllocationSite;
 use crate::policy::space::Space;
 use crate::util::alloc::allocators::{AllocatorSelector, Allocators};
+#[cfg(feature = "ro_space")]
+use crate::util::alloc::AllocationError;
+#[cfg(feature = "code_space")]
+use crate::util::alloc::BumpAllocator;
+use crate::util::alloc::FreeListAllocator;
 use crate::util::{Address, ObjectReference};
 use crate::util::{VMMutatorThread, VMWorkerThread};
-use crate::vm::VMBinding;
+use crate::vm::{Collection, ObjectModel, VMBinding};
 
 use enum_map::EnumMap;
 
@@ -71,14 +77,49 @@ pub struct Mutator<VM: VMBinding> {
     pub mutator_tls: VMMutatorThread,
     pub plan: &'static dyn Plan<VM = VM>,
     pub config: MutatorConfig<VM>,
+    /// The number of objects that `alloc` redirected to the large object space (see
+    /// `redirect_to_los`), and that have not been seen by `post_alloc` yet. `post_alloc` only
+    /// looks up the space of an object if this is not zero.
+    pub(crate) los_redirects: usize,
+    /// The number of objects that `alloc_with_site` allocated in the mature space of a
+    /// generational plan, and that have not been seen by `post_alloc` yet.
+    pub(crate) pretenured_objects: usize,
 }
 
 impl<VM: VMBinding> MutatorContext<VM> for Mutator<VM> {
     fn prepare(&mut self, tls: VMWorkerThread) {
-        (*self.config.prepare_func)(self, tls)
+        (*self.config.prepare_func)(self, tls);
+        if let Some(allocator) = self.nonmoving_allocator_mut() {
+            allocator.prepare();
+        }
     }
     fn release(&mut self, tls: VMWorkerThread) {
-        (*self.config.release_func)(self, tls)
+        (*self.config.release_func)(self, tls);
+        // The mutator is stopped, so we can read the counters of its allocators.
+        self.plan.base().stats.record_allocated_bytes(
+            self.mutator_tls,
+            self.allocated_bytes_per_semantics(),
+            false,
+        );
+        // The non-moving space is still being traced if the marking continues after this pause
+        // (e.g. the initial mark pause of a concurrent plan). Its blocks cannot be swept yet.
+        let plan = self.plan;
+        if let Some(allocator) = self.nonmoving_allocator_mut() {
+            if !plan.common().get_nonmoving().is_tracing() {
+                allocator.release();
+            }
+        }
+        // The code spaces free the runs of pages without live objects in a full heap GC, which
+        // may include the buffers of the code allocators. Give up the buffers in every GC, so a
+        // run without live objects is never in use.
+        #[cfg(feature = "code_space")]
+        for semantics in [AllocationSemantics::Code, AllocationSemantics::LargeCode] {
+            let selector = self.config.allocator_mapping[semantics];
+            unsafe { self.allocators.get_allocator_mut(selector) }
+                .downcast_mut::<BumpAllocator<VM>>()
+                .unwrap()
+                .reset();
+        }
     }
 
     // Note that this method is slow, and we expect VM bindings that care about performance to implement allocation fastpath sequence in their bindings.
@@ -89,11 +130,53 @@ impl<VM: VMBinding> MutatorContext<VM> for Mutator<VM> {
         offset: isize,
         allocator: AllocationSemantics,
     ) -> Address {
-        unsafe {
+        #[cfg(feature = "ro_space")]
+        if self.alloc_into_sealed_space(allocator) {
+            return Address::ZERO;
+        }
+        let redirected = self.redirect_to_los(size, align, offset, allocator);
+        let result = self.alloc_with_allocator(
+            self.config.allocator_mapping[redirected],
+            size,
+            align,
+            offset,
+        );
+        if redirected != allocator && !result.is_zero() {
+            self.los_redirects += 1;
+        }
+        result
+    }
+
+    fn alloc_many(
+        &mut self,
+        size: usize,
+        align: usize,
+        offset: isize,
+        allocator: AllocationSemantics,
+        count: usize,
+        result: &mut Vec<Address>,
+    ) -> usize {
+        #[cfg(feature = "ro_space")]
+        if self.alloc_into_sealed_space(allocator) {
+            return 0;
+        }
+        let redirected = self.redirect_to_los(size, align, offset, allocator);
+        let allocator_impl = unsafe {
             self.allocators
-                .get_allocator_mut(self.config.allocator_mapping[allocator])
+                .get_allocator_mut(self.config.allocator_mapping[redirected])
+        };
+        let mut allocated = 0;
+        while allocated < count {
+            let n = allocator_impl.alloc_many(size, align, offset, count - allocated, result);
+            if n == 0 {
+                break;
+            }
+            allocated += n;
         }
-        .alloc(size, align, offset)
+        if redirected != allocator {
+            self.los_redirects += allocated;
+        }
+        allocated
     }
 
     // Note that this method is slow, and we expect VM bindings that care about performance to implement allocation fastpath sequence in their bindings.
@@ -103,6 +186,27 @@ impl<VM: VMBinding> MutatorContext<VM> for Mutator<VM> {
         _bytes: usize,
         allocator: AllocationSemantics,
     ) {
+        if let Some(selector) = self.pretenure_allocator_of(refer, allocator) {
+            self.pretenured_objects -= 1;
+            unsafe { self.allocators.get_allocator_mut(selector) }
+                .get_space()
+                .initialize_object_metadata(refer, true);
+            // The object is not traced in nursery GCs, and the binding may initialize its fields
+            // without the write barrier. The barrier remembers the object, so the next nursery GC
+            // scans its fields.
+            if self.plan.constraints().needs_log_bit {
+                VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC
+                    .mark_as_unlogged::<VM>(refer, std::sync::atomic::Ordering::SeqCst);
+            }
+            self.barrier.object_probable_write(refer);
+            return;
+        }
+        let allocator = if self.is_redirected_to_los(refer, allocator) {
+            self.los_redirects -= 1;
+            AllocationSemantics::Los
+        } else {
+            allocator
+        };
         unsafe {
             self.allocators
                 .get_allocator_mut(self.config.allocator_mapping[allocator])
@@ -111,6 +215,38 @@ impl<VM: VMBinding> MutatorContext<VM> for Mutator<VM> {
         .initialize_object_metadata(refer, true)
     }
 
+    fn post_alloc_many(
+        &mut self,
+        objects: &[ObjectReference],
+        bytes: usize,
+        allocator: AllocationSemantics,
+    ) {
+        // `alloc_many()` decides whether to redirect the objects to the large object space once for
+        // all of them, as they have the same size and alignment. So we only need to check the first one.
+        let is_redirected = objects.first().map_or(false, |object| {
+            self.is_redirected_to_los(*object, allocator)
+        });
+        debug_assert!(
+            objects
+                .iter()
+                .all(|object| self.is_redirected_to_los(*object, allocator) == is_redirected),
+            "The objects were not allocated by one alloc_many() call with {:?}",
+            allocator
+        );
+        let redirected = if is_redirected {
+            self.los_redirects -= objects.len();
+            AllocationSemantics::Los
+        } else {
+            allocator
+        };
+        unsafe {
+            self.allocators
+                .get_allocator_mut(self.config.allocator_mapping[redirected])
+        }
+        .get_space()
+        .initialize_objects_metadata(objects, bytes, true)
+    }
+
     fn get_tls(&self) -> VMMutatorThread {
         self.mutator_tls
     }
@@ -128,21 +264,223 @@ impl<VM: VMBinding> MutatorContext<VM> for Mutator<VM> {
 }
 
 impl<VM: VMBinding> Mutator<VM> {
+    /// Allocate an object from an allocation site. In a generational plan, the survival rate of the
+    /// site is tracked, and a `Default` object from a pretenured site is allocated in the mature space.
+    pub(crate) fn alloc_with_site(
+        &mut self,
+        size: usize,
+        align: usize,
+        offset: isize,
+        semantics: AllocationSemantics,
+        site: AllocationSite,
+    ) -> Address {
+        let plan = self.plan;
+        let (gen, sites) = match plan.generational() {
+            Some(gen) if semantics == AllocationSemantics::Default => {
+                match gen.allocation_sites() {
+                    Some(sites) => (gen, sites),
+                    None => return self.alloc(size, align, offset, semantics),
+                }
+            }
+            _ => return self.alloc(size, align, offset, semantics),
+        };
+        // Objects with large alignments are left to `alloc`, which may redirect them to the large
+        // object space.
+        if align <= VM::MAX_ALIGNMENT && sites.is_pretenured(site) {
+            if let Some(selector) = gen.pretenure_allocator() {
+                let result = self.alloc_with_allocator(selector, size, align, offset);
+                if !result.is_zero() {
+                    self.pretenured_objects += 1;
+                }
+                return result;
+            }
+        }
+        let result = self.alloc(size, align, offset, semantics);
+        if !result.is_zero() && gen.is_address_in_nursery(result) {
+            sites.record_allocation(result, size, site);
+        }
+        result
+    }
+
+    fn alloc_with_allocator(
+        &mut self,
+        selector: AllocatorSelector,
+        size: usize,
+        align: usize,
+        offset: isize,
+    ) -> Address {
+        let allocator_impl = unsafe { self.allocators.get_allocator_mut(selector) };
+        allocator_impl.alloc(size, align, offset)
+    }
+
+    /// The bytes allocated by this mutator with the semantics since the mutator was created. The
+    /// bytes are counted by the allocator of the semantics, so an object that `alloc` redirected to
+    /// the large object space is counted as `Los`. A `Default` object that `alloc_with_site`
+    /// allocated in the mature space of a generational plan is counted as `Default`.
+    ///
+    /// This reads the thread local state of the allocators, so it should only be called by the
+    /// mutator thread, or when the mutator is stopped.
+    pub fn allocated_bytes(&self, semantics: AllocationSemantics) -> usize {
+        let selector = self.config.allocator_mapping[semantics];
+        if selector == AllocatorSelector::None {
+            return 0;
+        }
+        let mut bytes = unsafe { self.allocators.get_allocator(selector) }.allocated_bytes();
+        if semantics == AllocationSemantics::Default {
+            if let Some(pretenure) = self
+                .plan
+                .generational()
+                .and_then(|gen| gen.pretenure_allocator())
+            {
+                bytes += unsafe { self.allocators.get_allocator(pretenure) }.allocated_bytes();
+            }
+        }
+        bytes
+    }
+
+    fn allocated_bytes_per_semantics(&self) -> EnumMap<AllocationSemantics, usize> {
+        enum_map::enum_map! { semantics => self.allocated_bytes(semantics) }
+    }
+
+    /// Check if this is an allocation into the read-only space after the space is sealed. If so,
+    /// report the error to the binding.
+    #[cfg(feature = "ro_space")]
+    fn alloc_into_sealed_space(&self, semantics: AllocationSemantics) -> bool {
+        if semantics == AllocationSemantics::ReadOnly && self.plan.base().ro_space.is_sealed() {
+            VM::VMCollection::out_of_memory(
+                self.mutator_tls.0,
+                AllocationError::ReadOnlySpaceSealed,
+            );
+            true
+        } else {
+            false
+        }
+    }
+
     /// Get all the valid allocator selector (no duplicate)
     fn get_all_allocator_selectors(&self) -> Vec<AllocatorSelector> {
         use itertools::Itertools;
+        // The space mapping may include allocators that no semantics maps to, such as the
+        // allocator for pretenured objects in generational plans.
         self.config
             .allocator_mapping
             .iter()
             .map(|(_, selector)| *selector)
+            .chain(
+                self.config
+                    .space_mapping
+                    .iter()
+                    .map(|(selector, _)| *selector),
+            )
             .sorted()
             .dedup()
             .filter(|selector| *selector != AllocatorSelector::None)
             .collect()
     }
 
+    /// The free list allocator for the non-moving space of the common plan, if the plan uses the
+    /// common plan. Each mutator prepares and releases it in every GC, in addition to the
+    /// allocators prepared and released by the plan.
+    fn nonmoving_allocator_mut(&mut self) -> Option<&mut FreeListAllocator<VM>> {
+        match self.config.allocator_mapping[AllocationSemantics::NonMoving] {
+            selector @ AllocatorSelector::FreeList(_) => Some(
+                unsafe { self.allocators.get_allocator_mut(selector) }
+                    .downcast_mut::<FreeListAllocator<VM>>()
+                    .unwrap(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Decide whether an allocation should go to the large object space instead:
+    /// * A `NonMoving` object that is too large for the non-moving mark sweep space is allocated in
+    ///   the large object space, which does not move objects either.
+    /// * A `Default` or `NonMoving` object with a large alignment (larger than `VM::MAX_ALIGNMENT`)
+    ///   is allocated in the large object space if the padding for the alignment makes it too large
+    ///   for the allocator of the semantics.
+    fn redirect_to_los(
+        &self,
+        size: usize,
+        align: usize,
+        offset: isize,
+        semantics: AllocationSemantics,
+    ) -> AllocationSemantics {
+        let max_bytes = match self.config.allocator_mapping[semantics] {
+            AllocatorSelector::FreeList(_) => {
+                crate::policy::marksweepspace::native_ms::MAX_OBJECT_SIZE
+            }
+            _ => self.plan.constraints().max_non_los_default_alloc_bytes,
+        };
+        match semantics {
+            AllocationSemantics::NonMoving if size > max_bytes => AllocationSemantics::Los,
+            AllocationSemantics::Default | AllocationSemantics::NonMoving
+                if align > VM::MAX_ALIGNMENT =>
+            {
+                let allocator = unsafe {
+                    self.allocators
+                        .get_allocator(self.config.allocator_mapping[semantics])
+                };
+                if allocator.large_alignment_bytes(size, align, offset) > max_bytes {
+                    AllocationSemantics::Los
+                } else {
+                    semantics
+                }
+            }
+            _ => semantics,
+        }
+    }
+
+    /// Find out if `alloc` redirected an object of the semantics to the large object space (see
+    /// `redirect_to_los`). `post_alloc` does not know the alignment of the object, so we check the
+    /// space of the object by its address, but only if some objects have been redirected.
+    fn is_redirected_to_los(
+        &self,
+        object: ObjectReference,
+        semantics: AllocationSemantics,
+    ) -> bool {
+        if self.los_redirects == 0
+            || !matches!(
+                semantics,
+                AllocationSemantics::Default | AllocationSemantics::NonMoving
+            )
+        {
+            return false;
+        }
+        unsafe {
+            self.allocators
+                .get_allocator(self.config.allocator_mapping[AllocationSemantics::Los])
+        }
+        .get_space()
+        .in_space(object)
+    }
+
+    /// Find out if `alloc_with_site` allocated an object in the mature space of a generational plan.
+    /// Return the allocator it used. We only check the space of the object if some objects have
+    /// been allocated in the mature space.
+    fn pretenure_allocator_of(
+        &self,
+        object: ObjectReference,
+        semantics: AllocationSemantics,
+    ) -> Option<AllocatorSelector> {
+        if self.pretenured_objects == 0 || semantics != AllocationSemantics::Default {
+            return None;
+        }
+        let selector = self.plan.generational()?.pretenure_allocator()?;
+        let space = unsafe { self.allocators.get_allocator(selector) }.get_space();
+        if space.in_space(object) {
+            Some(selector)
+        } else {
+            None
+        }
+    }
+
     /// Inform each allocator about destroying. Call allocator-specific on destroy methods.
     pub fn on_destroy(&mut self) {
+        self.plan.base().stats.record_allocated_bytes(
+            self.mutator_tls,
+            self.allocated_bytes_per_semantics(),
+            true,
+        );
         for selector in self.get_all_allocator_selectors() {
             unsafe { self.allocators.get_allocator_mut(selector) }.on_mutator_destroy();
         }
@@ -164,7 +502,26 @@ pub trait MutatorContext<VM: VMBinding>: Send + 'static {
         offset: isize,
         allocator: AllocationSemantics,
     ) -> Address;
+    /// Allocate up to `count` objects of the same size, alignment and offset, and append their
+    /// addresses to `result`. Return the number of objects allocated, which is fewer than `count`
+    /// only if the allocation failed.
+    fn alloc_many(
+        &mut self,
+        size: usize,
+        align: usize,
+        offset: isize,
+        allocator: AllocationSemantics,
+        count: usize,
+        result: &mut Vec<Address>,
+    ) -> usize;
     fn post_alloc(&mut self, refer: ObjectReference, bytes: usize, allocator: AllocationSemantics);
+    /// Perform post-allocation actions for objects of the same size allocated with `alloc_many()`.
+    fn post_alloc_many(
+        &mut self,
+        objects: &[ObjectReference],
+        bytes: usize,
+        allocator: AllocationSemantics,
+    );
     fn flush_remembered_sets(&mut self) {
         self.barrier().flush();
     }
@@ -196,6 +553,7 @@ pub(crate) struct ReservedAllocators {
     pub n_immix: u8,
     pub n_mark_compact: u8,
     pub n_free_list: u8,
+    pub n_region: u8,
 }
 
 impl ReservedAllocators {
@@ -206,6 +564,7 @@ impl ReservedAllocators {
         n_immix: 0,
         n_mark_compact: 0,
         n_free_list: 0,
+        n_region: 0,
     };
     /// check if the number of each allocator is okay. Panics if any allocator exceeds the max number.
     fn validate(&self) {
@@ -234,6 +593,10 @@ impl ReservedAllocators {
             self.n_free_list as usize <= MAX_FREE_LIST_ALLOCATORS,
             "Allocator mapping declared more free list allocators than the max allowed."
         );
+        assert!(
+            self.n_region as usize <= MAX_REGION_ALLOCATORS,
+            "Allocator mapping declared more region allocators than the max allowed."
+        );
     }
 }
 
@@ -274,17 +637,23 @@ pub(crate) fn create_allocator_mapping(
     // spaces in common plan
 
     if include_common_plan {
-        map[AllocationSemantics::Immortal] =
-            AllocatorSelector::BumpPointer(reserved.n_bump_pointer);
-        reserved.n_bump_pointer += 1;
+        #[cfg(not(feature = "immortal_free_list"))]
+        {
+            map[AllocationSemantics::Immortal] =
+                AllocatorSelector::BumpPointer(reserved.n_bump_pointer);
+            reserved.n_bump_pointer += 1;
+        }
+        #[cfg(feature = "immortal_free_list")]
+        {
+            map[AllocationSemantics::Immortal] = AllocatorSelector::FreeList(reserved.n_free_list);
+            reserved.n_free_list += 1;
+        }
 
         map[AllocationSemantics::Los] = AllocatorSelector::LargeObject(reserved.n_large_object);
         reserved.n_large_object += 1;
 
-        // TODO: This should be freelist allocator once we use marksweep for nonmoving space.
-        map[AllocationSemantics::NonMoving] =
-            AllocatorSelector::BumpPointer(reserved.n_bump_pointer);
-        reserved.n_bump_pointer += 1;
+        map[AllocationSemantics::NonMoving] = AllocatorSelector::FreeList(reserved.n_free_list);
+        reserved.n_free_list += 1;
     }
 
     reserved.validate();
@@ -336,22 +705,32 @@ pub(crate) fn create_space_mapping<VM: VMBinding>(
     // spaces in CommonPlan
 
     if include_common_plan {
-        vec.push((
-            AllocatorSelector::BumpPointer(reserved.n_bump_pointer),
-            plan.common().get_immortal(),
-        ));
-        reserved.n_bump_pointer += 1;
+        #[cfg(not(feature = "immortal_free_list"))]
+        {
+            vec.push((
+                AllocatorSelector::BumpPointer(reserved.n_bump_pointer),
+                plan.common().get_immortal(),
+            ));
+            reserved.n_bump_pointer += 1;
+        }
+        #[cfg(feature = "immortal_free_list")]
+        {
+            vec.push((
+                AllocatorSelector::FreeList(reserved.n_free_list),
+                plan.common().get_immortal(),
+            ));
+            reserved.n_free_list += 1;
+        }
         vec.push((
             AllocatorSelector::LargeObject(reserved.n_large_object),
             plan.common().get_los(),
         ));
         reserved.n_large_object += 1;
-        // TODO: This should be freelist allocator once we use marksweep for nonmoving space.
         vec.push((
-            AllocatorSelector::BumpPointer(reserved.n_bump_pointer),
+            AllocatorSelector::FreeList(reserved.n_free_list),
             plan.common().get_nonmoving(),
         ));
-        reserved.n_bump_pointer += 1;
+        reserved.n_free_list += 1;
     }
 
     reserved.validate();
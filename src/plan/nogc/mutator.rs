@@ -71,6 +71,8 @@ pub fn create_nogc_mutator<VM: VMBinding>(
         barrier: Box::new(NoBarrier),
         mutator_tls,
         config,
+        los_redirects: 0,
+        pretenured_objects: 0,
         plan,
     }
 }
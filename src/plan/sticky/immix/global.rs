@@ -113,6 +113,10 @@ impl<VM: VMBinding> Plan for StickyImmix<VM> {
 
     fn prepare(&mut self, tls: crate::util::VMWorkerThread) {
         if self.is_current_gc_nursery() {
+            self.base().pause_time.set_collected_pages(
+                self.immix.immix_space.get_pages_allocated()
+                    + self.immix.common.los.nursery_pages(),
+            );
             // Prepare both large object space and immix space
             self.immix.immix_space.prepare(false);
             self.immix.common.los.prepare(false);
@@ -145,8 +149,8 @@ impl<VM: VMBinding> Plan for StickyImmix<VM> {
         space_full: bool,
         space: Option<&dyn crate::policy::space::Space<Self::VM>>,
     ) -> bool {
-        let nursery_full =
-            self.immix.immix_space.get_pages_allocated() > self.options().get_max_nursery_pages();
+        let nursery_full = self.immix.immix_space.get_pages_allocated()
+            > self.base().pause_time.nursery_pages(self.options());
         if space_full && space.is_some() && space.unwrap().name() == self.immix.immix_space.name() {
             self.next_gc_full_heap.store(true, Ordering::SeqCst);
         }
@@ -283,6 +287,12 @@ impl<VM: VMBinding> crate::plan::generational::global::GenerationalPlanExt<VM> f
                 .trace_object::<Q>(queue, object);
         }
 
+        if self.immix.common().get_nonmoving().in_space(object) {
+            // Non-moving objects are allocated as mature objects.
+            trace!("Non-moving mature object {}, skip", object);
+            return object;
+        }
+
         warn!(
             "Object {} is not in nursery or in LOS, it is not traced!",
             object
@@ -311,6 +321,7 @@ impl<VM: VMBinding> StickyImmix<VM> {
                 // In full heap GC, mature objects may die, and their unlogged bit needs to be reset.
                 // Along with the option above, we unlog them again during tracing.
                 reset_log_bit_in_major_gc: true,
+                rc_enabled: false,
             },
         );
         let full_heap_gc_count = immix.base().stats.new_event_counter("majorGC", true, true);
@@ -45,6 +45,8 @@ pub fn create_stickyimmix_mutator<VM: VMBinding>(
         ))),
         mutator_tls,
         config,
+        los_redirects: 0,
+        pretenured_objects: 0,
         plan: &*mmtk.plan,
     }
 }
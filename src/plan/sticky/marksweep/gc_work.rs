@@ -0,0 +1,19 @@
+use crate::policy::gc_work::DEFAULT_TRACE;
+use crate::scheduler::gc_work::PlanProcessEdges;
+use crate::{plan::generational::gc_work::GenNurseryProcessEdges, vm::VMBinding};
+
+use super::global::StickyMarkSweep;
+
+pub struct StickyMarkSweepNurseryGCWorkContext<VM: VMBinding>(std::marker::PhantomData<VM>);
+impl<VM: VMBinding> crate::scheduler::GCWorkContext for StickyMarkSweepNurseryGCWorkContext<VM> {
+    type VM = VM;
+    type PlanType = StickyMarkSweep<VM>;
+    type ProcessEdgesWorkType = GenNurseryProcessEdges<VM, Self::PlanType>;
+}
+
+pub struct StickyMarkSweepMatureGCWorkContext<VM: VMBinding>(std::marker::PhantomData<VM>);
+impl<VM: VMBinding> crate::scheduler::GCWorkContext for StickyMarkSweepMatureGCWorkContext<VM> {
+    type VM = VM;
+    type PlanType = StickyMarkSweep<VM>;
+    type ProcessEdgesWorkType = PlanProcessEdges<VM, Self::PlanType, DEFAULT_TRACE>;
+}
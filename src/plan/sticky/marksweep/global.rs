@@ -0,0 +1,311 @@
+use crate::plan::generational::global::GenerationalPlan;
+use crate::plan::global::BasePlan;
+use crate::plan::global::CommonPlan;
+use crate::plan::global::CreateGeneralPlanArgs;
+use crate::plan::global::CreateSpecificPlanArgs;
+use crate::plan::GcStatus;
+use crate::plan::PlanConstraints;
+use crate::policy::marksweepspace::native_ms::{MarkSweepSpace, MarkSweepSpaceArgs};
+use crate::policy::sft::SFT;
+use crate::policy::space::Space;
+use crate::scheduler::GCWorkScheduler;
+use crate::util::heap::VMRequest;
+use crate::util::metadata::side_metadata::{SideMetadataContext, SideMetadataSanity};
+use crate::util::statistics::counter::EventCounter;
+use crate::util::VMWorkerThread;
+use crate::vm::ObjectModel;
+use crate::vm::VMBinding;
+use crate::Plan;
+
+use atomic::Ordering;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+
+use mmtk_macros::PlanTraceObject;
+
+use super::gc_work::StickyMarkSweepMatureGCWorkContext;
+use super::gc_work::StickyMarkSweepNurseryGCWorkContext;
+
+/// Sticky mark sweep. This is a generational plan without copying. Objects are allocated into a
+/// native mark sweep space, and the mark bits are kept across nursery GCs. A nursery GC only traces
+/// the unmarked objects (the objects allocated since the last GC) from the roots and the objects
+/// remembered by the object barrier. A full heap GC clears the mark bits and traces the whole heap.
+#[derive(PlanTraceObject)]
+pub struct StickyMarkSweep<VM: VMBinding> {
+    #[fallback_trace]
+    common: CommonPlan<VM>,
+    #[trace]
+    ms: MarkSweepSpace<VM>,
+    gc_full_heap: AtomicBool,
+    next_gc_full_heap: AtomicBool,
+    full_heap_gc_count: Arc<Mutex<EventCounter>>,
+}
+
+pub const STICKY_MS_CONSTRAINTS: PlanConstraints = PlanConstraints {
+    moves_objects: false,
+    gc_header_bits: 2,
+    gc_header_words: 0,
+    num_specialized_scans: 1,
+    max_non_los_default_alloc_bytes: crate::policy::marksweepspace::native_ms::MAX_OBJECT_SIZE,
+    needs_log_bit: true,
+    barrier: crate::plan::BarrierSelector::ObjectBarrier,
+    // We may trace duplicate edges in sticky mark sweep (or any plan that uses object remembering barrier). See https://github.com/mmtk/mmtk-core/issues/743.
+    may_trace_duplicate_edges: true,
+    ..PlanConstraints::default()
+};
+
+impl<VM: VMBinding> Plan for StickyMarkSweep<VM> {
+    type VM = VM;
+
+    fn constraints(&self) -> &'static PlanConstraints {
+        &STICKY_MS_CONSTRAINTS
+    }
+
+    fn base(&self) -> &BasePlan<Self::VM> {
+        &self.common.base
+    }
+
+    fn generational(&self) -> Option<&dyn GenerationalPlan<VM = Self::VM>> {
+        Some(self)
+    }
+
+    fn common(&self) -> &CommonPlan<Self::VM> {
+        &self.common
+    }
+
+    fn schedule_collection(&'static self, scheduler: &GCWorkScheduler<Self::VM>) {
+        self.base().set_collection_kind::<Self>(self);
+        self.base().set_gc_status(GcStatus::GcPrepare);
+
+        let is_full_heap = self.requires_full_heap_collection();
+        self.gc_full_heap.store(is_full_heap, Ordering::SeqCst);
+
+        if !is_full_heap {
+            info!("Nursery GC");
+            scheduler.schedule_common_work::<StickyMarkSweepNurseryGCWorkContext<VM>>(self);
+        } else {
+            info!("Full heap GC");
+            scheduler.schedule_common_work::<StickyMarkSweepMatureGCWorkContext<VM>>(self);
+        }
+    }
+
+    fn get_spaces(&self) -> Vec<&dyn Space<Self::VM>> {
+        let mut ret = self.common.get_spaces();
+        ret.push(&self.ms);
+        ret
+    }
+
+    fn get_allocator_mapping(
+        &self,
+    ) -> &'static enum_map::EnumMap<crate::AllocationSemantics, crate::util::alloc::AllocatorSelector>
+    {
+        &super::mutator::ALLOCATOR_MAPPING
+    }
+
+    fn prepare(&mut self, tls: VMWorkerThread) {
+        if self.is_current_gc_nursery() {
+            self.base().pause_time.set_collected_pages(
+                self.ms.get_pages_allocated() + self.common.los.nursery_pages(),
+            );
+            // Keep the mark bits in the mark sweep space. Only prepare the spaces that we collect.
+            self.ms.prepare_nursery();
+            self.common.los.prepare(false);
+        } else {
+            self.full_heap_gc_count.lock().unwrap().inc();
+            self.common.prepare(tls, true);
+            self.ms.prepare();
+        }
+    }
+
+    fn release(&mut self, tls: VMWorkerThread) {
+        // The dead objects are unmarked in both kinds of GCs, so the blocks are swept in the same way.
+        self.ms.release();
+        if self.is_current_gc_nursery() {
+            self.common.los.release(false);
+        } else {
+            self.common.release(tls, true);
+        }
+    }
+
+    fn end_of_gc(&mut self, _tls: VMWorkerThread) {
+        let next_gc_full_heap =
+            crate::plan::generational::global::CommonGenPlan::should_next_gc_be_full_heap(self);
+        self.next_gc_full_heap
+            .store(next_gc_full_heap, Ordering::Relaxed);
+    }
+
+    fn collection_required(&self, space_full: bool, space: Option<&dyn Space<Self::VM>>) -> bool {
+        let nursery_full =
+            self.ms.get_pages_allocated() > self.base().pause_time.nursery_pages(self.options());
+        if space_full && space.is_some() && space.unwrap().name() == self.ms.name() {
+            self.next_gc_full_heap.store(true, Ordering::SeqCst);
+        }
+        self.base().collection_required(self, space_full) || nursery_full
+    }
+
+    fn last_collection_was_exhaustive(&self) -> bool {
+        self.gc_full_heap.load(Ordering::Relaxed)
+    }
+
+    fn get_used_pages(&self) -> usize {
+        self.common.get_used_pages() + self.ms.reserved_pages()
+    }
+
+    fn sanity_check_object(&self, object: crate::util::ObjectReference) -> bool {
+        if self.is_current_gc_nursery() {
+            // Every reachable object should be logged
+            if !VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC.is_unlogged::<VM>(object, Ordering::SeqCst) {
+                error!("Object {} is not unlogged (all objects that have been traced should be unlogged/mature)", object);
+                return false;
+            }
+
+            // Every reachable object should be marked
+            if self.ms.in_space(object) && !self.ms.is_marked(object) {
+                error!(
+                    "Object {} is not marked (all objects that have been traced should be marked)",
+                    object
+                );
+                return false;
+            } else if self.common.los.in_space(object) && !self.common.los.is_live(object) {
+                error!("LOS Object {} is not marked", object);
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl<VM: VMBinding> GenerationalPlan for StickyMarkSweep<VM> {
+    fn is_current_gc_nursery(&self) -> bool {
+        !self.gc_full_heap.load(Ordering::SeqCst)
+    }
+
+    fn is_object_in_nursery(&self, object: crate::util::ObjectReference) -> bool {
+        self.ms.in_space(object) && !self.ms.is_marked(object)
+    }
+
+    // Same as sticky immix, we need object metadata to tell if an object is in the nursery. For the
+    // memory slice copying barrier, we conservatively treat every address as a mature address.
+    fn is_address_in_nursery(&self, _addr: crate::util::Address) -> bool {
+        false
+    }
+
+    fn get_mature_physical_pages_available(&self) -> usize {
+        self.ms.available_physical_pages()
+    }
+
+    fn get_mature_reserved_pages(&self) -> usize {
+        self.ms.reserved_pages()
+    }
+
+    fn force_full_heap_collection(&self) {
+        self.next_gc_full_heap.store(true, Ordering::SeqCst);
+    }
+
+    fn last_collection_full_heap(&self) -> bool {
+        self.gc_full_heap.load(Ordering::SeqCst)
+    }
+}
+
+impl<VM: VMBinding> crate::plan::generational::global::GenerationalPlanExt<VM>
+    for StickyMarkSweep<VM>
+{
+    fn trace_object_nursery<Q: crate::ObjectQueue>(
+        &self,
+        queue: &mut Q,
+        object: crate::util::ObjectReference,
+        _worker: &mut crate::scheduler::GCWorker<VM>,
+    ) -> crate::util::ObjectReference {
+        if self.ms.in_space(object) {
+            if !self.is_object_in_nursery(object) {
+                // Mature object
+                trace!("Mark sweep mature object {}, skip", object);
+                return object;
+            }
+            trace!("Mark sweep nursery object {} is being traced", object);
+            return self.ms.trace_object(queue, object);
+        }
+
+        if self.common.get_los().in_space(object) {
+            return self.common.get_los().trace_object::<Q>(queue, object);
+        }
+
+        if self.common.get_nonmoving().in_space(object) {
+            // Non-moving objects are allocated as mature objects.
+            trace!("Non-moving mature object {}, skip", object);
+            return object;
+        }
+
+        warn!(
+            "Object {} is not in nursery or in LOS, it is not traced!",
+            object
+        );
+        object
+    }
+}
+
+impl<VM: VMBinding> StickyMarkSweep<VM> {
+    pub fn new(args: CreateGeneralPlanArgs<VM>) -> Self {
+        let mut plan_args = CreateSpecificPlanArgs {
+            global_args: args,
+            constraints: &STICKY_MS_CONSTRAINTS,
+            global_side_metadata_specs: SideMetadataContext::new_global_specs(
+                &crate::plan::generational::new_generational_global_metadata_specs::<VM>(),
+            ),
+        };
+
+        let ms = MarkSweepSpace::new_with_args(
+            plan_args.get_space_args("ms", true, VMRequest::discontiguous()),
+            MarkSweepSpaceArgs {
+                // Every object we trace in nursery GC becomes a mature object.
+                // Every object we trace in full heap GC is a mature object. Thus in both cases,
+                // they should be unlogged.
+                unlog_object_when_traced: true,
+                // In full heap GC, mature objects may die, and their unlogged bit needs to be reset.
+                // Along with the option above, we unlog them again during tracing.
+                reset_log_bit_in_major_gc: true,
+                immortal: false,
+                defrag: false,
+            },
+        );
+        let common = CommonPlan::new(plan_args);
+        let full_heap_gc_count = common.base.stats.new_event_counter("majorGC", true, true);
+
+        let res = StickyMarkSweep {
+            common,
+            ms,
+            gc_full_heap: AtomicBool::new(false),
+            next_gc_full_heap: AtomicBool::new(false),
+            full_heap_gc_count,
+        };
+
+        let mut side_metadata_sanity_checker = SideMetadataSanity::new();
+        res.common
+            .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+        res.ms
+            .verify_side_metadata_sanity(&mut side_metadata_sanity_checker);
+        res
+    }
+
+    fn requires_full_heap_collection(&self) -> bool {
+        // Separate each condition so the code is clear
+        #[allow(clippy::if_same_then_else, clippy::needless_bool)]
+        if self.base().user_triggered_collection.load(Ordering::SeqCst)
+            && *self.base().options.full_heap_system_gc
+        {
+            // User triggered collection, and we force full heap for user triggered collection
+            true
+        } else if self.next_gc_full_heap.load(Ordering::SeqCst)
+            || self.base().cur_collection_attempts.load(Ordering::SeqCst) > 1
+        {
+            // Forces full heap collection
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn ms_space(&self) -> &MarkSweepSpace<VM> {
+        &self.ms
+    }
+}
@@ -0,0 +1,79 @@
+use crate::plan::barriers::ObjectBarrier;
+use crate::plan::generational::barrier::GenObjectBarrierSemantics;
+use crate::plan::mutator_context::{
+    create_allocator_mapping, create_space_mapping, MutatorConfig, ReservedAllocators,
+};
+use crate::plan::sticky::marksweep::global::StickyMarkSweep;
+use crate::plan::AllocationSemantics;
+use crate::util::alloc::allocators::Allocators;
+use crate::util::alloc::AllocatorSelector;
+use crate::util::alloc::FreeListAllocator;
+use crate::util::opaque_pointer::VMWorkerThread;
+use crate::util::VMMutatorThread;
+use crate::vm::VMBinding;
+use crate::{Mutator, MMTK};
+
+use enum_map::EnumMap;
+
+fn get_freelist_allocator_mut<VM: VMBinding>(
+    mutator: &mut Mutator<VM>,
+) -> &mut FreeListAllocator<VM> {
+    unsafe {
+        mutator
+            .allocators
+            .get_allocator_mut(mutator.config.allocator_mapping[AllocationSemantics::Default])
+    }
+    .downcast_mut::<FreeListAllocator<VM>>()
+    .unwrap()
+}
+
+pub fn stickyms_mutator_prepare<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
+    get_freelist_allocator_mut::<VM>(mutator).prepare();
+}
+
+pub fn stickyms_mutator_release<VM: VMBinding>(mutator: &mut Mutator<VM>, _tls: VMWorkerThread) {
+    get_freelist_allocator_mut::<VM>(mutator).release();
+}
+
+// Sticky mark sweep always uses the native free list allocator, even if the feature `malloc_mark_sweep` is enabled.
+const RESERVED_ALLOCATORS: ReservedAllocators = ReservedAllocators {
+    n_free_list: 1,
+    ..ReservedAllocators::DEFAULT
+};
+
+lazy_static! {
+    pub static ref ALLOCATOR_MAPPING: EnumMap<AllocationSemantics, AllocatorSelector> = {
+        let mut map = create_allocator_mapping(RESERVED_ALLOCATORS, true);
+        map[AllocationSemantics::Default] = AllocatorSelector::FreeList(0);
+        map
+    };
+}
+
+pub fn create_stickyms_mutator<VM: VMBinding>(
+    mutator_tls: VMMutatorThread,
+    mmtk: &'static MMTK<VM>,
+) -> Mutator<VM> {
+    let stickyms = mmtk.plan.downcast_ref::<StickyMarkSweep<VM>>().unwrap();
+    let config = MutatorConfig {
+        allocator_mapping: &ALLOCATOR_MAPPING,
+        space_mapping: Box::new({
+            let mut vec = create_space_mapping(RESERVED_ALLOCATORS, true, &*mmtk.plan);
+            vec.push((AllocatorSelector::FreeList(0), stickyms.ms_space()));
+            vec
+        }),
+        prepare_func: &stickyms_mutator_prepare,
+        release_func: &stickyms_mutator_release,
+    };
+
+    Mutator {
+        allocators: Allocators::<VM>::new(mutator_tls, &*mmtk.plan, &config.space_mapping),
+        barrier: Box::new(ObjectBarrier::new(GenObjectBarrierSemantics::new(
+            mmtk, stickyms,
+        ))),
+        mutator_tls,
+        config,
+        los_redirects: 0,
+        pretenured_objects: 0,
+        plan: &*mmtk.plan,
+    }
+}
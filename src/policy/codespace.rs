@@ -0,0 +1,549 @@
+//! A space for the code generated by the VM, e.g. JIT-compiled methods.
+//!
+//! The pages of the space are executable but not writable (W^X) at the addresses of the objects. A
+//! binding writes a code object between [`CodeSpace::begin_write`] and [`CodeSpace::end_write`]:
+//! * On Linux, the memory of the space is an in-memory file, and each run of pages that the space
+//!   hands out is also mapped at another address (the writable alias), where it is writable but
+//!   not executable. `begin_write` returns the alias, so the protection of the pages never changes,
+//!   and the code in the pages can run while the binding writes to them.
+//! * On the other systems, `begin_write` makes the pages of the written range writable and not
+//!   executable, and `end_write` makes them executable again once no thread writes to them. The
+//!   code in those pages cannot run in between.
+//!
+//! The GC never changes the protection of the pages. MMTk does not write to the memory of the
+//! space either: the allocators do not zero the objects or fill the alignment gaps, and the mark
+//! bits and the log bits must be on the side.
+//!
+//! The objects are allocated marked, and dead code is only reclaimed in full heap GCs. The space
+//! remembers the runs of pages that the allocators acquire and the objects allocated in them. A
+//! full heap GC frees the runs without live objects, and gives their memory back to the OS, so
+//! the pages are zeroed when they are handed out again. As the code allocators give up their
+//! buffers in every GC (see `Mutator::release()`), a run without live objects is not in use.
+
+use crate::mmtk::SFT_MAP;
+use crate::plan::{ObjectQueue, VectorObjectQueue};
+use crate::policy::sft::GCWorkerMutRef;
+use crate::policy::sft::SFT;
+use crate::policy::space::{CommonSpace, Space};
+use crate::util::address::Address;
+#[cfg(not(target_os = "linux"))]
+use crate::util::constants::BYTES_IN_PAGE;
+use crate::util::conversions;
+use crate::util::heap::layout::mmapper::Mmapper;
+use crate::util::heap::layout::vm_layout_constants::BYTES_IN_CHUNK;
+use crate::util::heap::{FreeListPageResource, PageResource};
+use crate::util::memory;
+use crate::util::metadata;
+use crate::util::ObjectReference;
+use crate::vm::{ObjectModel, VMBinding};
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::Ordering;
+use std::sync::{Mutex, RwLock};
+
+/// The code space. It is a non-moving space whose pages are executable but not writable.
+pub struct CodeSpace<VM: VMBinding> {
+    mark_state: u8,
+    common: CommonSpace<VM>,
+    pr: FreeListPageResource<VM>,
+    memory: CodeMemory,
+    /// The runs of pages acquired from the space, indexed by their start.
+    runs: RwLock<BTreeMap<Address, CodeRun>>,
+    /// The objects allocated in the space, which are not known to be dead yet.
+    objects: Mutex<Vec<ObjectReference>>,
+}
+
+/// A run of pages acquired from the code space.
+struct CodeRun {
+    pages: usize,
+    /// The address where the binding writes to the first page of the run.
+    writable: Address,
+}
+
+const GC_MARK_BIT_MASK: u8 = 1;
+
+impl<VM: VMBinding> SFT for CodeSpace<VM> {
+    fn name(&self) -> &str {
+        self.get_name()
+    }
+    fn is_live(&self, object: ObjectReference) -> bool {
+        self.is_marked(object)
+    }
+    fn is_reachable(&self, object: ObjectReference) -> bool {
+        self.is_marked(object)
+    }
+    #[cfg(feature = "object_pinning")]
+    fn pin_object(&self, _object: ObjectReference) -> bool {
+        false
+    }
+    #[cfg(feature = "object_pinning")]
+    fn unpin_object(&self, _object: ObjectReference) -> bool {
+        false
+    }
+    #[cfg(feature = "object_pinning")]
+    fn is_object_pinned(&self, _object: ObjectReference) -> bool {
+        true
+    }
+    fn is_movable(&self) -> bool {
+        false
+    }
+    #[cfg(feature = "sanity")]
+    fn is_sane(&self) -> bool {
+        true
+    }
+    fn initialize_object_metadata(&self, object: ObjectReference, _alloc: bool) {
+        self.initialize_mark_bit(object);
+        if self.common.needs_log_bit {
+            VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC.mark_as_unlogged::<VM>(object, Ordering::SeqCst);
+        }
+        #[cfg(feature = "global_alloc_bit")]
+        crate::util::alloc_bit::set_alloc_bit::<VM>(object);
+        self.objects.lock().unwrap().push(object);
+    }
+    #[cfg(feature = "is_mmtk_object")]
+    fn is_mmtk_object(&self, addr: Address) -> bool {
+        crate::util::alloc_bit::is_alloced_object::<VM>(addr).is_some()
+    }
+    fn sft_trace_object(
+        &self,
+        queue: &mut VectorObjectQueue,
+        object: ObjectReference,
+        _worker: GCWorkerMutRef,
+    ) -> ObjectReference {
+        self.trace_object(queue, object)
+    }
+}
+
+impl<VM: VMBinding> Space<VM> for CodeSpace<VM> {
+    fn as_space(&self) -> &dyn Space<VM> {
+        self
+    }
+    fn as_sft(&self) -> &(dyn SFT + Sync + 'static) {
+        self
+    }
+    fn get_page_resource(&self) -> &dyn PageResource<VM> {
+        &self.pr
+    }
+    fn common(&self) -> &CommonSpace<VM> {
+        &self.common
+    }
+
+    fn initialize_sft(&self) {
+        self.common().initialize_sft(self.as_sft())
+    }
+
+    /// This is called with the acquire lock of the space held for every run of pages acquired from
+    /// the space, before the pages are handed out. The memory is mapped here, so the mmapper will
+    /// not map the chunks again.
+    fn grow_space(&self, start: Address, bytes: usize, new_chunk: bool) {
+        let writable = self
+            .memory
+            .map(start, bytes, self.common.mmapper)
+            .unwrap_or_else(|e| {
+                panic!(
+                    "Failed to map {} ({} bytes at {}): {}",
+                    self.get_name(),
+                    bytes,
+                    start,
+                    e
+                )
+            });
+        if new_chunk {
+            unsafe { SFT_MAP.update(self.as_sft(), start, bytes) };
+        }
+        let run = CodeRun {
+            pages: conversions::bytes_to_pages(bytes),
+            writable,
+        };
+        let old = self.runs.write().unwrap().insert(start, run);
+        debug_assert!(old.is_none());
+    }
+
+    fn is_writable(&self) -> bool {
+        false
+    }
+
+    fn release_multiple_pages(&mut self, _start: Address) {
+        panic!("codespace only releases the runs of pages without live objects in a GC")
+    }
+}
+
+use crate::scheduler::GCWorker;
+use crate::util::copy::CopySemantics;
+
+impl<VM: VMBinding> crate::policy::gc_work::PolicyTraceObject<VM> for CodeSpace<VM> {
+    fn trace_object<Q: ObjectQueue, const KIND: crate::policy::gc_work::TraceKind>(
+        &self,
+        queue: &mut Q,
+        object: ObjectReference,
+        _copy: Option<CopySemantics>,
+        _worker: &mut GCWorker<VM>,
+    ) -> ObjectReference {
+        self.trace_object(queue, object)
+    }
+    fn may_move_objects<const KIND: crate::policy::gc_work::TraceKind>() -> bool {
+        false
+    }
+}
+
+impl<VM: VMBinding> CodeSpace<VM> {
+    pub fn new(args: crate::policy::space::PlanCreateSpaceArgs<VM>) -> Self {
+        let vm_map = args.vm_map;
+        let is_discontiguous = args.vmrequest.is_discontiguous();
+        let common = CommonSpace::new(args.into_policy_args(
+            false,
+            false,
+            metadata::extract_side_metadata(&[*VM::VMObjectModel::LOCAL_MARK_BIT_SPEC]),
+        ));
+        // Marking or unlogging an object must not write to the object.
+        assert!(
+            VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.is_on_side(),
+            "{} needs the mark bits on the side",
+            common.name
+        );
+        assert!(
+            !common.needs_log_bit || VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC.is_on_side(),
+            "{} needs the log bits on the side",
+            common.name
+        );
+        let memory = CodeMemory::new(common.name)
+            .unwrap_or_else(|e| panic!("Failed to create the memory of {}: {}", common.name, e));
+        CodeSpace {
+            mark_state: 0,
+            pr: if is_discontiguous {
+                FreeListPageResource::new_discontiguous(vm_map)
+            } else {
+                FreeListPageResource::new_contiguous(common.start, common.extent, vm_map)
+            },
+            common,
+            memory,
+            runs: RwLock::new(BTreeMap::new()),
+            objects: Mutex::new(vec![]),
+        }
+    }
+
+    /// Find the run of pages that contains `[start, start + bytes)`, and return its start.
+    fn find_run<'a>(
+        runs: &'a BTreeMap<Address, CodeRun>,
+        start: Address,
+        bytes: usize,
+    ) -> Option<(Address, &'a CodeRun)> {
+        let (run_start, run) = runs.range(..=start).next_back()?;
+        if start + bytes <= *run_start + conversions::pages_to_bytes(run.pages) {
+            Some((*run_start, run))
+        } else {
+            None
+        }
+    }
+
+    /// Start writing to `[start, start + bytes)`, and return the address where the binding writes
+    /// to `start`, or `None` if the range is not in a run of pages acquired from the space. See
+    /// [`crate::memory_manager::code_space_begin_write`].
+    pub fn begin_write(&self, start: Address, bytes: usize) -> Option<Address> {
+        let runs = self.runs.read().unwrap();
+        let (run_start, run) = Self::find_run(&runs, start, bytes)?;
+        self.memory.begin_write(start, bytes).unwrap_or_else(|e| {
+            panic!(
+                "Failed to make {} bytes at {} writable in {}: {}",
+                bytes,
+                start,
+                self.get_name(),
+                e
+            )
+        });
+        Some(run.writable + (start - run_start))
+    }
+
+    /// Finish writing to `[start, start + bytes)`, which was started by [`CodeSpace::begin_write`].
+    pub fn end_write(&self, start: Address, bytes: usize) {
+        debug_assert!(Self::find_run(&self.runs.read().unwrap(), start, bytes).is_some());
+        self.memory.end_write(start, bytes).unwrap_or_else(|e| {
+            panic!(
+                "Failed to make {} bytes at {} executable in {}: {}",
+                bytes,
+                start,
+                self.get_name(),
+                e
+            )
+        });
+    }
+
+    pub fn prepare(&mut self, full_heap: bool) {
+        // The objects are allocated marked, so the GCs that are not full heap keep them.
+        if full_heap {
+            self.mark_state = GC_MARK_BIT_MASK - self.mark_state;
+        }
+    }
+
+    pub fn release(&mut self, full_heap: bool) {
+        if full_heap {
+            self.sweep();
+        }
+    }
+
+    /// Forget the dead objects, and free the runs of pages without live objects.
+    fn sweep(&mut self) {
+        let mark_state = self.mark_state;
+        let runs = self.runs.get_mut().unwrap();
+        let mut live_runs = HashSet::new();
+        self.objects.get_mut().unwrap().retain(|object| {
+            if Self::test_mark(*object, mark_state) {
+                let start = object.to_object_start::<VM>();
+                let (run_start, _) = runs.range(..=start).next_back().unwrap();
+                live_runs.insert(*run_start);
+                true
+            } else {
+                #[cfg(feature = "global_alloc_bit")]
+                crate::util::alloc_bit::unset_alloc_bit::<VM>(*object);
+                false
+            }
+        });
+        let dead_runs: Vec<Address> = runs
+            .keys()
+            .filter(|start| !live_runs.contains(*start))
+            .copied()
+            .collect();
+        for start in dead_runs {
+            let run = runs.remove(&start).unwrap();
+            let bytes = conversions::pages_to_bytes(run.pages);
+            self.memory
+                .unmap(start, bytes, run.writable)
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "Failed to free {} bytes at {} in {}: {}",
+                        bytes, start, self.common.name, e
+                    )
+                });
+            self.pr.release_pages(start);
+        }
+    }
+
+    pub fn trace_object<Q: ObjectQueue>(
+        &self,
+        queue: &mut Q,
+        object: ObjectReference,
+    ) -> ObjectReference {
+        #[cfg(feature = "global_alloc_bit")]
+        debug_assert!(
+            crate::util::alloc_bit::is_alloced::<VM>(object),
+            "{:x}: alloc bit not set",
+            object
+        );
+        if Self::test_and_mark(object, self.mark_state) {
+            queue.enqueue(object);
+        }
+        object
+    }
+
+    fn is_marked(&self, object: ObjectReference) -> bool {
+        Self::test_mark(object, self.mark_state)
+    }
+
+    /// Set the mark bit of a new object to the current mark state.
+    fn initialize_mark_bit(&self, object: ObjectReference) {
+        VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.store_atomic::<VM, u8>(
+            object,
+            self.mark_state,
+            None,
+            Ordering::SeqCst,
+        );
+    }
+
+    fn test_mark(object: ObjectReference, mark_state: u8) -> bool {
+        VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.load_atomic::<VM, u8>(object, None, Ordering::SeqCst)
+            == mark_state
+    }
+
+    fn test_and_mark(object: ObjectReference, mark_state: u8) -> bool {
+        loop {
+            let old_value = VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.load_atomic::<VM, u8>(
+                object,
+                None,
+                Ordering::SeqCst,
+            );
+            if old_value == mark_state {
+                return false;
+            }
+            if VM::VMObjectModel::LOCAL_MARK_BIT_SPEC
+                .compare_exchange_metadata::<VM, u8>(
+                    object,
+                    old_value,
+                    mark_state,
+                    None,
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                )
+                .is_ok()
+            {
+                return true;
+            }
+        }
+    }
+}
+
+/// Iterate the chunks that overlap `[start, start + bytes)`.
+fn chunks_in(start: Address, bytes: usize) -> impl Iterator<Item = Address> {
+    let first = conversions::chunk_align_down(start);
+    let end = conversions::chunk_align_up(start + bytes);
+    (0..(end - first))
+        .step_by(BYTES_IN_CHUNK)
+        .map(move |offset| first + offset)
+}
+
+/// The memory of a code space on Linux. The chunks of the space are mapped from an in-memory file
+/// as executable at their addresses, and each run of pages is also mapped from the file as
+/// writable at its alias.
+#[cfg(target_os = "linux")]
+struct CodeMemory {
+    file: std::fs::File,
+    /// The offsets in the file of the chunks mapped so far, indexed by the start of the chunks.
+    offsets: Mutex<HashMap<Address, usize>>,
+}
+
+#[cfg(target_os = "linux")]
+impl CodeMemory {
+    fn new(name: &str) -> std::io::Result<Self> {
+        Ok(CodeMemory {
+            file: memory::memfd_create(name)?,
+            offsets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Map the chunks of a new run of pages `[start, start + bytes)` that are not mapped yet, and
+    /// map the run at its writable alias. Return the alias.
+    fn map(&self, start: Address, bytes: usize, mmapper: &dyn Mmapper) -> std::io::Result<Address> {
+        let mut offsets = self.offsets.lock().unwrap();
+        for chunk in chunks_in(start, bytes) {
+            if offsets.contains_key(&chunk) {
+                continue;
+            }
+            let offset = offsets.len() * BYTES_IN_CHUNK;
+            self.file.set_len((offset + BYTES_IN_CHUNK) as u64)?;
+            memory::mmap_shared(
+                &self.file,
+                offset,
+                Some(chunk),
+                BYTES_IN_CHUNK,
+                libc::PROT_READ | libc::PROT_EXEC,
+            )?;
+            mmapper.mark_as_mapped(chunk, BYTES_IN_CHUNK);
+            offsets.insert(chunk, offset);
+        }
+        // The chunks of the run may not be contiguous in the file. Map the whole run from the file
+        // offset of its start to get a contiguous range of addresses, and then map the part of the
+        // run in each of the other chunks over it.
+        let offset_of = |addr: Address| {
+            let chunk = conversions::chunk_align_down(addr);
+            offsets[&chunk] + (addr - chunk)
+        };
+        let rw = libc::PROT_READ | libc::PROT_WRITE;
+        let alias = memory::mmap_shared(&self.file, offset_of(start), None, bytes, rw)?;
+        for chunk in chunks_in(start, bytes).skip(1) {
+            let size = usize::min(BYTES_IN_CHUNK, start + bytes - chunk);
+            memory::mmap_shared(
+                &self.file,
+                offsets[&chunk],
+                Some(alias + (chunk - start)),
+                size,
+                rw,
+            )?;
+        }
+        Ok(alias)
+    }
+
+    /// The binding writes to the alias, so the protection of the pages does not change.
+    fn begin_write(&self, _start: Address, _bytes: usize) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn end_write(&self, _start: Address, _bytes: usize) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    /// Free the memory of a run of pages, and unmap its alias. The pages read as zeros afterwards.
+    fn unmap(&self, _start: Address, bytes: usize, alias: Address) -> std::io::Result<()> {
+        memory::madvise_remove(alias, bytes)?;
+        memory::munmap(alias, bytes)
+    }
+}
+
+/// The memory of a code space on the systems without in-memory files. The chunks of the space are
+/// anonymous memory, and the pages being written are made writable and not executable.
+#[cfg(not(target_os = "linux"))]
+struct CodeMemory {
+    /// The chunks mapped so far.
+    chunks: Mutex<HashSet<Address>>,
+    /// The number of threads writing to each page that is writable.
+    writers: Mutex<HashMap<Address, usize>>,
+}
+
+#[cfg(not(target_os = "linux"))]
+impl CodeMemory {
+    fn new(_name: &str) -> std::io::Result<Self> {
+        Ok(CodeMemory {
+            chunks: Mutex::new(HashSet::new()),
+            writers: Mutex::new(HashMap::new()),
+        })
+    }
+
+    const FLAGS: libc::c_int = libc::MAP_ANON | libc::MAP_PRIVATE | libc::MAP_FIXED;
+
+    /// Map the chunks of a new run of pages `[start, start + bytes)` that are not mapped yet. The
+    /// binding writes to the pages at their addresses, so return `start`.
+    fn map(&self, start: Address, bytes: usize, mmapper: &dyn Mmapper) -> std::io::Result<Address> {
+        let mut chunks = self.chunks.lock().unwrap();
+        for chunk in chunks_in(start, bytes) {
+            if chunks.insert(chunk) {
+                let prot = libc::PROT_READ | libc::PROT_EXEC;
+                memory::mmap_fixed(chunk, BYTES_IN_CHUNK, prot, Self::FLAGS)?;
+                mmapper.mark_as_mapped(chunk, BYTES_IN_CHUNK);
+            }
+        }
+        Ok(start)
+    }
+
+    /// Iterate the pages that overlap `[start, start + bytes)`.
+    fn pages_in(start: Address, bytes: usize) -> impl Iterator<Item = Address> {
+        let first = start.align_down(BYTES_IN_PAGE);
+        let end = (start + bytes).align_up(BYTES_IN_PAGE);
+        (0..(end - first))
+            .step_by(BYTES_IN_PAGE)
+            .map(move |offset| first + offset)
+    }
+
+    /// Make the pages writable and not executable.
+    fn begin_write(&self, start: Address, bytes: usize) -> std::io::Result<()> {
+        let mut writers = self.writers.lock().unwrap();
+        for page in Self::pages_in(start, bytes) {
+            let count = writers.entry(page).or_insert(0);
+            if *count == 0 {
+                memory::munprotect(page, BYTES_IN_PAGE)?;
+            }
+            *count += 1;
+        }
+        Ok(())
+    }
+
+    /// Make the pages executable again if no other thread writes to them.
+    fn end_write(&self, start: Address, bytes: usize) -> std::io::Result<()> {
+        let mut writers = self.writers.lock().unwrap();
+        for page in Self::pages_in(start, bytes) {
+            let count = writers
+                .get_mut(&page)
+                .expect("end_write() without begin_write()");
+            *count -= 1;
+            if *count == 0 {
+                writers.remove(&page);
+                memory::mprotect_executable(page, BYTES_IN_PAGE)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the pages of a run with fresh pages, which read as zeros.
+    fn unmap(&self, start: Address, bytes: usize, _writable: Address) -> std::io::Result<()> {
+        let writers = self.writers.lock().unwrap();
+        debug_assert!(Self::pages_in(start, bytes).all(|page| !writers.contains_key(&page)));
+        memory::mmap_fixed(start, bytes, libc::PROT_READ | libc::PROT_EXEC, Self::FLAGS)
+    }
+}
@@ -60,6 +60,16 @@ impl<VM: VMBinding> SFT for CopySpace<VM> {
         crate::util::alloc_bit::set_alloc_bit::<VM>(_object);
     }
 
+    fn initialize_objects_metadata(
+        &self,
+        _objects: &[ObjectReference],
+        _bytes: usize,
+        _alloc: bool,
+    ) {
+        #[cfg(feature = "global_alloc_bit")]
+        crate::util::alloc_bit::set_alloc_bits::<VM>(_objects);
+    }
+
     fn get_forwarded_object(&self, object: ObjectReference) -> Option<ObjectReference> {
         if !self.is_from_space() {
             return None;
@@ -0,0 +1,98 @@
+//! The state of defragmentation shared by the policies that evacuate their sparse blocks in a
+//! defrag GC, i.e. the Immix space and the native mark sweep space. The policies decide which blocks
+//! to evacuate themselves.
+
+use crate::policy::space::Space;
+use crate::vm::*;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Whether the current GC is a defrag GC, and how many clean pages are left for the objects
+/// evacuated in it.
+#[derive(Debug, Default)]
+pub struct DefragState {
+    /// Is current GC a defrag GC?
+    in_defrag_collection: AtomicBool,
+    /// Is defrag space exhausted?
+    defrag_space_exhausted: AtomicBool,
+    /// The number of remaining clean pages in defrag space.
+    available_clean_pages_for_defrag: AtomicUsize,
+}
+
+impl DefragState {
+    const DEFRAG_HEADROOM_PERCENT: usize = 2;
+
+    /// Check if the current GC is a defrag GC.
+    pub fn in_defrag(&self) -> bool {
+        self.in_defrag_collection.load(Ordering::Acquire)
+    }
+
+    /// Determine whether the current GC should do defragmentation. It should if it is an emergency
+    /// GC, if an earlier attempt of it did not free enough memory, if it is a full heap GC
+    /// triggered by the user (and `full_heap_system_gc` is set), or if the space needs it.
+    pub fn decide_whether_to_defrag(
+        &self,
+        emergency_collection: bool,
+        collection_attempts: usize,
+        user_triggered_full_heap: bool,
+        space_needs_defrag: bool,
+    ) -> bool {
+        let in_defrag = emergency_collection
+            || (collection_attempts > 1)
+            || user_triggered_full_heap
+            || space_needs_defrag;
+        self.in_defrag_collection
+            .store(in_defrag, Ordering::Release);
+        in_defrag
+    }
+
+    /// Get the number of defrag headroom pages.
+    pub fn defrag_headroom_pages<VM: VMBinding>(&self, space: &dyn Space<VM>) -> usize {
+        space.get_page_resource().reserved_pages() * Self::DEFRAG_HEADROOM_PERCENT / 100
+    }
+
+    /// Check if the defrag space is exhausted.
+    pub fn space_exhausted(&self) -> bool {
+        self.defrag_space_exhausted.load(Ordering::Acquire)
+    }
+
+    /// Update available_clean_pages_for_defrag counter when a clean block of `block_pages` pages is
+    /// allocated for copying.
+    pub fn notify_new_clean_block(&self, block_pages: usize) {
+        let available_clean_pages_for_defrag = self.available_clean_pages_for_defrag.fetch_update(
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+            |available_clean_pages_for_defrag| {
+                Some(available_clean_pages_for_defrag.saturating_sub(block_pages))
+            },
+        );
+        if available_clean_pages_for_defrag.unwrap() <= block_pages {
+            self.defrag_space_exhausted.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Prepare work. Should be called in the prepare of the space. Return the number of free pages
+    /// that the space may count on when it chooses the blocks to evacuate. The pages reserved for
+    /// collection can also be used for copying.
+    pub fn prepare<VM: VMBinding>(&self, space: &dyn Space<VM>) -> usize {
+        self.defrag_space_exhausted.store(false, Ordering::Release);
+
+        // Calculate available free space for defragmentation.
+        let available_clean_pages_for_defrag = (VM::VMActivePlan::global().get_total_pages()
+            as isize
+            - VM::VMActivePlan::global().get_reserved_pages() as isize
+            + self.defrag_headroom_pages(space) as isize)
+            .max(0) as usize;
+
+        self.available_clean_pages_for_defrag.store(
+            available_clean_pages_for_defrag
+                + VM::VMActivePlan::global().get_collection_reserved_pages(),
+            Ordering::Release,
+        );
+        available_clean_pages_for_defrag
+    }
+
+    /// Release work. Should be called in the release of the space.
+    pub fn release(&self) {
+        self.in_defrag_collection.store(false, Ordering::Release);
+    }
+}
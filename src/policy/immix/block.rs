@@ -21,6 +21,8 @@ pub enum BlockState {
     Marked,
     /// the block is marked as reusable.
     Reusable { unavailable_lines: u8 },
+    /// the block survived a GC, and is not swept yet (lazy sweeping).
+    Unswept,
 }
 
 impl BlockState {
@@ -30,6 +32,8 @@ impl BlockState {
     const MARK_UNMARKED: u8 = u8::MAX;
     /// Private constant
     const MARK_MARKED: u8 = u8::MAX - 1;
+    /// Private constant
+    const MARK_UNSWEPT: u8 = u8::MAX - 2;
 }
 
 impl From<u8> for BlockState {
@@ -38,6 +42,7 @@ impl From<u8> for BlockState {
             Self::MARK_UNALLOCATED => BlockState::Unallocated,
             Self::MARK_UNMARKED => BlockState::Unmarked,
             Self::MARK_MARKED => BlockState::Marked,
+            Self::MARK_UNSWEPT => BlockState::Unswept,
             unavailable_lines => BlockState::Reusable { unavailable_lines },
         }
     }
@@ -50,6 +55,7 @@ impl From<BlockState> for u8 {
             BlockState::Unmarked => BlockState::MARK_UNMARKED,
             BlockState::Marked => BlockState::MARK_MARKED,
             BlockState::Reusable { unavailable_lines } => unavailable_lines,
+            BlockState::Unswept => BlockState::MARK_UNSWEPT,
         }
     }
 }
@@ -186,6 +192,30 @@ impl Block {
         RegionIterator::<Line>::new(self.start_line(), self.end_line())
     }
 
+    /// Mark lines for all the objects in this block that have a non-zero reference count.
+    /// Line marks from previous GCs are discarded.
+    #[allow(clippy::assertions_on_constants)]
+    pub fn mark_lines_by_ref_count<VM: VMBinding>(&self, line_mark_state: u8) {
+        debug_assert!(!super::BLOCK_ONLY);
+        Line::MARK_TABLE.bzero_metadata(self.start(), Self::BYTES);
+        let mut cursor = self.start();
+        while cursor < self.end() {
+            if crate::util::ref_count::RC_SIDE_METADATA_SPEC
+                .load_atomic::<u8>(cursor, Ordering::Relaxed)
+                == 0
+            {
+                cursor += MIN_OBJECT_SIZE;
+                continue;
+            }
+            let object = crate::util::ObjectReference::from_address::<VM>(cursor);
+            Line::mark_lines_for_object::<VM>(object, line_mark_state);
+            // Skip to the end of the object.
+            let object_end =
+                object.to_object_start::<VM>() + VM::VMObjectModel::get_current_size(object);
+            cursor = (cursor + MIN_OBJECT_SIZE).max(object_end.align_up(MIN_OBJECT_SIZE));
+        }
+    }
+
     /// Sweep this block.
     /// Return true if the block is swept.
     pub fn sweep<VM: VMBinding>(
@@ -209,30 +239,7 @@ impl Block {
                 _ => unreachable!(),
             }
         } else {
-            // Calculate number of marked lines and holes.
-            let mut marked_lines = 0;
-            let mut holes = 0;
-            let mut prev_line_is_marked = true;
-            let line_mark_state = line_mark_state.unwrap();
-
-            for line in self.lines() {
-                if line.is_marked(line_mark_state) {
-                    marked_lines += 1;
-                    prev_line_is_marked = true;
-                } else {
-                    if prev_line_is_marked {
-                        holes += 1;
-                    }
-
-                    #[cfg(feature = "global_alloc_bit")]
-                    crate::util::alloc_bit::bzero_alloc_bit(line.start(), Line::BYTES);
-
-                    #[cfg(feature = "immix_zero_on_release")]
-                    crate::util::memory::zero(line.start(), Line::BYTES);
-
-                    prev_line_is_marked = false;
-                }
-            }
+            let (marked_lines, holes) = self.sweep_lines(space, line_mark_state.unwrap());
 
             if marked_lines == 0 {
                 // Release the block if non of its lines are marked.
@@ -258,6 +265,81 @@ impl Block {
             }
         }
     }
+
+    /// Sweep the lines in this block. Clear the metadata for the lines that are not marked.
+    /// Return the number of marked lines and the number of holes.
+    fn sweep_lines<VM: VMBinding>(
+        &self,
+        space: &ImmixSpace<VM>,
+        line_mark_state: u8,
+    ) -> (usize, usize) {
+        // Calculate number of marked lines and holes.
+        let mut marked_lines = 0;
+        let mut holes = 0;
+        let mut prev_line_is_marked = true;
+
+        for line in self.lines() {
+            if line.is_marked(line_mark_state) {
+                marked_lines += 1;
+                prev_line_is_marked = true;
+            } else {
+                if prev_line_is_marked {
+                    holes += 1;
+                }
+
+                #[cfg(feature = "global_alloc_bit")]
+                crate::util::alloc_bit::bzero_alloc_bit(line.start(), Line::BYTES);
+
+                #[cfg(feature = "immix_zero_on_release")]
+                crate::util::memory::zero(line.start(), Line::BYTES);
+
+                if space.rc_enabled() {
+                    // Objects in the line are dead. Clear their counts and log bits for new objects.
+                    crate::util::ref_count::RC_SIDE_METADATA_SPEC
+                        .bzero_metadata(line.start(), Line::BYTES);
+                    if let crate::util::metadata::MetadataSpec::OnSide(side) =
+                        *VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC
+                    {
+                        side.bzero_metadata(line.start(), Line::BYTES);
+                    }
+                }
+
+                prev_line_is_marked = false;
+            }
+        }
+        (marked_lines, holes)
+    }
+
+    /// Sweep this block when an allocator acquires it with lazy sweeping. Unlike `sweep()`, the
+    /// block is neither released nor pushed to the reusable block list, as the allocator may not
+    /// run on a GC worker. A block without marked lines is used by the allocator as a whole.
+    /// Return true if the block has free lines.
+    pub fn sweep_lazily<VM: VMBinding>(&self, space: &ImmixSpace<VM>, line_mark_state: u8) -> bool {
+        debug_assert_eq!(self.get_state(), BlockState::Unswept);
+        let (marked_lines, holes) = self.sweep_lines(space, line_mark_state);
+        if marked_lines == 0 {
+            // None of its lines are marked. The allocator can use the whole block.
+            self.set_state(BlockState::Unmarked);
+            return true;
+        }
+        let has_free_lines = marked_lines != Block::LINES;
+        if has_free_lines {
+            // There are holes. Mark the block as reusable.
+            self.set_state(BlockState::Reusable {
+                unavailable_lines: marked_lines as _,
+            });
+        } else {
+            // Clear mark state.
+            self.set_state(BlockState::Unmarked);
+        }
+        // Update mark histogram for the next defrag GC.
+        if super::DEFRAG {
+            space.defrag.add_marked_lines(holes, marked_lines);
+        }
+        // Record number of holes in block side metadata.
+        self.set_holes(holes);
+        has_free_lines
+    }
 }
 
 /// A non-block single-linked list to store blocks.
@@ -3,33 +3,32 @@ use super::{
     line::Line,
     ImmixSpace,
 };
-use crate::policy::space::Space;
+use crate::policy::defrag::DefragState;
 use crate::util::linear_scan::Region;
 use crate::{util::constants::LOG_BYTES_IN_PAGE, vm::*};
 use spin::Mutex;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub type Histogram = [usize; Defrag::NUM_BINS];
 
 #[derive(Debug, Default)]
 pub struct Defrag {
-    /// Is current GC a defrag GC?
-    in_defrag_collection: AtomicBool,
-    /// Is defrag space exhausted?
-    defrag_space_exhausted: AtomicBool,
+    /// Whether the current GC is a defrag GC, and the clean pages left for copying.
+    state: DefragState,
     /// A list of completed mark histograms reported by workers
     pub mark_histograms: Mutex<Vec<Histogram>>,
     /// A block with number of holes greater than this threshold will be defragmented.
     pub defrag_spill_threshold: AtomicUsize,
-    /// The number of remaining clean pages in defrag space.
-    available_clean_pages_for_defrag: AtomicUsize,
+    /// The maximum number of lines to evacuate in the current GC, as decided by the plan.
+    max_evacuated_lines: AtomicUsize,
+    /// The number of live lines in the blocks chosen for evacuation in the current GC.
+    evacuated_lines: AtomicUsize,
 }
 
 impl Defrag {
     const NUM_BINS: usize = (Block::LINES >> 1) + 1;
     const DEFRAG_LINE_REUSE_RATIO: f32 = 0.99;
     const MIN_SPILL_THRESHOLD: usize = 2;
-    const DEFRAG_HEADROOM_PERCENT: usize = 2;
 
     /// Allocate a new local histogram.
     pub const fn new_histogram(&self) -> Histogram {
@@ -41,12 +40,23 @@ impl Defrag {
         self.mark_histograms.lock().push(histogram)
     }
 
+    /// Report the marked lines of a block that is swept lazily by an allocator.
+    pub fn add_marked_lines(&self, holes: usize, marked_lines: usize) {
+        let mut mark_histograms = self.mark_histograms.lock();
+        if mark_histograms.is_empty() {
+            mark_histograms.push(self.new_histogram());
+        }
+        mark_histograms[0][holes] += marked_lines;
+    }
+
     /// Check if the current GC is a defrag GC.
     pub fn in_defrag(&self) -> bool {
-        self.in_defrag_collection.load(Ordering::Acquire)
+        self.state.in_defrag()
     }
 
-    /// Determine whether the current GC should do defragmentation.
+    /// Determine whether the current GC should do defragmentation. If it does, at most
+    /// `max_evacuated_pages` pages of live lines are evacuated.
+    #[allow(clippy::too_many_arguments)]
     pub fn decide_whether_to_defrag(
         &self,
         emergency_collection: bool,
@@ -55,46 +65,43 @@ impl Defrag {
         user_triggered: bool,
         exhausted_reusable_space: bool,
         full_heap_system_gc: bool,
+        max_evacuated_pages: Option<usize>,
     ) {
-        let in_defrag = super::DEFRAG
-            && (emergency_collection
-                || (collection_attempts > 1)
-                || !exhausted_reusable_space
-                || super::STRESS_DEFRAG
-                || (collect_whole_heap && user_triggered && full_heap_system_gc));
-        // println!("Defrag: {}", in_defrag);
-        self.in_defrag_collection
-            .store(in_defrag, Ordering::Release)
+        if super::DEFRAG {
+            self.state.decide_whether_to_defrag(
+                emergency_collection,
+                collection_attempts,
+                collect_whole_heap && user_triggered && full_heap_system_gc,
+                !exhausted_reusable_space || super::STRESS_DEFRAG,
+            );
+        }
+        let max_evacuated_lines = max_evacuated_pages.map_or(usize::MAX, |pages| {
+            pages.saturating_mul(1 << (LOG_BYTES_IN_PAGE as usize - Line::LOG_BYTES))
+        });
+        self.max_evacuated_lines
+            .store(max_evacuated_lines, Ordering::Release);
+    }
+
+    /// Get the number of pages of live lines chosen for evacuation in the current GC.
+    pub fn evacuated_pages(&self) -> usize {
+        self.evacuated_lines.load(Ordering::Acquire)
+            >> (LOG_BYTES_IN_PAGE as usize - Line::LOG_BYTES)
     }
 
     /// Get the number of defrag headroom pages.
     pub fn defrag_headroom_pages<VM: VMBinding>(&self, space: &ImmixSpace<VM>) -> usize {
-        space.get_page_resource().reserved_pages() * Self::DEFRAG_HEADROOM_PERCENT / 100
+        self.state.defrag_headroom_pages(space)
     }
 
     /// Check if the defrag space is exhausted.
     pub fn space_exhausted(&self) -> bool {
-        self.defrag_space_exhausted.load(Ordering::Acquire)
+        self.state.space_exhausted()
     }
 
     /// Update available_clean_pages_for_defrag counter when a clean block is allocated.
     pub fn notify_new_clean_block(&self, copy: bool) {
         if copy {
-            let available_clean_pages_for_defrag =
-                self.available_clean_pages_for_defrag.fetch_update(
-                    Ordering::SeqCst,
-                    Ordering::SeqCst,
-                    |available_clean_pages_for_defrag| {
-                        if available_clean_pages_for_defrag <= Block::PAGES {
-                            Some(0)
-                        } else {
-                            Some(available_clean_pages_for_defrag - Block::PAGES)
-                        }
-                    },
-                );
-            if available_clean_pages_for_defrag.unwrap() <= Block::PAGES {
-                self.defrag_space_exhausted.store(true, Ordering::SeqCst);
-            }
+            self.state.notify_new_clean_block(Block::PAGES);
         }
     }
 
@@ -102,30 +109,13 @@ impl Defrag {
     #[allow(clippy::assertions_on_constants)]
     pub fn prepare<VM: VMBinding>(&self, space: &ImmixSpace<VM>) {
         debug_assert!(super::DEFRAG);
-        self.defrag_space_exhausted.store(false, Ordering::Release);
-
-        // Calculate available free space for defragmentation.
+        self.evacuated_lines.store(0, Ordering::Release);
 
-        let mut available_clean_pages_for_defrag = VM::VMActivePlan::global().get_total_pages()
-            as isize
-            - VM::VMActivePlan::global().get_reserved_pages() as isize
-            + self.defrag_headroom_pages(space) as isize;
-        if available_clean_pages_for_defrag < 0 {
-            available_clean_pages_for_defrag = 0
-        };
-
-        self.available_clean_pages_for_defrag
-            .store(available_clean_pages_for_defrag as usize, Ordering::Release);
+        let available_clean_pages_for_defrag = self.state.prepare(space);
 
         if self.in_defrag() {
-            self.establish_defrag_spill_threshold(space)
+            self.establish_defrag_spill_threshold(space, available_clean_pages_for_defrag)
         }
-
-        self.available_clean_pages_for_defrag.store(
-            available_clean_pages_for_defrag as usize
-                + VM::VMActivePlan::global().get_collection_reserved_pages(),
-            Ordering::Release,
-        );
     }
 
     /// Get the numebr of all the recyclable lines in all the reusable blocks.
@@ -149,20 +139,27 @@ impl Defrag {
     }
 
     /// Calculate the defrag threshold.
-    fn establish_defrag_spill_threshold<VM: VMBinding>(&self, space: &ImmixSpace<VM>) {
+    fn establish_defrag_spill_threshold<VM: VMBinding>(
+        &self,
+        space: &ImmixSpace<VM>,
+        available_clean_pages_for_defrag: usize,
+    ) {
         let mut spill_avail_histograms = self.new_histogram();
         let clean_lines = self.get_available_lines(space, &mut spill_avail_histograms);
         let available_lines = clean_lines
-            + (self
-                .available_clean_pages_for_defrag
-                .load(Ordering::Acquire)
-                << (LOG_BYTES_IN_PAGE as usize - Line::LOG_BYTES));
+            + (available_clean_pages_for_defrag << (LOG_BYTES_IN_PAGE as usize - Line::LOG_BYTES));
 
         // Number of lines we will evacuate.
         let mut required_lines = 0isize;
         // Number of to-space free lines we can use for defragmentation.
         let mut limit = (available_lines as f32 / Self::DEFRAG_LINE_REUSE_RATIO) as isize;
+        // Number of lines we can evacuate within the pause time target.
+        let max_required_lines = self
+            .max_evacuated_lines
+            .load(Ordering::Acquire)
+            .min(isize::MAX as usize) as isize;
         let mut threshold = Block::LINES >> 1;
+        let mut evacuated_lines = 0;
         let mark_histograms = self.mark_histograms.lock();
         // Blocks are grouped by buckets, indexed by the number of holes in the block.
         // `mark_histograms` remembers the number of live lines for each bucket.
@@ -180,21 +177,25 @@ impl Defrag {
             // Update counters
             limit -= this_bucket_avail;
             required_lines += this_bucket_mark;
-            // Stop scanning. Lines to evacuate exceeds the free to-space lines.
-            if limit < required_lines {
+            // Stop scanning. Lines to evacuate exceeds the free to-space lines, or cannot be
+            // evacuated within the pause time target.
+            if limit < required_lines || required_lines > max_required_lines {
                 break;
             }
+            evacuated_lines = required_lines;
         }
         // println!("threshold: {}", threshold);
         debug_assert!(threshold >= Self::MIN_SPILL_THRESHOLD);
         self.defrag_spill_threshold
             .store(threshold, Ordering::Release);
+        self.evacuated_lines
+            .store(evacuated_lines as usize, Ordering::Release);
     }
 
     /// Release work. Should be called in ImmixSpace::release.
     #[allow(clippy::assertions_on_constants)]
     pub fn release<VM: VMBinding>(&self, _space: &ImmixSpace<VM>) {
         debug_assert!(super::DEFRAG);
-        self.in_defrag_collection.store(false, Ordering::Release);
+        self.state.release();
     }
 }
@@ -23,7 +23,7 @@ use crate::{
     MMTK,
 };
 use atomic::Ordering;
-use std::sync::{atomic::AtomicU8, atomic::AtomicUsize, Arc};
+use std::sync::{atomic::AtomicBool, atomic::AtomicU8, atomic::AtomicUsize, Arc};
 
 pub(crate) const TRACE_KIND_FAST: TraceKind = 0;
 pub(crate) const TRACE_KIND_DEFRAG: TraceKind = 1;
@@ -39,12 +39,17 @@ pub struct ImmixSpace<VM: VMBinding> {
     line_unavail_state: AtomicU8,
     /// A list of all reusable blocks
     pub reusable_blocks: ReusableBlockPool,
+    /// A list of the blocks that survived a GC and are not swept yet. This is only used with lazy sweeping.
+    unswept_blocks: ReusableBlockPool,
     /// Defrag utilities
     pub(super) defrag: Defrag,
     /// How many lines have been consumed since last GC?
     lines_consumed: AtomicUsize,
     /// Object mark state
     mark_state: u8,
+    /// Mark new objects as live when they are allocated. A concurrent marking plan sets this while
+    /// marking is in progress, as new objects are not reachable from the snapshot.
+    allocate_as_live: AtomicBool,
     /// Work packet scheduler
     scheduler: Arc<GCWorkScheduler<VM>>,
     /// Some settings for this space
@@ -67,6 +72,12 @@ pub struct ImmixSpaceArgs {
     /// bit to differentiate them. So we reset all the log bits in major GCs,
     /// and unlogged the objects when they are traced (alive).
     pub reset_log_bit_in_major_gc: bool,
+    /// Use reference counts to decide object liveness.
+    /// In a reference counting plan, objects may be reclaimed without a trace. An object is live
+    /// if its reference count is not zero, and lines are marked by the live objects in the sweeping phase,
+    /// rather than during tracing. Reference counts are reset in major GCs, and the plan is responsible to
+    /// rebuild the reference counts when it traces objects.
+    pub rc_enabled: bool,
 }
 
 unsafe impl<VM: VMBinding> Sync for ImmixSpace<VM> {}
@@ -89,6 +100,10 @@ impl<VM: VMBinding> SFT for ImmixSpace<VM> {
     }
 
     fn is_live(&self, object: ObjectReference) -> bool {
+        if self.space_args.rc_enabled {
+            // Reference counting never moves objects.
+            return crate::util::ref_count::is_counted::<VM>(object);
+        }
         if super::NEVER_MOVE_OBJECTS {
             // We won't forward objects.
             self.is_marked(object)
@@ -116,9 +131,27 @@ impl<VM: VMBinding> SFT for ImmixSpace<VM> {
     fn is_sane(&self) -> bool {
         true
     }
-    fn initialize_object_metadata(&self, _object: ObjectReference, _alloc: bool) {
+    fn initialize_object_metadata(&self, object: ObjectReference, _alloc: bool) {
         #[cfg(feature = "global_alloc_bit")]
-        crate::util::alloc_bit::set_alloc_bit::<VM>(_object);
+        crate::util::alloc_bit::set_alloc_bit::<VM>(object);
+        if self.allocate_as_live.load(Ordering::Relaxed) {
+            self.mark_as_live(object);
+        }
+    }
+    fn initialize_objects_metadata(
+        &self,
+        objects: &[ObjectReference],
+        _bytes: usize,
+        _alloc: bool,
+    ) {
+        #[cfg(feature = "global_alloc_bit")]
+        crate::util::alloc_bit::set_alloc_bits::<VM>(objects);
+        // The lines of each object need to be marked.
+        if self.allocate_as_live.load(Ordering::Relaxed) {
+            for object in objects {
+                self.mark_as_live(*object);
+            }
+        }
     }
     #[cfg(feature = "is_mmtk_object")]
     fn is_mmtk_object(&self, addr: Address) -> bool {
@@ -259,6 +292,16 @@ impl<VM: VMBinding> ImmixSpace<VM> {
                 "Invalid args when the plan does not use log bit"
             );
         }
+        if space_args.rc_enabled {
+            assert!(
+                !super::BLOCK_ONLY,
+                "Reference counting requires line marks to reclaim memory"
+            );
+            assert!(
+                !args.constraints.moves_objects,
+                "Reference counting does not move objects"
+            );
+        }
 
         super::validate_features();
         let vm_map = args.vm_map;
@@ -287,9 +330,11 @@ impl<VM: VMBinding> ImmixSpace<VM> {
             line_unavail_state: AtomicU8::new(Line::RESET_MARK_STATE),
             lines_consumed: AtomicUsize::new(0),
             reusable_blocks: ReusableBlockPool::new(scheduler.num_workers()),
+            unswept_blocks: ReusableBlockPool::new(scheduler.num_workers()),
             defrag: Defrag::default(),
             // Set to the correct mark state when inititialized. We cannot rely on prepare to set it (prepare may get skipped in nursery GCs).
             mark_state: Self::MARKED_STATE,
+            allocate_as_live: AtomicBool::new(false),
             scheduler: scheduler.clone(),
             space_args,
         }
@@ -298,6 +343,7 @@ impl<VM: VMBinding> ImmixSpace<VM> {
     /// Flush the thread-local queues in BlockPageResource
     pub fn flush_page_resource(&self) {
         self.reusable_blocks.flush_all();
+        self.unswept_blocks.flush_all();
         #[cfg(target_pointer_width = "64")]
         self.pr.flush_all()
     }
@@ -312,7 +358,8 @@ impl<VM: VMBinding> ImmixSpace<VM> {
         self.defrag.in_defrag()
     }
 
-    /// check if the current GC should do defragmentation.
+    /// check if the current GC should do defragmentation. `max_evacuated_pages` bounds the live
+    /// pages that a defrag GC evacuates, if the plan has a limit.
     pub fn decide_whether_to_defrag(
         &self,
         emergency_collection: bool,
@@ -320,18 +367,54 @@ impl<VM: VMBinding> ImmixSpace<VM> {
         collection_attempts: usize,
         user_triggered_collection: bool,
         full_heap_system_gc: bool,
+        max_evacuated_pages: Option<usize>,
     ) -> bool {
         self.defrag.decide_whether_to_defrag(
             emergency_collection,
             collect_whole_heap,
             collection_attempts,
             user_triggered_collection,
-            self.reusable_blocks.len() == 0,
+            self.reusable_blocks.len() == 0 && self.unswept_blocks.len() == 0,
             full_heap_system_gc,
+            max_evacuated_pages,
         );
         self.defrag.in_defrag()
     }
 
+    /// Get the number of live pages chosen for evacuation in the current defrag GC.
+    /// This is only valid after `prepare()`.
+    pub fn defrag_evacuated_pages(&self) -> usize {
+        self.defrag.evacuated_pages()
+    }
+
+    /// Set whether new objects are marked as live when they are allocated.
+    /// This should only be changed when mutators are stopped.
+    pub fn set_allocate_as_live(&self, allocate_as_live: bool) {
+        self.allocate_as_live
+            .store(allocate_as_live, Ordering::Relaxed);
+    }
+
+    /// Mark a new object and its lines, so it survives the current concurrent marking.
+    fn mark_as_live(&self, object: ObjectReference) {
+        self.attempt_mark(object, self.mark_state);
+        if !super::BLOCK_ONLY {
+            self.mark_lines(object);
+        } else {
+            Block::containing::<VM>(object).set_state(BlockState::Marked);
+        }
+    }
+
+    /// Check if reference counting is used to decide object liveness.
+    pub fn rc_enabled(&self) -> bool {
+        self.space_args.rc_enabled
+    }
+
+    /// Check if the blocks are swept lazily when allocators acquire them. Reference counting marks
+    /// lines by the reference counts in the sweep, so it always sweeps in GC.
+    fn lazy_sweeping(&self) -> bool {
+        super::LAZY_SWEEPING && !self.space_args.rc_enabled
+    }
+
     /// Get work packet scheduler
     fn scheduler(&self) -> &GCWorkScheduler<VM> {
         &self.scheduler
@@ -352,6 +435,11 @@ impl<VM: VMBinding> ImmixSpace<VM> {
                 self.defrag.prepare(self);
             }
 
+            // The blocks that are not swept since the last GC are swept in `PrepareBlockState`.
+            if self.lazy_sweeping() {
+                self.unswept_blocks.reset();
+            }
+
             // Prepare each block for GC
             let threshold = self.defrag.defrag_spill_threshold.load(Ordering::Acquire);
             // # Safety: ImmixSpace reference is always valid within this collection cycle.
@@ -397,7 +485,7 @@ impl<VM: VMBinding> ImmixSpace<VM> {
             self.reusable_blocks.reset();
         }
         // Sweep chunks and blocks
-        let work_packets = self.generate_sweep_tasks();
+        let work_packets = self.generate_sweep_tasks(major_gc);
         self.scheduler().work_buckets[WorkBucketStage::Release].bulk_add(work_packets);
         if super::DEFRAG {
             self.defrag.release(self);
@@ -409,7 +497,7 @@ impl<VM: VMBinding> ImmixSpace<VM> {
     }
 
     /// Generate chunk sweep tasks
-    fn generate_sweep_tasks(&self) -> Vec<Box<dyn GCWork<VM>>> {
+    fn generate_sweep_tasks(&self, major_gc: bool) -> Vec<Box<dyn GCWork<VM>>> {
         self.defrag.mark_histograms.lock().clear();
         // # Safety: ImmixSpace reference is always valid within this collection cycle.
         let space = unsafe { &*(self as *const Self) };
@@ -421,6 +509,7 @@ impl<VM: VMBinding> ImmixSpace<VM> {
             Box::new(SweepChunk {
                 space,
                 chunk,
+                major_gc,
                 epilogue: epilogue.clone(),
             })
         });
@@ -434,9 +523,18 @@ impl<VM: VMBinding> ImmixSpace<VM> {
         self.pr.release_block(block);
     }
 
-    /// Allocate a clean block.
+    /// Release a clean block that an allocator acquired with [`ImmixSpace::get_clean_block`] but
+    /// did not allocate into.
+    pub fn release_clean_block(&self, block: Block) {
+        self.lines_consumed
+            .fetch_sub(Block::LINES, Ordering::SeqCst);
+        self.release_block(block);
+    }
+
+    /// Allocate a clean block. The block is not zeroed. The allocator zeroes it as the option
+    /// `nursery_zeroing` specifies.
     pub fn get_clean_block(&self, tls: VMThread, copy: bool) -> Option<Block> {
-        let block_address = self.acquire(tls, Block::PAGES);
+        let block_address = self.acquire_pages(tls, Block::PAGES, false);
         if block_address.is_zero() {
             return None;
         }
@@ -455,7 +553,11 @@ impl<VM: VMBinding> ImmixSpace<VM> {
             return None;
         }
         loop {
-            if let Some(block) = self.reusable_blocks.pop() {
+            if let Some(block) = self
+                .reusable_blocks
+                .pop()
+                .or_else(|| self.get_unswept_block(copy))
+            {
                 // Skip blocks that should be evacuated.
                 if copy && block.is_defrag_source() {
                     continue;
@@ -479,6 +581,22 @@ impl<VM: VMBinding> ImmixSpace<VM> {
         }
     }
 
+    /// Pop an unswept block and sweep it, until we find a block with free lines. Only mutators sweep
+    /// the blocks lazily. Sweeping in a copying allocator would bring the sweeping cost back to GC.
+    fn get_unswept_block(&self, copy: bool) -> Option<Block> {
+        if copy || !self.lazy_sweeping() {
+            return None;
+        }
+        // The lines of the unswept blocks are marked in the last GC.
+        let line_mark_state = self.line_unavail_state.load(Ordering::Acquire);
+        while let Some(block) = self.unswept_blocks.pop() {
+            if block.sweep_lazily(self, line_mark_state) {
+                return Some(block);
+            }
+        }
+        None
+    }
+
     /// Trace and mark objects without evacuation.
     pub fn trace_object_without_moving(
         &self,
@@ -617,6 +735,14 @@ impl<VM: VMBinding> ImmixSpace<VM> {
     pub fn mark_lines(&self, object: ObjectReference) {
         debug_assert!(!super::BLOCK_ONLY);
         Line::mark_lines_for_object::<VM>(object, self.line_mark_state.load(Ordering::Acquire));
+        // With lazy sweeping, the lines are not counted in GC. Mark the block as well, so the sweep
+        // can tell the blocks without marked lines and release them.
+        if self.lazy_sweeping() {
+            let block = Block::containing::<VM>(object);
+            if block.get_state() != BlockState::Marked {
+                block.set_state(BlockState::Marked);
+            }
+        }
     }
 
     /// Atomically mark an object.
@@ -722,6 +848,7 @@ impl<VM: VMBinding> ImmixSpace<VM> {
         }
     }
 
+    /// Get the number of pages allocated into since the last GC.
     pub(crate) fn get_pages_allocated(&self) -> usize {
         self.lines_consumed.load(Ordering::SeqCst) >> (LOG_BYTES_IN_PAGE - Line::LOG_BYTES as u8)
     }
@@ -769,6 +896,11 @@ impl<VM: VMBinding> PrepareBlockState<VM> {
                 unimplemented!("We cannot bulk zero unlogged bit.")
             }
         }
+        if self.space.space_args.rc_enabled {
+            // Reference counts are rebuilt by the plan during tracing.
+            crate::util::ref_count::RC_SIDE_METADATA_SPEC
+                .bzero_metadata(self.chunk.start(), Chunk::BYTES);
+        }
     }
 }
 
@@ -776,6 +908,9 @@ impl<VM: VMBinding> GCWork<VM> for PrepareBlockState<VM> {
     fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
         // Clear object mark table for this chunk
         self.reset_object_mark();
+        // The histogram of the blocks swept here is not used, as the defrag threshold of this GC is
+        // already established.
+        let mut histogram = self.space.defrag.new_histogram();
         // Iterate over all blocks in this chunk
         for block in self.chunk.iter_region::<Block>() {
             let state = block.get_state();
@@ -783,6 +918,17 @@ impl<VM: VMBinding> GCWork<VM> for PrepareBlockState<VM> {
             if state == BlockState::Unallocated {
                 continue;
             }
+            // Sweep the blocks that are not swept since the last GC. Their lines are marked in the
+            // last GC. Skip the block if it is released.
+            if state == BlockState::Unswept
+                && block.sweep(
+                    self.space,
+                    &mut histogram,
+                    Some(self.space.line_unavail_state.load(Ordering::Acquire)),
+                )
+            {
+                continue;
+            }
             // Check if this block needs to be defragmented.
             let is_defrag_source = if !super::DEFRAG {
                 // Do not set any block as defrag source if defrag is disabled.
@@ -822,6 +968,8 @@ impl<VM: VMBinding> GCWork<VM> for PrepareBlockState<VM> {
 struct SweepChunk<VM: VMBinding> {
     space: &'static ImmixSpace<VM>,
     chunk: Chunk,
+    /// Is this a full heap GC? Only a full heap GC marks all the live objects in the space.
+    major_gc: bool,
     /// A destructor invoked when all `SweepChunk` packets are finished.
     epilogue: Arc<FlushPageResource<VM>>,
 }
@@ -843,6 +991,29 @@ impl<VM: VMBinding> GCWork<VM> for SweepChunk<VM> {
                 .iter_region::<Block>()
                 .filter(|block| block.get_state() != BlockState::Unallocated)
             {
+                if self.space.lazy_sweeping() {
+                    // Release the block now if none of its objects is marked in a full heap GC, so
+                    // its pages are returned in the GC rather than when an allocator sweeps it.
+                    if self.major_gc
+                        && block.get_state() == BlockState::Unmarked
+                        && block.sweep(self.space, &mut histogram, line_mark_state)
+                    {
+                        continue;
+                    }
+                    // Leave the block to the allocators. A block that is not swept since the last
+                    // GC is already in the list.
+                    if block.get_state() != BlockState::Unswept {
+                        block.set_state(BlockState::Unswept);
+                        self.space.unswept_blocks.push(block);
+                    }
+                    allocated_blocks += 1;
+                    continue;
+                }
+                if self.space.space_args.rc_enabled {
+                    // Live objects are not necessarily traced in this GC. Mark lines for objects
+                    // that are still referenced.
+                    block.mark_lines_by_ref_count::<VM>(line_mark_state.unwrap());
+                }
                 if !block.sweep(self.space, &mut histogram, line_mark_state) {
                     // Block is live. Increment the allocated block count.
                     allocated_blocks += 1;
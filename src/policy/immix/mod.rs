@@ -33,6 +33,9 @@ pub const PREFER_COPY_ON_NURSERY_GC: bool = !cfg!(feature = "immix_non_moving");
 /// If we have other reasons to move objects, we need to add them here.
 pub const NEVER_MOVE_OBJECTS: bool = !DEFRAG && !PREFER_COPY_ON_NURSERY_GC;
 
+/// Sweep the blocks when allocators acquire them rather than in GC. See `ImmixSpace::lazy_sweeping`.
+pub const LAZY_SWEEPING: bool = cfg!(feature = "immix_lazy_sweeping");
+
 /// Mark lines when scanning objects.
 /// Otherwise, do it at mark time.
 pub const MARK_LINE_AT_SCAN_TIME: bool = true;
@@ -45,6 +48,8 @@ macro_rules! validate {
 fn validate_features() {
     // Block-only immix cannot do defragmentation
     validate!(DEFRAG => !BLOCK_ONLY);
-    // Number of lines in a block should not exceed BlockState::MARK_MARKED
-    assert!(Block::LINES / 2 <= u8::MAX as usize - 2);
+    // Lazy sweeping sweeps lines, and the block marks of unswept blocks are overwritten.
+    validate!(LAZY_SWEEPING => !BLOCK_ONLY);
+    // Number of lines in a block should not exceed BlockState::MARK_UNSWEPT
+    assert!(Block::LINES / 2 <= u8::MAX as usize - 3);
 }
@@ -4,22 +4,39 @@ use crate::policy::sft::SFT;
 use crate::policy::space::{CommonSpace, Space};
 use crate::util::address::Address;
 use crate::util::heap::{MonotonePageResource, PageResource};
+#[cfg(feature = "ro_space")]
+use crate::util::memory;
 
+#[cfg(feature = "ro_space")]
+use crate::util::linear_scan::{DefaultObjectSize, ObjectIterator};
+#[cfg(feature = "ro_space")]
+use crate::util::VMWorkerThread;
 use crate::util::{metadata, ObjectReference};
 
 use crate::plan::{ObjectQueue, VectorObjectQueue};
 
 use crate::policy::sft::GCWorkerMutRef;
+#[cfg(feature = "ro_space")]
+use crate::vm::edge_shape::Edge;
 use crate::vm::{ObjectModel, VMBinding};
+#[cfg(feature = "ro_space")]
+use crate::vm::{RootsWorkFactory, Scanning};
+
+use std::sync::atomic::AtomicBool;
 
 /// This type implements a simple immortal collection
 /// policy. Under this policy all that is required is for the
 /// "collector" to propagate marks in a liveness trace.  It does not
 /// actually collect.
+///
+/// An immortal space can be sealed (see [`ImmortalSpace::seal`]), after which its pages are
+/// read-only, and its objects are roots.
 pub struct ImmortalSpace<VM: VMBinding> {
     mark_state: u8,
     common: CommonSpace<VM>,
     pr: MonotonePageResource<VM>,
+    /// Whether the space is sealed. No object can be allocated in a sealed space.
+    sealed: AtomicBool,
 }
 
 const GC_MARK_BIT_MASK: u8 = 1;
@@ -59,18 +76,7 @@ impl<VM: VMBinding> SFT for ImmortalSpace<VM> {
         true
     }
     fn initialize_object_metadata(&self, object: ObjectReference, _alloc: bool) {
-        let old_value = VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.load_atomic::<VM, u8>(
-            object,
-            None,
-            Ordering::SeqCst,
-        );
-        let new_value = (old_value & GC_MARK_BIT_MASK) | self.mark_state;
-        VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.store_atomic::<VM, u8>(
-            object,
-            new_value,
-            None,
-            Ordering::SeqCst,
-        );
+        self.initialize_mark_bit(object);
 
         if self.common.needs_log_bit {
             VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC.mark_as_unlogged::<VM>(object, Ordering::SeqCst);
@@ -78,6 +84,20 @@ impl<VM: VMBinding> SFT for ImmortalSpace<VM> {
         #[cfg(feature = "global_alloc_bit")]
         crate::util::alloc_bit::set_alloc_bit::<VM>(object);
     }
+    fn initialize_objects_metadata(&self, objects: &[ObjectReference], bytes: usize, _alloc: bool) {
+        for object in objects {
+            self.initialize_mark_bit(*object);
+        }
+        if self.common.needs_log_bit {
+            VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC.mark_objects_as_unlogged::<VM>(
+                objects,
+                bytes,
+                Ordering::SeqCst,
+            );
+        }
+        #[cfg(feature = "global_alloc_bit")]
+        crate::util::alloc_bit::set_alloc_bits::<VM>(objects);
+    }
     #[cfg(feature = "is_mmtk_object")]
     fn is_mmtk_object(&self, addr: Address) -> bool {
         crate::util::alloc_bit::is_alloced_object::<VM>(addr).is_some()
@@ -150,7 +170,117 @@ impl<VM: VMBinding> ImmortalSpace<VM> {
                 MonotonePageResource::new_contiguous(common.start, common.extent, vm_map)
             },
             common,
+            sealed: AtomicBool::new(false),
+        }
+    }
+
+    /// Seal the space. The pages allocated to the space are made read-only, so writing to an object
+    /// in the space faults. A GC cannot update the fields of a sealed object, so the objects that
+    /// it refers to must not move (e.g. the objects in the same space, in other immortal spaces, or
+    /// in the large object space). The GC scans the sealed objects as roots (see
+    /// [`ImmortalSpace::scan_sealed_objects`]), and checks this.
+    ///
+    /// The mark bits of the objects must be on the side, as a GC still marks the sealed objects.
+    /// The space must not acquire pages after it is sealed. It is up to the caller to stop
+    /// allocating into the space.
+    #[cfg(feature = "ro_space")]
+    pub fn seal(&self) {
+        assert!(
+            VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.is_on_side(),
+            "Cannot seal {}: the mark bits are in the object headers",
+            self.get_name()
+        );
+        let _lock = self.common.acquire_lock.lock().unwrap();
+        if self.sealed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        for (start, bytes) in self.allocated_regions() {
+            memory::mprotect_readonly(start, bytes).unwrap_or_else(|e| {
+                panic!(
+                    "Failed to seal {} ({} bytes at {}): {}",
+                    self.get_name(),
+                    bytes,
+                    start,
+                    e
+                )
+            });
+        }
+    }
+
+    /// Get the memory allocated to the space, as a list of (start, bytes).
+    #[cfg(feature = "ro_space")]
+    fn allocated_regions(&self) -> Vec<(Address, usize)> {
+        self.pr.allocated_regions()
+    }
+
+    /// Has the space been sealed?
+    pub fn is_sealed(&self) -> bool {
+        self.sealed.load(Ordering::SeqCst)
+    }
+
+    /// Iterate over the objects in the space, using the alloc bits.
+    #[cfg(feature = "ro_space")]
+    fn objects(&self) -> impl Iterator<Item = ObjectReference> {
+        self.allocated_regions()
+            .into_iter()
+            .flat_map(|(start, bytes)| {
+                ObjectIterator::<VM, DefaultObjectSize<VM>, true>::new(start, start + bytes)
+            })
+    }
+
+    /// Report the objects that the sealed objects refer to as node roots. The GC cannot update the
+    /// fields of a sealed object, so it panics if a referent may move. The sealed objects are
+    /// marked in `prepare()`, so the transitive closure does not scan them again.
+    #[cfg(feature = "ro_space")]
+    pub fn scan_sealed_objects(
+        &self,
+        tls: VMWorkerThread,
+        mut factory: impl RootsWorkFactory<VM::VMEdge>,
+    ) {
+        if !self.is_sealed() {
+            return;
+        }
+        let mut referents = vec![];
+        for object in self.objects() {
+            assert!(
+                VM::VMScanning::support_edge_enqueuing(tls, object),
+                "{}: A sealed object must support edge enqueuing",
+                object
+            );
+            VM::VMScanning::scan_object(tls, object, &mut |edge: VM::VMEdge| {
+                let referent = edge.load();
+                if referent.is_null() {
+                    return;
+                }
+                assert!(
+                    !referent.is_movable(),
+                    "{} in the sealed {} refers to {}, which may move",
+                    object,
+                    self.get_name(),
+                    referent
+                );
+                referents.push(referent);
+            });
         }
+        if !referents.is_empty() {
+            factory.create_process_node_roots_work(referents);
+        }
+    }
+
+    /// Set the mark bit of a new object to the current mark state.
+    fn initialize_mark_bit(&self, object: ObjectReference) {
+        let old_value = VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.load_atomic::<VM, u8>(
+            object,
+            None,
+            Ordering::SeqCst,
+        );
+        let new_value = (old_value & GC_MARK_BIT_MASK) | self.mark_state;
+        VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.store_atomic::<VM, u8>(
+            object,
+            new_value,
+            None,
+            Ordering::SeqCst,
+        );
     }
 
     fn test_and_mark(object: ObjectReference, value: u8) -> bool {
@@ -183,6 +313,14 @@ impl<VM: VMBinding> ImmortalSpace<VM> {
 
     pub fn prepare(&mut self) {
         self.mark_state = GC_MARK_BIT_MASK - self.mark_state;
+        // The sealed objects are roots, and their fields cannot be updated. Mark them before the
+        // transitive closure starts, so it never scans them.
+        #[cfg(feature = "ro_space")]
+        if self.is_sealed() {
+            for object in self.objects() {
+                ImmortalSpace::<VM>::test_and_mark(object, self.mark_state);
+            }
+        }
     }
 
     pub fn release(&mut self) {}
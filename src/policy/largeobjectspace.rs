@@ -1,16 +1,22 @@
 use atomic::Ordering;
+use std::sync::atomic::AtomicUsize;
 
 use crate::plan::ObjectQueue;
 use crate::plan::VectorObjectQueue;
 use crate::policy::sft::GCWorkerMutRef;
 use crate::policy::sft::SFT;
 use crate::policy::space::{CommonSpace, Space};
-use crate::util::constants::BYTES_IN_PAGE;
+use crate::util::constants::{BYTES_IN_PAGE, LOG_BYTES_IN_PAGE};
+use crate::util::conversions;
 use crate::util::heap::{FreeListPageResource, PageResource};
+use crate::util::memory;
 use crate::util::metadata;
+use crate::util::metadata::side_metadata::SideMetadataSpec;
+use crate::util::metadata::MetadataSpec;
 use crate::util::opaque_pointer::*;
 use crate::util::treadmill::TreadMill;
 use crate::util::{Address, ObjectReference};
+use crate::vm::ActivePlan;
 use crate::vm::ObjectModel;
 use crate::vm::VMBinding;
 
@@ -28,6 +34,8 @@ pub struct LargeObjectSpace<VM: VMBinding> {
     mark_state: u8,
     in_nursery_gc: bool,
     treadmill: TreadMill,
+    /// The number of pages allocated since the last GC.
+    nursery_pages: AtomicUsize,
 }
 
 impl<VM: VMBinding> SFT for LargeObjectSpace<VM> {
@@ -139,6 +147,12 @@ impl<VM: VMBinding> crate::policy::gc_work::PolicyTraceObject<VM> for LargeObjec
 }
 
 impl<VM: VMBinding> LargeObjectSpace<VM> {
+    /// The number of pages between the first page of an allocation and the page where the object
+    /// starts (side). It is recorded at the page of the object start, and it is only non-zero if
+    /// the padding for a large alignment is at least a page.
+    pub const PADDING_PAGES_TABLE: SideMetadataSpec =
+        crate::util::metadata::side_metadata::spec_defs::LOS_PADDING_PAGES;
+
     pub fn new(
         args: crate::policy::space::PlanCreateSpaceArgs<VM>,
         protect_memory_on_release: bool,
@@ -148,7 +162,10 @@ impl<VM: VMBinding> LargeObjectSpace<VM> {
         let common = CommonSpace::new(args.into_policy_args(
             false,
             false,
-            metadata::extract_side_metadata(&[*VM::VMObjectModel::LOCAL_LOS_MARK_NURSERY_SPEC]),
+            metadata::extract_side_metadata(&[
+                *VM::VMObjectModel::LOCAL_LOS_MARK_NURSERY_SPEC,
+                MetadataSpec::OnSide(Self::PADDING_PAGES_TABLE),
+            ]),
         ));
         let mut pr = if is_discontiguous {
             FreeListPageResource::new_discontiguous(vm_map)
@@ -162,6 +179,7 @@ impl<VM: VMBinding> LargeObjectSpace<VM> {
             mark_state: 0,
             in_nursery_gc: false,
             treadmill: TreadMill::new(),
+            nursery_pages: AtomicUsize::new(0),
         }
     }
 
@@ -180,6 +198,7 @@ impl<VM: VMBinding> LargeObjectSpace<VM> {
         if full_heap {
             self.sweep_large_pages(false);
         }
+        self.nursery_pages.store(0, Ordering::Relaxed);
     }
     // Allow nested-if for this function to make it clear that test_and_mark() is only executed
     // for the outer condition is met.
@@ -225,10 +244,12 @@ impl<VM: VMBinding> LargeObjectSpace<VM> {
 
     fn sweep_large_pages(&mut self, sweep_nursery: bool) {
         let sweep = |object: ObjectReference| {
-            #[cfg(feature = "global_alloc_bit")]
-            crate::util::alloc_bit::unset_alloc_bit::<VM>(object);
-            self.pr
-                .release_pages(get_super_page(object.to_object_start::<VM>()));
+            let page = get_super_page(object.to_object_start::<VM>());
+            let first_page = self.first_page_of(page);
+            if first_page != page {
+                Self::PADDING_PAGES_TABLE.store_atomic::<usize>(page, 0, Ordering::Relaxed);
+            }
+            self.free_object(object, first_page);
         };
         if sweep_nursery {
             for object in self.treadmill.collect_nursery() {
@@ -243,7 +264,183 @@ impl<VM: VMBinding> LargeObjectSpace<VM> {
 
     /// Allocate an object
     pub fn allocate_pages(&self, tls: VMThread, pages: usize) -> Address {
-        self.acquire(tls, pages)
+        let start = self.acquire(tls, pages);
+        if !start.is_zero() {
+            self.nursery_pages.fetch_add(pages, Ordering::Relaxed);
+        }
+        start
+    }
+
+    /// Get the number of pages allocated since the last GC.
+    pub fn nursery_pages(&self) -> usize {
+        self.nursery_pages.load(Ordering::Relaxed)
+    }
+
+    /// Clear the metadata of an object that is removed from the treadmill, and release its pages
+    /// starting at `first_page`.
+    fn free_object(&self, object: ObjectReference, first_page: Address) {
+        #[cfg(feature = "global_alloc_bit")]
+        crate::util::alloc_bit::unset_alloc_bit::<VM>(object);
+        // A reference counting plan may use the count for the object. Clear the count so a new object
+        // allocated at the same address will not be seen as counted.
+        if self
+            .common
+            .metadata
+            .global
+            .contains(&crate::util::ref_count::RC_SIDE_METADATA_SPEC)
+        {
+            crate::util::ref_count::set_count::<VM>(object, 0);
+        }
+        self.pr.release_pages(first_page);
+    }
+
+    /// Resize an object to `new_size` bytes. See [`crate::memory_manager::resize_large_object`].
+    pub fn resize_object(
+        &self,
+        tls: VMThread,
+        object: ObjectReference,
+        new_size: usize,
+    ) -> Option<ObjectReference> {
+        let object_start = object.to_object_start::<VM>();
+        let page = get_super_page(object_start);
+        let first_page = self.first_page_of(page);
+        let old_pages = self.pr.get_allocated_pages(first_page);
+        // Keep at least the page where the object starts, even if the new size is zero.
+        let pages = conversions::bytes_to_pages_up(object_start + new_size.max(1) - first_page);
+
+        if pages == old_pages {
+            return Some(object);
+        }
+        if pages < old_pages {
+            self.pr.shrink_pages(first_page, pages);
+            return Some(object);
+        }
+        if self.grow_in_place(tls, first_page, old_pages, pages) {
+            return Some(object);
+        }
+        if first_page != page {
+            // The object would lose its large alignment if we moved it to other pages.
+            return None;
+        }
+        self.move_object(tls, object, first_page, old_pages, pages)
+    }
+
+    /// Try to grow the pages of an object to `pages` in place.
+    fn grow_in_place(
+        &self,
+        tls: VMThread,
+        first_page: Address,
+        old_pages: usize,
+        pages: usize,
+    ) -> bool {
+        let grown_pages = pages - old_pages;
+        let reserved = self.pr.reserve_pages(grown_pages);
+        // Growing an object is an allocation. We cannot block for a GC here, so leave it to
+        // `acquire()` when we move the object.
+        let should_poll = VM::VMActivePlan::is_mutator(tls)
+            && VM::VMActivePlan::global().should_trigger_gc_when_heap_is_full();
+        if (should_poll && self.get_gc_trigger().poll(false, Some(self.as_space())))
+            || !self.pr.grow_pages(first_page, pages, reserved, tls)
+        {
+            self.pr.clear_request(reserved);
+            return false;
+        }
+
+        use crate::util::heap::layout::Mmapper;
+        let grown = first_page + conversions::pages_to_bytes(old_pages);
+        let bytes = conversions::pages_to_bytes(grown_pages);
+        if let Err(mmap_error) = self
+            .common
+            .mmapper
+            .ensure_mapped(grown, grown_pages)
+            .and(self.common.metadata.try_map_metadata_space(grown, bytes))
+        {
+            memory::handle_mmap_error::<VM>(mmap_error, tls);
+        }
+        if self.is_zeroed() {
+            memory::zero(grown, bytes);
+        }
+        true
+    }
+
+    /// Move an object to new pages without copying its bytes if possible. The object is treated as
+    /// a newly allocated object after it is moved.
+    fn move_object(
+        &self,
+        tls: VMThread,
+        object: ObjectReference,
+        first_page: Address,
+        old_pages: usize,
+        pages: usize,
+    ) -> Option<ObjectReference> {
+        let new_first_page = self.acquire_pages(tls, pages, false);
+        if new_first_page.is_zero() {
+            return None;
+        }
+        // Check this before we move the pages, as the metadata may be in the header.
+        let nursery_object = self.is_in_nursery(object);
+
+        let old_bytes = conversions::pages_to_bytes(old_pages);
+        let bytes = conversions::pages_to_bytes(pages);
+        if !Self::remap_pages(first_page, old_bytes, new_first_page, bytes) {
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    first_page.to_ptr::<u8>(),
+                    new_first_page.to_mut_ptr::<u8>(),
+                    old_bytes,
+                );
+            }
+            if self.is_zeroed() {
+                memory::zero(new_first_page + old_bytes, bytes - old_bytes);
+            }
+        }
+
+        self.treadmill.remove(object, nursery_object);
+        self.free_object(object, first_page);
+        let new_object = ObjectReference::from_raw_address(
+            new_first_page + (object.to_raw_address() - first_page),
+        );
+        self.initialize_object_metadata(new_object, true);
+        Some(new_object)
+    }
+
+    /// Move the pages at `from` to `to` with `mremap()`. The pages grown by `mremap()` are zeroed.
+    /// Return false if the pages cannot be moved this way.
+    #[cfg(target_os = "linux")]
+    fn remap_pages(from: Address, old_bytes: usize, to: Address, bytes: usize) -> bool {
+        // mremap() fails if the pages span more than one mapping.
+        if memory::mremap_fixed(from, old_bytes, to, bytes).is_err() {
+            return false;
+        }
+        // The old pages are unmapped now, but they still belong to the space. Map them again.
+        if let Err(e) = unsafe { memory::dzmmap(from, old_bytes) } {
+            panic!("Failed to map the pages at {} again: {:?}", from, e);
+        }
+        true
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn remap_pages(_from: Address, _old_bytes: usize, _to: Address, _bytes: usize) -> bool {
+        false
+    }
+
+    /// Record that the object starting at `object_start` is allocated in the pages starting at
+    /// `pages_start`, and the padding for its alignment is at least a page. Otherwise we would not
+    /// find the pages to release when the object dies.
+    pub(crate) fn record_padded_object(&self, object_start: Address, pages_start: Address) {
+        let page = get_super_page(object_start);
+        debug_assert!(page > pages_start);
+        Self::PADDING_PAGES_TABLE.store_atomic::<usize>(
+            page,
+            (page - pages_start) >> LOG_BYTES_IN_PAGE,
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Get the first page of the allocation of an object that starts in `page`.
+    fn first_page_of(&self, page: Address) -> Address {
+        let padding_pages = Self::PADDING_PAGES_TABLE.load_atomic::<usize>(page, Ordering::Relaxed);
+        page - (padding_pages << LOG_BYTES_IN_PAGE)
     }
 
     fn test_and_mark(&self, object: ObjectReference, value: u8) -> bool {
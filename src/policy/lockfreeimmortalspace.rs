@@ -114,18 +114,22 @@ impl<VM: VMBinding> Space<VM> for LockFreeImmortalSpace<VM> {
         data_pages + meta_pages
     }
 
-    fn acquire(&self, _tls: VMThread, pages: usize) -> Address {
+    fn acquire_pages(&self, _tls: VMThread, pages: usize, zero: bool) -> Address {
         let bytes = conversions::pages_to_bytes(pages);
         let start = unsafe { Address::from_usize(self.cursor.fetch_add(bytes, Ordering::Relaxed)) };
         if start + bytes > self.limit {
             panic!("OutOfMemory")
         }
-        if self.slow_path_zeroing {
+        if zero {
             crate::util::memory::zero(start, bytes);
         }
         start
     }
 
+    fn is_zeroed(&self) -> bool {
+        self.slow_path_zeroing
+    }
+
     /// Get the name of the space
     ///
     /// We have to override the default implementation because
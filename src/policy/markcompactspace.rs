@@ -5,41 +5,141 @@ use crate::policy::gc_work::TraceKind;
 use crate::policy::sft::GCWorkerMutRef;
 use crate::scheduler::GCWorker;
 use crate::util::alloc::allocator::align_allocation_no_fill;
-use crate::util::constants::LOG_BYTES_IN_WORD;
 use crate::util::copy::CopySemantics;
 use crate::util::heap::{MonotonePageResource, PageResource};
-use crate::util::metadata::extract_side_metadata;
+use crate::util::linear_scan::Region;
+use crate::util::metadata::side_metadata::SideMetadataSpec;
+use crate::util::metadata::{extract_side_metadata, MetadataSpec};
 use crate::util::{alloc_bit, Address, ObjectReference};
 use crate::{vm::*, ObjectQueue};
 use atomic::Ordering;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::RwLock;
 
 pub(crate) const TRACE_KIND_MARK: TraceKind = 0;
 pub(crate) const TRACE_KIND_FORWARD: TraceKind = 1;
 
+/// The number of bytes covered by one entry of the forwarding offset table (in log).
+pub const LOG_BYTES_IN_FORWARDING_BLOCK: usize = 9;
+
+/// The unit of parallel forwarding calculation and compaction. Each region is processed by one
+/// work packet, which compacts the live objects in the region (by the address of the objects) in
+/// address order.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Eq)]
+pub struct CompactionRegion(Address);
+
+impl Region for CompactionRegion {
+    const LOG_BYTES: usize = 18;
+
+    fn from_aligned_address(address: Address) -> Self {
+        debug_assert!(address.is_aligned_to(Self::BYTES));
+        Self(address)
+    }
+
+    fn start(&self) -> Address {
+        self.0
+    }
+}
+
+/// The per-region states of a compaction. The addresses are stored as `usize`.
+#[derive(Default)]
+struct RegionInfo {
+    /// The bytes of the live objects in the region after they are compacted. This includes the
+    /// alignment padding between the objects, which depends on where the objects are compacted to.
+    /// The `i`-th element is the bytes if the destination is `i * VM::MIN_ALIGNMENT` modulo
+    /// `VM::MAX_ALIGNMENT`.
+    live_bytes: Vec<AtomicUsize>,
+    /// The start of the memory of the first live object. This may be in the previous region.
+    live_start: AtomicUsize,
+    /// The end of the memory of the last live object. This may be in a following region.
+    live_end: AtomicUsize,
+    /// The address that the live objects in this region are compacted to.
+    destination: AtomicUsize,
+    /// The number of regions that need to be compacted before this region can be compacted, as this
+    /// region will overwrite their live objects.
+    pending_dependencies: AtomicUsize,
+    /// The regions that depend on this region, `dependents_start..dependents_end`.
+    dependents_start: AtomicUsize,
+    dependents_end: AtomicUsize,
+}
+
+impl RegionInfo {
+    fn new(destination_offsets: usize) -> Self {
+        Self {
+            live_bytes: (0..destination_offsets)
+                .map(|_| AtomicUsize::new(0))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn load(value: &AtomicUsize) -> Address {
+        unsafe { Address::from_usize(value.load(Ordering::Relaxed)) }
+    }
+
+    fn live_bytes(&self) -> Vec<usize> {
+        self.live_bytes
+            .iter()
+            .map(|bytes| bytes.load(Ordering::Relaxed))
+            .collect()
+    }
+
+    fn has_live_objects(&self) -> bool {
+        self.live_bytes[0].load(Ordering::Relaxed) != 0
+    }
+}
+
+/// The compaction regions of the current GC.
+struct CompactionRegions {
+    /// The end of the allocated memory in the space when the GC starts.
+    end: Address,
+    /// The regions from the start of the space to `end`.
+    regions: Vec<RegionInfo>,
+}
+
+impl CompactionRegions {
+    fn empty() -> Self {
+        Self {
+            end: Address::ZERO,
+            regions: vec![],
+        }
+    }
+}
+
+/// A space that compacts the live objects by sliding them towards the start of the space, in the
+/// style of the parallel Lisp-2 algorithm. The space is divided into [`CompactionRegion`]s, and the
+/// forwarding calculation and the compaction are done for the regions in parallel. Instead of a
+/// forwarding pointer in each object header, the forwarding addresses are calculated from a side
+/// metadata offset table ([`MarkCompactSpace::FORWARDING_TABLE`]).
 pub struct MarkCompactSpace<VM: VMBinding> {
     common: CommonSpace<VM>,
     pr: MonotonePageResource<VM>,
+    /// The compaction regions. They are set up before the forwarding calculation, and cleared when
+    /// the space is released.
+    compaction: RwLock<CompactionRegions>,
+    /// The number of regions in which we have not counted the live bytes. The packet that counts
+    /// the last region computes the destinations of all the regions.
+    regions_to_count: AtomicUsize,
+    /// The end of the compacted objects.
+    compacted_end: AtomicUsize,
+    /// Are the forwarding addresses available? This is true between the forwarding calculation and
+    /// the compaction.
+    forwarding: AtomicBool,
 }
 
 const GC_MARK_BIT_MASK: u8 = 1;
 
-/// For each MarkCompact object, we need one extra word for storing forwarding pointer (Lisp-2 implementation).
-/// Note that considering the object alignment, we may end up allocating/reserving more than one word per object.
-/// See [`MarkCompactSpace::HEADER_RESERVED_IN_BYTES`].
-pub const GC_EXTRA_HEADER_WORD: usize = 1;
-const GC_EXTRA_HEADER_BYTES: usize = GC_EXTRA_HEADER_WORD << LOG_BYTES_IN_WORD;
-
 impl<VM: VMBinding> SFT for MarkCompactSpace<VM> {
     fn name(&self) -> &str {
         self.get_name()
     }
 
     fn get_forwarded_object(&self, object: ObjectReference) -> Option<ObjectReference> {
-        let forwarding_pointer = Self::get_header_forwarding_pointer(object);
-        if forwarding_pointer.is_null() {
-            None
+        if self.forwarding.load(Ordering::SeqCst) && alloc_bit::is_alloced::<VM>(object) {
+            Some(self.get_forwarding_address(object))
         } else {
-            Some(forwarding_pointer)
+            None
         }
     }
 
@@ -72,6 +172,15 @@ impl<VM: VMBinding> SFT for MarkCompactSpace<VM> {
         crate::util::alloc_bit::set_alloc_bit::<VM>(object);
     }
 
+    fn initialize_objects_metadata(
+        &self,
+        objects: &[ObjectReference],
+        _bytes: usize,
+        _alloc: bool,
+    ) {
+        crate::util::alloc_bit::set_alloc_bits::<VM>(objects);
+    }
+
     #[cfg(feature = "sanity")]
     fn is_sane(&self) -> bool {
         true
@@ -148,55 +257,20 @@ impl<VM: VMBinding> crate::policy::gc_work::PolicyTraceObject<VM> for MarkCompac
 }
 
 impl<VM: VMBinding> MarkCompactSpace<VM> {
-    /// We need one extra header word for each object. Considering the alignment requirement, this is
-    /// the actual bytes we need to reserve for each allocation.
-    pub const HEADER_RESERVED_IN_BYTES: usize = if VM::MAX_ALIGNMENT > GC_EXTRA_HEADER_BYTES {
-        VM::MAX_ALIGNMENT
-    } else {
-        GC_EXTRA_HEADER_BYTES
-    }
-    .next_power_of_two();
-
-    // The following are a few functions for manipulating header forwarding poiner.
-    // Basically for each allocation request, we allocate extra bytes of [`HEADER_RESERVED_IN_BYTES`].
-    // From the allocation result we get (e.g. `alloc_res`), `alloc_res + HEADER_RESERVED_IN_BYTES` is the cell
-    // address we return to the binding. It ensures we have at least one word (`GC_EXTRA_HEADER_WORD`) before
-    // the cell address, and ensures the cell address is properly aligned.
-    // From the cell address, `cell - GC_EXTRA_HEADER_WORD` is where we store the header forwarding pointer.
-
-    /// Get the address for header forwarding pointer
-    fn header_forwarding_pointer_address(object: ObjectReference) -> Address {
-        object.to_object_start::<VM>() - GC_EXTRA_HEADER_BYTES
-    }
-
-    /// Get header forwarding pointer for an object
-    fn get_header_forwarding_pointer(object: ObjectReference) -> ObjectReference {
-        unsafe { Self::header_forwarding_pointer_address(object).load::<ObjectReference>() }
-    }
-
-    /// Store header forwarding pointer for an object
-    fn store_header_forwarding_pointer(
-        object: ObjectReference,
-        forwarding_pointer: ObjectReference,
-    ) {
-        unsafe {
-            Self::header_forwarding_pointer_address(object)
-                .store::<ObjectReference>(forwarding_pointer);
-        }
-    }
-
-    // Clear header forwarding pointer for an object
-    fn clear_header_forwarding_pointer(object: ObjectReference) {
-        crate::util::memory::zero(
-            Self::header_forwarding_pointer_address(object),
-            GC_EXTRA_HEADER_BYTES,
-        );
-    }
+    /// The forwarding offset table (side). For each forwarding block, it records the address that
+    /// the first live object in the block would be compacted to, before the object is aligned.
+    /// The forwarding address of any object is calculated from the entry of its block and the
+    /// sizes of the live objects before it in the block.
+    pub const FORWARDING_TABLE: SideMetadataSpec =
+        crate::util::metadata::side_metadata::spec_defs::MC_FORWARDING_TABLE;
 
     pub fn new(args: crate::policy::space::PlanCreateSpaceArgs<VM>) -> Self {
         let vm_map = args.vm_map;
         let is_discontiguous = args.vmrequest.is_discontiguous();
-        let local_specs = extract_side_metadata(&[*VM::VMObjectModel::LOCAL_MARK_BIT_SPEC]);
+        let local_specs = extract_side_metadata(&[
+            *VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
+            MetadataSpec::OnSide(Self::FORWARDING_TABLE),
+        ]);
         let common = CommonSpace::new(args.into_policy_args(true, false, local_specs));
         MarkCompactSpace {
             pr: if is_discontiguous {
@@ -205,12 +279,25 @@ impl<VM: VMBinding> MarkCompactSpace<VM> {
                 MonotonePageResource::new_contiguous(common.start, common.extent, vm_map)
             },
             common,
+            compaction: RwLock::new(CompactionRegions::empty()),
+            regions_to_count: AtomicUsize::new(0),
+            compacted_end: AtomicUsize::new(0),
+            forwarding: AtomicBool::new(false),
         }
     }
 
     pub fn prepare(&self) {}
 
-    pub fn release(&self) {}
+    pub fn release(&self) {
+        let mut compaction = self.compaction.write().unwrap();
+        if !compaction.regions.is_empty() {
+            // reset the bump pointer
+            let end = unsafe { Address::from_usize(self.compacted_end.load(Ordering::SeqCst)) };
+            debug!("Compact end: to = {}", end);
+            self.pr.reset_cursor(end);
+        }
+        *compaction = CompactionRegions::empty();
+    }
 
     pub fn trace_mark_object<Q: ObjectQueue>(
         &self,
@@ -244,7 +331,7 @@ impl<VM: VMBinding> MarkCompactSpace<VM> {
             queue.enqueue(object);
         }
 
-        Self::get_header_forwarding_pointer(object)
+        self.get_forwarding_address(object)
     }
 
     pub fn test_and_mark(object: ObjectReference) -> bool {
@@ -318,77 +405,409 @@ impl<VM: VMBinding> MarkCompactSpace<VM> {
         Self::is_marked(object)
     }
 
-    pub fn calculate_forwarding_pointer(&self) {
+    /// Get the address range of the objects that belong to the region.
+    fn region_range(&self, compaction: &CompactionRegions, index: usize) -> (Address, Address) {
+        let start = self.common.start + (index << CompactionRegion::LOG_BYTES);
+        let end = CompactionRegion::from_aligned_address(start).end();
+        (
+            start,
+            if end < compaction.end {
+                end
+            } else {
+                compaction.end
+            },
+        )
+    }
+
+    /// Iterate over the objects with the alloc bit in the address range.
+    fn iterate_objects(
+        start: Address,
+        end: Address,
+    ) -> crate::util::linear_scan::ObjectIterator<VM, MarkCompactObjectSize<VM>, true> {
+        crate::util::linear_scan::ObjectIterator::<VM, MarkCompactObjectSize<VM>, true>::new(
+            start, end,
+        )
+    }
+
+    /// Get the address that the object will be copied to, if it is compacted at `to`. Return the
+    /// start of the copied object.
+    fn align_to_copy(object: ObjectReference, to: Address) -> Address {
+        let align = VM::VMObjectModel::get_align_when_copied(object);
+        let offset = VM::VMObjectModel::get_align_offset_when_copied(object);
+        align_allocation_no_fill::<VM>(to, align, offset)
+    }
+
+    /// The number of the offsets modulo `VM::MAX_ALIGNMENT` that a region may be compacted to.
+    /// A region is compacted right after the live objects of the previous region, so its
+    /// destination is only aligned to `VM::MIN_ALIGNMENT`.
+    fn destination_offsets() -> usize {
+        VM::MAX_ALIGNMENT / VM::MIN_ALIGNMENT
+    }
+
+    /// Set up the compaction regions for the allocated memory in the space. Return the number of
+    /// regions.
+    pub fn prepare_compaction_regions(&self) -> usize {
         let start = self.common.start;
         let end = self.pr.cursor();
-        let mut to = start;
-
-        let linear_scan =
-            crate::util::linear_scan::ObjectIterator::<VM, MarkCompactObjectSize<VM>, true>::new(
-                start, end,
-            );
-        for obj in linear_scan.filter(|obj| Self::to_be_compacted(*obj)) {
-            let copied_size =
-                VM::VMObjectModel::get_size_when_copied(obj) + Self::HEADER_RESERVED_IN_BYTES;
-            let align = VM::VMObjectModel::get_align_when_copied(obj);
-            let offset = VM::VMObjectModel::get_align_offset_when_copied(obj);
-            to = align_allocation_no_fill::<VM>(to, align, offset);
-            let new_obj = VM::VMObjectModel::get_reference_when_copied_to(
-                obj,
-                to + Self::HEADER_RESERVED_IN_BYTES,
-            );
+        debug_assert!(CompactionRegion::is_aligned(start));
+        debug_assert!(CompactionRegion::BYTES >= VM::MAX_ALIGNMENT);
+        let num_regions = if end > start {
+            (end - start + CompactionRegion::BYTES - 1) >> CompactionRegion::LOG_BYTES
+        } else {
+            0
+        };
+        let mut compaction = self.compaction.write().unwrap();
+        compaction.end = end;
+        compaction.regions = (0..num_regions)
+            .map(|_| RegionInfo::new(Self::destination_offsets()))
+            .collect();
+        self.regions_to_count.store(num_regions, Ordering::SeqCst);
+        self.compacted_end.store(start.as_usize(), Ordering::SeqCst);
+        num_regions
+    }
+
+    /// Count the bytes of the live objects in a region after compaction, and clear the alloc bits
+    /// of the dead objects, so only the live objects are found by the alloc bits from now on.
+    /// Return true if this is the last region to count.
+    pub fn count_live_bytes(&self, index: usize) -> bool {
+        {
+            let compaction = self.compaction.read().unwrap();
+            let info = &compaction.regions[index];
+            let (start, end) = self.region_range(&compaction, index);
+            // The destination of the region is not known until all the regions are counted. The
+            // padding for the objects only depends on the destination modulo their alignment, so
+            // we count from a cursor at each offset modulo `VM::MAX_ALIGNMENT`, and the planning
+            // uses the count for the actual destination. `calculate_forwarding()` checks that the
+            // objects take the same bytes from the actual destination.
+            let bases: Vec<Address> = (0..Self::destination_offsets())
+                .map(|i| unsafe { Address::from_usize(VM::MAX_ALIGNMENT + i * VM::MIN_ALIGNMENT) })
+                .collect();
+            let mut cursors = bases.clone();
+            let mut live_start = None;
+            let mut live_end = start;
+            for obj in Self::iterate_objects(start, end) {
+                if !Self::to_be_compacted(obj) {
+                    alloc_bit::unset_alloc_bit::<VM>(obj);
+                    continue;
+                }
+                debug_assert!(VM::VMObjectModel::get_align_when_copied(obj) <= VM::MAX_ALIGNMENT);
+                let obj_start = obj.to_object_start::<VM>();
+                live_start.get_or_insert(obj_start);
+                live_end = obj_start + VM::VMObjectModel::get_current_size(obj);
+                for to in cursors.iter_mut() {
+                    *to = Self::align_to_copy(obj, *to)
+                        + VM::VMObjectModel::get_size_when_copied(obj);
+                }
+            }
+            for ((bytes, to), base) in info.live_bytes.iter().zip(cursors).zip(bases) {
+                bytes.store(to - base, Ordering::Relaxed);
+            }
+            info.live_start
+                .store(live_start.unwrap_or(start).as_usize(), Ordering::Relaxed);
+            info.live_end.store(live_end.as_usize(), Ordering::Relaxed);
+        }
+        self.regions_to_count.fetch_sub(1, Ordering::SeqCst) == 1
+    }
+
+    /// Compute the destination of each region, and the dependencies between the regions for the
+    /// compaction. A region cannot be compacted until the regions whose live objects it would
+    /// overwrite are compacted. Return the regions with live objects, whose forwarding addresses
+    /// need to be calculated.
+    pub fn compute_region_destinations(&self) -> Vec<usize> {
+        let compaction = self.compaction.read().unwrap();
+        let liveness: Vec<RegionLiveness> = compaction
+            .regions
+            .iter()
+            .map(|info| RegionLiveness {
+                live_bytes: info.live_bytes(),
+                live_start: RegionInfo::load(&info.live_start),
+                live_end: RegionInfo::load(&info.live_end),
+            })
+            .collect();
+        let plans = plan_compaction(
+            self.common.start,
+            VM::MIN_ALIGNMENT,
+            VM::MAX_ALIGNMENT,
+            &liveness,
+        );
 
-            Self::store_header_forwarding_pointer(obj, new_obj);
+        let mut live_regions = vec![];
+        for (index, (info, plan)) in compaction.regions.iter().zip(plans.iter()).enumerate() {
+            info.destination
+                .store(plan.destination.as_usize(), Ordering::Relaxed);
+            info.pending_dependencies
+                .store(plan.pending_dependencies, Ordering::SeqCst);
+            info.dependents_start
+                .store(plan.dependents.start, Ordering::Relaxed);
+            info.dependents_end
+                .store(plan.dependents.end, Ordering::Relaxed);
+            if info.has_live_objects() {
+                live_regions.push(index);
+            }
+        }
 
+        self.forwarding.store(true, Ordering::SeqCst);
+        live_regions
+    }
+
+    /// Calculate the forwarding addresses of the live objects in a region, and record them in the
+    /// forwarding offset table.
+    pub fn calculate_forwarding(&self, index: usize) {
+        let compaction = self.compaction.read().unwrap();
+        let info = &compaction.regions[index];
+        let (start, end) = self.region_range(&compaction, index);
+        let destination = RegionInfo::load(&info.destination);
+        let mut to = destination;
+        let mut last_block = Address::ZERO;
+        for obj in Self::iterate_objects(start, end) {
+            let block = obj
+                .to_address::<VM>()
+                .align_down(1 << LOG_BYTES_IN_FORWARDING_BLOCK);
+            if block != last_block {
+                Self::FORWARDING_TABLE.store_atomic::<usize>(
+                    block,
+                    to.as_usize(),
+                    Ordering::Relaxed,
+                );
+                last_block = block;
+            }
+            to = Self::align_to_copy(obj, to);
             trace!(
-                "Calculate forward: {} (size when copied = {}) ~> {} (size = {})",
+                "Calculate forward: {} (size when copied = {}) ~> {}",
                 obj,
                 VM::VMObjectModel::get_size_when_copied(obj),
-                to,
-                copied_size
+                to
             );
+            to += VM::VMObjectModel::get_size_when_copied(obj);
+        }
+        // The destinations of the regions are computed from the bytes counted before the
+        // destinations are known. The objects must take the same bytes from the destination.
+        assert_eq!(
+            to - destination,
+            info.live_bytes[destination_offset(destination, VM::MIN_ALIGNMENT, VM::MAX_ALIGNMENT)]
+                .load(Ordering::Relaxed),
+            "The live objects in region {} take different bytes at {}",
+            index,
+            destination
+        );
+        self.compacted_end
+            .fetch_max(to.as_usize(), Ordering::SeqCst);
+    }
+
+    /// Get the forwarding address of a live object from the forwarding offset table. This scans the
+    /// live objects before the object in its forwarding block.
+    pub fn get_forwarding_address(&self, object: ObjectReference) -> ObjectReference {
+        debug_assert!(self.forwarding.load(Ordering::Relaxed));
+        let addr = object.to_address::<VM>();
+        let block = addr.align_down(1 << LOG_BYTES_IN_FORWARDING_BLOCK);
+        let mut to = unsafe {
+            Address::from_usize(
+                Self::FORWARDING_TABLE.load_atomic::<usize>(block, Ordering::Relaxed),
+            )
+        };
+        for obj in Self::iterate_objects(block, addr + 1usize) {
+            to = Self::align_to_copy(obj, to);
+            if obj == object {
+                return VM::VMObjectModel::get_reference_when_copied_to(obj, to);
+            }
+            to += VM::VMObjectModel::get_size_when_copied(obj);
+        }
+        unreachable!("{} is not a live object", object)
+    }
+
+    /// Start the compaction. The forwarding addresses are no longer available after this. Return
+    /// the regions that can be compacted immediately.
+    pub fn start_compaction(&self) -> Vec<usize> {
+        self.forwarding.store(false, Ordering::SeqCst);
+        let compaction = self.compaction.read().unwrap();
+        compaction
+            .regions
+            .iter()
+            .enumerate()
+            .filter(|(_, info)| {
+                info.has_live_objects() && info.pending_dependencies.load(Ordering::SeqCst) == 0
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Compact the live objects in a region. The objects are slid to the destination of the
+    /// region in address order. Return the regions that can be compacted after this region.
+    pub fn compact_region(&self, index: usize) -> Vec<usize> {
+        let compaction = self.compaction.read().unwrap();
+        let info = &compaction.regions[index];
+        debug_assert_eq!(info.pending_dependencies.load(Ordering::SeqCst), 0);
+        let (start, end) = self.region_range(&compaction, index);
+        let mut to = RegionInfo::load(&info.destination);
+        for obj in Self::iterate_objects(start, end) {
+            // clear the alloc bit
+            alloc_bit::unset_alloc_bit::<VM>(obj);
+
+            let copied_size = VM::VMObjectModel::get_size_when_copied(obj);
+            let new_object =
+                VM::VMObjectModel::get_reference_when_copied_to(obj, Self::align_to_copy(obj, to));
+
+            // copy object
+            trace!(" copy from {} to {}", obj, new_object);
+            let end_of_new_object = VM::VMObjectModel::copy_to(obj, new_object, Address::ZERO);
+            // update alloc_bit,
+            alloc_bit::set_alloc_bit::<VM>(new_object);
+            to = new_object.to_object_start::<VM>() + copied_size;
+            debug_assert_eq!(end_of_new_object, to);
+        }
 
-            to += copied_size;
+        // The live objects in this region have been moved. The regions that overwrite them can
+        // be compacted if they do not depend on other regions.
+        let mut ready = vec![];
+        let dependents_start = info.dependents_start.load(Ordering::Relaxed);
+        let dependents_end = info.dependents_end.load(Ordering::Relaxed);
+        for dependent in dependents_start..dependents_end {
+            let dependent_info = &compaction.regions[dependent];
+            if dependent_info.has_live_objects()
+                && dependent_info
+                    .pending_dependencies
+                    .fetch_sub(1, Ordering::SeqCst)
+                    == 1
+            {
+                ready.push(dependent);
+            }
         }
-        debug!("Calculate forward end: to = {}", to);
+        ready
     }
+}
 
-    pub fn compact(&self) {
-        let start = self.common.start;
-        let end = self.pr.cursor();
-        let mut to = end;
+/// The live objects in a compaction region, counted before the compaction is planned.
+#[derive(Debug, Clone)]
+struct RegionLiveness {
+    /// The bytes of the live objects after they are compacted, including the alignment padding.
+    /// It is indexed by the offset of the destination, see [`destination_offset`].
+    live_bytes: Vec<usize>,
+    /// The start of the memory of the first live object.
+    live_start: Address,
+    /// The end of the memory of the last live object.
+    live_end: Address,
+}
 
-        let linear_scan =
-            crate::util::linear_scan::ObjectIterator::<VM, MarkCompactObjectSize<VM>, true>::new(
-                start, end,
-            );
-        for obj in linear_scan {
-            // clear the alloc bit
-            alloc_bit::unset_alloc_bit::<VM>(obj);
+/// Where a compaction region is compacted to, and how it depends on the other regions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RegionPlan {
+    /// The address that the live objects in the region are compacted to.
+    destination: Address,
+    /// The number of regions that must be compacted before this region.
+    pending_dependencies: usize,
+    /// The regions that depend on this region.
+    dependents: std::ops::Range<usize>,
+}
 
-            let forwarding_pointer = Self::get_header_forwarding_pointer(obj);
-
-            trace!("Compact {} to {}", obj, forwarding_pointer);
-            if !forwarding_pointer.is_null() {
-                let copied_size = VM::VMObjectModel::get_size_when_copied(obj);
-                let new_object = forwarding_pointer;
-                Self::clear_header_forwarding_pointer(new_object);
-
-                // copy object
-                trace!(" copy from {} to {}", obj, new_object);
-                let end_of_new_object = VM::VMObjectModel::copy_to(obj, new_object, Address::ZERO);
-                // update alloc_bit,
-                alloc_bit::set_alloc_bit::<VM>(new_object);
-                to = new_object.to_object_start::<VM>() + copied_size;
-                debug_assert_eq!(end_of_new_object, to);
+/// The index into [`RegionLiveness::live_bytes`] for a destination, i.e. the offset of the
+/// destination modulo `max_align`, in the unit of `min_align`.
+fn destination_offset(destination: Address, min_align: usize, max_align: usize) -> usize {
+    debug_assert!(destination.is_aligned_to(min_align));
+    (destination.as_usize() & (max_align - 1)) / min_align
+}
+
+/// Plan the compaction of the regions of a space that starts at `space_start`. The regions are
+/// compacted one after another in address order. Each region is compacted right after the live
+/// objects of the previous region, so no object is moved to a higher address. A region depends on
+/// the regions before it whose live objects overlap its destination.
+fn plan_compaction(
+    space_start: Address,
+    min_align: usize,
+    max_align: usize,
+    regions: &[RegionLiveness],
+) -> Vec<RegionPlan> {
+    let num_regions = regions.len();
+    let region_index = |addr: Address| (addr - space_start) >> CompactionRegion::LOG_BYTES;
+    let is_live = |index: usize| regions[index].live_bytes[0] != 0;
+    let live_regions: Vec<usize> = (0..num_regions).filter(|&index| is_live(index)).collect();
+
+    // The destinations, and the bytes of the live objects at the destinations. The regions are
+    // compacted one after another.
+    let mut to = space_start;
+    let mut live_bytes = vec![0; num_regions];
+    let mut plans: Vec<RegionPlan> = regions
+        .iter()
+        .enumerate()
+        .map(|(index, region)| {
+            let destination = to;
+            if region.live_bytes[0] != 0 {
+                // The objects are slid in address order, so the destination must not be above
+                // the first live object. Otherwise an object would overwrite the next object
+                // before that object is copied.
+                assert!(
+                    destination <= region.live_start,
+                    "Region {} is compacted to {}, which is above its first live object at {}",
+                    index,
+                    destination,
+                    region.live_start
+                );
+                live_bytes[index] =
+                    region.live_bytes[destination_offset(destination, min_align, max_align)];
+                to += live_bytes[index];
             }
+            RegionPlan {
+                destination,
+                pending_dependencies: 0,
+                dependents: 0..0,
+            }
+        })
+        .collect();
+
+    // For each region `r`, find the first and the last region whose live objects overlap `r`.
+    let mut first_overlapping: Vec<usize> = (0..num_regions).collect();
+    let mut last_overlapping: Vec<usize> = (0..num_regions).collect();
+    for &index in live_regions.iter() {
+        let first = region_index(regions[index].live_start);
+        let last = region_index(regions[index].live_end - 1usize);
+        for r in first..=last {
+            first_overlapping[r] = first_overlapping[r].min(index);
+            last_overlapping[r] = last_overlapping[r].max(index);
         }
+    }
 
-        debug!("Compact end: to = {}", to);
-
-        // reset the bump pointer
-        self.pr.reset_cursor(to);
+    // A region depends on the regions (before itself) whose live objects overlap its
+    // destination. Both ends of the dependencies are monotonic in the address of the regions.
+    let dependencies: Vec<(usize, usize)> = live_regions
+        .iter()
+        .map(|&index| {
+            if index == 0 {
+                // No region before the first region.
+                return (1, 0);
+            }
+            let dest_start = plans[index].destination;
+            let dest_end = dest_start + live_bytes[index] - 1usize;
+            let first = first_overlapping[region_index(dest_start)];
+            let last = last_overlapping[region_index(dest_end)].min(index - 1);
+            (first, last)
+        })
+        .collect();
+    // The number of the regions with live objects before each region.
+    let mut live_before = vec![0; num_regions + 1];
+    for index in 0..num_regions {
+        live_before[index + 1] = live_before[index] + is_live(index) as usize;
+    }
+    for (&index, &(first, last)) in live_regions.iter().zip(dependencies.iter()) {
+        if first <= last {
+            plans[index].pending_dependencies = live_before[last + 1] - live_before[first];
+        }
     }
+    // The dependents of a region are contiguous in `live_regions`.
+    let mut begin = 0;
+    let mut end = 0;
+    for (i, &index) in live_regions.iter().enumerate() {
+        begin = begin.max(i + 1);
+        while begin < live_regions.len() && dependencies[begin].1 < index {
+            begin += 1;
+        }
+        end = end.max(begin);
+        while end < live_regions.len() && dependencies[end].0 <= index {
+            end += 1;
+        }
+        if begin < end {
+            plans[index].dependents = live_regions[begin]..live_regions[end - 1] + 1;
+        }
+    }
+    plans
 }
 
 struct MarkCompactObjectSize<VM>(std::marker::PhantomData<VM>);
@@ -397,3 +816,144 @@ impl<VM: VMBinding> crate::util::linear_scan::LinearScanObjectSize for MarkCompa
         VM::VMObjectModel::get_current_size(object)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGION: usize = CompactionRegion::BYTES;
+    const MIN_ALIGN: usize = 4;
+    const MAX_ALIGN: usize = 8;
+
+    fn space_start() -> Address {
+        unsafe { Address::from_usize(0x1000_0000_0000) }
+    }
+
+    /// A region whose live objects take the same bytes wherever they are compacted to.
+    fn live(live_bytes: usize, live_start: usize, live_end: usize) -> RegionLiveness {
+        RegionLiveness {
+            live_bytes: vec![live_bytes; MAX_ALIGN / MIN_ALIGN],
+            live_start: space_start() + live_start,
+            live_end: space_start() + live_end,
+        }
+    }
+
+    fn dead(index: usize) -> RegionLiveness {
+        live(0, index * REGION, index * REGION)
+    }
+
+    fn destinations(regions: &[RegionLiveness]) -> Vec<Address> {
+        plan_compaction(space_start(), MIN_ALIGN, MAX_ALIGN, regions)
+            .into_iter()
+            .map(|plan| plan.destination)
+            .collect()
+    }
+
+    #[test]
+    fn test_destinations_follow_previous_region() {
+        // An 8-byte object that is aligned to 8 bytes. It takes 4 more bytes for the padding if
+        // it is compacted to an address that is not aligned to 8 bytes.
+        let aligned_object = RegionLiveness {
+            live_bytes: vec![8, 12],
+            live_start: space_start() + 2 * REGION,
+            live_end: space_start() + 2 * REGION + 8usize,
+        };
+        let regions = [
+            live(100, 0, 100),
+            dead(1),
+            aligned_object,
+            live(300, 3 * REGION, 3 * REGION + 300),
+        ];
+        assert_eq!(
+            destinations(&regions),
+            vec![
+                space_start(),
+                space_start() + 100usize,
+                space_start() + 100usize,
+                space_start() + 112usize,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dense_heap() {
+        // Region 0 is full of live 12-byte objects that are aligned to 4 bytes. The last one
+        // starts at `REGION - 8`, and ends in region 1.
+        assert_eq!(REGION % 12, 4);
+        let regions = [
+            live(REGION + 4, 0, REGION + 4),
+            live(1200, REGION + 4, REGION + 1204),
+            live(REGION - 1204, REGION + 1204, 2 * REGION),
+        ];
+        let plans = plan_compaction(space_start(), MIN_ALIGN, MAX_ALIGN, &regions);
+        // Nothing moves. Each region is compacted to where its first live object is.
+        for (index, (plan, region)) in plans.iter().zip(regions.iter()).enumerate() {
+            assert_eq!(plan.destination, region.live_start, "region {}", index);
+        }
+        // Region 1 is compacted over the last object of region 0.
+        assert_eq!(plans[1].pending_dependencies, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_destination_above_live_objects() {
+        // Region 1 claims more live bytes than there is memory before its first live object.
+        let regions = [live(REGION / 2, 0, REGION / 2), live(8, REGION / 4, REGION)];
+        destinations(&regions);
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let regions = [
+            // Compacted in place.
+            live(REGION / 2, 0, REGION / 2),
+            // Its last object ends in region 2. It is compacted over region 0 and region 1.
+            live(REGION, REGION, 2 * REGION + 16),
+            // Compacted over region 1. Its first object is in region 2 after the last object of region 1.
+            live(64, 2 * REGION + 16, 2 * REGION + 80),
+            // Compacted over region 1.
+            live(8, 3 * REGION, 3 * REGION + 8),
+            dead(4),
+        ];
+        let plans = plan_compaction(space_start(), MIN_ALIGN, MAX_ALIGN, &regions);
+        let expected = [
+            (space_start(), 0, 1..2),
+            (space_start() + REGION / 2, 1, 2..4),
+            (space_start() + REGION * 3 / 2, 1, 0..0),
+            (space_start() + REGION * 3 / 2 + 64usize, 1, 0..0),
+            (space_start() + REGION * 3 / 2 + 72usize, 0, 0..0),
+        ];
+        for (index, (plan, (destination, pending_dependencies, dependents))) in
+            plans.into_iter().zip(expected.into_iter()).enumerate()
+        {
+            assert_eq!(
+                plan,
+                RegionPlan {
+                    destination,
+                    pending_dependencies,
+                    dependents,
+                },
+                "region {}",
+                index
+            );
+        }
+    }
+
+    #[test]
+    fn test_dependencies_on_one_region() {
+        // Each region only has a few live bytes, so they are all compacted into region 0.
+        let regions = [
+            live(8, 0, 8),
+            live(8, REGION, REGION + 8),
+            live(8, 2 * REGION, 2 * REGION + 8),
+        ];
+        let plans = plan_compaction(space_start(), MIN_ALIGN, MAX_ALIGN, &regions);
+        assert_eq!(plans[1].destination, space_start() + 8usize);
+        assert_eq!(plans[2].destination, space_start() + 16usize);
+        // Region 0 keeps its live objects where they are, so both region 1 and 2 wait for it.
+        assert_eq!(plans[1].pending_dependencies, 1);
+        assert_eq!(plans[2].pending_dependencies, 1);
+        assert_eq!(plans[0].dependents, 1..3);
+        assert_eq!(plans[1].dependents, 0..0);
+    }
+}
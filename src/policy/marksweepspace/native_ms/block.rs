@@ -42,7 +42,7 @@ impl Region for Block {
 }
 
 impl Block {
-    pub const METADATA_SPECS: [SideMetadataSpec; 7] = [
+    pub const METADATA_SPECS: [SideMetadataSpec; 8] = [
         Self::MARK_TABLE,
         Self::NEXT_BLOCK_TABLE,
         Self::PREV_BLOCK_TABLE,
@@ -50,6 +50,7 @@ impl Block {
         Self::SIZE_TABLE,
         Self::BLOCK_LIST_TABLE,
         Self::TLS_TABLE,
+        Self::LIVE_BYTES_TABLE,
     ];
 
     /// Block mark table (side)
@@ -83,6 +84,11 @@ impl Block {
     pub const TLS_TABLE: SideMetadataSpec =
         crate::util::metadata::side_metadata::spec_defs::MS_BLOCK_TLS;
 
+    /// The bytes of the cells that are live in the block, counted by the GC. This is only used for
+    /// defragmentation.
+    pub const LIVE_BYTES_TABLE: SideMetadataSpec =
+        crate::util::metadata::side_metadata::spec_defs::MS_BLOCK_LIVE_BYTES;
+
     pub fn load_free_list(&self) -> Address {
         unsafe { Address::from_usize(Block::FREE_LIST_TABLE.load::<usize>(self.start())) }
     }
@@ -198,6 +204,30 @@ impl Block {
         }))
     }
 
+    /// Get the bytes of the live cells in the block.
+    pub fn live_bytes(&self) -> usize {
+        Self::LIVE_BYTES_TABLE.load_atomic::<u32>(self.start(), Ordering::SeqCst) as usize
+    }
+
+    /// Add the bytes of a live cell to the block.
+    pub fn add_live_bytes(&self, bytes: usize) {
+        Self::LIVE_BYTES_TABLE.fetch_add_atomic::<u32>(
+            self.start(),
+            bytes as u32,
+            Ordering::SeqCst,
+        );
+    }
+
+    /// Set the bytes of the live cells in the block.
+    pub fn store_live_bytes(&self, bytes: usize) {
+        Self::LIVE_BYTES_TABLE.store_atomic::<u32>(self.start(), bytes as u32, Ordering::SeqCst);
+    }
+
+    /// Is the block a defragmentation source in the current GC?
+    pub fn is_defrag_source(&self) -> bool {
+        self.get_state() == BlockState::DefragSource
+    }
+
     pub fn has_free_cells(&self) -> bool {
         !self.load_free_list().is_zero()
     }
@@ -218,7 +248,8 @@ impl Block {
     pub fn attempt_release<VM: VMBinding>(self, space: &MarkSweepSpace<VM>) -> bool {
         match self.get_state() {
             BlockState::Unallocated => false,
-            BlockState::Unmarked => {
+            // A defragmentation source is marked if any of its objects is not evacuated.
+            BlockState::Unmarked | BlockState::DefragSource => {
                 unsafe {
                     let block_list = loop {
                         let list = self.load_block_list();
@@ -397,6 +428,8 @@ pub enum BlockState {
     Unmarked,
     /// the block is allocated and marked.
     Marked,
+    /// the block is allocated and not marked, and its objects are evacuated in the current GC.
+    DefragSource,
 }
 
 impl BlockState {
@@ -406,6 +439,8 @@ impl BlockState {
     const MARK_UNMARKED: u8 = u8::MAX;
     /// Private constant
     const MARK_MARKED: u8 = u8::MAX - 1;
+    /// Private constant
+    const MARK_DEFRAG_SOURCE: u8 = u8::MAX - 2;
 }
 
 impl From<u8> for BlockState {
@@ -414,6 +449,7 @@ impl From<u8> for BlockState {
             Self::MARK_UNALLOCATED => BlockState::Unallocated,
             Self::MARK_UNMARKED => BlockState::Unmarked,
             Self::MARK_MARKED => BlockState::Marked,
+            Self::MARK_DEFRAG_SOURCE => BlockState::DefragSource,
             _ => unreachable!(),
         }
     }
@@ -425,6 +461,7 @@ impl From<BlockState> for u8 {
             BlockState::Unallocated => BlockState::MARK_UNALLOCATED,
             BlockState::Unmarked => BlockState::MARK_UNMARKED,
             BlockState::Marked => BlockState::MARK_MARKED,
+            BlockState::DefragSource => BlockState::MARK_DEFRAG_SOURCE,
         }
     }
 }
@@ -272,7 +272,7 @@ pub fn mi_bin<VM: VMBinding>(size: usize, align: usize) -> usize {
     mi_bin_from_size(size)
 }
 
-fn mi_bin_from_size(size: usize) -> usize {
+pub(crate) fn mi_bin_from_size(size: usize) -> usize {
     // adapted from _mi_bin in mimalloc
     let mut wsize: usize = mi_wsize_from_size(size);
     debug_assert!(wsize <= MI_LARGE_OBJ_WSIZE_MAX);
@@ -0,0 +1,151 @@
+use super::{Block, BlockState, MarkSweepSpace};
+use crate::policy::defrag::DefragState;
+use crate::util::constants::LOG_BYTES_IN_PAGE;
+use crate::util::linear_scan::Region;
+use crate::vm::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Opportunistic evacuation for the mark sweep space. In a defrag GC, the blocks with a low
+/// occupancy are chosen as sources before tracing. The live objects in the sources are copied into
+/// other blocks of the same size class, and the sources are released in the sweep.
+///
+/// The occupancy of a block is the live bytes counted in the last GC. A block that is used by an
+/// allocator after the last GC may have new objects, so it is not chosen as a source.
+#[derive(Debug, Default)]
+pub struct Defrag {
+    /// Whether the current GC is a defrag GC, and the clean pages left for copying.
+    state: DefragState,
+    /// The maximum number of live bytes to evacuate in the current GC, as decided by the plan.
+    max_evacuated_bytes: AtomicUsize,
+    /// The live bytes in the blocks chosen for evacuation in the current GC.
+    evacuated_bytes: AtomicUsize,
+    /// The live bytes in the blocks that survived the last GC.
+    live_bytes: AtomicUsize,
+    /// The number of blocks that survived the last GC.
+    live_blocks: AtomicUsize,
+}
+
+impl Defrag {
+    /// A block is a defrag source if less than this percentage of its cells are live.
+    const SOURCE_OCCUPANCY_PERCENT: usize = 40;
+    /// Do a defrag GC if more than this percentage of the memory in the blocks that survived the
+    /// last GC is free.
+    const FRAGMENTATION_PERCENT: usize = 30;
+    const BLOCK_PAGES: usize = Block::BYTES >> LOG_BYTES_IN_PAGE;
+
+    /// Check if the current GC is a defrag GC.
+    pub fn in_defrag(&self) -> bool {
+        self.state.in_defrag()
+    }
+
+    /// Determine whether the current GC should do defragmentation. If it does, at most
+    /// `max_evacuated_pages` pages of live bytes are evacuated.
+    pub fn decide_whether_to_defrag(
+        &self,
+        emergency_collection: bool,
+        collection_attempts: usize,
+        user_triggered: bool,
+        full_heap_system_gc: bool,
+        max_evacuated_pages: Option<usize>,
+    ) -> bool {
+        let live_blocks = self.live_blocks.load(Ordering::Acquire);
+        let fragmented = self.live_bytes.load(Ordering::Acquire) * 100
+            < (live_blocks << Block::LOG_BYTES) * (100 - Self::FRAGMENTATION_PERCENT);
+        let max_evacuated_bytes = max_evacuated_pages.map_or(usize::MAX, |pages| {
+            pages.saturating_mul(1 << LOG_BYTES_IN_PAGE)
+        });
+        self.max_evacuated_bytes
+            .store(max_evacuated_bytes, Ordering::Release);
+        self.state.decide_whether_to_defrag(
+            emergency_collection,
+            collection_attempts,
+            user_triggered && full_heap_system_gc,
+            fragmented,
+        )
+    }
+
+    /// Get the number of pages of live bytes chosen for evacuation in the current GC.
+    pub fn evacuated_pages(&self) -> usize {
+        self.evacuated_bytes.load(Ordering::Acquire) >> LOG_BYTES_IN_PAGE
+    }
+
+    /// Get the number of defrag headroom pages.
+    pub fn defrag_headroom_pages<VM: VMBinding>(&self, space: &MarkSweepSpace<VM>) -> usize {
+        self.state.defrag_headroom_pages(space)
+    }
+
+    /// Check if the defrag space is exhausted.
+    pub fn space_exhausted(&self) -> bool {
+        self.state.space_exhausted()
+    }
+
+    /// Update available_clean_pages_for_defrag counter when a clean block is allocated for copying.
+    pub fn notify_new_clean_block(&self) {
+        self.state.notify_new_clean_block(Self::BLOCK_PAGES);
+    }
+
+    /// Count the blocks that survive a GC. This is called when the blocks are swept.
+    pub fn add_live_blocks(&self, live_bytes: usize, live_blocks: usize) {
+        self.live_bytes.fetch_add(live_bytes, Ordering::SeqCst);
+        self.live_blocks.fetch_add(live_blocks, Ordering::SeqCst);
+    }
+
+    /// Prepare work. Should be called in MarkSweepSpace::prepare, before the live bytes of the
+    /// blocks are reset.
+    pub fn prepare<VM: VMBinding>(&self, space: &MarkSweepSpace<VM>) {
+        self.live_bytes.store(0, Ordering::Release);
+        self.live_blocks.store(0, Ordering::Release);
+        self.evacuated_bytes.store(0, Ordering::Release);
+
+        let available_clean_pages_for_defrag = self.state.prepare(space);
+
+        if self.in_defrag() {
+            self.select_defrag_sources(space, available_clean_pages_for_defrag);
+        }
+    }
+
+    /// Choose the blocks with the lowest occupancy as defrag sources, as long as their live
+    /// objects fit in the available clean pages, and can be copied within the pause time target.
+    fn select_defrag_sources<VM: VMBinding>(
+        &self,
+        space: &MarkSweepSpace<VM>,
+        available_clean_pages: usize,
+    ) {
+        let mut candidates = vec![];
+        for chunk in space.chunk_map.all_chunks() {
+            for block in chunk
+                .iter_region::<Block>()
+                .filter(|block| block.get_state() != BlockState::Unallocated)
+            {
+                let cell_size = block.load_block_cell_size();
+                let capacity = Block::BYTES / cell_size * cell_size;
+                let live_bytes = block.live_bytes();
+                if live_bytes * 100 < capacity * Self::SOURCE_OCCUPANCY_PERCENT {
+                    candidates.push((live_bytes, block));
+                }
+            }
+        }
+        candidates.sort_by_key(|(live_bytes, _)| *live_bytes);
+
+        let limit = available_clean_pages << LOG_BYTES_IN_PAGE;
+        // Number of bytes we can evacuate within the pause time target.
+        let max_required_bytes = self.max_evacuated_bytes.load(Ordering::Acquire);
+        let mut required_bytes = 0;
+        for (live_bytes, block) in candidates {
+            if required_bytes + live_bytes > limit
+                || required_bytes + live_bytes > max_required_bytes
+            {
+                break;
+            }
+            required_bytes += live_bytes;
+            block.set_state(BlockState::DefragSource);
+        }
+        self.evacuated_bytes
+            .store(required_bytes, Ordering::Release);
+    }
+
+    /// Release work. Should be called in MarkSweepSpace::release.
+    pub fn release(&self) {
+        self.state.release();
+    }
+}
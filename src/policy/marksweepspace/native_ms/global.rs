@@ -1,3 +1,4 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize};
 use std::sync::Arc;
 
 use atomic::Ordering;
@@ -14,20 +15,23 @@ use crate::{
     vm::VMBinding,
 };
 
-#[cfg(feature = "is_mmtk_object")]
-use crate::util::Address;
-
 use crate::plan::ObjectQueue;
 use crate::plan::VectorObjectQueue;
+use crate::policy::gc_work::TraceKind;
 use crate::policy::sft::SFT;
 use crate::policy::space::{CommonSpace, Space};
 use crate::util::constants::LOG_BYTES_IN_PAGE;
 use crate::util::heap::chunk_map::*;
 use crate::util::linear_scan::Region;
+use crate::util::object_forwarding as ForwardingWord;
+use crate::util::Address;
 use crate::util::VMThread;
 use crate::vm::ObjectModel;
 use std::sync::Mutex;
 
+pub(crate) const TRACE_KIND_FAST: TraceKind = 0;
+pub(crate) const TRACE_KIND_DEFRAG: TraceKind = 1;
+
 /// The result for `MarkSweepSpace.acquire_block()`. `MarkSweepSpace` will attempt
 /// to allocate from abandoned blocks first. If none found, it will get a new block
 /// from the page resource.
@@ -41,6 +45,33 @@ pub enum BlockAcquireResult {
     AbandonedUnswept(Block),
 }
 
+/// Some arguments for the mark sweep space.
+#[derive(Default)]
+pub struct MarkSweepSpaceArgs {
+    /// Mark an object as unlogged when we trace it. In sticky mark sweep, every object that is
+    /// traced becomes a mature object, and it needs to be unlogged so the barrier remembers it
+    /// when it is modified.
+    pub unlog_object_when_traced: bool,
+    /// Reset log bit at the start of a major GC. In sticky mark sweep, the mature objects and the
+    /// nursery objects are in the same space, and the log bit of a dead mature object has to be
+    /// cleared before its cell is reused by a nursery object. We reset all the log bits in major GCs,
+    /// and unlog the objects again when they are traced.
+    pub reset_log_bit_in_major_gc: bool,
+    /// The space is immortal. GC does not reclaim any object in the space, and objects are only
+    /// reclaimed when they are freed explicitly with `free_object()`. This is used for the immortal
+    /// space of the common plan with the feature `immortal_free_list`.
+    pub immortal: bool,
+    /// The space is the non-moving space of the common plan. It is only collected in the GCs that
+    /// trace the whole heap. Objects are marked when they are allocated, so the other GCs (such as
+    /// nursery GCs) and the sweeping after them keep the objects, and they are unlogged if the plan
+    /// uses the log bit, as the barrier needs to remember them like mature objects.
+    pub nonmoving: bool,
+    /// Evacuate the live objects from the blocks with a low occupancy in defrag GCs, so the blocks
+    /// can be released. The plan needs to trace the space with `TRACE_KIND_DEFRAG` in a defrag GC
+    /// (see `decide_whether_to_defrag()`), and provide a copy context for the space.
+    pub defrag: bool,
+}
+
 /// A mark sweep space.
 pub struct MarkSweepSpace<VM: VMBinding> {
     pub common: CommonSpace<VM>,
@@ -53,6 +84,19 @@ pub struct MarkSweepSpace<VM: VMBinding> {
     /// lists. In a GC, we also 'flush' all the local blocks to this global pool so they
     /// can be used by allocators from other threads.
     pub abandoned: Mutex<AbandonedBlockLists>,
+    /// Is the space being traced in a GC? The mark bits are incomplete until the trace is done,
+    /// so unswept blocks cannot be swept and reused in the meantime. This only matters if objects
+    /// are copied into this space during a GC.
+    tracing: AtomicBool,
+    /// The number of blocks handed out to the allocators since the last GC.
+    blocks_acquired: AtomicUsize,
+    /// The cells freed since the last GC in an immortal space. They are put back to the free lists
+    /// of their blocks in the next GC, when no allocator is using the free lists.
+    freed_cells: Mutex<Vec<Address>>,
+    /// Defragmentation state. This is only used if `space_args.defrag` is set.
+    defrag: Defrag,
+    /// Some settings for this space
+    space_args: MarkSweepSpaceArgs,
 }
 
 pub struct AbandonedBlockLists {
@@ -78,7 +122,26 @@ impl<VM: VMBinding> SFT for MarkSweepSpace<VM> {
         self.common.name
     }
 
+    fn get_forwarded_object(&self, object: ObjectReference) -> Option<ObjectReference> {
+        if !Block::containing::<VM>(object).is_defrag_source() {
+            return None;
+        }
+
+        if ForwardingWord::is_forwarded::<VM>(object) {
+            Some(ForwardingWord::read_forwarding_pointer::<VM>(object))
+        } else {
+            None
+        }
+    }
+
     fn is_live(&self, object: crate::util::ObjectReference) -> bool {
+        self.space_args.immortal || self.is_reachable(object)
+    }
+
+    fn is_reachable(&self, object: ObjectReference) -> bool {
+        if self.space_args.defrag && ForwardingWord::is_forwarded::<VM>(object) {
+            return true;
+        }
         VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.is_marked::<VM>(object, Ordering::SeqCst)
     }
 
@@ -98,7 +161,7 @@ impl<VM: VMBinding> SFT for MarkSweepSpace<VM> {
     }
 
     fn is_movable(&self) -> bool {
-        false
+        self.space_args.defrag
     }
 
     #[cfg(feature = "sanity")]
@@ -106,9 +169,17 @@ impl<VM: VMBinding> SFT for MarkSweepSpace<VM> {
         true
     }
 
-    fn initialize_object_metadata(&self, _object: crate::util::ObjectReference, _alloc: bool) {
+    fn initialize_object_metadata(&self, object: crate::util::ObjectReference, _alloc: bool) {
+        if self.space_args.nonmoving {
+            VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.mark::<VM>(object, Ordering::SeqCst);
+            Block::containing::<VM>(object).set_state(BlockState::Marked);
+        }
+        // Immortal and non-moving objects are mature objects, and the barrier needs to remember them.
+        if (self.space_args.immortal || self.space_args.nonmoving) && self.common.needs_log_bit {
+            VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC.mark_as_unlogged::<VM>(object, Ordering::SeqCst);
+        }
         #[cfg(feature = "global_alloc_bit")]
-        crate::util::alloc_bit::set_alloc_bit::<VM>(_object);
+        crate::util::alloc_bit::set_alloc_bit::<VM>(object);
     }
 
     #[cfg(feature = "is_mmtk_object")]
@@ -153,18 +224,23 @@ impl<VM: VMBinding> Space<VM> for MarkSweepSpace<VM> {
 }
 
 impl<VM: VMBinding> crate::policy::gc_work::PolicyTraceObject<VM> for MarkSweepSpace<VM> {
-    fn trace_object<Q: ObjectQueue, const KIND: crate::policy::gc_work::TraceKind>(
+    fn trace_object<Q: ObjectQueue, const KIND: TraceKind>(
         &self,
         queue: &mut Q,
         object: ObjectReference,
-        _copy: Option<CopySemantics>,
-        _worker: &mut GCWorker<VM>,
+        copy: Option<CopySemantics>,
+        worker: &mut GCWorker<VM>,
     ) -> ObjectReference {
-        self.trace_object(queue, object)
+        if KIND == TRACE_KIND_DEFRAG && Block::containing::<VM>(object).is_defrag_source() {
+            debug_assert!(self.in_defrag());
+            self.trace_object_with_opportunistic_copy(queue, object, copy.unwrap(), worker)
+        } else {
+            self.trace_object(queue, object)
+        }
     }
 
-    fn may_move_objects<const KIND: crate::policy::gc_work::TraceKind>() -> bool {
-        false
+    fn may_move_objects<const KIND: TraceKind>() -> bool {
+        KIND == TRACE_KIND_DEFRAG
     }
 }
 
@@ -181,11 +257,28 @@ impl<VM: VMBinding> MarkSweepSpace<VM> {
     }
 
     pub fn new(args: crate::policy::space::PlanCreateSpaceArgs<VM>) -> MarkSweepSpace<VM> {
+        Self::new_with_args(args, MarkSweepSpaceArgs::default())
+    }
+
+    pub fn new_with_args(
+        args: crate::policy::space::PlanCreateSpaceArgs<VM>,
+        space_args: MarkSweepSpaceArgs,
+    ) -> MarkSweepSpace<VM> {
+        if space_args.unlog_object_when_traced || space_args.reset_log_bit_in_major_gc {
+            assert!(
+                args.constraints.needs_log_bit,
+                "Invalid args when the plan does not use log bit"
+            );
+        }
+        assert!(
+            !(space_args.defrag && (space_args.immortal || space_args.nonmoving)),
+            "An immortal or non-moving space cannot be defragmented"
+        );
         let scheduler = args.scheduler.clone();
         let vm_map = args.vm_map;
         let is_discontiguous = args.vmrequest.is_discontiguous();
         let local_specs = {
-            metadata::extract_side_metadata(&vec![
+            let mut specs = vec![
                 MetadataSpec::OnSide(Block::NEXT_BLOCK_TABLE),
                 MetadataSpec::OnSide(Block::PREV_BLOCK_TABLE),
                 MetadataSpec::OnSide(Block::FREE_LIST_TABLE),
@@ -197,11 +290,18 @@ impl<VM: VMBinding> MarkSweepSpace<VM> {
                 MetadataSpec::OnSide(Block::BLOCK_LIST_TABLE),
                 MetadataSpec::OnSide(Block::TLS_TABLE),
                 MetadataSpec::OnSide(Block::MARK_TABLE),
+                MetadataSpec::OnSide(Block::LIVE_BYTES_TABLE),
                 MetadataSpec::OnSide(ChunkMap::ALLOC_TABLE),
                 *VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
-            ])
+            ];
+            if space_args.defrag {
+                specs.push(*VM::VMObjectModel::LOCAL_FORWARDING_BITS_SPEC);
+                specs.push(*VM::VMObjectModel::LOCAL_FORWARDING_POINTER_SPEC);
+            }
+            metadata::extract_side_metadata(&specs)
         };
-        let common = CommonSpace::new(args.into_policy_args(false, false, local_specs));
+        let common =
+            CommonSpace::new(args.into_policy_args(false, space_args.immortal, local_specs));
         MarkSweepSpace {
             pr: if is_discontiguous {
                 FreeListPageResource::new_discontiguous(vm_map)
@@ -216,10 +316,15 @@ impl<VM: VMBinding> MarkSweepSpace<VM> {
                 unswept: new_empty_block_lists(),
                 consumed: new_empty_block_lists(),
             }),
+            tracing: AtomicBool::new(false),
+            blocks_acquired: AtomicUsize::new(0),
+            freed_cells: Mutex::new(vec![]),
+            defrag: Defrag::default(),
+            space_args,
         }
     }
 
-    fn trace_object<Q: ObjectQueue>(
+    pub fn trace_object<Q: ObjectQueue>(
         &self,
         queue: &mut Q,
         object: ObjectReference,
@@ -236,11 +341,70 @@ impl<VM: VMBinding> MarkSweepSpace<VM> {
             VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.mark::<VM>(object, Ordering::SeqCst);
             let block = Block::containing::<VM>(object);
             block.set_state(BlockState::Marked);
+            if self.space_args.defrag {
+                block.add_live_bytes(block.load_block_cell_size());
+            }
+            if self.space_args.unlog_object_when_traced {
+                VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC
+                    .mark_as_unlogged::<VM>(object, Ordering::SeqCst);
+            }
             queue.enqueue(object);
         }
         object
     }
 
+    pub fn is_marked(&self, object: ObjectReference) -> bool {
+        VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.is_marked::<VM>(object, Ordering::SeqCst)
+    }
+
+    /// Mark an object that was just copied into this space, such as an object promoted from a
+    /// nursery. The object may be copied after this space was traced, so we mark it as live.
+    pub fn post_copy(&self, object: ObjectReference) {
+        #[cfg(feature = "global_alloc_bit")]
+        crate::util::alloc_bit::set_alloc_bit::<VM>(object);
+        VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.mark::<VM>(object, Ordering::SeqCst);
+        let block = Block::containing::<VM>(object);
+        block.set_state(BlockState::Marked);
+        if self.space_args.defrag {
+            block.add_live_bytes(block.load_block_cell_size());
+        }
+    }
+
+    /// Trace an object in a defrag source. The object is copied out of the block if there is
+    /// enough space for defragmentation. Otherwise it is marked in place, and the block is kept.
+    fn trace_object_with_opportunistic_copy(
+        &self,
+        queue: &mut impl ObjectQueue,
+        object: ObjectReference,
+        semantics: CopySemantics,
+        worker: &mut GCWorker<VM>,
+    ) -> ObjectReference {
+        let forwarding_status = ForwardingWord::attempt_to_forward::<VM>(object);
+        if ForwardingWord::state_is_forwarded_or_being_forwarded(forwarding_status) {
+            // We lost the forwarding race. Wait until the winner has copied or marked the object.
+            ForwardingWord::spin_and_get_forwarded_object::<VM>(object, forwarding_status)
+        } else if self.is_marked(object) {
+            // We won the forwarding race, but the object was marked in place by another thread.
+            ForwardingWord::clear_forwarding_bits::<VM>(object);
+            object
+        } else if self.defrag.space_exhausted() {
+            // Mark the object in place. We clear the forwarding bits after marking it, so a thread
+            // that wins the race later sees the object marked.
+            let object = self.trace_object(queue, object);
+            ForwardingWord::clear_forwarding_bits::<VM>(object);
+            object
+        } else {
+            // The copy context marks the new object in its block.
+            let new_object = ForwardingWord::forward_object::<VM>(
+                object,
+                semantics,
+                worker.get_copy_context_mut(),
+            );
+            queue.enqueue(new_object);
+            new_object
+        }
+    }
+
     pub fn record_new_block(&self, block: Block) {
         block.init();
         self.chunk_map.set(block.chunk(), ChunkState::Allocated);
@@ -250,7 +414,61 @@ impl<VM: VMBinding> MarkSweepSpace<VM> {
         Block::NEXT_BLOCK_TABLE
     }
 
+    /// Decide whether the current GC is a defrag GC. This should be called when a GC is scheduled.
+    /// `max_evacuated_pages` bounds the live pages that a defrag GC evacuates, if the plan has a
+    /// limit.
+    pub fn decide_whether_to_defrag(
+        &self,
+        emergency_collection: bool,
+        collection_attempts: usize,
+        user_triggered: bool,
+        full_heap_system_gc: bool,
+        max_evacuated_pages: Option<usize>,
+    ) -> bool {
+        self.space_args.defrag
+            && self.defrag.decide_whether_to_defrag(
+                emergency_collection,
+                collection_attempts,
+                user_triggered,
+                full_heap_system_gc,
+                max_evacuated_pages,
+            )
+    }
+
+    /// Get the number of live pages chosen for evacuation in the current defrag GC.
+    /// This is only valid after `prepare()`.
+    pub fn defrag_evacuated_pages(&self) -> usize {
+        self.defrag.evacuated_pages()
+    }
+
+    /// Is the current GC a defrag GC?
+    pub fn in_defrag(&self) -> bool {
+        self.space_args.defrag && self.defrag.in_defrag()
+    }
+
+    /// Get the number of pages reserved for copying in a defrag GC.
+    pub fn defrag_headroom_pages(&self) -> usize {
+        if self.space_args.defrag {
+            self.defrag.defrag_headroom_pages(self)
+        } else {
+            0
+        }
+    }
+
     pub fn prepare(&mut self) {
+        if self.space_args.defrag {
+            // Choose the sources with the live bytes counted in the last GC, and count them again
+            // in this GC.
+            self.defrag.prepare(self);
+            for chunk in self.chunk_map.all_chunks() {
+                Block::LIVE_BYTES_TABLE.bzero_metadata(chunk.start(), Chunk::BYTES);
+                // The released sources may still have the forwarding bits of the objects copied
+                // in the last defrag GC.
+                if let MetadataSpec::OnSide(side) = *VM::VMObjectModel::LOCAL_FORWARDING_BITS_SPEC {
+                    side.bzero_metadata(chunk.start(), Chunk::BYTES);
+                }
+            }
+        }
         if let MetadataSpec::OnSide(side) = *VM::VMObjectModel::LOCAL_MARK_BIT_SPEC {
             for chunk in self.chunk_map.all_chunks() {
                 side.bzero_metadata(chunk.start(), Chunk::BYTES);
@@ -258,9 +476,48 @@ impl<VM: VMBinding> MarkSweepSpace<VM> {
         } else {
             unimplemented!("in header mark bit is not supported");
         }
+        if self.space_args.reset_log_bit_in_major_gc {
+            if let MetadataSpec::OnSide(side) = *VM::VMObjectModel::GLOBAL_LOG_BIT_SPEC {
+                // We zero all the log bits in major GC, and for every object we trace, we will mark the log bit again.
+                for chunk in self.chunk_map.all_chunks() {
+                    side.bzero_metadata(chunk.start(), Chunk::BYTES);
+                }
+            } else {
+                unimplemented!("We cannot bulk zero unlogged bit.")
+            }
+        }
+        self.blocks_acquired.store(0, Ordering::SeqCst);
+        self.tracing.store(true, Ordering::SeqCst);
+    }
+
+    /// Prepare for a nursery GC with sticky mark bits. The mark bits are kept, so the objects that
+    /// survived earlier GCs stay marked, and only the objects allocated since the last GC are traced.
+    pub fn prepare_nursery(&mut self) {
+        self.blocks_acquired.store(0, Ordering::SeqCst);
+        self.tracing.store(true, Ordering::SeqCst);
+    }
+
+    /// Is the space being traced? This is true from `prepare()` to `release()`, which may span
+    /// several pauses for a concurrent plan.
+    pub(crate) fn is_tracing(&self) -> bool {
+        self.tracing.load(Ordering::SeqCst)
+    }
+
+    /// Get the number of pages in the blocks that the allocators acquired since the last GC. A block that
+    /// is reused is counted in whole, though some of its cells may still be occupied.
+    pub(crate) fn get_pages_allocated(&self) -> usize {
+        self.blocks_acquired.load(Ordering::SeqCst)
+            << (Block::LOG_BYTES - LOG_BYTES_IN_PAGE as usize)
     }
 
     pub fn release(&mut self) {
+        if self.space_args.immortal {
+            // Nothing is swept in an immortal space. Only the cells freed explicitly are reused.
+            self.release_freed_cells();
+            self.tracing.store(false, Ordering::SeqCst);
+            return;
+        }
+
         // We sweep and release unmarked blocks here. For sweeping cells inside each block, we either
         // do that when we release mutators (eager sweeping), or do that at allocation time (lazy sweeping).
         use crate::scheduler::WorkBucketStage;
@@ -269,6 +526,58 @@ impl<VM: VMBinding> MarkSweepSpace<VM> {
 
         let mut abandoned = self.abandoned.lock().unwrap();
         abandoned.move_consumed_to_unswept();
+        self.tracing.store(false, Ordering::SeqCst);
+        if self.space_args.defrag {
+            self.defrag.release();
+        }
+    }
+
+    /// Free an object in an immortal space. The cell of the object can be reused after the next GC.
+    pub fn free_object(&self, object: ObjectReference) {
+        assert!(
+            self.space_args.immortal,
+            "Cannot free {} in {}, which is not immortal",
+            object,
+            self.get_name()
+        );
+        debug_assert!(self.in_space(object));
+        #[cfg(feature = "global_alloc_bit")]
+        crate::util::alloc_bit::unset_alloc_bit::<VM>(object);
+        // The object may not start at its cell, e.g. if the object is aligned.
+        let start = object.to_object_start::<VM>();
+        let block = Block::from_unaligned_address(start);
+        let cell_size = block.load_block_cell_size();
+        let cell = block.start() + (start - block.start()) / cell_size * cell_size;
+        self.freed_cells.lock().unwrap().push(cell);
+    }
+
+    /// Put the cells freed since the last GC back to the free lists of their blocks.
+    fn release_freed_cells(&mut self) {
+        let freed_cells = std::mem::take(self.freed_cells.get_mut().unwrap());
+        let mut full_blocks = vec![];
+        for cell in freed_cells {
+            let block = Block::from_unaligned_address(cell);
+            if !block.has_free_cells() {
+                // A full block may be in a list of consumed blocks, where allocators never look for
+                // free cells. Move it to the abandoned available blocks so any allocator can reuse it.
+                // Mutators are stopped, so we can take the block from the list of its allocator.
+                unsafe {
+                    let block_list = block.load_block_list();
+                    debug_assert!(!block_list.is_null());
+                    (*block_list).lock();
+                    (*block_list).remove(block);
+                    (*block_list).unlock();
+                }
+                full_blocks.push(block);
+            }
+            unsafe { cell.store::<Address>(block.load_free_list()) };
+            block.store_free_list(cell);
+        }
+        let abandoned = self.abandoned.get_mut().unwrap();
+        for block in full_blocks {
+            let bin = mi_bin_from_size(block.load_block_cell_size());
+            abandoned.available[bin].push(block);
+        }
     }
 
     /// Release a block.
@@ -292,20 +601,37 @@ impl<VM: VMBinding> MarkSweepSpace<VM> {
             let mut abandoned = self.abandoned.lock().unwrap();
             let bin = mi_bin::<VM>(size, align);
 
-            {
-                let abandoned_available = &mut abandoned.available;
-                if !abandoned_available[bin].is_empty() {
-                    let block = abandoned_available[bin].pop().unwrap();
-                    return BlockAcquireResult::AbandonedAvailable(block);
+            while let Some(block) = abandoned.available[bin].pop() {
+                if block.is_defrag_source() {
+                    // The objects are copied out of the block. The block is released in the sweep
+                    // if all of them are copied.
+                    abandoned.consumed[bin].push(block);
+                    continue;
                 }
+                self.on_block_acquired(block);
+                return BlockAcquireResult::AbandonedAvailable(block);
             }
 
-            {
+            // Sweeping a block while we are tracing would free the cells that are not marked yet.
+            if !self.tracing.load(Ordering::SeqCst) {
                 let abandoned_unswept = &mut abandoned.unswept;
                 if !abandoned_unswept[bin].is_empty() {
                     let block = abandoned_unswept[bin].pop().unwrap();
+                    self.on_block_acquired(block);
                     return BlockAcquireResult::AbandonedUnswept(block);
                 }
+            } else if self.in_defrag() {
+                // Copy the objects from the defrag sources into the fuller blocks. The free list of an
+                // unswept block only has the cells that were free before this GC, so we can use them
+                // without sweeping the block.
+                while let Some(block) = abandoned.unswept[bin].pop() {
+                    if block.is_defrag_source() || !block.has_free_cells() {
+                        abandoned.consumed[bin].push(block);
+                        continue;
+                    }
+                    self.on_block_acquired(block);
+                    return BlockAcquireResult::AbandonedAvailable(block);
+                }
             }
         }
 
@@ -313,7 +639,21 @@ impl<VM: VMBinding> MarkSweepSpace<VM> {
         if acquired.is_zero() {
             BlockAcquireResult::Exhausted
         } else {
-            BlockAcquireResult::Fresh(Block::from_unaligned_address(acquired))
+            if self.tracing.load(Ordering::SeqCst) && self.in_defrag() {
+                self.defrag.notify_new_clean_block();
+            }
+            let block = Block::from_unaligned_address(acquired);
+            self.on_block_acquired(block);
+            BlockAcquireResult::Fresh(block)
+        }
+    }
+
+    /// Count a block that is handed out to an allocator. Once a mutator allocates in a block, we do
+    /// not know its live bytes until the next GC, so it cannot be chosen as a defrag source.
+    fn on_block_acquired(&self, block: Block) {
+        self.blocks_acquired.fetch_add(1, Ordering::SeqCst);
+        if self.space_args.defrag && !self.tracing.load(Ordering::SeqCst) {
+            block.store_live_bytes(Block::BYTES);
         }
     }
 
@@ -339,6 +679,8 @@ impl<VM: VMBinding> GCWork<VM> for SweepChunk<VM> {
         debug_assert!(self.space.chunk_map.get(self.chunk) == ChunkState::Allocated);
         // number of allocated blocks.
         let mut allocated_blocks = 0;
+        // live bytes in the allocated blocks.
+        let mut live_bytes = 0;
         // Iterate over all allocated blocks in this chunk.
         for block in self
             .chunk
@@ -348,11 +690,66 @@ impl<VM: VMBinding> GCWork<VM> for SweepChunk<VM> {
             if !block.attempt_release(self.space) {
                 // Block is live. Increment the allocated block count.
                 allocated_blocks += 1;
+                live_bytes += block.live_bytes();
             }
         }
+        if self.space.space_args.defrag {
+            self.space
+                .defrag
+                .add_live_blocks(live_bytes, allocated_blocks);
+        }
         // Set this chunk as free if there is not live blocks.
         if allocated_blocks == 0 {
             self.space.chunk_map.set(self.chunk, ChunkState::Free)
         }
     }
 }
+
+use crate::policy::copy_context::PolicyCopyContext;
+use crate::util::alloc::Allocator;
+use crate::util::alloc::FreeListAllocator;
+use crate::util::VMWorkerThread;
+use crate::Plan;
+
+/// Mark sweep copy context. It has one free list allocator. This is used by plans that copy
+/// objects into a mark sweep space, e.g. a generational plan promoting its nursery objects.
+pub struct MarkSweepCopyContext<VM: VMBinding> {
+    allocator: FreeListAllocator<VM>,
+    space: &'static MarkSweepSpace<VM>,
+}
+
+impl<VM: VMBinding> PolicyCopyContext for MarkSweepCopyContext<VM> {
+    type VM = VM;
+
+    fn prepare(&mut self) {
+        self.allocator.prepare();
+    }
+    fn release(&mut self) {
+        self.allocator.release();
+    }
+    fn alloc_copy(
+        &mut self,
+        _original: ObjectReference,
+        bytes: usize,
+        align: usize,
+        offset: isize,
+    ) -> Address {
+        self.allocator.alloc(bytes, align, offset)
+    }
+    fn post_copy(&mut self, obj: ObjectReference, _bytes: usize) {
+        self.space.post_copy(obj)
+    }
+}
+
+impl<VM: VMBinding> MarkSweepCopyContext<VM> {
+    pub fn new(
+        tls: VMWorkerThread,
+        plan: &'static dyn Plan<VM = VM>,
+        space: &'static MarkSweepSpace<VM>,
+    ) -> Self {
+        MarkSweepCopyContext {
+            allocator: FreeListAllocator::new(tls.0, space, plan),
+            space,
+        }
+    }
+}
@@ -1,7 +1,9 @@
 mod block;
 mod block_list;
+mod defrag;
 mod global;
 
 pub use block::*;
 pub use block_list::*;
+pub use defrag::*;
 pub use global::*;
@@ -15,11 +15,14 @@ pub mod space;
 
 /// Copy context defines the thread local copy allocator for copying policies.
 pub mod copy_context;
+/// The state of defragmentation shared by the evacuating policies
+pub mod defrag;
 /// Policy specific GC work
 pub mod gc_work;
 pub mod sft;
 pub mod sft_map;
 
+pub mod codespace;
 pub mod copyspace;
 pub mod immix;
 pub mod immortalspace;
@@ -27,3 +30,5 @@ pub mod largeobjectspace;
 pub mod lockfreeimmortalspace;
 pub mod markcompactspace;
 pub mod marksweepspace;
+pub mod region;
+pub mod vmspace;
@@ -0,0 +1,123 @@
+use crate::util::constants::*;
+use crate::util::heap::chunk_map::Chunk;
+use crate::util::linear_scan::Region;
+use crate::util::metadata::side_metadata::SideMetadataSpec;
+use crate::util::metadata::MetadataSpec;
+use crate::util::Address;
+use crate::vm::*;
+use std::sync::atomic::Ordering;
+
+/// The state of a heap region.
+#[repr(u8)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RegionState {
+    /// The region is not allocated.
+    Unallocated = 0,
+    /// The region is allocated by mutators since the last pause. Young regions are always in the
+    /// collection set of the next evacuating pause.
+    Young = 1,
+    /// The region survived a pause, or it is allocated by GC workers to copy objects into.
+    Old = 2,
+    /// The region is in the collection set of the current pause. Its live objects are evacuated,
+    /// and the region is released at the end of the pause.
+    Collecting = 3,
+}
+
+impl From<u8> for RegionState {
+    fn from(state: u8) -> Self {
+        match state {
+            0 => RegionState::Unallocated,
+            1 => RegionState::Young,
+            2 => RegionState::Old,
+            3 => RegionState::Collecting,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Data structure to reference a region in the region space.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Eq, Hash)]
+pub struct HeapRegion(Address);
+
+impl Region for HeapRegion {
+    const LOG_BYTES: usize = 18;
+
+    fn from_aligned_address(address: Address) -> Self {
+        debug_assert!(address.is_aligned_to(Self::BYTES));
+        Self(address)
+    }
+
+    fn start(&self) -> Address {
+        self.0
+    }
+}
+
+impl HeapRegion {
+    /// Log pages in a region
+    pub const LOG_PAGES: usize = Self::LOG_BYTES - LOG_BYTES_IN_PAGE as usize;
+    /// Pages in a region
+    pub const PAGES: usize = 1 << Self::LOG_PAGES;
+
+    /// Region state table (side)
+    pub const STATE_TABLE: SideMetadataSpec =
+        crate::util::metadata::side_metadata::spec_defs::REGION_STATE;
+
+    /// Region live bytes table (side)
+    pub const LIVE_BYTES_TABLE: SideMetadataSpec =
+        crate::util::metadata::side_metadata::spec_defs::REGION_LIVE_BYTES;
+
+    /// Get the chunk containing the region.
+    pub fn chunk(&self) -> Chunk {
+        Chunk::from_unaligned_address(self.0)
+    }
+
+    /// Get the region state.
+    pub fn get_state(&self) -> RegionState {
+        Self::STATE_TABLE
+            .load_atomic::<u8>(self.start(), Ordering::SeqCst)
+            .into()
+    }
+
+    /// Set the region state.
+    pub fn set_state(&self, state: RegionState) {
+        Self::STATE_TABLE.store_atomic::<u8>(self.start(), state as u8, Ordering::SeqCst);
+    }
+
+    /// Get the live bytes in the region, as counted by the current or the last full heap mark.
+    pub fn live_bytes(&self) -> usize {
+        Self::LIVE_BYTES_TABLE.load_atomic::<u32>(self.start(), Ordering::SeqCst) as usize
+    }
+
+    /// Count the bytes of a marked object in the region.
+    pub fn add_live_bytes(&self, bytes: usize) {
+        Self::LIVE_BYTES_TABLE.fetch_add_atomic::<u32>(
+            self.start(),
+            bytes as u32,
+            Ordering::SeqCst,
+        );
+    }
+
+    /// Clear the live bytes before a full heap mark.
+    pub fn reset_live_bytes(&self) {
+        Self::LIVE_BYTES_TABLE.store_atomic::<u32>(self.start(), 0, Ordering::SeqCst);
+    }
+
+    /// Initialize a newly allocated region.
+    pub fn init(&self, state: RegionState) {
+        debug_assert_ne!(state, RegionState::Unallocated);
+        self.reset_live_bytes();
+        self.set_state(state);
+    }
+
+    /// Deinitialize a region before it is released. The objects in the region are all dead (or
+    /// evacuated), so their metadata is cleared.
+    pub fn deinit<VM: VMBinding>(&self) {
+        #[cfg(feature = "global_alloc_bit")]
+        crate::util::alloc_bit::bzero_alloc_bit(self.start(), Self::BYTES);
+        if let MetadataSpec::OnSide(side) = *VM::VMObjectModel::LOCAL_FORWARDING_BITS_SPEC {
+            side.bzero_metadata(self.start(), Self::BYTES);
+        }
+        self.set_state(RegionState::Unallocated);
+    }
+}
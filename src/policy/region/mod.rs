@@ -0,0 +1,17 @@
+//! A region space divides the heap into fixed-size regions. Each region keeps a remembered set of
+//! the objects outside the region that may point into it, so any set of regions can be evacuated
+//! without tracing the rest of the heap.
+
+pub mod heap_region;
+pub mod regionspace;
+pub mod remset;
+
+pub use heap_region::*;
+pub use regionspace::*;
+
+/// Regions that have more live bytes than this percentage (as counted by the last full heap mark)
+/// are not worth evacuating, and are not chosen as candidates for the collection set.
+pub const LIVE_THRESHOLD_PERCENT: usize = 85;
+
+/// The candidate regions chosen by a full heap mark are evacuated in roughly this many pauses.
+pub const MIXED_PAUSES_PER_MARK: usize = 8;
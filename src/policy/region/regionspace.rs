@@ -0,0 +1,570 @@
+use super::remset::{RegionRemSet, RemSet};
+use super::{HeapRegion, RegionState, LIVE_THRESHOLD_PERCENT, MIXED_PAUSES_PER_MARK};
+use crate::plan::VectorObjectQueue;
+use crate::policy::gc_work::TraceKind;
+use crate::policy::sft::GCWorkerMutRef;
+use crate::policy::sft::SFT;
+use crate::policy::space::{CommonSpace, Space};
+use crate::util::constants::BYTES_IN_PAGE;
+use crate::util::copy::*;
+use crate::util::heap::chunk_map::*;
+use crate::util::heap::BlockPageResource;
+use crate::util::heap::PageResource;
+use crate::util::linear_scan::Region;
+use crate::util::metadata::side_metadata::SideMetadataSpec;
+use crate::util::metadata::{self, MetadataSpec};
+use crate::util::object_forwarding as ForwardingWord;
+use crate::util::{Address, ObjectReference};
+use crate::vm::*;
+use crate::{
+    plan::ObjectQueue,
+    scheduler::{GCWork, GCWorkScheduler, GCWorker, WorkBucketStage},
+    util::opaque_pointer::{VMThread, VMWorkerThread},
+    MMTK,
+};
+use atomic::Ordering;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::{Arc, Mutex};
+
+/// Mark objects in place, and count the live bytes in each region.
+pub(crate) const TRACE_KIND_MARK: TraceKind = 0;
+/// Evacuate the objects in the collection set. Other objects are not traced.
+pub(crate) const TRACE_KIND_EVACUATE: TraceKind = 1;
+
+/// A space that divides the heap into fixed-size regions, and evacuates a set of regions
+/// (the collection set) in each pause.
+///
+/// The space does not find the references into the collection set by itself. The plan has to
+/// maintain the remembered sets in [`RegionSpace::remset`], so it can scan the objects outside
+/// the collection set that may point into it.
+pub struct RegionSpace<VM: VMBinding> {
+    common: CommonSpace<VM>,
+    pr: BlockPageResource<VM, HeapRegion>,
+    /// Allocation status for all chunks in the region space
+    pub chunk_map: ChunkMap,
+    /// The remembered sets of the regions.
+    pub remset: RemSet<VM>,
+    /// Is the current pause marking the whole heap?
+    marking: AtomicBool,
+    /// The number of young regions allocated since the last pause.
+    young_regions: AtomicUsize,
+    /// Old regions that are worth evacuating, chosen by the last full heap mark. They are sorted by
+    /// their live bytes in descending order, so the cheapest region to evacuate is at the end.
+    candidates: Mutex<Vec<HeapRegion>>,
+    /// How many candidates are added to the collection set in each evacuating pause.
+    candidates_per_pause: AtomicUsize,
+    /// The collection set of the current pause.
+    collection_set: Mutex<HashSet<HeapRegion>>,
+    /// Work packet scheduler
+    scheduler: Arc<GCWorkScheduler<VM>>,
+}
+
+impl<VM: VMBinding> SFT for RegionSpace<VM> {
+    fn name(&self) -> &str {
+        self.get_name()
+    }
+
+    fn get_forwarded_object(&self, object: ObjectReference) -> Option<ObjectReference> {
+        if HeapRegion::containing::<VM>(object).get_state() != RegionState::Collecting {
+            return None;
+        }
+        if ForwardingWord::is_forwarded::<VM>(object) {
+            Some(ForwardingWord::read_forwarding_pointer::<VM>(object))
+        } else {
+            None
+        }
+    }
+
+    fn is_live(&self, object: ObjectReference) -> bool {
+        if HeapRegion::containing::<VM>(object).get_state() == RegionState::Collecting {
+            ForwardingWord::is_forwarded::<VM>(object)
+        } else if self.is_marking() {
+            self.is_marked(object)
+        } else {
+            // An evacuating pause only reclaims the collection set.
+            true
+        }
+    }
+    fn is_movable(&self) -> bool {
+        true
+    }
+    #[cfg(feature = "sanity")]
+    fn is_sane(&self) -> bool {
+        true
+    }
+    fn initialize_object_metadata(&self, _object: ObjectReference, _alloc: bool) {
+        #[cfg(feature = "global_alloc_bit")]
+        crate::util::alloc_bit::set_alloc_bit::<VM>(_object);
+    }
+    fn initialize_objects_metadata(
+        &self,
+        _objects: &[ObjectReference],
+        _bytes: usize,
+        _alloc: bool,
+    ) {
+        #[cfg(feature = "global_alloc_bit")]
+        crate::util::alloc_bit::set_alloc_bits::<VM>(_objects);
+    }
+    #[cfg(feature = "is_mmtk_object")]
+    fn is_mmtk_object(&self, addr: Address) -> bool {
+        crate::util::alloc_bit::is_alloced_object::<VM>(addr).is_some()
+    }
+    fn sft_trace_object(
+        &self,
+        _queue: &mut VectorObjectQueue,
+        _object: ObjectReference,
+        _worker: GCWorkerMutRef,
+    ) -> ObjectReference {
+        panic!("We do not use SFT to trace objects for RegionSpace. sft_trace_object() cannot be used.")
+    }
+}
+
+impl<VM: VMBinding> Space<VM> for RegionSpace<VM> {
+    fn as_space(&self) -> &dyn Space<VM> {
+        self
+    }
+    fn as_sft(&self) -> &(dyn SFT + Sync + 'static) {
+        self
+    }
+    fn get_page_resource(&self) -> &dyn PageResource<VM> {
+        &self.pr
+    }
+    fn common(&self) -> &CommonSpace<VM> {
+        &self.common
+    }
+    fn initialize_sft(&self) {
+        self.common().initialize_sft(self.as_sft())
+    }
+    fn release_multiple_pages(&mut self, _start: Address) {
+        panic!("regionspace only releases pages enmasse")
+    }
+    fn set_copy_for_sft_trace(&mut self, _semantics: Option<CopySemantics>) {
+        panic!("We do not use SFT to trace objects for RegionSpace. set_copy_context() cannot be used.")
+    }
+}
+
+impl<VM: VMBinding> crate::policy::gc_work::PolicyTraceObject<VM> for RegionSpace<VM> {
+    fn trace_object<Q: ObjectQueue, const KIND: TraceKind>(
+        &self,
+        queue: &mut Q,
+        object: ObjectReference,
+        copy: Option<CopySemantics>,
+        worker: &mut GCWorker<VM>,
+    ) -> ObjectReference {
+        if KIND == TRACE_KIND_MARK {
+            self.trace_object_without_moving(queue, object)
+        } else if KIND == TRACE_KIND_EVACUATE {
+            if HeapRegion::containing::<VM>(object).get_state() == RegionState::Collecting {
+                self.trace_object_with_copy(queue, object, copy.unwrap(), worker)
+            } else {
+                object
+            }
+        } else {
+            unreachable!()
+        }
+    }
+
+    fn may_move_objects<const KIND: TraceKind>() -> bool {
+        if KIND == TRACE_KIND_MARK {
+            false
+        } else if KIND == TRACE_KIND_EVACUATE {
+            true
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+impl<VM: VMBinding> RegionSpace<VM> {
+    const MARKED_STATE: u8 = 1;
+
+    /// Get side metadata specs
+    fn side_metadata_specs() -> Vec<SideMetadataSpec> {
+        metadata::extract_side_metadata(&[
+            MetadataSpec::OnSide(HeapRegion::STATE_TABLE),
+            MetadataSpec::OnSide(HeapRegion::LIVE_BYTES_TABLE),
+            MetadataSpec::OnSide(ChunkMap::ALLOC_TABLE),
+            *VM::VMObjectModel::LOCAL_MARK_BIT_SPEC,
+            *VM::VMObjectModel::LOCAL_FORWARDING_BITS_SPEC,
+            *VM::VMObjectModel::LOCAL_FORWARDING_POINTER_SPEC,
+        ])
+    }
+
+    pub fn new(args: crate::policy::space::PlanCreateSpaceArgs<VM>) -> Self {
+        let vm_map = args.vm_map;
+        let scheduler = args.scheduler.clone();
+        let common =
+            CommonSpace::new(args.into_policy_args(true, false, Self::side_metadata_specs()));
+        RegionSpace {
+            pr: if common.vmrequest.is_discontiguous() {
+                BlockPageResource::new_discontiguous(
+                    HeapRegion::LOG_PAGES,
+                    vm_map,
+                    scheduler.num_workers(),
+                )
+            } else {
+                BlockPageResource::new_contiguous(
+                    HeapRegion::LOG_PAGES,
+                    common.start,
+                    common.extent,
+                    vm_map,
+                    scheduler.num_workers(),
+                )
+            },
+            common,
+            chunk_map: ChunkMap::new(),
+            remset: RemSet::default(),
+            marking: AtomicBool::new(false),
+            young_regions: AtomicUsize::new(0),
+            candidates: Mutex::new(vec![]),
+            candidates_per_pause: AtomicUsize::new(0),
+            collection_set: Mutex::new(HashSet::new()),
+            scheduler,
+        }
+    }
+
+    /// Is the current pause marking the whole heap?
+    pub fn is_marking(&self) -> bool {
+        self.marking.load(Ordering::SeqCst)
+    }
+
+    /// Iterate over all the allocated regions.
+    fn allocated_regions(&self) -> impl Iterator<Item = HeapRegion> + '_ {
+        self.chunk_map
+            .all_chunks()
+            .filter(|chunk| self.chunk_map.get(*chunk) == ChunkState::Allocated)
+            .flat_map(|chunk| chunk.iter_region::<HeapRegion>())
+            .filter(|region| region.get_state() != RegionState::Unallocated)
+    }
+
+    /// Get the number of pages in the young regions.
+    pub fn young_pages(&self) -> usize {
+        self.young_regions.load(Ordering::SeqCst) * HeapRegion::PAGES
+    }
+
+    /// Get the number of pages to reserve for the next evacuating pause. In the worst case, all the
+    /// young objects survive, and the live bytes of the next candidates have not changed since the
+    /// last full heap mark.
+    pub fn evacuation_reserve_pages(&self) -> usize {
+        let candidates = self.candidates.lock().unwrap();
+        let n = self.candidates_per_pause.load(Ordering::SeqCst);
+        let candidate_bytes: usize = candidates
+            .iter()
+            .rev()
+            .take(n)
+            .map(|region| region.live_bytes())
+            .sum();
+        self.young_pages() + (candidate_bytes + BYTES_IN_PAGE - 1) / BYTES_IN_PAGE
+    }
+
+    /// Are there any candidate regions left from the last full heap mark?
+    pub fn has_candidates(&self) -> bool {
+        !self.candidates.lock().unwrap().is_empty()
+    }
+
+    /// Prepare for a pause that marks the whole heap. The remembered sets are rebuilt by the trace.
+    pub fn prepare_mark(&self) {
+        if !VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.is_on_side() {
+            // For header metadata, we use cyclic mark bits.
+            unimplemented!("cyclic mark bits is not supported at the moment");
+        }
+        self.marking.store(true, Ordering::SeqCst);
+        self.remset.clear();
+        // # Safety: RegionSpace reference is always valid within this collection cycle.
+        let space = unsafe { &*(self as *const Self) };
+        let work_packets = self
+            .chunk_map
+            .generate_tasks(|chunk| Box::new(PrepareChunkForMark { space, chunk }));
+        self.scheduler.work_buckets[WorkBucketStage::Prepare].bulk_add(work_packets);
+    }
+
+    /// Release the regions without live objects after a full heap mark, and choose the candidate
+    /// regions to evacuate in the following pauses.
+    pub fn release_mark(&self) {
+        let mut freed = HashSet::new();
+        let mut candidates = vec![];
+        for region in self.allocated_regions() {
+            let live_bytes = region.live_bytes();
+            if live_bytes == 0 {
+                self.release_region(region);
+                freed.insert(region);
+            } else {
+                region.set_state(RegionState::Old);
+                if live_bytes * 100 < HeapRegion::BYTES * LIVE_THRESHOLD_PERCENT {
+                    candidates.push(region);
+                }
+            }
+        }
+        candidates.sort_by_key(|region| std::cmp::Reverse(region.live_bytes()));
+        self.candidates_per_pause.store(
+            (candidates.len() + MIXED_PAUSES_PER_MARK - 1) / MIXED_PAUSES_PER_MARK,
+            Ordering::SeqCst,
+        );
+        *self.candidates.lock().unwrap() = candidates;
+        self.remset.release_regions(&freed);
+        self.young_regions.store(0, Ordering::SeqCst);
+        self.marking.store(false, Ordering::SeqCst);
+        self.flush_page_resource();
+    }
+
+    /// Choose the collection set for an evacuating pause: all the young regions, and up to `max_candidates`
+    /// candidate regions from the last full heap mark. Return the number of candidates chosen.
+    pub fn select_collection_set(&self, max_candidates: usize) -> usize {
+        let mut collection_set = self.collection_set.lock().unwrap();
+        debug_assert!(collection_set.is_empty());
+        for region in self.allocated_regions() {
+            if region.get_state() == RegionState::Young {
+                region.set_state(RegionState::Collecting);
+                collection_set.insert(region);
+            }
+        }
+        let mut candidates = self.candidates.lock().unwrap();
+        let mut chosen = 0;
+        while chosen < max_candidates {
+            match candidates.pop() {
+                Some(region) => {
+                    // The region has not been released since the last full heap mark.
+                    debug_assert_eq!(region.get_state(), RegionState::Old);
+                    region.set_state(RegionState::Collecting);
+                    collection_set.insert(region);
+                    chosen += 1;
+                }
+                None => break,
+            }
+        }
+        chosen
+    }
+
+    /// Get the number of candidates to evacuate in each pause, as suggested by the last full heap mark.
+    pub fn candidates_per_pause(&self) -> usize {
+        self.candidates_per_pause.load(Ordering::SeqCst)
+    }
+
+    /// Take the remembered sets of the collection set. The entries have to be scanned in the
+    /// current pause to find the references into the collection set.
+    pub fn take_collection_set_remsets(&self) -> RegionRemSet<VM> {
+        self.remset.take(&self.collection_set.lock().unwrap())
+    }
+
+    /// Release the collection set after an evacuating pause. All the live objects in it have been evacuated.
+    pub fn release_collection_set(&self) {
+        let collection_set = std::mem::take(&mut *self.collection_set.lock().unwrap());
+        for region in collection_set.iter() {
+            self.release_region(*region);
+        }
+        self.remset.release_regions(&collection_set);
+        self.young_regions.store(0, Ordering::SeqCst);
+        self.flush_page_resource();
+    }
+
+    /// Flush the thread-local queues in BlockPageResource
+    fn flush_page_resource(&self) {
+        #[cfg(target_pointer_width = "64")]
+        self.pr.flush_all()
+    }
+
+    /// Release a region.
+    fn release_region(&self, region: HeapRegion) {
+        region.deinit::<VM>();
+        self.pr.release_block(region);
+    }
+
+    /// Allocate a clean region. Mutators allocate young regions, and GC workers allocate old regions
+    /// to copy objects into.
+    pub fn get_clean_region(&self, tls: VMThread, copy: bool) -> Option<HeapRegion> {
+        let region_address = self.acquire(tls, HeapRegion::PAGES);
+        if region_address.is_zero() {
+            return None;
+        }
+        let region = HeapRegion::from_aligned_address(region_address);
+        if copy {
+            region.init(RegionState::Old);
+        } else {
+            region.init(RegionState::Young);
+            self.young_regions.fetch_add(1, Ordering::SeqCst);
+        }
+        self.chunk_map.set(region.chunk(), ChunkState::Allocated);
+        Some(region)
+    }
+
+    /// If a reference at `src` (the address of an object or a slice) to `target` crosses regions,
+    /// and `target` is in this space, return the region of `target`. Such a reference has to be
+    /// recorded in the remembered set of that region.
+    pub fn crossing_region(&self, src: Address, target: ObjectReference) -> Option<HeapRegion> {
+        if target.is_null() || !self.in_space(target) {
+            return None;
+        }
+        let region = HeapRegion::containing::<VM>(target);
+        if region.includes_address(src) {
+            None
+        } else {
+            Some(region)
+        }
+    }
+
+    /// Is the address in a young region? Young regions are always in the next collection set,
+    /// so the references from them do not need to be remembered.
+    pub fn is_in_young_region(&self, addr: Address) -> bool {
+        self.address_in_space(addr)
+            && HeapRegion::from_unaligned_address(addr).get_state() == RegionState::Young
+    }
+
+    /// Trace and mark objects without evacuation.
+    pub fn trace_object_without_moving(
+        &self,
+        queue: &mut impl ObjectQueue,
+        object: ObjectReference,
+    ) -> ObjectReference {
+        #[cfg(feature = "global_alloc_bit")]
+        debug_assert!(
+            crate::util::alloc_bit::is_alloced::<VM>(object),
+            "{:x}: alloc bit not set",
+            object
+        );
+        if self.attempt_mark(object) {
+            HeapRegion::containing::<VM>(object)
+                .add_live_bytes(VM::VMObjectModel::get_current_size(object));
+            queue.enqueue(object);
+        }
+        object
+    }
+
+    /// Trace and evacuate an object in the collection set.
+    pub fn trace_object_with_copy(
+        &self,
+        queue: &mut impl ObjectQueue,
+        object: ObjectReference,
+        semantics: CopySemantics,
+        worker: &mut GCWorker<VM>,
+    ) -> ObjectReference {
+        #[cfg(feature = "global_alloc_bit")]
+        debug_assert!(
+            crate::util::alloc_bit::is_alloced::<VM>(object),
+            "{:x}: alloc bit not set",
+            object
+        );
+        let forwarding_status = ForwardingWord::attempt_to_forward::<VM>(object);
+        if ForwardingWord::state_is_forwarded_or_being_forwarded(forwarding_status) {
+            ForwardingWord::spin_and_get_forwarded_object::<VM>(object, forwarding_status)
+        } else {
+            let new_object = ForwardingWord::forward_object::<VM>(
+                object,
+                semantics,
+                worker.get_copy_context_mut(),
+            );
+            debug_assert_eq!(
+                HeapRegion::containing::<VM>(new_object).get_state(),
+                RegionState::Old
+            );
+            queue.enqueue(new_object);
+            new_object
+        }
+    }
+
+    /// Post copy routine for the objects copied into this space.
+    pub fn post_copy(&self, _object: ObjectReference, _bytes: usize) {
+        #[cfg(feature = "global_alloc_bit")]
+        crate::util::alloc_bit::set_alloc_bit::<VM>(_object);
+    }
+
+    /// Atomically mark an object.
+    fn attempt_mark(&self, object: ObjectReference) -> bool {
+        loop {
+            let old_value = VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.load_atomic::<VM, u8>(
+                object,
+                None,
+                Ordering::SeqCst,
+            );
+            if old_value == Self::MARKED_STATE {
+                return false;
+            }
+
+            if VM::VMObjectModel::LOCAL_MARK_BIT_SPEC
+                .compare_exchange_metadata::<VM, u8>(
+                    object,
+                    old_value,
+                    Self::MARKED_STATE,
+                    None,
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                )
+                .is_ok()
+            {
+                break;
+            }
+        }
+        true
+    }
+
+    /// Check if an object is marked.
+    fn is_marked(&self, object: ObjectReference) -> bool {
+        VM::VMObjectModel::LOCAL_MARK_BIT_SPEC.load_atomic::<VM, u8>(object, None, Ordering::SeqCst)
+            == Self::MARKED_STATE
+    }
+}
+
+/// A work packet to clear the object marks and the live bytes of the regions in a chunk before a
+/// full heap mark.
+pub struct PrepareChunkForMark<VM: VMBinding> {
+    pub space: &'static RegionSpace<VM>,
+    pub chunk: Chunk,
+}
+
+impl<VM: VMBinding> GCWork<VM> for PrepareChunkForMark<VM> {
+    fn do_work(&mut self, _worker: &mut GCWorker<VM>, _mmtk: &'static MMTK<VM>) {
+        if let MetadataSpec::OnSide(side) = *VM::VMObjectModel::LOCAL_MARK_BIT_SPEC {
+            side.bzero_metadata(self.chunk.start(), Chunk::BYTES);
+        }
+        for region in self.chunk.iter_region::<HeapRegion>() {
+            if region.get_state() != RegionState::Unallocated {
+                region.reset_live_bytes();
+            }
+        }
+    }
+}
+
+use crate::plan::Plan;
+use crate::policy::copy_context::PolicyCopyContext;
+use crate::util::alloc::Allocator;
+use crate::util::alloc::RegionAllocator;
+
+/// Copy allocator for RegionSpace
+pub struct RegionCopyContext<VM: VMBinding> {
+    allocator: RegionAllocator<VM>,
+}
+
+impl<VM: VMBinding> PolicyCopyContext for RegionCopyContext<VM> {
+    type VM = VM;
+
+    fn prepare(&mut self) {
+        self.allocator.reset();
+    }
+    fn release(&mut self) {
+        self.allocator.reset();
+    }
+    fn alloc_copy(
+        &mut self,
+        _original: ObjectReference,
+        bytes: usize,
+        align: usize,
+        offset: isize,
+    ) -> Address {
+        self.allocator.alloc(bytes, align, offset)
+    }
+    fn post_copy(&mut self, obj: ObjectReference, bytes: usize) {
+        self.allocator.region_space().post_copy(obj, bytes)
+    }
+}
+
+impl<VM: VMBinding> RegionCopyContext<VM> {
+    pub fn new(
+        tls: VMWorkerThread,
+        plan: &'static dyn Plan<VM = VM>,
+        space: &'static RegionSpace<VM>,
+    ) -> Self {
+        RegionCopyContext {
+            allocator: RegionAllocator::new(tls.0, space, plan, true),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use super::HeapRegion;
+use crate::util::linear_scan::Region;
+use crate::util::ObjectReference;
+use crate::vm::edge_shape::MemorySlice;
+use crate::vm::VMBinding;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// The remembered set of a region. It records the objects (and the array slices) outside the
+/// region that may contain references into the region.
+pub struct RegionRemSet<VM: VMBinding> {
+    pub objects: HashSet<ObjectReference>,
+    pub slices: HashSet<VM::VMMemorySlice>,
+}
+
+impl<VM: VMBinding> Default for RegionRemSet<VM> {
+    fn default() -> Self {
+        Self {
+            objects: HashSet::new(),
+            slices: HashSet::new(),
+        }
+    }
+}
+
+impl<VM: VMBinding> RegionRemSet<VM> {
+    /// Remove the entries that are located in the given regions.
+    fn remove_sources_in(&mut self, regions: &HashSet<HeapRegion>) {
+        self.objects
+            .retain(|object| !regions.contains(&HeapRegion::containing::<VM>(*object)));
+        self.slices
+            .retain(|slice| !regions.contains(&HeapRegion::from_unaligned_address(slice.start())));
+    }
+}
+
+/// The remembered sets of all the regions in a region space.
+///
+/// Entries are added by the barrier (for references created by mutators), and by GC workers (for
+/// references in the objects they scan). An entry may become stale if the field is overwritten
+/// later, or if the source object dies. Stale entries only cost some extra scanning, as long as the
+/// memory of the source is not reused. So the entries located in a region are removed when the
+/// region is released.
+pub struct RemSet<VM: VMBinding> {
+    sets: Mutex<HashMap<HeapRegion, RegionRemSet<VM>>>,
+}
+
+impl<VM: VMBinding> Default for RemSet<VM> {
+    fn default() -> Self {
+        Self {
+            sets: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<VM: VMBinding> RemSet<VM> {
+    /// Record that each object may point into the region paired with it.
+    pub fn add_objects(&self, entries: impl IntoIterator<Item = (HeapRegion, ObjectReference)>) {
+        let mut sets = self.sets.lock().unwrap();
+        for (region, object) in entries {
+            sets.entry(region).or_default().objects.insert(object);
+        }
+    }
+
+    /// Record that each array slice may point into the region paired with it.
+    pub fn add_slices(&self, entries: impl IntoIterator<Item = (HeapRegion, VM::VMMemorySlice)>) {
+        let mut sets = self.sets.lock().unwrap();
+        for (region, slice) in entries {
+            sets.entry(region).or_default().slices.insert(slice);
+        }
+    }
+
+    /// Take the remembered sets of the given regions. The entries that are located in the given
+    /// regions themselves are skipped: they are only scanned if they are reached by the trace.
+    pub fn take(&self, regions: &HashSet<HeapRegion>) -> RegionRemSet<VM> {
+        let mut sets = self.sets.lock().unwrap();
+        let mut result = RegionRemSet::default();
+        for region in regions {
+            if let Some(set) = sets.remove(region) {
+                result.objects.extend(set.objects);
+                result.slices.extend(set.slices);
+            }
+        }
+        result.remove_sources_in(regions);
+        result
+    }
+
+    /// Forget the released regions. Their remembered sets are dropped, and so are the entries that
+    /// are located in them, as the memory may be reused for other objects.
+    pub fn release_regions(&self, regions: &HashSet<HeapRegion>) {
+        if regions.is_empty() {
+            return;
+        }
+        let mut sets = self.sets.lock().unwrap();
+        sets.retain(|region, _| !regions.contains(region));
+        for set in sets.values_mut() {
+            set.remove_sources_in(regions);
+        }
+    }
+
+    /// Drop all the remembered sets. A full heap mark rebuilds them.
+    pub fn clear(&self) {
+        self.sets.lock().unwrap().clear();
+    }
+}
@@ -81,6 +81,15 @@ pub trait SFT {
     /// Initialize object metadata (in the header, or in the side metadata).
     fn initialize_object_metadata(&self, object: ObjectReference, alloc: bool);
 
+    /// Initialize object metadata for a batch of objects of `bytes` bytes in this space, in the order
+    /// they were allocated (e.g. by `alloc_many()`). This saves the dynamic
+    /// dispatch for each object, and a space may override this to initialize the metadata in bulk.
+    fn initialize_objects_metadata(&self, objects: &[ObjectReference], _bytes: usize, alloc: bool) {
+        for object in objects {
+            self.initialize_object_metadata(*object, alloc);
+        }
+    }
+
     /// Trace objects through SFT. This along with [`SFTProcessEdges`](mmtk/scheduler/gc_work/SFTProcessEdges)
     /// provides an easy way for most plans to trace objects without the need to implement any plan-specific
     /// code. However, tracing objects for some policies are more complicated, and they do not provide an
@@ -96,6 +105,30 @@ pub trait SFT {
     ) -> ObjectReference;
 }
 
+/// Call `f` for each run of contiguous objects in `objects`, which are objects of `bytes` bytes in
+/// the order they were allocated (e.g. by `alloc_many()`). An object is in the same run as the
+/// previous object if it follows the previous object with at most the padding for alignment between
+/// them, so the memory between the first and the last object of a run is not allocated to any other
+/// object. A space can initialize the metadata for such a range in bulk.
+pub(crate) fn for_each_contiguous_run<VM: VMBinding>(
+    objects: &[ObjectReference],
+    bytes: usize,
+    mut f: impl FnMut(&[ObjectReference]),
+) {
+    let mut run_start = 0;
+    for i in 1..=objects.len() {
+        let contiguous = i < objects.len() && {
+            let prev = objects[i - 1].to_address::<VM>();
+            let next = objects[i].to_address::<VM>();
+            next > prev && next - prev <= bytes + VM::MAX_ALIGNMENT
+        };
+        if !contiguous {
+            f(&objects[run_start..i]);
+            run_start = i;
+        }
+    }
+}
+
 // Create erased VM refs for these types that will be used in `sft_trace_object()`.
 // In this way, we can store the refs with <VM> in SFT (which cannot have parameters with generic type parameters)
 
@@ -62,8 +62,11 @@ pub trait SFTMap {
 
 pub(crate) fn create_sft_map() -> Box<dyn SFTMap> {
     cfg_if::cfg_if! {
-        if #[cfg(all(feature = "malloc_mark_sweep", target_pointer_width = "64"))] {
+        if #[cfg(all(any(feature = "malloc_mark_sweep", feature = "vm_space"), target_pointer_width = "64"))] {
             // 64-bit malloc mark sweep needs a chunk-based SFT map, but the sparse map is not suitable for 64bits.
+            // The VM space needs it for the same reason: its regions are wherever the VM has mapped them outside the
+            // MMTk heap range, and the space map only has entries for the heap range. This costs a side metadata load
+            // for each SFT lookup, so we only use it when one of these features is enabled.
             return Box::new(dense_chunk_map::SFTDenseChunkMap::<'static>::new());
         } else if #[cfg(target_pointer_width = "64")] {
             return Box::new(space_map::SFTSpaceMap::<'static>::new());
@@ -239,7 +242,8 @@ mod dense_chunk_map {
     /// it provides some flexibility so we can set SFT at chunk basis for 64bits for decent performance.
     /// For example, when we use library malloc for mark sweep, we have no control of where the
     /// library malloc may allocate into, so we cannot use the space map. And using a sparse chunk map
-    /// will be costly in terms of memory. In this case, the dense chunk map is a good solution.
+    /// will be costly in terms of memory. In this case, the dense chunk map is a good solution. The
+    /// same applies to the regions of the VM space, which the VM maps outside the MMTk heap range.
     pub struct SFTDenseChunkMap<'a> {
         /// The dense table, one entry per space. We use side metadata to store the space index for each chunk.
         /// 0 is EMPTY_SPACE_SFT.
@@ -1,3 +1,4 @@
+use crate::plan::BarrierSelector;
 use crate::plan::PlanConstraints;
 use crate::scheduler::GCWorkScheduler;
 use crate::util::conversions::*;
@@ -22,6 +23,7 @@ use crate::mmtk::SFT_MAP;
 use crate::policy::sft::EMPTY_SFT_NAME;
 use crate::policy::sft::SFT;
 use crate::util::copy::*;
+use crate::util::heap::chunk_map::ChunkMap;
 use crate::util::heap::gc_trigger::GCTrigger;
 use crate::util::heap::layout::heap_layout::Mmapper;
 use crate::util::heap::layout::heap_layout::VMMap;
@@ -48,7 +50,14 @@ pub trait Space<VM: VMBinding>: 'static + SFT + Sync + Downcast {
     /// Currently after we create a boxed plan, spaces in the plan have a non-moving address.
     fn initialize_sft(&self);
 
+    /// Acquire pages from the space. The pages are zeroed if the space is zeroed.
     fn acquire(&self, tls: VMThread, pages: usize) -> Address {
+        self.acquire_pages(tls, pages, self.is_zeroed())
+    }
+
+    /// Acquire pages from the space, and zero them if `zero` is true. The bump pointer allocators
+    /// use this to acquire unzeroed pages, and zero them as the option `nursery_zeroing` specifies.
+    fn acquire_pages(&self, tls: VMThread, pages: usize, zero: bool) -> Address {
         trace!("Space.acquire, tls={:?}", tls);
         // Should we poll to attempt to GC?
         // - If tls is collector, we cannot attempt a GC.
@@ -136,8 +145,15 @@ pub trait Space<VM: VMBinding>: 'static + SFT + Sync + Downcast {
                         map_sidemetadata();
                     }
 
-                    // TODO: Concurrent zeroing
-                    if self.common().zeroed {
+                    // The card table is mapped with the other side metadata above. Let the GC know
+                    // that it needs to look for dirty cards in the new chunks.
+                    if let Some(chunk_map) = self.common().card_table_chunks.as_ref() {
+                        if res.new_chunk {
+                            crate::util::card_table::register_chunks(chunk_map, res.start, bytes);
+                        }
+                    }
+
+                    if zero {
                         memory::zero(res.start, bytes);
                     }
 
@@ -265,6 +281,14 @@ pub trait Space<VM: VMBinding>: 'static + SFT + Sync + Downcast {
             panic!("failed to mmap meta memory");
         }
 
+        if let Some(chunk_map) = self.common().card_table_chunks.as_ref() {
+            crate::util::card_table::register_chunks(
+                chunk_map,
+                self.common().start,
+                self.common().extent,
+            );
+        }
+
         use crate::util::heap::layout::mmapper::Mmapper;
         self.common()
             .mmapper
@@ -286,6 +310,18 @@ pub trait Space<VM: VMBinding>: 'static + SFT + Sync + Downcast {
         self.common().name
     }
 
+    /// Does the space guarantee that the pages it hands out are zeroed?
+    fn is_zeroed(&self) -> bool {
+        self.common().zeroed
+    }
+
+    /// Can the allocators write to the memory of the space? The code space is not writable at the
+    /// addresses of its objects (see [`crate::policy::codespace`]), so the allocators do not zero
+    /// the objects or fill the alignment gaps in it.
+    fn is_writable(&self) -> bool {
+        true
+    }
+
     fn common(&self) -> &CommonSpace<VM>;
     fn get_gc_trigger(&self) -> &GCTrigger<VM> {
         self.common().gc_trigger.as_ref()
@@ -403,6 +439,10 @@ pub struct CommonSpace<VM: VMBinding> {
     // TODO: This should be a constant for performance.
     pub needs_log_bit: bool,
 
+    /// The chunks of this space that are covered by the card table, if the plan uses the card
+    /// table barrier. The GC looks for dirty cards in these chunks.
+    pub card_table_chunks: Option<ChunkMap>,
+
     /// A lock used during acquire() to make sure only one thread can allocate.
     pub acquire_lock: Mutex<()>,
 
@@ -468,6 +508,12 @@ impl<VM: VMBinding> CommonSpace<VM> {
             vm_map: args.plan_args.vm_map,
             mmapper: args.plan_args.mmapper,
             needs_log_bit: args.plan_args.constraints.needs_log_bit,
+            card_table_chunks: args
+                .plan_args
+                .constraints
+                .barrier
+                .equals(BarrierSelector::CardTableBarrier)
+                .then(crate::util::card_table::new_chunk_map),
             gc_trigger: args.plan_args.gc_trigger,
             metadata: SideMetadataContext {
                 global: args.plan_args.global_side_metadata_specs,
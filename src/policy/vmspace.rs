@@ -0,0 +1,234 @@
+//! A space for the memory that the VM populates itself, such as a boot image or a snapshot of an
+//! ahead-of-time compiled runtime.
+//!
+//! The space consists of the regions registered by the binding. A region is either an address
+//! range that the VM has already mapped and populated, or an image file that MMTk maps for the VM
+//! (see [`crate::memory_manager::register_vm_space_region`] and
+//! [`crate::memory_manager::map_vm_space_image`]). The regions are outside the MMTk heap range,
+//! and they are not counted in the heap size. The space may also have a fixed address range in the
+//! heap range, which the VM maps and populates itself (see the option `vm_space_size`). The objects
+//! in the space are immortal and never moved, and they are traced in the same way as the objects in
+//! an immortal space.
+//!
+//! The SFT entries of the space are set for the whole chunks that the regions span, and the SFT
+//! tells whether an address is in the space. So the memory in those chunks around a region is
+//! taken as in the space, too.
+
+use crate::mmtk::SFT_MAP;
+use crate::plan::{ObjectQueue, VectorObjectQueue};
+use crate::policy::immortalspace::ImmortalSpace;
+use crate::policy::sft::GCWorkerMutRef;
+use crate::policy::sft::SFT;
+use crate::policy::space::{CommonSpace, Space};
+use crate::util::address::Address;
+use crate::util::heap::layout::vm_layout_constants::{AVAILABLE_END, AVAILABLE_START};
+use crate::util::heap::PageResource;
+use crate::util::opaque_pointer::VMThread;
+use crate::util::ObjectReference;
+use crate::vm::VMBinding;
+
+use std::sync::RwLock;
+
+/// The VM space. Objects are never allocated in this space by MMTk.
+pub struct VMSpace<VM: VMBinding> {
+    immortal: ImmortalSpace<VM>,
+    /// The regions of the space, as (start, end).
+    regions: RwLock<Vec<(Address, Address)>>,
+    /// The region specified by the options, or the address range of the space if it has one. It is
+    /// added when the SFT is initialized.
+    initial_region: Option<(Address, usize)>,
+}
+
+impl<VM: VMBinding> SFT for VMSpace<VM> {
+    fn name(&self) -> &str {
+        self.get_name()
+    }
+    fn is_live(&self, object: ObjectReference) -> bool {
+        self.immortal.is_live(object)
+    }
+    fn is_reachable(&self, object: ObjectReference) -> bool {
+        self.immortal.is_reachable(object)
+    }
+    #[cfg(feature = "object_pinning")]
+    fn pin_object(&self, object: ObjectReference) -> bool {
+        self.immortal.pin_object(object)
+    }
+    #[cfg(feature = "object_pinning")]
+    fn unpin_object(&self, object: ObjectReference) -> bool {
+        self.immortal.unpin_object(object)
+    }
+    #[cfg(feature = "object_pinning")]
+    fn is_object_pinned(&self, object: ObjectReference) -> bool {
+        self.immortal.is_object_pinned(object)
+    }
+    fn is_movable(&self) -> bool {
+        false
+    }
+    #[cfg(feature = "sanity")]
+    fn is_sane(&self) -> bool {
+        true
+    }
+    fn initialize_object_metadata(&self, object: ObjectReference, alloc: bool) {
+        self.immortal.initialize_object_metadata(object, alloc)
+    }
+    #[cfg(feature = "is_mmtk_object")]
+    fn is_mmtk_object(&self, addr: Address) -> bool {
+        self.immortal.is_mmtk_object(addr)
+    }
+    fn sft_trace_object(
+        &self,
+        queue: &mut VectorObjectQueue,
+        object: ObjectReference,
+        _worker: GCWorkerMutRef,
+    ) -> ObjectReference {
+        self.trace_object(queue, object)
+    }
+}
+
+impl<VM: VMBinding> Space<VM> for VMSpace<VM> {
+    fn as_space(&self) -> &dyn Space<VM> {
+        self
+    }
+    fn as_sft(&self) -> &(dyn SFT + Sync + 'static) {
+        self
+    }
+    fn get_page_resource(&self) -> &dyn PageResource<VM> {
+        self.immortal.get_page_resource()
+    }
+    fn common(&self) -> &CommonSpace<VM> {
+        self.immortal.common()
+    }
+
+    // The address range of the space is not used. We only set the SFT for the regions.
+    fn initialize_sft(&self) {
+        if let Some((start, size)) = self.initial_region {
+            self.add_region(start, size);
+        }
+    }
+
+    fn acquire(&self, _tls: VMThread, _pages: usize) -> Address {
+        panic!("vmspace does not acquire pages")
+    }
+
+    // The regions may be anywhere outside the heap range, so the SFT knows them but the VM map does
+    // not.
+    fn address_in_space(&self, start: Address) -> bool {
+        SFT_MAP.get_checked(start).name() == self.get_name()
+    }
+
+    fn release_multiple_pages(&mut self, _start: Address) {
+        panic!("vmspace does not release pages")
+    }
+}
+
+use crate::scheduler::GCWorker;
+use crate::util::copy::CopySemantics;
+
+impl<VM: VMBinding> crate::policy::gc_work::PolicyTraceObject<VM> for VMSpace<VM> {
+    fn trace_object<Q: ObjectQueue, const KIND: crate::policy::gc_work::TraceKind>(
+        &self,
+        queue: &mut Q,
+        object: ObjectReference,
+        _copy: Option<CopySemantics>,
+        _worker: &mut GCWorker<VM>,
+    ) -> ObjectReference {
+        self.trace_object(queue, object)
+    }
+    fn may_move_objects<const KIND: crate::policy::gc_work::TraceKind>() -> bool {
+        false
+    }
+}
+
+impl<VM: VMBinding> VMSpace<VM> {
+    /// Create a VM space. If `initial_region` is given, the region is added to the space when the
+    /// SFT of the space is initialized. Otherwise, if the space has a fixed address range in the
+    /// heap range, the whole range is added.
+    pub fn new(
+        args: crate::policy::space::PlanCreateSpaceArgs<VM>,
+        initial_region: Option<(Address, usize)>,
+    ) -> Self {
+        let immortal = ImmortalSpace::new(args);
+        let initial_region = initial_region.or_else(|| {
+            let common = immortal.common();
+            common.contiguous.then(|| (common.start, common.extent))
+        });
+        VMSpace {
+            immortal,
+            regions: RwLock::new(vec![]),
+            initial_region,
+        }
+    }
+
+    /// Add a region of memory to the space. The memory must have been mapped, and populated with
+    /// objects by the VM. The region must not overlap with the other regions. It must be outside the
+    /// address range that MMTk uses for its heap, unless it is in the address range of the space.
+    pub fn add_region(&self, start: Address, size: usize) {
+        assert!(size > 0, "Adding an empty region to {}", self.get_name());
+        let end = start + size;
+        let mut regions = self.regions.write().unwrap();
+        if let Some((region_start, region_end)) = regions
+            .iter()
+            .find(|&&(region_start, region_end)| start < region_end && region_start < end)
+        {
+            panic!(
+                "The region {}..{} overlaps with the region {}..{} of {}",
+                start,
+                end,
+                region_start,
+                region_end,
+                self.get_name()
+            );
+        }
+        // MMTk may give any chunk in the heap range to its spaces, except the chunks in the address
+        // range of this space.
+        let common = self.common();
+        let in_space_range =
+            common.contiguous && start >= common.start && end <= common.start + common.extent;
+        assert!(
+            in_space_range || end <= AVAILABLE_START || start >= AVAILABLE_END,
+            "The region {}..{} overlaps with the MMTk heap range {}..{}",
+            start,
+            end,
+            AVAILABLE_START,
+            AVAILABLE_END
+        );
+
+        if self
+            .common()
+            .metadata
+            .try_map_metadata_space(start, size)
+            .is_err()
+        {
+            panic!("failed to mmap meta memory");
+        }
+        if let Some(chunk_map) = self.common().card_table_chunks.as_ref() {
+            crate::util::card_table::register_chunks(chunk_map, start, size);
+        }
+        use crate::util::heap::layout::mmapper::Mmapper;
+        self.common().mmapper.mark_as_mapped(start, size);
+        unsafe { SFT_MAP.eager_initialize(self.as_sft(), start, size) };
+
+        regions.push((start, end));
+    }
+
+    /// Get the regions of the space, as (start, end).
+    pub fn regions(&self) -> Vec<(Address, Address)> {
+        self.regions.read().unwrap().clone()
+    }
+
+    pub fn prepare(&mut self) {
+        self.immortal.prepare();
+    }
+
+    pub fn release(&mut self) {
+        self.immortal.release();
+    }
+
+    pub fn trace_object<Q: ObjectQueue>(
+        &self,
+        queue: &mut Q,
+        object: ObjectReference,
+    ) -> ObjectReference {
+        self.immortal.trace_object(queue, object)
+    }
+}
@@ -8,7 +8,7 @@ use std::sync::Arc;
 
 use crate::plan::gc_requester::GCRequester;
 use crate::scheduler::gc_work::{EndOfGC, ScheduleCollection};
-use crate::scheduler::{CoordinatorMessage, GCWork};
+use crate::scheduler::{CoordinatorMessage, GCWork, WorkBucketStage};
 use crate::util::VMWorkerThread;
 use crate::vm::VMBinding;
 use crate::MMTK;
@@ -83,6 +83,10 @@ impl<VM: VMBinding> GCController<VM> {
     /// Coordinate workers to perform GC in response to a GC request.
     pub fn do_gc_until_completion(&mut self) {
         let gc_start = std::time::Instant::now();
+        // Concurrent work packets run in this pause until the plan deactivates their bucket.
+        let concurrent_work =
+            self.scheduler.work_buckets[WorkBucketStage::Concurrent].is_activated();
+        self.mmtk.plan.base().pause_time.on_gc_start();
         // Schedule collection.
         self.initiate_coordinator_work(&mut ScheduleCollection, true);
 
@@ -122,6 +126,11 @@ impl<VM: VMBinding> GCController<VM> {
 
         // Tell GC trigger that GC ended - this happens before EndOfGC where we resume mutators.
         self.mmtk.plan.base().gc_trigger.policy.on_gc_end(self.mmtk);
+        self.mmtk
+            .plan
+            .base()
+            .pause_time
+            .on_gc_end(gc_start.elapsed(), concurrent_work);
 
         // Finalization: Resume mutators, reset gc states
         // Note: Resume-mutators must happen after all work buckets are closed.
@@ -43,6 +43,8 @@ impl<C: GCWorkContext + 'static> GCWork<C::VM> for Prepare<C> {
         // We assume this is the only running work packet that accesses plan at the point of execution
         #[allow(clippy::cast_ref_to_mut)]
         let plan_mut: &mut C::PlanType = unsafe { &mut *(self.plan as *const _ as *mut _) };
+        // The buffers that are being zeroed concurrently may be reclaimed in this GC.
+        crate::util::alloc::nursery_zeroing::wait_for_helper();
         plan_mut.prepare(worker.tls);
 
         for mutator in <C::VM as VMBinding>::VMActivePlan::mutators() {
@@ -502,6 +504,11 @@ impl<E: ProcessEdgesWork> GCWork<E::VM> for ScanVMSpecificRoots<E> {
     fn do_work(&mut self, worker: &mut GCWorker<E::VM>, mmtk: &'static MMTK<E::VM>) {
         trace!("ScanStaticRoots");
         let factory = ProcessEdgesWorkRootsWorkFactory::<E>::new(mmtk);
+        #[cfg(feature = "ro_space")]
+        mmtk.plan
+            .base()
+            .ro_space
+            .scan_sealed_objects(worker.tls, factory.clone());
         <E::VM as VMBinding>::VMScanning::scan_vm_specific_roots(worker.tls, factory);
     }
 }
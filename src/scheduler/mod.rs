@@ -8,6 +8,7 @@ pub(crate) use scheduler::CoordinatorMessage;
 pub(crate) use scheduler::GCWorkScheduler;
 
 mod stat;
+pub use stat::PauseTimeModel;
 pub(self) mod work_counter;
 
 mod work;
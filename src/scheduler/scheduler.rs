@@ -67,6 +67,7 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
             WorkBucketStage::Compact => WorkBucket::new(false, worker_monitor.clone(), worker_group.clone()),
             WorkBucketStage::Release => WorkBucket::new(false, worker_monitor.clone(), worker_group.clone()),
             WorkBucketStage::Final => WorkBucket::new(false, worker_monitor.clone(), worker_group.clone()),
+            WorkBucketStage::Concurrent => WorkBucket::new(false, worker_monitor.clone(), worker_group.clone()),
         };
 
         // Set the open condition of each bucket.
@@ -75,14 +76,19 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
             // This vec will grow for each stage we call with open_next()
             let first_stw_stage = WorkBucketStage::first_stw_stage();
             let mut open_stages: Vec<WorkBucketStage> = vec![first_stw_stage];
-            // The rest will open after the previous stage is done.
+            // The rest will open after the previous stage is done, and the concurrent work (if any)
+            // is done. The concurrent bucket is opened by the plan.
             let stages = (0..WorkBucketStage::LENGTH).map(WorkBucketStage::from_usize);
             for stage in stages {
-                if stage != WorkBucketStage::Unconstrained && stage != first_stw_stage {
+                if stage != WorkBucketStage::Unconstrained
+                    && stage != first_stw_stage
+                    && stage != WorkBucketStage::Concurrent
+                {
                     let cur_stages = open_stages.clone();
                     work_buckets[stage].set_open_condition(
                         move |scheduler: &GCWorkScheduler<VM>| {
                             scheduler.are_buckets_drained(&cur_stages)
+                                && scheduler.is_concurrent_work_drained()
                         },
                     );
                     open_stages.push(stage);
@@ -246,8 +252,32 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
         buckets.iter().all(|&b| self.work_buckets[b].is_drained())
     }
 
+    /// Check if there is no pending concurrent work that the current pause has to wait for.
+    /// Concurrent work packets in a deactivated concurrent bucket will be executed after the pause.
+    fn is_concurrent_work_drained(&self) -> bool {
+        let bucket = &self.work_buckets[WorkBucketStage::Concurrent];
+        !bucket.is_activated() || bucket.is_empty()
+    }
+
+    /// Check if all the buckets for the current pause are empty. Concurrent work packets are not
+    /// considered.
     pub fn all_buckets_empty(&self) -> bool {
-        self.work_buckets.values().all(|bucket| bucket.is_empty())
+        self.work_buckets
+            .iter()
+            .all(|(id, bucket)| id == WorkBucketStage::Concurrent || bucket.is_empty())
+    }
+
+    /// Activate the concurrent bucket, so GC workers will execute concurrent work packets while
+    /// mutators are running. This should only be used by plans that need concurrent workers.
+    pub fn activate_concurrent_bucket(&self) {
+        debug_assert!(!self.work_buckets[WorkBucketStage::Concurrent].is_activated());
+        self.work_buckets[WorkBucketStage::Concurrent].activate();
+        self.work_buckets[WorkBucketStage::Concurrent].notify_all_workers();
+    }
+
+    /// Deactivate the concurrent bucket when the concurrent work is finished.
+    pub fn deactivate_concurrent_bucket(&self) {
+        self.work_buckets[WorkBucketStage::Concurrent].deactivate();
     }
 
     /// Schedule "sentinel" work packets for all activated buckets.
@@ -299,7 +329,7 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
 
     pub fn deactivate_all(&self) {
         self.work_buckets.iter().for_each(|(id, bkt)| {
-            if id != WorkBucketStage::Unconstrained {
+            if id != WorkBucketStage::Unconstrained && id != WorkBucketStage::Concurrent {
                 bkt.deactivate();
             }
         });
@@ -308,7 +338,10 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
     pub fn reset_state(&self) {
         let first_stw_stage = WorkBucketStage::first_stw_stage();
         self.work_buckets.iter().for_each(|(id, bkt)| {
-            if id != WorkBucketStage::Unconstrained && id != first_stw_stage {
+            if id != WorkBucketStage::Unconstrained
+                && id != first_stw_stage
+                && id != WorkBucketStage::Concurrent
+            {
                 bkt.deactivate();
             }
         });
@@ -317,7 +350,7 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
     pub fn debug_assert_all_buckets_deactivated(&self) {
         if cfg!(debug_assertions) {
             self.work_buckets.iter().for_each(|(id, bkt)| {
-                if id != WorkBucketStage::Unconstrained {
+                if id != WorkBucketStage::Unconstrained && id != WorkBucketStage::Concurrent {
                     assert!(!bkt.is_activated());
                 }
             });
@@ -434,8 +467,15 @@ impl<VM: VMBinding> GCWorkScheduler<VM> {
                         break 'polling_loop;
                     }
                     debug_assert!(!self.worker_group.has_designated_work());
-                    // The current pause is finished if we can't open more buckets.
-                    worker.sender.send(CoordinatorMessage::Finish).unwrap();
+                    // The current pause is finished if we can't open more buckets. If the plan
+                    // has concurrent workers, they may also run out of work when mutators are
+                    // running. There is no pause to finish unless the stop-the-world stages
+                    // have been opened.
+                    if !worker.mmtk.plan.constraints().needs_concurrent_workers
+                        || self.work_buckets[WorkBucketStage::first_stw_stage()].is_activated()
+                    {
+                        worker.sender.send(CoordinatorMessage::Finish).unwrap();
+                    }
                 }
                 // Otherwise, if there is still pending coordinator work, the last parked
                 // worker will wait on the monitor, too.  The coordinator will notify a
@@ -2,12 +2,15 @@
 use super::work_counter::{WorkCounter, WorkCounterBase, WorkDuration};
 #[cfg(feature = "perf_counter")]
 use crate::scheduler::work_counter::WorkPerfEvent;
+use crate::util::options::Options;
 use crate::vm::VMBinding;
 use crate::MMTK;
 use std::any::TypeId;
 use std::collections::HashMap;
 use std::marker::PhantomData;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
 
 /// Merge and print the work-packet level statistics from all worker threads
 #[derive(Default)]
@@ -245,3 +248,272 @@ impl<VM: VMBinding> WorkerLocalStat<VM> {
         counters
     }
 }
+
+/// The measured pause times of a kind of GC, and the amount of work that can be done within the
+/// pause time target.
+///
+/// A pause is modelled as a fixed cost plus a cost per page, fitted by least squares to the recent
+/// pauses. The older pauses weigh less, so the model follows changes in the cost of the work.
+struct CostEstimate {
+    samples: Mutex<CostSamples>,
+    /// The number of pages that can be processed within the target. 0 means we do not know yet.
+    target_pages: AtomicUsize,
+}
+
+/// The weighted sums of the measured pauses.
+#[derive(Default)]
+struct CostSamples {
+    weight: f64,
+    pages: f64,
+    pause_ns: f64,
+    pages_squared: f64,
+    pages_times_pause_ns: f64,
+}
+
+impl CostSamples {
+    /// The weight of the earlier pauses is multiplied by this for each new pause.
+    const DECAY: f64 = 0.5;
+
+    fn add(&mut self, pages: f64, pause_ns: f64) {
+        self.weight = self.weight * Self::DECAY + 1f64;
+        self.pages = self.pages * Self::DECAY + pages;
+        self.pause_ns = self.pause_ns * Self::DECAY + pause_ns;
+        self.pages_squared = self.pages_squared * Self::DECAY + pages * pages;
+        self.pages_times_pause_ns = self.pages_times_pause_ns * Self::DECAY + pages * pause_ns;
+    }
+
+    /// Return the fixed cost of a pause and the cost per page, in nanoseconds. If the pauses have
+    /// not processed different numbers of pages, or if the fit is not meaningful, the cost is
+    /// taken as proportional to the pages.
+    fn fit(&self) -> (f64, f64) {
+        let variance = self.weight * self.pages_squared - self.pages * self.pages;
+        if variance > f64::EPSILON * self.weight * self.pages_squared {
+            let per_page =
+                (self.weight * self.pages_times_pause_ns - self.pages * self.pause_ns) / variance;
+            let fixed = (self.pause_ns - per_page * self.pages) / self.weight;
+            if per_page > 0f64 && fixed >= 0f64 {
+                return (fixed, per_page);
+            }
+        }
+        (0f64, self.pause_ns / self.pages)
+    }
+}
+
+impl CostEstimate {
+    fn new() -> Self {
+        Self {
+            samples: Mutex::new(CostSamples::default()),
+            target_pages: AtomicUsize::new(0),
+        }
+    }
+
+    /// Update the cost with a pause of `pause_ns` that processed `pages`, and return the number
+    /// of pages that can be processed within `target_ns`.
+    fn update(&self, pause_ns: u64, pages: usize, target_ns: u64) -> usize {
+        let mut samples = self.samples.lock().unwrap();
+        samples.add(pages as f64, pause_ns as f64);
+        let (fixed, per_page) = samples.fit();
+        let target_pages = (((target_ns as f64 - fixed) / per_page) as usize).max(1);
+        self.target_pages.store(target_pages, Ordering::Relaxed);
+        target_pages
+    }
+
+    fn target_pages(&self) -> Option<usize> {
+        match self.target_pages.load(Ordering::Relaxed) {
+            0 => None,
+            pages => Some(pages),
+        }
+    }
+}
+
+/// Measure the pause times of GCs, and predict how much a plan can collect or evacuate within the
+/// pause time target (see [`pause_target_ms`](crate::util::options::Options::pause_target_ms)).
+///
+/// Two kinds of GCs are measured separately, as reported by the plan:
+/// * GCs that collect part of the heap (e.g. nursery GCs). Their cost is used to size the nursery.
+/// * Defrag GCs. Their cost per evacuated page is used to limit the evacuation in later defrag GCs.
+///   It includes the cost of tracing the heap, so the limit is conservative.
+///
+/// The model uses the wall clock time of a pause, from the GC request to the resumption of the
+/// mutators. A pause during which the concurrent work packets ([`WorkBucketStage::Concurrent`](crate::scheduler::WorkBucketStage::Concurrent))
+/// may run (e.g. the final mark pause of a concurrent plan) is not measured, as its length depends
+/// on the concurrent work left over rather than on the pages the plan reports.
+pub struct PauseTimeModel {
+    /// The pause time target in nanoseconds. 0 means there is no target.
+    target_ns: u64,
+    /// The number of pages collected by the current GC, as reported by the plan. 0 means the
+    /// current GC is not measured as a partial GC.
+    collected_pages: AtomicUsize,
+    /// The number of pages evacuated by the current GC, as reported by the plan. 0 means the
+    /// current GC is not measured as a defrag GC.
+    evacuated_pages: AtomicUsize,
+    /// The cost of partial GCs.
+    collection: CostEstimate,
+    /// The cost of defrag GCs.
+    evacuation: CostEstimate,
+}
+
+impl PauseTimeModel {
+    pub fn new(pause_target_ms: usize) -> Self {
+        Self {
+            target_ns: pause_target_ms as u64 * 1_000_000,
+            collected_pages: AtomicUsize::new(0),
+            evacuated_pages: AtomicUsize::new(0),
+            collection: CostEstimate::new(),
+            evacuation: CostEstimate::new(),
+        }
+    }
+
+    /// Is a pause time target set?
+    pub fn is_enabled(&self) -> bool {
+        self.target_ns != 0
+    }
+
+    /// Record the number of pages collected by the current GC. A plan calls this in a GC that
+    /// collects part of the heap (e.g. a nursery GC), so the cost of the GC is used to predict
+    /// later GCs. The other GCs are not measured.
+    pub fn set_collected_pages(&self, pages: usize) {
+        self.collected_pages.store(pages, Ordering::Relaxed);
+    }
+
+    /// Record the number of pages the current GC evacuates for defragmentation. A plan calls this
+    /// in a defrag GC, so the cost of the GC is used to limit the evacuation of later defrag GCs.
+    pub fn set_evacuated_pages(&self, pages: usize) {
+        self.evacuated_pages.store(pages, Ordering::Relaxed);
+    }
+
+    /// Forget the pages reported for the previous GC.
+    pub(crate) fn on_gc_start(&self) {
+        self.collected_pages.store(0, Ordering::Relaxed);
+        self.evacuated_pages.store(0, Ordering::Relaxed);
+    }
+
+    /// Update the cost with the pause time of the GC that just finished. `concurrent_work` tells
+    /// whether concurrent work packets may have run in the pause, in which case it is not measured.
+    pub(crate) fn on_gc_end(&self, pause: Duration, concurrent_work: bool) {
+        let collected_pages = self.collected_pages.swap(0, Ordering::Relaxed);
+        let evacuated_pages = self.evacuated_pages.swap(0, Ordering::Relaxed);
+        if !self.is_enabled() || concurrent_work {
+            return;
+        }
+        let pause_ns = pause.as_nanos() as u64;
+        if collected_pages != 0 {
+            let target_pages = self
+                .collection
+                .update(pause_ns, collected_pages, self.target_ns);
+            trace!(
+                "pause time model: {} pages can be collected within the target",
+                target_pages
+            );
+        }
+        if evacuated_pages != 0 {
+            let target_pages = self
+                .evacuation
+                .update(pause_ns, evacuated_pages, self.target_ns);
+            trace!(
+                "pause time model: {} pages can be evacuated within the target",
+                target_pages
+            );
+        }
+    }
+
+    /// The number of pages that we expect to collect within the pause time target. Return `None`
+    /// if there is no target, or if no GC has been measured yet.
+    pub fn target_pages(&self) -> Option<usize> {
+        self.collection.target_pages()
+    }
+
+    /// The number of pages that we expect to evacuate within the pause time target in a defrag GC.
+    /// Return `None` if there is no target, or if no defrag GC has been measured yet.
+    pub fn evacuation_target_pages(&self) -> Option<usize> {
+        self.evacuation.target_pages()
+    }
+
+    /// The number of pages a plan may allocate into its nursery before it collects the nursery.
+    /// This is the number of pages that can be collected within the pause time target, bounded by
+    /// the nursery size. Without a target, this is the max nursery size.
+    pub fn nursery_pages(&self, options: &Options) -> usize {
+        let max = options.get_max_nursery_pages();
+        match self.target_pages() {
+            Some(pages) => pages.clamp(options.get_min_nursery_pages(), max),
+            None => max,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measure(model: &PauseTimeModel, pause: Duration, collected: usize, evacuated: usize) {
+        model.on_gc_start();
+        if collected != 0 {
+            model.set_collected_pages(collected);
+        }
+        if evacuated != 0 {
+            model.set_evacuated_pages(evacuated);
+        }
+        model.on_gc_end(pause, false);
+    }
+
+    #[test]
+    fn pause_time_model_without_target() {
+        let model = PauseTimeModel::new(0);
+        assert!(!model.is_enabled());
+        measure(&model, Duration::from_millis(10), 100, 0);
+        assert_eq!(model.target_pages(), None);
+        let options = Options::default();
+        assert_eq!(
+            model.nursery_pages(&options),
+            options.get_max_nursery_pages()
+        );
+    }
+
+    #[test]
+    fn pause_time_model_with_target() {
+        let model = PauseTimeModel::new(10);
+        assert!(model.is_enabled());
+        // Not measured yet
+        assert_eq!(model.target_pages(), None);
+
+        // A pause of 20ms that collects 1000 pages: with one pause, the cost is proportional to
+        // the pages, so we can collect 500 pages in 10ms.
+        measure(&model, Duration::from_millis(20), 1000, 0);
+        assert_eq!(model.target_pages(), Some(500));
+
+        // A GC that is not measured does not change the prediction.
+        measure(&model, Duration::from_millis(100), 0, 0);
+        assert_eq!(model.target_pages(), Some(500));
+
+        // Neither does a pause in which concurrent work packets may run.
+        model.on_gc_start();
+        model.set_collected_pages(1000);
+        model.on_gc_end(Duration::from_millis(100), true);
+        assert_eq!(model.target_pages(), Some(500));
+
+        // Defrag GCs are measured separately.
+        assert_eq!(model.evacuation_target_pages(), None);
+        measure(&model, Duration::from_millis(40), 0, 100);
+        assert_eq!(model.evacuation_target_pages(), Some(25));
+        assert_eq!(model.target_pages(), Some(500));
+    }
+
+    /// The pauses have a fixed cost of 2ms and cost 10us per page, so 800 pages can be collected
+    /// in 10ms. If the plan collects as many pages as the model predicts, the pauses converge on
+    /// the target.
+    #[test]
+    fn pause_time_model_converges_on_target() {
+        let pause_of = |pages: usize| Duration::from_micros(2000 + 10 * pages as u64);
+        let target = Duration::from_millis(10);
+        let model = PauseTimeModel::new(10);
+        let mut pages = 4096;
+        for _ in 0..10 {
+            measure(&model, pause_of(pages), pages, 0);
+            pages = model.target_pages().unwrap();
+        }
+        assert!((795..=800).contains(&pages), "{} pages", pages);
+        let pause = pause_of(pages);
+        assert!(pause <= target, "{:?}", pause);
+        assert!(pause >= target.mul_f64(0.99), "{:?}", pause);
+    }
+}
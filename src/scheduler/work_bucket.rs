@@ -287,6 +287,13 @@ pub enum WorkBucketStage {
     Release,
     /// Resume mutators and end GC.
     Final,
+    /// Work that runs concurrently with mutators, such as concurrent marking.
+    ///
+    /// This bucket is not opened or closed with the stop-the-world stages. A plan activates it
+    /// when it starts concurrent work, and deactivates it when the concurrent work is finished.
+    /// When this bucket is activated during a pause, the stages after `Prepare` will not open
+    /// until it is drained.
+    Concurrent,
 }
 
 impl WorkBucketStage {
@@ -0,0 +1,75 @@
+//! Counting the bytes that an allocator allocates.
+//!
+//! An allocator with a thread local buffer does not count the objects in its bump pointer fast path,
+//! which a binding may inline. It counts the bytes it used in a buffer when it retires the buffer,
+//! and the bytes used in the current buffer are computed from the bump cursor when the count is
+//! read. An object allocated outside a thread local buffer is counted in the slow path that
+//! allocates it.
+
+use crate::util::Address;
+
+/// The per-allocator count of the allocated bytes, including the padding for alignment.
+pub(crate) struct AllocatedBytes {
+    /// The bytes allocated in the retired buffers and outside the thread local buffers.
+    retired: usize,
+    /// The start of the current thread local buffer. It is zero if there is no thread local buffer.
+    buffer_start: Address,
+}
+
+impl AllocatedBytes {
+    pub const ZERO: Self = AllocatedBytes {
+        retired: 0,
+        buffer_start: Address::ZERO,
+    };
+
+    /// The allocator retires the current thread local buffer (whose cursor is `old_cursor`), and
+    /// starts bump allocation at `cursor`. A zero `cursor` means the allocator has no thread local
+    /// buffer from now on.
+    pub fn new_buffer(&mut self, old_cursor: Address, cursor: Address) {
+        self.retired += self.bytes_in_buffer(old_cursor);
+        self.buffer_start = cursor;
+    }
+
+    /// The allocator allocated `bytes` outside its thread local buffer.
+    pub fn add(&mut self, bytes: usize) {
+        self.retired += bytes;
+    }
+
+    /// The bytes allocated so far, if the cursor of the current thread local buffer is `cursor`.
+    pub fn get(&self, cursor: Address) -> usize {
+        self.retired + self.bytes_in_buffer(cursor)
+    }
+
+    fn bytes_in_buffer(&self, cursor: Address) -> usize {
+        if self.buffer_start.is_zero() || cursor <= self.buffer_start {
+            0
+        } else {
+            cursor - self.buffer_start
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUFFER_START: usize = 0x4000_0000;
+
+    #[test]
+    fn count_retired_and_current_buffers() {
+        let mut allocated = AllocatedBytes::ZERO;
+        let start = unsafe { Address::from_usize(BUFFER_START) };
+        assert_eq!(allocated.get(Address::ZERO), 0);
+        allocated.new_buffer(Address::ZERO, start);
+        assert_eq!(allocated.get(start + 64usize), 64);
+        // Retire the buffer after 128 bytes, and start another one.
+        let next = start + 4096usize;
+        allocated.new_buffer(start + 128usize, next);
+        assert_eq!(allocated.get(next), 128);
+        allocated.add(1000);
+        assert_eq!(allocated.get(next + 32usize), 1160);
+        // Reset without a new buffer.
+        allocated.new_buffer(next + 32usize, Address::ZERO);
+        assert_eq!(allocated.get(Address::ZERO), 1160);
+    }
+}
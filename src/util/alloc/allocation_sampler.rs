@@ -0,0 +1,221 @@
+//! Allocation sampling for heap profilers.
+//!
+//! A sampler picks one allocation every `alloc_sample_interval` bytes on average. The distance
+//! between two samples is drawn from a geometric distribution, so the samples are not biased
+//! towards objects of a particular size or allocation pattern (the same approach as JVMTI
+//! `SampledObjectAlloc`).
+//!
+//! For bump pointer allocation, the sampler reuses the trick that precise stress tests use: the
+//! bump limit is clamped to the next sample point, so the fast path (including a fast path that is
+//! inlined by the binding) fails at the sample point and calls into the slow path. The allocator
+//! then restores the real limit, allocates the object, and records it as the pending sample.
+//!
+//! The allocators report the pending sample to the binding in the path that sampled the object,
+//! so an allocation that is not sampled pays nothing more than the bump pointer check (or a counter
+//! for the allocators that do not bump allocate).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::util::opaque_pointer::{VMMutatorThread, VMThread};
+use crate::util::Address;
+use crate::vm::{Collection, VMBinding};
+
+/// Used to give each sampler a different random sequence.
+static SEED: AtomicU64 = AtomicU64::new(0x9e37_79b9_7f4a_7c15);
+
+/// The per-allocator state for allocation sampling.
+pub(crate) struct AllocationSampler {
+    /// The mean number of bytes between two samples. 0 means sampling is disabled.
+    interval: usize,
+    /// The number of bytes to allocate before the next sample.
+    bytes_until_sample: usize,
+    /// The state of the random number generator (xorshift64*).
+    rng: u64,
+    /// The start of the part of the current thread local buffer that is not accounted for in
+    /// `bytes_until_sample` yet. It is zero if there is no thread local buffer.
+    buffer_start: Address,
+    /// The real limit of the current thread local buffer.
+    buffer_limit: Address,
+    /// The sampled object that has not been reported to the binding yet.
+    pending: Option<(Address, usize)>,
+}
+
+impl AllocationSampler {
+    /// A sampler that never samples.
+    pub const DISABLED: Self = AllocationSampler {
+        interval: 0,
+        bytes_until_sample: 0,
+        rng: 0,
+        buffer_start: Address::ZERO,
+        buffer_limit: Address::ZERO,
+        pending: None,
+    };
+
+    /// Create a sampler that samples every `interval` bytes on average.
+    pub fn new(interval: usize) -> Self {
+        if interval == 0 {
+            return Self::DISABLED;
+        }
+        // Scramble the seed with splitmix64 so the samplers do not produce correlated sequences.
+        let mut seed = SEED.fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed);
+        seed = (seed ^ (seed >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        seed = (seed ^ (seed >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        seed ^= seed >> 31;
+        let mut sampler = AllocationSampler {
+            interval,
+            bytes_until_sample: 0,
+            // xorshift must not start from 0.
+            rng: seed | 1,
+            buffer_start: Address::ZERO,
+            buffer_limit: Address::ZERO,
+            pending: None,
+        };
+        sampler.bytes_until_sample = sampler.next_interval();
+        sampler
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.interval != 0
+    }
+
+    /// Draw the number of bytes until the next sample from a geometric distribution with the mean
+    /// of `interval`.
+    fn next_interval(&mut self) -> usize {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let random = self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d);
+        // A uniform number in (0, 1].
+        let uniform = ((random >> 11) + 1) as f64 / (1u64 << 53) as f64;
+        let bytes = -uniform.ln() * self.interval as f64;
+        (bytes as usize).max(1)
+    }
+
+    /// Record an object as sampled, and draw the distance to the next sample.
+    fn sample(&mut self, addr: Address, size: usize) {
+        trace!("Sampled allocation {} ({} bytes)", addr, size);
+        self.pending = Some((addr, size));
+        self.bytes_until_sample = self.next_interval();
+    }
+
+    /// Take the sampled object that has not been reported yet.
+    pub fn take_pending(&mut self) -> Option<(Address, usize)> {
+        self.pending.take()
+    }
+
+    /// Report the sampled object to the binding with `Collection::sample_allocation()`, if there is
+    /// one. `tls` is the mutator thread that owns the allocator.
+    #[inline(always)]
+    pub fn report<VM: VMBinding>(&mut self, tls: VMThread) {
+        if let Some((addr, size)) = self.take_pending() {
+            VM::VMCollection::sample_allocation(VMMutatorThread(tls), addr, size);
+        }
+    }
+
+    /// The real limit of the current thread local buffer.
+    pub fn buffer_limit(&self) -> Address {
+        self.buffer_limit
+    }
+
+    /// Is the bump limit clamped to a sample point? If so, a failed bump allocation should call
+    /// the sampling slow path rather than acquiring a new buffer.
+    pub fn is_limit_clamped(&self, limit: Address) -> bool {
+        self.is_enabled() && limit < self.buffer_limit
+    }
+
+    /// The allocator retires the current thread local buffer (whose cursor is `old_cursor`), and
+    /// starts bump allocation in `[cursor, limit)`. Return the limit the allocator should use.
+    /// A zero `cursor` means the allocator has no thread local buffer from now on.
+    pub fn new_buffer(&mut self, old_cursor: Address, cursor: Address, limit: Address) -> Address {
+        if !self.is_enabled() {
+            return limit;
+        }
+        if !self.buffer_start.is_zero() && old_cursor > self.buffer_start {
+            self.bytes_until_sample = self
+                .bytes_until_sample
+                .saturating_sub(old_cursor - self.buffer_start);
+        }
+        self.buffer_start = cursor;
+        self.buffer_limit = limit;
+        self.clamped_limit(cursor)
+    }
+
+    /// The allocator bump allocated an object of `size` bytes at `addr` in the current thread
+    /// local buffer after restoring the real limit, and the bump cursor is now `cursor`. Return
+    /// the limit the allocator should use.
+    pub fn sample_in_buffer(&mut self, addr: Address, size: usize, cursor: Address) -> Address {
+        debug_assert!(self.is_enabled());
+        let allocated = cursor - self.buffer_start;
+        if allocated >= self.bytes_until_sample {
+            self.sample(addr, size);
+        } else {
+            self.bytes_until_sample -= allocated;
+        }
+        self.buffer_start = cursor;
+        self.clamped_limit(cursor)
+    }
+
+    /// The allocator allocated an object of `size` bytes at `addr` outside its thread local buffer.
+    pub fn sample_object(&mut self, addr: Address, size: usize) {
+        if !self.is_enabled() || addr.is_zero() {
+            return;
+        }
+        if size >= self.bytes_until_sample {
+            self.sample(addr, size);
+        } else {
+            self.bytes_until_sample -= size;
+        }
+    }
+
+    fn clamped_limit(&self, cursor: Address) -> Address {
+        if cursor.is_zero() || self.buffer_limit - cursor <= self.bytes_until_sample {
+            self.buffer_limit
+        } else {
+            cursor + self.bytes_until_sample
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUFFER_START: usize = 0x4000_0000;
+    const BUFFER_BYTES: usize = 1 << 20;
+
+    #[test]
+    fn disabled_sampler_keeps_limit() {
+        let mut sampler = AllocationSampler::DISABLED;
+        let start = unsafe { Address::from_usize(BUFFER_START) };
+        let limit = sampler.new_buffer(Address::ZERO, start, start + BUFFER_BYTES);
+        assert_eq!(limit, start + BUFFER_BYTES);
+        assert!(!sampler.is_limit_clamped(start));
+        sampler.sample_object(start, BUFFER_BYTES);
+        assert!(sampler.take_pending().is_none());
+    }
+
+    #[test]
+    fn limit_is_clamped_to_sample_point() {
+        let mut sampler = AllocationSampler::new(1024);
+        let start = unsafe { Address::from_usize(BUFFER_START) };
+        let end = start + BUFFER_BYTES;
+        let mut limit = sampler.new_buffer(Address::ZERO, start, end);
+        let mut cursor = start;
+        let mut samples = 0;
+        // Bump allocate 16-byte objects the way an allocator would.
+        while cursor + 16usize <= end {
+            if cursor + 16usize > limit {
+                assert!(sampler.is_limit_clamped(limit));
+                limit = sampler.sample_in_buffer(cursor, 16, cursor + 16usize);
+                let (addr, size) = sampler.take_pending().unwrap();
+                assert_eq!(addr, cursor);
+                assert_eq!(size, 16);
+                samples += 1;
+            }
+            cursor += 16usize;
+            assert!(limit <= end);
+        }
+        // We expect about 1024 samples in 1MB. Use a loose bound so the test is not flaky.
+        assert!(samples > 512 && samples < 2048, "samples = {}", samples);
+    }
+}
@@ -5,6 +5,7 @@ use crate::plan::Plan;
 use crate::policy::space::Space;
 use crate::util::constants::*;
 use crate::util::opaque_pointer::*;
+use crate::util::options::NurseryZeroingOptions;
 use crate::vm::VMBinding;
 use crate::vm::{ActivePlan, Collection};
 use downcast_rs::Downcast;
@@ -18,6 +19,9 @@ pub enum AllocationError {
     /// The OS is unable to mmap or acquire more memory. Critical error. MMTk expects the VM to
     /// abort if such an error is thrown.
     MmapOutOfMemory,
+    /// The VM tried to allocate into the read-only space after the space was sealed with
+    /// `memory_manager::seal_readonly_space()`. This is a bug in the VM.
+    ReadOnlySpaceSealed,
 }
 
 pub fn align_allocation_no_fill<VM: VMBinding>(
@@ -50,14 +54,15 @@ pub fn align_allocation_inner<VM: VMBinding>(
         debug_assert!(VM::MIN_ALIGNMENT >= BYTES_IN_INT);
     }
     debug_assert!(!(fillalignmentgap && region.is_zero()));
-    debug_assert!(alignment <= VM::MAX_ALIGNMENT);
+    debug_assert!(alignment.is_power_of_two());
     debug_assert!(offset >= 0);
     debug_assert!(region.is_aligned_to(VM::ALLOC_END_ALIGNMENT));
     debug_assert!((alignment & (VM::MIN_ALIGNMENT - 1)) == 0);
     debug_assert!((offset & (VM::MIN_ALIGNMENT - 1) as isize) == 0);
 
-    // No alignment ever required.
-    if alignment <= known_alignment || VM::MAX_ALIGNMENT <= VM::MIN_ALIGNMENT {
+    // No alignment required. Note that we cannot skip the alignment just because
+    // `VM::MAX_ALIGNMENT <= VM::MIN_ALIGNMENT`, as the alignment may be a large alignment.
+    if alignment <= known_alignment {
         return region;
     }
 
@@ -78,8 +83,8 @@ pub fn align_allocation_inner<VM: VMBinding>(
 pub fn fill_alignment_gap<VM: VMBinding>(immut_start: Address, end: Address) {
     let mut start = immut_start;
 
-    if VM::MAX_ALIGNMENT - VM::MIN_ALIGNMENT == BYTES_IN_INT {
-        // At most a single hole
+    if VM::MAX_ALIGNMENT - VM::MIN_ALIGNMENT == BYTES_IN_INT && end - start <= BYTES_IN_INT {
+        // At most a single hole, unless the alignment is a large alignment.
         if end - start != 0 {
             unsafe {
                 start.store(VM::ALIGNMENT_VALUE);
@@ -95,6 +100,35 @@ pub fn fill_alignment_gap<VM: VMBinding>(immut_start: Address, end: Address) {
     }
 }
 
+/// Bump allocate objects of the same size, alignment and offset in `[cursor, limit)` until `count`
+/// objects are allocated or the next object does not fit. The addresses of the objects are appended
+/// to `result`. The alignment gaps are filled if `fill_gaps` is true. Return the new cursor.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn bump_allocate_many<VM: VMBinding>(
+    mut cursor: Address,
+    limit: Address,
+    size: usize,
+    align: usize,
+    offset: isize,
+    count: usize,
+    result: &mut Vec<Address>,
+    fill_gaps: bool,
+) -> Address {
+    for _ in 0..count {
+        let start = align_allocation_no_fill::<VM>(cursor, align, offset);
+        let end = start + size;
+        if end > limit {
+            break;
+        }
+        if fill_gaps {
+            fill_alignment_gap::<VM>(cursor, start);
+        }
+        result.push(start);
+        cursor = end;
+    }
+    cursor
+}
+
 pub fn get_maximum_aligned_size<VM: VMBinding>(size: usize, alignment: usize) -> usize {
     get_maximum_aligned_size_inner::<VM>(size, alignment, VM::MIN_ALIGNMENT)
 }
@@ -114,7 +148,7 @@ pub fn get_maximum_aligned_size_inner<VM: VMBinding>(
     debug_assert!(size == size & !(known_alignment - 1));
     debug_assert!(known_alignment >= VM::MIN_ALIGNMENT);
 
-    if VM::MAX_ALIGNMENT <= VM::MIN_ALIGNMENT || alignment <= known_alignment {
+    if alignment <= known_alignment {
         size
     } else {
         size + alignment - known_alignment
@@ -164,6 +198,38 @@ pub trait Allocator<VM: VMBinding>: Downcast {
     /// * `offset` the required offset in bytes.
     fn alloc(&mut self, size: usize, align: usize, offset: isize) -> Address;
 
+    /// Allocate up to `count` objects of the same size, alignment and offset, and append their
+    /// addresses to `result`. An implementation may allocate fewer objects than requested, and the
+    /// caller should call this again for the rest. It allocates at least one object unless the
+    /// allocation fails. Allocators with a thread local buffer should override this to bump allocate
+    /// the objects in one go, and take the slow path at most once.
+    ///
+    /// Return the number of objects allocated. 0 means the allocation failed, as in [`alloc`](Allocator::alloc).
+    ///
+    /// Arguments:
+    /// * `size`: the allocation size in bytes for each object.
+    /// * `align`: the required alignment in bytes.
+    /// * `offset` the required offset in bytes.
+    /// * `count`: the number of objects to allocate.
+    /// * `result`: the vector to append the addresses of the allocated objects to.
+    fn alloc_many(
+        &mut self,
+        size: usize,
+        align: usize,
+        offset: isize,
+        count: usize,
+        result: &mut Vec<Address>,
+    ) -> usize {
+        debug_assert!(count > 0);
+        let addr = self.alloc(size, align, offset);
+        if addr.is_zero() {
+            0
+        } else {
+            result.push(addr);
+            1
+        }
+    }
+
     /// Slowpath allocation attempt. This function is explicitly not inlined for performance
     /// considerations.
     ///
@@ -360,6 +426,31 @@ pub trait Allocator<VM: VMBinding>: Downcast {
         self.alloc_slow_once(size, align, offset)
     }
 
+    /// Enable allocation sampling for this allocator, so it samples one allocation every `interval`
+    /// bytes on average. See the option `alloc_sample_interval`. An allocator that does not
+    /// override this method does not support allocation sampling.
+    fn enable_allocation_sampling(&mut self, _interval: usize) {}
+
+    /// The number of bytes that this allocator uses to allocate an object of `size` bytes with a
+    /// large alignment, i.e. an alignment larger than `VM::MAX_ALIGNMENT`. This includes the
+    /// padding for the alignment. If this is larger than `max_non_los_default_alloc_bytes` (see
+    /// [`crate::plan::PlanConstraints`]), the mutator allocates the object in the large object
+    /// space instead.
+    fn large_alignment_bytes(&self, size: usize, align: usize, _offset: isize) -> usize {
+        get_maximum_aligned_size::<VM>(size, align)
+    }
+
+    /// Set how this allocator zeroes the memory it hands out to a mutator. See the option
+    /// `nursery_zeroing`. An allocator that does not override this method gets zeroed memory from
+    /// its space, or zeroes the memory in its own way.
+    fn set_nursery_zeroing(&mut self, _mode: NurseryZeroingOptions) {}
+
+    /// The bytes that this allocator has allocated since it was created. An allocator with a bump
+    /// pointer fast path counts the bytes it used in a thread local buffer (including the padding
+    /// for alignment) when it retires the buffer, so the fast path does not pay for the counting.
+    /// The other allocators count each object in [`alloc`](Allocator::alloc).
+    fn allocated_bytes(&self) -> usize;
+
     /// The [`crate::plan::Mutator`] that includes this allocator is going to be destroyed. Some allocators
     /// may need to save/transfer its thread local data to the space.
     fn on_mutator_destroy(&mut self) {
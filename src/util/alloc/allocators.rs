@@ -4,6 +4,7 @@ use crate::plan::Plan;
 use crate::policy::largeobjectspace::LargeObjectSpace;
 use crate::policy::marksweepspace::malloc_ms::MallocSpace;
 use crate::policy::marksweepspace::native_ms::MarkSweepSpace;
+use crate::policy::region::RegionSpace;
 use crate::policy::space::Space;
 use crate::util::alloc::LargeObjectAllocator;
 use crate::util::alloc::MallocAllocator;
@@ -13,13 +14,15 @@ use crate::vm::VMBinding;
 
 use super::FreeListAllocator;
 use super::MarkCompactAllocator;
+use super::RegionAllocator;
 
 pub(crate) const MAX_BUMP_ALLOCATORS: usize = 6;
 pub(crate) const MAX_LARGE_OBJECT_ALLOCATORS: usize = 2;
 pub(crate) const MAX_MALLOC_ALLOCATORS: usize = 1;
 pub(crate) const MAX_IMMIX_ALLOCATORS: usize = 1;
-pub(crate) const MAX_FREE_LIST_ALLOCATORS: usize = 2;
+pub(crate) const MAX_FREE_LIST_ALLOCATORS: usize = 3;
 pub(crate) const MAX_MARK_COMPACT_ALLOCATORS: usize = 1;
+pub(crate) const MAX_REGION_ALLOCATORS: usize = 1;
 
 // The allocators set owned by each mutator. We provide a fixed number of allocators for each allocator type in the mutator,
 // and each plan will select part of the allocators to use.
@@ -33,6 +36,7 @@ pub struct Allocators<VM: VMBinding> {
     pub immix: [MaybeUninit<ImmixAllocator<VM>>; MAX_IMMIX_ALLOCATORS],
     pub free_list: [MaybeUninit<FreeListAllocator<VM>>; MAX_FREE_LIST_ALLOCATORS],
     pub markcompact: [MaybeUninit<MarkCompactAllocator<VM>>; MAX_MARK_COMPACT_ALLOCATORS],
+    pub region: [MaybeUninit<RegionAllocator<VM>>; MAX_REGION_ALLOCATORS],
 }
 
 impl<VM: VMBinding> Allocators<VM> {
@@ -52,6 +56,7 @@ impl<VM: VMBinding> Allocators<VM> {
             AllocatorSelector::MarkCompact(index) => {
                 self.markcompact[index as usize].assume_init_ref()
             }
+            AllocatorSelector::Region(index) => self.region[index as usize].assume_init_ref(),
             AllocatorSelector::None => panic!("Allocator mapping is not initialized"),
         }
     }
@@ -75,10 +80,57 @@ impl<VM: VMBinding> Allocators<VM> {
             AllocatorSelector::MarkCompact(index) => {
                 self.markcompact[index as usize].assume_init_mut()
             }
+            AllocatorSelector::Region(index) => self.region[index as usize].assume_init_mut(),
             AllocatorSelector::None => panic!("Allocator mapping is not initialized"),
         }
     }
 
+    /// Describe the bump pointer fast-path of the allocator for the selector, if the allocator has
+    /// one that a VM compiler can inline. Returns `None` for the other allocators. `needs_zeroing`
+    /// tells whether the fast-path needs to zero the objects it allocates.
+    pub fn get_fast_path(
+        selector: AllocatorSelector,
+        needs_zeroing: bool,
+    ) -> Option<AllocatorFastPath> {
+        fn offset_in<T, U>(base: *const T, field: *const U) -> usize {
+            field as usize - base as usize
+        }
+
+        let allocators = MaybeUninit::<Self>::uninit();
+        let base = allocators.as_ptr();
+        let (kind, allocator_offset, (cursor_offset, limit_offset)) = match selector {
+            AllocatorSelector::BumpPointer(index) => (
+                AllocatorFastPathKind::BumpPointer,
+                offset_in(base, unsafe {
+                    std::ptr::addr_of!((*base).bump_pointer[index as usize])
+                }),
+                BumpAllocator::<VM>::cursor_and_limit_offsets(),
+            ),
+            AllocatorSelector::Immix(index) => (
+                AllocatorFastPathKind::Immix,
+                offset_in(base, unsafe {
+                    std::ptr::addr_of!((*base).immix[index as usize])
+                }),
+                ImmixAllocator::<VM>::cursor_and_limit_offsets(),
+            ),
+            AllocatorSelector::Region(index) => (
+                AllocatorFastPathKind::Region,
+                offset_in(base, unsafe {
+                    std::ptr::addr_of!((*base).region[index as usize])
+                }),
+                RegionAllocator::<VM>::cursor_and_limit_offsets(),
+            ),
+            _ => return None,
+        };
+        Some(AllocatorFastPath {
+            kind,
+            selector,
+            cursor_offset: allocator_offset + cursor_offset,
+            limit_offset: allocator_offset + limit_offset,
+            needs_zeroing,
+        })
+    }
+
     pub fn new(
         mutator_tls: VMMutatorThread,
         plan: &'static dyn Plan<VM = VM>,
@@ -91,6 +143,7 @@ impl<VM: VMBinding> Allocators<VM> {
             immix: unsafe { MaybeUninit::uninit().assume_init() },
             free_list: unsafe { MaybeUninit::uninit().assume_init() },
             markcompact: unsafe { MaybeUninit::uninit().assume_init() },
+            region: unsafe { MaybeUninit::uninit().assume_init() },
         };
 
         for &(selector, space) in space_mapping.iter() {
@@ -138,10 +191,38 @@ impl<VM: VMBinding> Allocators<VM> {
                         plan,
                     ));
                 }
+                AllocatorSelector::Region(index) => {
+                    ret.region[index as usize].write(RegionAllocator::new(
+                        mutator_tls.0,
+                        space.downcast_ref::<RegionSpace<VM>>().unwrap(),
+                        plan,
+                        false,
+                    ));
+                }
                 AllocatorSelector::None => panic!("Allocator mapping is not initialized"),
             }
         }
 
+        // Precise stress tests use the bump pointer limit to force the slow path, so they cannot
+        // be used together with allocation sampling.
+        let sample_interval = *plan.options().alloc_sample_interval;
+        let precise_stress =
+            plan.base().is_stress_test_gc_enabled() && plan.base().is_precise_stress();
+        if sample_interval != 0 && !precise_stress {
+            for &(selector, _) in space_mapping.iter() {
+                unsafe { ret.get_allocator_mut(selector) }
+                    .enable_allocation_sampling(sample_interval);
+            }
+        }
+
+        let nursery_zeroing = *plan.options().nursery_zeroing;
+        for &(selector, space) in space_mapping.iter() {
+            // An allocator cannot zero the objects in a space that it cannot write to.
+            if space.is_writable() {
+                unsafe { ret.get_allocator_mut(selector) }.set_nursery_zeroing(nursery_zeroing);
+            }
+        }
+
         ret
     }
 }
@@ -167,9 +248,48 @@ pub enum AllocatorSelector {
     Immix(u8),
     MarkCompact(u8),
     FreeList(u8),
+    Region(u8),
     None,
 }
 
+/// The kind of the bump pointer fast-path of an allocator.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AllocatorFastPathKind {
+    /// [`BumpAllocator`]. The fast-path bumps the cursor if the object fits below the limit.
+    BumpPointer,
+    /// [`ImmixAllocator`]. The fast-path is the same as `BumpPointer` for the current line run.
+    /// The allocator uses a different path for medium objects when the fast-path fails, so the
+    /// binding should not make any assumption about the cursor and the limit after the slow-path.
+    Immix,
+    /// [`RegionAllocator`]. The fast-path is the same as `BumpPointer`.
+    Region,
+}
+
+/// Describes the bump pointer fast-path of an allocator, so a VM compiler can inline it. The
+/// offsets are from the start of [`Allocators`], which is the first field of [`crate::Mutator`].
+///
+/// The fast-path aligns the cursor, and bumps it by the object size if the new cursor is not
+/// above the limit. Otherwise, the binding should call [`crate::memory_manager::alloc`], which
+/// handles the slow-path. Note that the limit may be manipulated to force the slow-path,
+/// e.g. for precise stress tests. The binding should only compare the new cursor against the limit,
+/// and should not assume the limit is the end of the thread local buffer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AllocatorFastPath {
+    /// The kind of the allocator.
+    pub kind: AllocatorFastPathKind,
+    /// The allocator in the allocators set.
+    pub selector: AllocatorSelector,
+    /// The byte offset of the bump cursor (an `Address`).
+    pub cursor_offset: usize,
+    /// The byte offset of the bump limit (an `Address`).
+    pub limit_offset: usize,
+    /// The memory between the cursor and the limit is not zeroed, and the binding must zero the
+    /// objects allocated in the fast-path. This is true if the option `nursery_zeroing` is
+    /// `Temporal`.
+    pub needs_zeroing: bool,
+}
+
 impl Default for AllocatorSelector {
     fn default() -> Self {
         AllocatorSelector::None
@@ -1,4 +1,9 @@
-use super::allocator::{align_allocation_no_fill, fill_alignment_gap};
+use super::allocated_bytes::AllocatedBytes;
+use super::allocation_sampler::AllocationSampler;
+use super::allocator::{
+    align_allocation_no_fill, bump_allocate_many, fill_alignment_gap, get_maximum_aligned_size,
+};
+use super::nursery_zeroing::NurseryZeroing;
 use crate::util::Address;
 
 use crate::util::alloc::Allocator;
@@ -7,6 +12,7 @@ use crate::plan::Plan;
 use crate::policy::space::Space;
 use crate::util::conversions::bytes_to_pages;
 use crate::util::opaque_pointer::*;
+use crate::util::options::NurseryZeroingOptions;
 use crate::vm::VMBinding;
 
 const BYTES_IN_PAGE: usize = 1 << 12;
@@ -25,15 +31,30 @@ pub struct BumpAllocator<VM: VMBinding> {
     space: &'static dyn Space<VM>,
     /// [`Plan`] instance that this allocator instance is associated with.
     plan: &'static dyn Plan<VM = VM>,
+    /// Allocation sampling state. This is disabled unless the option `alloc_sample_interval` is set.
+    sampler: AllocationSampler,
+    /// How to zero the blocks. See the option `nursery_zeroing`.
+    zeroing: NurseryZeroing,
+    /// The bytes allocated by this allocator.
+    allocated: AllocatedBytes,
+    /// Whether the allocator can write to its space, i.e. [`Space::is_writable`].
+    space_writable: bool,
 }
 
 impl<VM: VMBinding> BumpAllocator<VM> {
     pub fn set_limit(&mut self, start: Address, limit: Address) {
+        self.allocated.new_buffer(self.cursor, start);
         self.cursor = start;
         self.limit = limit;
     }
 
     pub fn reset(&mut self) {
+        self.sampler
+            .new_buffer(self.cursor, Address::ZERO, Address::ZERO);
+        // The spaces of bump allocators reclaim their pages as a whole in a GC, including the
+        // buffer zeroed ahead.
+        let _ = self.zeroing.reset();
+        self.allocated.new_buffer(self.cursor, Address::ZERO);
         self.cursor = unsafe { Address::zero() };
         self.limit = unsafe { Address::zero() };
     }
@@ -41,6 +62,27 @@ impl<VM: VMBinding> BumpAllocator<VM> {
     pub fn rebind(&mut self, space: &'static dyn Space<VM>) {
         self.reset();
         self.space = space;
+        self.space_writable = space.is_writable();
+    }
+
+    /// Fill the alignment gap in `[start, end)`, unless the allocator cannot write to its space.
+    fn fill_alignment_gap(&self, start: Address, end: Address) {
+        if self.space_writable {
+            fill_alignment_gap::<VM>(start, end);
+        }
+    }
+
+    /// Get the byte offsets of the `cursor` and the `limit` fields in this struct. A VM compiler
+    /// can use them to inline the bump pointer fast-path. See [`crate::util::alloc::AllocatorFastPath`].
+    pub(crate) fn cursor_and_limit_offsets() -> (usize, usize) {
+        let allocator = std::mem::MaybeUninit::<Self>::uninit();
+        let base = allocator.as_ptr();
+        unsafe {
+            (
+                std::ptr::addr_of!((*base).cursor) as usize - base as usize,
+                std::ptr::addr_of!((*base).limit) as usize - base as usize,
+            )
+        }
     }
 }
 
@@ -67,11 +109,16 @@ impl<VM: VMBinding> Allocator<VM> for BumpAllocator<VM> {
         let new_cursor = result + size;
 
         if new_cursor > self.limit {
+            if self.sampler.is_limit_clamped(self.limit) {
+                trace!("Reached an allocation sample point, go to alloc sampled");
+                return self.alloc_sampled(size, align, offset);
+            }
             trace!("Thread local buffer used up, go to alloc slow path");
             self.alloc_slow(size, align, offset)
         } else {
-            fill_alignment_gap::<VM>(self.cursor, result);
+            self.fill_alignment_gap(self.cursor, result);
             self.cursor = new_cursor;
+            self.zeroing.zero_object(result, size);
             trace!(
                 "Bump allocation size: {}, result: {}, new_cursor: {}, limit: {}",
                 size,
@@ -83,6 +130,42 @@ impl<VM: VMBinding> Allocator<VM> for BumpAllocator<VM> {
         }
     }
 
+    fn alloc_many(
+        &mut self,
+        size: usize,
+        align: usize,
+        offset: isize,
+        count: usize,
+        result: &mut Vec<Address>,
+    ) -> usize {
+        let allocated = result.len();
+        self.cursor = bump_allocate_many::<VM>(
+            self.cursor,
+            self.limit,
+            size,
+            align,
+            offset,
+            count,
+            result,
+            self.space_writable,
+        );
+        for addr in &result[allocated..] {
+            self.zeroing.zero_object(*addr, size);
+        }
+        let allocated = result.len() - allocated;
+        if allocated == count {
+            return allocated;
+        }
+        // The buffer is used up. Go to the slow path for the next object.
+        let addr = self.alloc(size, align, offset);
+        if addr.is_zero() {
+            allocated
+        } else {
+            result.push(addr);
+            allocated + 1
+        }
+    }
+
     fn alloc_slow_once(&mut self, size: usize, align: usize, offset: isize) -> Address {
         trace!("alloc_slow");
         self.acquire_block(size, align, offset, false)
@@ -117,9 +200,10 @@ impl<VM: VMBinding> Allocator<VM> for BumpAllocator<VM> {
         if new_cursor > self.cursor + self.limit.as_usize() {
             self.acquire_block(size, align, offset, true)
         } else {
-            fill_alignment_gap::<VM>(self.cursor, result);
+            self.fill_alignment_gap(self.cursor, result);
             self.limit -= new_cursor - self.cursor;
             self.cursor = new_cursor;
+            self.zeroing.zero_object(result, size);
             trace!(
                 "alloc_slow: Bump allocation size: {}, result: {}, new_cursor: {}, limit: {}",
                 size,
@@ -134,6 +218,18 @@ impl<VM: VMBinding> Allocator<VM> for BumpAllocator<VM> {
     fn get_tls(&self) -> VMThread {
         self.tls
     }
+
+    fn enable_allocation_sampling(&mut self, interval: usize) {
+        self.sampler = AllocationSampler::new(interval);
+    }
+
+    fn set_nursery_zeroing(&mut self, mode: NurseryZeroingOptions) {
+        self.zeroing = NurseryZeroing::new(mode, BLOCK_SIZE);
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.allocated.get(self.cursor)
+    }
 }
 
 impl<VM: VMBinding> BumpAllocator<VM> {
@@ -148,6 +244,28 @@ impl<VM: VMBinding> BumpAllocator<VM> {
             limit: unsafe { Address::zero() },
             space,
             plan,
+            sampler: AllocationSampler::DISABLED,
+            zeroing: NurseryZeroing::BULK,
+            allocated: AllocatedBytes::ZERO,
+            space_writable: space.is_writable(),
+        }
+    }
+
+    /// The bump allocation failed because the limit is clamped to the next sample point. Restore
+    /// the real limit, and sample the allocation if it fits into the thread local buffer.
+    fn alloc_sampled(&mut self, size: usize, align: usize, offset: isize) -> Address {
+        self.limit = self.sampler.buffer_limit();
+        let result = align_allocation_no_fill::<VM>(self.cursor, align, offset);
+        let new_cursor = result + size;
+        if new_cursor > self.limit {
+            self.alloc_slow(size, align, offset)
+        } else {
+            self.fill_alignment_gap(self.cursor, result);
+            self.cursor = new_cursor;
+            self.zeroing.zero_object(result, size);
+            self.limit = self.sampler.sample_in_buffer(result, size, new_cursor);
+            self.sampler.report::<VM>(self.tls);
+            result
         }
     }
 
@@ -158,8 +276,14 @@ impl<VM: VMBinding> BumpAllocator<VM> {
         offset: isize,
         stress_test: bool,
     ) -> Address {
-        let block_size = (size + BLOCK_MASK) & (!BLOCK_MASK);
-        let acquired_start = self.space.acquire(self.tls, bytes_to_pages(block_size));
+        // Make sure the object fits in the block after padding for a large alignment.
+        let block_size = (get_maximum_aligned_size::<VM>(size, align) + BLOCK_MASK) & (!BLOCK_MASK);
+        let (space, tls) = (self.space, self.tls);
+        let acquired_start = self
+            .zeroing
+            .acquire_buffer(block_size, space.is_zeroed(), || {
+                space.acquire_pages(tls, bytes_to_pages(block_size), false)
+            });
         if acquired_start.is_zero() {
             trace!("Failed to acquire a new block");
             acquired_start
@@ -170,7 +294,12 @@ impl<VM: VMBinding> BumpAllocator<VM> {
                 acquired_start
             );
             if !stress_test {
-                self.set_limit(acquired_start, acquired_start + block_size);
+                let limit = self.sampler.new_buffer(
+                    self.cursor,
+                    acquired_start,
+                    acquired_start + block_size,
+                );
+                self.set_limit(acquired_start, limit);
                 self.alloc(size, align, offset)
             } else {
                 // For a stress test, we artificially make the fastpath fail by
@@ -1,6 +1,8 @@
 // This is a free list allocator written based on Microsoft's mimalloc allocator https://www.microsoft.com/en-us/research/publication/mimalloc-free-list-sharding-in-action/
 
 use crate::policy::marksweepspace::native_ms::*;
+use crate::util::alloc::allocated_bytes::AllocatedBytes;
+use crate::util::alloc::allocation_sampler::AllocationSampler;
 use crate::util::alloc::allocator;
 use crate::util::alloc::Allocator;
 use crate::util::linear_scan::Region;
@@ -27,6 +29,10 @@ pub struct FreeListAllocator<VM: VMBinding> {
     pub unswept_blocks: BlockLists,
     /// full blocks
     pub consumed_blocks: BlockLists,
+    /// Allocation sampling state. This is disabled unless the option `alloc_sample_interval` is set.
+    sampler: AllocationSampler,
+    /// The bytes allocated by this allocator.
+    allocated: AllocatedBytes,
 }
 
 impl<VM: VMBinding> Allocator<VM> for FreeListAllocator<VM> {
@@ -44,39 +50,19 @@ impl<VM: VMBinding> Allocator<VM> for FreeListAllocator<VM> {
 
     // Find a block with free space and allocate to it
     fn alloc(&mut self, size: usize, align: usize, offset: isize) -> Address {
-        debug_assert!(
-            size <= MAX_BIN_SIZE,
-            "Alloc request for {} bytes is too big.",
-            size
-        );
-        debug_assert!(align <= VM::MAX_ALIGNMENT);
         debug_assert!(align >= VM::MIN_ALIGNMENT);
 
-        if let Some(block) = self.find_free_block_local(size, align) {
-            let cell = self.block_alloc(block);
-            if !cell.is_zero() {
-                // We succeeded in fastpath alloc, this cannot be precise stress test
-                debug_assert!(
-                    !(*self.plan.options().precise_stress
-                        && self.plan.base().is_stress_test_gc_enabled())
-                );
-
-                let res = allocator::align_allocation::<VM>(cell, align, offset);
-                // Make sure that the allocation region is within the cell
-                #[cfg(debug_assertions)]
-                {
-                    let cell_size = block.load_block_cell_size();
-                    debug_assert!(
-                        res + size <= cell + cell_size,
-                        "Allocating (size = {}, align = {}, offset = {}) to the cell {} of size {}, but the end of the allocation region {} is beyond the cell end {}",
-                        size, align, offset, cell, cell_size, res + size, cell + cell_size
-                    );
-                }
-                return res;
-            }
+        let res = if align > VM::MAX_ALIGNMENT {
+            self.alloc_large_alignment(size, align, offset)
+        } else {
+            self.alloc_in_cell(size, align, offset)
+        };
+        if !res.is_zero() {
+            self.allocated.add(size);
         }
-
-        self.alloc_slow(size, align, offset)
+        self.sampler.sample_object(res, size);
+        self.sampler.report::<VM>(self.tls);
+        res
     }
 
     fn alloc_slow_once(&mut self, size: usize, align: usize, offset: isize) -> Address {
@@ -121,6 +107,18 @@ impl<VM: VMBinding> Allocator<VM> for FreeListAllocator<VM> {
     fn on_mutator_destroy(&mut self) {
         self.abandon_blocks();
     }
+
+    fn enable_allocation_sampling(&mut self, interval: usize) {
+        self.sampler = AllocationSampler::new(interval);
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.allocated.get(Address::ZERO)
+    }
+
+    fn large_alignment_bytes(&self, size: usize, align: usize, offset: isize) -> usize {
+        Self::large_alignment_cell_size(size, align, offset)
+    }
 }
 
 impl<VM: VMBinding> FreeListAllocator<VM> {
@@ -138,9 +136,74 @@ impl<VM: VMBinding> FreeListAllocator<VM> {
             available_blocks_stress: new_empty_block_lists(),
             unswept_blocks: new_empty_block_lists(),
             consumed_blocks: new_empty_block_lists(),
+            sampler: AllocationSampler::DISABLED,
+            allocated: AllocatedBytes::ZERO,
         }
     }
 
+    // Find a block with free space and allocate to it
+    fn alloc_in_cell(&mut self, size: usize, align: usize, offset: isize) -> Address {
+        debug_assert!(
+            size <= MAX_BIN_SIZE,
+            "Alloc request for {} bytes is too big.",
+            size
+        );
+        debug_assert!(align <= VM::MAX_ALIGNMENT);
+
+        if let Some(block) = self.find_free_block_local(size, align) {
+            let cell = self.block_alloc(block);
+            if !cell.is_zero() {
+                // We succeeded in fastpath alloc, this cannot be precise stress test
+                debug_assert!(
+                    !(*self.plan.options().precise_stress
+                        && self.plan.base().is_stress_test_gc_enabled())
+                );
+
+                let res = allocator::align_allocation::<VM>(cell, align, offset);
+                // Make sure that the allocation region is within the cell
+                #[cfg(debug_assertions)]
+                {
+                    let cell_size = block.load_block_cell_size();
+                    debug_assert!(
+                        res + size <= cell + cell_size,
+                        "Allocating (size = {}, align = {}, offset = {}) to the cell {} of size {}, but the end of the allocation region {} is beyond the cell end {}",
+                        size, align, offset, cell, cell_size, res + size, cell + cell_size
+                    );
+                }
+                return res;
+            }
+        }
+
+        self.alloc_slow(size, align, offset)
+    }
+
+    /// Allocate with an alignment larger than `VM::MAX_ALIGNMENT`. The cells of a power-of-two size
+    /// class are aligned to their size, as blocks are aligned to `Block::BYTES`. So we allocate
+    /// a cell that is large enough for the padding and the object, and align the object in it.
+    fn alloc_large_alignment(&mut self, size: usize, align: usize, offset: isize) -> Address {
+        let cell_size = Self::large_alignment_cell_size(size, align, offset);
+        let cell = self.alloc_in_cell(cell_size, VM::MIN_ALIGNMENT, 0);
+        if cell.is_zero() {
+            return cell;
+        }
+        debug_assert!(
+            cell.is_aligned_to(align),
+            "Cell {} of size {} is not aligned to {}",
+            cell,
+            cell_size,
+            align
+        );
+        allocator::align_allocation::<VM>(cell, align, offset)
+    }
+
+    /// The size of the cell to allocate an object with an alignment larger than
+    /// `VM::MAX_ALIGNMENT`.
+    fn large_alignment_cell_size(size: usize, align: usize, offset: isize) -> usize {
+        debug_assert!(align.is_power_of_two());
+        let padding = (-offset).rem_euclid(align as isize) as usize;
+        (padding + size).max(align).next_power_of_two()
+    }
+
     // Find a free cell within a given block
     fn block_alloc(&mut self, block: Block) -> Address {
         let cell = block.load_free_list();
@@ -1,5 +1,9 @@
-use super::allocator::{align_allocation_no_fill, fill_alignment_gap};
+use super::allocated_bytes::AllocatedBytes;
+use super::allocation_sampler::AllocationSampler;
+use super::allocator::{align_allocation_no_fill, bump_allocate_many, fill_alignment_gap};
+use super::nursery_zeroing::NurseryZeroing;
 use crate::plan::Plan;
+use crate::policy::immix::block::Block;
 use crate::policy::immix::line::*;
 use crate::policy::immix::ImmixSpace;
 use crate::policy::space::Space;
@@ -7,6 +11,7 @@ use crate::util::alloc::allocator::get_maximum_aligned_size;
 use crate::util::alloc::Allocator;
 use crate::util::linear_scan::Region;
 use crate::util::opaque_pointer::VMThread;
+use crate::util::options::NurseryZeroingOptions;
 use crate::util::rust_util::unlikely;
 use crate::util::Address;
 use crate::vm::*;
@@ -35,10 +40,33 @@ pub struct ImmixAllocator<VM: VMBinding> {
     request_for_large: bool,
     /// Hole-searching cursor
     line: Option<Line>,
+    /// Allocation sampling state. This is disabled unless the option `alloc_sample_interval` is set.
+    sampler: AllocationSampler,
+    /// How to zero the blocks and the lines. See the option `nursery_zeroing`.
+    zeroing: NurseryZeroing,
+    /// The bytes allocated by this allocator. The objects allocated with the large cursor are
+    /// counted one by one, as they are always allocated in the slow path.
+    allocated: AllocatedBytes,
 }
 
 impl<VM: VMBinding> ImmixAllocator<VM> {
+    /// Reset the allocator before a GC traces the heap. The clean block zeroed ahead is returned to
+    /// the space, as the GC has not started sweeping the space.
+    pub fn prepare(&mut self) {
+        if let Some(start) = self.zeroing.reset() {
+            self.immix_space()
+                .release_clean_block(Block::from_aligned_address(start));
+        }
+        self.reset();
+    }
+
     pub fn reset(&mut self) {
+        self.sampler
+            .new_buffer(self.cursor, Address::ZERO, Address::ZERO);
+        // A clean block is only zeroed ahead after `prepare()` if the mutators run during the GC
+        // (e.g. concurrent marking). Nothing is allocated in it, so the sweep reclaims it.
+        let _ = self.zeroing.reset();
+        self.allocated.new_buffer(self.cursor, Address::ZERO);
         self.cursor = Address::ZERO;
         self.limit = Address::ZERO;
         self.large_cursor = Address::ZERO;
@@ -46,6 +74,19 @@ impl<VM: VMBinding> ImmixAllocator<VM> {
         self.request_for_large = false;
         self.line = None;
     }
+
+    /// Get the byte offsets of the `cursor` and the `limit` fields in this struct. A VM compiler
+    /// can use them to inline the bump pointer fast-path. See [`crate::util::alloc::AllocatorFastPath`].
+    pub(crate) fn cursor_and_limit_offsets() -> (usize, usize) {
+        let allocator = std::mem::MaybeUninit::<Self>::uninit();
+        let base = allocator.as_ptr();
+        unsafe {
+            (
+                std::ptr::addr_of!((*base).cursor) as usize - base as usize,
+                std::ptr::addr_of!((*base).limit) as usize - base as usize,
+            )
+        }
+    }
 }
 
 impl<VM: VMBinding> Allocator<VM> for ImmixAllocator<VM> {
@@ -62,7 +103,7 @@ impl<VM: VMBinding> Allocator<VM> for ImmixAllocator<VM> {
     }
 
     fn get_thread_local_buffer_granularity(&self) -> usize {
-        crate::policy::immix::block::Block::BYTES
+        Block::BYTES
     }
 
     fn alloc(&mut self, size: usize, align: usize, offset: isize) -> Address {
@@ -83,6 +124,9 @@ impl<VM: VMBinding> Allocator<VM> for ImmixAllocator<VM> {
             if get_maximum_aligned_size::<VM>(size, align) > Line::BYTES {
                 // Size larger than a line: do large allocation
                 self.overflow_alloc(size, align, offset)
+            } else if self.sampler.is_limit_clamped(self.limit) {
+                // Reached an allocation sample point
+                self.alloc_sampled(size, align, offset)
             } else {
                 // Size smaller than a line: fit into holes
                 self.alloc_slow_hot(size, align, offset)
@@ -91,6 +135,7 @@ impl<VM: VMBinding> Allocator<VM> for ImmixAllocator<VM> {
             // Simple bump allocation.
             fill_alignment_gap::<VM>(self.cursor, result);
             self.cursor = new_cursor;
+            self.zeroing.zero_object(result, size);
             trace!(
                 "{:?}: Bump allocation size: {}, result: {}, new_cursor: {}, limit: {}",
                 self.tls,
@@ -103,6 +148,47 @@ impl<VM: VMBinding> Allocator<VM> for ImmixAllocator<VM> {
         }
     }
 
+    fn alloc_many(
+        &mut self,
+        size: usize,
+        align: usize,
+        offset: isize,
+        count: usize,
+        result: &mut Vec<Address>,
+    ) -> usize {
+        // Objects larger than a line are allocated one by one with the large cursor.
+        let allocated = if get_maximum_aligned_size::<VM>(size, align) > Line::BYTES {
+            0
+        } else {
+            let allocated = result.len();
+            self.cursor = bump_allocate_many::<VM>(
+                self.cursor,
+                self.limit,
+                size,
+                align,
+                offset,
+                count,
+                result,
+                true,
+            );
+            for addr in &result[allocated..] {
+                self.zeroing.zero_object(*addr, size);
+            }
+            result.len() - allocated
+        };
+        if allocated == count {
+            return allocated;
+        }
+        // The hole is used up. Go to the slow path for the next object.
+        let addr = self.alloc(size, align, offset);
+        if addr.is_zero() {
+            allocated
+        } else {
+            result.push(addr);
+            allocated + 1
+        }
+    }
+
     /// Acquire a clean block from ImmixSpace for allocation.
     fn alloc_slow_once(&mut self, size: usize, align: usize, offset: isize) -> Address {
         trace!("{:?}: alloc_slow_once", self.tls);
@@ -166,6 +252,18 @@ impl<VM: VMBinding> Allocator<VM> for ImmixAllocator<VM> {
     fn get_tls(&self) -> VMThread {
         self.tls
     }
+
+    fn enable_allocation_sampling(&mut self, interval: usize) {
+        self.sampler = AllocationSampler::new(interval);
+    }
+
+    fn set_nursery_zeroing(&mut self, mode: NurseryZeroingOptions) {
+        self.zeroing = NurseryZeroing::new(mode, Block::BYTES);
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.allocated.get(self.cursor)
+    }
 }
 
 impl<VM: VMBinding> ImmixAllocator<VM> {
@@ -187,6 +285,9 @@ impl<VM: VMBinding> ImmixAllocator<VM> {
             large_limit: Address::ZERO,
             request_for_large: false,
             line: None,
+            sampler: AllocationSampler::DISABLED,
+            zeroing: NurseryZeroing::BULK,
+            allocated: AllocatedBytes::ZERO,
         }
     }
 
@@ -206,11 +307,36 @@ impl<VM: VMBinding> ImmixAllocator<VM> {
             rtn
         } else {
             fill_alignment_gap::<VM>(self.large_cursor, start);
+            self.allocated.add(end - self.large_cursor);
             self.large_cursor = end;
+            self.zeroing.zero_object(start, size);
+            // The slow path above allocates through this branch, so we only sample here.
+            self.sampler.sample_object(start, size);
+            self.sampler.report::<VM>(self.tls);
             start
         }
     }
 
+    /// The bump allocation of a small object failed because the limit is clamped to the next
+    /// sample point. Restore the real limit, and sample the allocation if it fits into the
+    /// current hole.
+    fn alloc_sampled(&mut self, size: usize, align: usize, offset: isize) -> Address {
+        trace!("{:?}: alloc_sampled", self.tls);
+        self.limit = self.sampler.buffer_limit();
+        let result = align_allocation_no_fill::<VM>(self.cursor, align, offset);
+        let new_cursor = result + size;
+        if new_cursor > self.limit {
+            self.alloc_slow_hot(size, align, offset)
+        } else {
+            fill_alignment_gap::<VM>(self.cursor, result);
+            self.cursor = new_cursor;
+            self.zeroing.zero_object(result, size);
+            self.limit = self.sampler.sample_in_buffer(result, size, new_cursor);
+            self.sampler.report::<VM>(self.tls);
+            result
+        }
+    }
+
     /// Bump allocate small objects into recyclable lines (i.e. holes).
     fn alloc_slow_hot(&mut self, size: usize, align: usize, offset: isize) -> Address {
         trace!("{:?}: alloc_slow_hot", self.tls);
@@ -244,8 +370,11 @@ impl<VM: VMBinding> ImmixAllocator<VM> {
             if let Some((start_line, end_line)) = self.immix_space().get_next_available_lines(line)
             {
                 // Find recyclable lines. Update the bump allocation cursor and limit.
+                self.allocated.new_buffer(self.cursor, start_line.start());
+                self.limit =
+                    self.sampler
+                        .new_buffer(self.cursor, start_line.start(), end_line.start());
                 self.cursor = start_line.start();
-                self.limit = end_line.start();
                 trace!(
                     "{:?}: acquire_recyclable_lines -> {:?} [{:?}, {:?}) {:?}",
                     self.tls,
@@ -254,9 +383,11 @@ impl<VM: VMBinding> ImmixAllocator<VM> {
                     end_line,
                     self.tls
                 );
-                crate::util::memory::zero(self.cursor, self.limit - self.cursor);
+                self.zeroing
+                    .zero_buffer(self.cursor, end_line.start() - self.cursor);
                 debug_assert!(
-                    align_allocation_no_fill::<VM>(self.cursor, align, offset) + size <= self.limit
+                    align_allocation_no_fill::<VM>(self.cursor, align, offset) + size
+                        <= end_line.start()
                 );
                 let block = line.block();
                 self.line = if end_line == block.end_line() {
@@ -290,25 +421,35 @@ impl<VM: VMBinding> ImmixAllocator<VM> {
 
     // Get a clean block from ImmixSpace.
     fn acquire_clean_block(&mut self, size: usize, align: usize, offset: isize) -> Address {
-        match self.immix_space().get_clean_block(self.tls, self.copy) {
-            None => Address::ZERO,
-            Some(block) => {
-                trace!(
-                    "{:?}: Acquired a new block {:?} -> {:?}",
-                    self.tls,
-                    block.start(),
-                    block.end()
-                );
-                if self.request_for_large {
-                    self.large_cursor = block.start();
-                    self.large_limit = block.end();
-                } else {
-                    self.cursor = block.start();
-                    self.limit = block.end();
-                }
-                self.alloc(size, align, offset)
-            }
+        let (space, tls, copy) = (self.immix_space(), self.tls, self.copy);
+        let start = self
+            .zeroing
+            .acquire_buffer(Block::BYTES, space.is_zeroed(), || {
+                space
+                    .get_clean_block(tls, copy)
+                    .map_or(Address::ZERO, |block| block.start())
+            });
+        if start.is_zero() {
+            return Address::ZERO;
+        }
+        let block = Block::from_aligned_address(start);
+        trace!(
+            "{:?}: Acquired a new block {:?} -> {:?}",
+            self.tls,
+            block.start(),
+            block.end()
+        );
+        if self.request_for_large {
+            self.large_cursor = block.start();
+            self.large_limit = block.end();
+        } else {
+            self.allocated.new_buffer(self.cursor, block.start());
+            self.limit = self
+                .sampler
+                .new_buffer(self.cursor, block.start(), block.end());
+            self.cursor = block.start();
         }
+        self.alloc(size, align, offset)
     }
 
     /// Return whether the TLAB has been exhausted and we need to acquire a new block. Assumes that
@@ -1,9 +1,12 @@
 use crate::plan::Plan;
 use crate::policy::largeobjectspace::LargeObjectSpace;
 use crate::policy::space::Space;
+use crate::util::alloc::allocated_bytes::AllocatedBytes;
+use crate::util::alloc::allocation_sampler::AllocationSampler;
 use crate::util::alloc::{allocator, Allocator};
+use crate::util::constants::BYTES_IN_PAGE;
 use crate::util::opaque_pointer::*;
-use crate::util::Address;
+use crate::util::{Address, ObjectReference};
 use crate::vm::VMBinding;
 
 #[repr(C)]
@@ -14,6 +17,10 @@ pub struct LargeObjectAllocator<VM: VMBinding> {
     space: &'static LargeObjectSpace<VM>,
     /// [`Plan`] instance that this allocator instance is associated with.
     plan: &'static dyn Plan<VM = VM>,
+    /// Allocation sampling state. This is disabled unless the option `alloc_sample_interval` is set.
+    sampler: AllocationSampler,
+    /// The bytes allocated by this allocator.
+    allocated: AllocatedBytes,
 }
 
 impl<VM: VMBinding> Allocator<VM> for LargeObjectAllocator<VM> {
@@ -38,7 +45,14 @@ impl<VM: VMBinding> Allocator<VM> for LargeObjectAllocator<VM> {
         let cell: Address = self.alloc_slow(size, align, offset);
         // We may get a null ptr from alloc due to the VM being OOM
         if !cell.is_zero() {
-            allocator::align_allocation::<VM>(cell, align, offset)
+            let result = allocator::align_allocation::<VM>(cell, align, offset);
+            if result - cell >= BYTES_IN_PAGE {
+                self.space.record_padded_object(result, cell);
+            }
+            self.allocated.add(size);
+            self.sampler.sample_object(result, size);
+            self.sampler.report::<VM>(self.tls);
+            result
         } else {
             cell
         }
@@ -55,14 +69,37 @@ impl<VM: VMBinding> Allocator<VM> for LargeObjectAllocator<VM> {
             sp + header
         }
     }
+
+    fn enable_allocation_sampling(&mut self, interval: usize) {
+        self.sampler = AllocationSampler::new(interval);
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.allocated.get(Address::ZERO)
+    }
 }
 
 impl<VM: VMBinding> LargeObjectAllocator<VM> {
+    /// Resize an object in the large object space of this allocator. See
+    /// [`crate::memory_manager::resize_large_object`].
+    pub fn resize(&mut self, object: ObjectReference, new_size: usize) -> Option<ObjectReference> {
+        if !self.space.in_space(object) {
+            return None;
+        }
+        self.space.resize_object(self.tls, object, new_size)
+    }
+
     pub fn new(
         tls: VMThread,
         space: &'static LargeObjectSpace<VM>,
         plan: &'static dyn Plan<VM = VM>,
     ) -> Self {
-        LargeObjectAllocator { tls, space, plan }
+        LargeObjectAllocator {
+            tls,
+            space,
+            plan,
+            sampler: AllocationSampler::DISABLED,
+            allocated: AllocatedBytes::ZERO,
+        }
     }
 }
@@ -1,5 +1,7 @@
 use crate::policy::marksweepspace::malloc_ms::MallocSpace;
 use crate::policy::space::Space;
+use crate::util::alloc::allocated_bytes::AllocatedBytes;
+use crate::util::alloc::allocation_sampler::AllocationSampler;
 use crate::util::alloc::Allocator;
 use crate::util::opaque_pointer::*;
 use crate::util::Address;
@@ -14,6 +16,10 @@ pub struct MallocAllocator<VM: VMBinding> {
     space: &'static MallocSpace<VM>,
     /// [`Plan`] instance that this allocator instance is associated with.
     plan: &'static dyn Plan<VM = VM>,
+    /// Allocation sampling state. This is disabled unless the option `alloc_sample_interval` is set.
+    sampler: AllocationSampler,
+    /// The bytes allocated by this allocator.
+    allocated: AllocatedBytes,
 }
 
 impl<VM: VMBinding> Allocator<VM> for MallocAllocator<VM> {
@@ -26,7 +32,13 @@ impl<VM: VMBinding> Allocator<VM> for MallocAllocator<VM> {
     }
 
     fn alloc(&mut self, size: usize, align: usize, offset: isize) -> Address {
-        self.alloc_slow(size, align, offset)
+        let result = self.alloc_slow(size, align, offset);
+        if !result.is_zero() {
+            self.allocated.add(size);
+        }
+        self.sampler.sample_object(result, size);
+        self.sampler.report::<VM>(self.tls);
+        result
     }
 
     fn get_tls(&self) -> VMThread {
@@ -42,6 +54,14 @@ impl<VM: VMBinding> Allocator<VM> for MallocAllocator<VM> {
 
         self.space.alloc(self.tls, size, align, offset)
     }
+
+    fn enable_allocation_sampling(&mut self, interval: usize) {
+        self.sampler = AllocationSampler::new(interval);
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.allocated.get(Address::ZERO)
+    }
 }
 
 impl<VM: VMBinding> MallocAllocator<VM> {
@@ -50,6 +70,12 @@ impl<VM: VMBinding> MallocAllocator<VM> {
         space: &'static MallocSpace<VM>,
         plan: &'static dyn Plan<VM = VM>,
     ) -> Self {
-        MallocAllocator { tls, space, plan }
+        MallocAllocator {
+            tls,
+            space,
+            plan,
+            sampler: AllocationSampler::DISABLED,
+            allocated: AllocatedBytes::ZERO,
+        }
     }
 }
@@ -3,11 +3,11 @@ use crate::plan::Plan;
 use crate::policy::space::Space;
 use crate::util::alloc::Allocator;
 use crate::util::opaque_pointer::*;
+use crate::util::options::NurseryZeroingOptions;
 use crate::util::Address;
 use crate::vm::VMBinding;
 
 /// A thin wrapper(specific implementation) of bump allocator
-/// reserve extra bytes when allocating
 #[repr(C)]
 pub struct MarkCompactAllocator<VM: VMBinding> {
     bump_allocator: BumpAllocator<VM>,
@@ -49,16 +49,7 @@ impl<VM: VMBinding> Allocator<VM> for MarkCompactAllocator<VM> {
     }
 
     fn alloc(&mut self, size: usize, align: usize, offset: isize) -> Address {
-        let rtn = self
-            .bump_allocator
-            .alloc(size + Self::HEADER_RESERVED_IN_BYTES, align, offset);
-        // Check if the result is valid and return the actual object start address
-        // Note that `rtn` can be null in the case of OOM
-        if !rtn.is_zero() {
-            rtn + Self::HEADER_RESERVED_IN_BYTES
-        } else {
-            rtn
-        }
+        self.bump_allocator.alloc(size, align, offset)
     }
 
     fn alloc_slow_once(&mut self, size: usize, align: usize, offset: isize) -> Address {
@@ -84,11 +75,21 @@ impl<VM: VMBinding> Allocator<VM> for MarkCompactAllocator<VM> {
         self.bump_allocator
             .alloc_slow_once_precise_stress(size, align, offset, need_poll)
     }
+
+    fn enable_allocation_sampling(&mut self, interval: usize) {
+        self.bump_allocator.enable_allocation_sampling(interval)
+    }
+
+    fn set_nursery_zeroing(&mut self, mode: NurseryZeroingOptions) {
+        self.bump_allocator.set_nursery_zeroing(mode)
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.bump_allocator.allocated_bytes()
+    }
 }
 
 impl<VM: VMBinding> MarkCompactAllocator<VM> {
-    pub const HEADER_RESERVED_IN_BYTES: usize =
-        crate::policy::markcompactspace::MarkCompactSpace::<VM>::HEADER_RESERVED_IN_BYTES;
     pub fn new(
         tls: VMThread,
         space: &'static dyn Space<VM>,
@@ -6,8 +6,16 @@ pub use allocator::fill_alignment_gap;
 pub use allocator::AllocationError;
 pub use allocator::Allocator;
 
+/// Allocation sampling for heap profilers
+pub(crate) mod allocation_sampler;
+
+/// Counting the bytes allocated by each allocator
+pub(crate) mod allocated_bytes;
+
 /// A list of all the allocators, embedded in Mutator
 pub(crate) mod allocators;
+pub use allocators::AllocatorFastPath;
+pub use allocators::AllocatorFastPathKind;
 pub use allocators::AllocatorSelector;
 
 /// Bump pointer allocator
@@ -33,5 +41,12 @@ pub use free_list_allocator::FreeListAllocator;
 mod markcompact_allocator;
 pub use markcompact_allocator::MarkCompactAllocator;
 
+/// Region allocator (a bump pointer allocator that allocates into the regions of a region space)
+mod region_allocator;
+pub use region_allocator::RegionAllocator;
+
 /// Embedded metadata pages
 pub(crate) mod embedded_meta_data;
+
+/// Zeroing the thread local buffers of the bump pointer allocators
+pub(crate) mod nursery_zeroing;
@@ -0,0 +1,275 @@
+//! Zeroing the thread local buffers of the bump pointer allocators.
+//!
+//! The option `nursery_zeroing` selects how the memory that bump pointer allocators (including the
+//! Immix allocator) hand out to mutators is zeroed. The allocators acquire unzeroed pages from
+//! their spaces, and use [`NurseryZeroing`] to zero them. Allocators that do not allocate for
+//! mutators (e.g. copying allocators) always zero their buffers in bulk, which is the same as what
+//! a zeroed space does on acquiring pages.
+//!
+//! For `Concurrent` zeroing, a helper thread shared by all the allocators zeroes the next buffer of
+//! an allocator while the mutator allocates in the current one. The helper thread serves the
+//! requests in order. A GC waits for the helper thread before it starts, as the buffers being
+//! zeroed may be reclaimed by the GC. An allocator takes its next buffer back when it is reset, and
+//! either returns it to its space or leaves it to the GC to reclaim.
+
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Condvar, Mutex};
+
+use crate::util::memory;
+use crate::util::options::NurseryZeroingOptions;
+use crate::util::Address;
+
+/// With `Adaptive`, buffers larger than this are zeroed with non-temporal stores.
+const ADAPTIVE_NONTEMPORAL_BYTES: usize = 64 * 1024;
+
+struct HelperState {
+    /// The channel to the helper thread. It is `None` before the helper thread is spawned.
+    sender: Option<Sender<(Address, usize)>>,
+    /// The number of requests submitted to the helper thread.
+    submitted: usize,
+    /// The number of requests the helper thread has completed.
+    completed: usize,
+}
+
+static HELPER_STATE: Mutex<HelperState> = Mutex::new(HelperState {
+    sender: None,
+    submitted: 0,
+    completed: 0,
+});
+/// Notified when the helper thread completes a request.
+static HELPER_COMPLETED: Condvar = Condvar::new();
+
+fn helper_thread_main(receiver: Receiver<(Address, usize)>) {
+    for (start, bytes) in receiver {
+        // The mutator will not touch the buffer until it takes it, so do not pollute the cache.
+        memory::zero_nontemporal(start, bytes);
+        HELPER_STATE.lock().unwrap().completed += 1;
+        HELPER_COMPLETED.notify_all();
+    }
+}
+
+/// Ask the helper thread to zero a buffer. Return the ticket to wait for.
+fn submit_to_helper(start: Address, bytes: usize) -> usize {
+    let mut state = HELPER_STATE.lock().unwrap();
+    let sender = state.sender.get_or_insert_with(|| {
+        let (sender, receiver) = channel();
+        std::thread::Builder::new()
+            .name("MMTk Zeroing Helper".to_string())
+            .spawn(move || helper_thread_main(receiver))
+            .unwrap();
+        sender
+    });
+    sender.send((start, bytes)).unwrap();
+    state.submitted += 1;
+    state.submitted
+}
+
+/// Wait until the helper thread has completed the request of the ticket.
+fn wait_for_helper_ticket(ticket: usize) {
+    let mut state = HELPER_STATE.lock().unwrap();
+    while state.completed < ticket {
+        state = HELPER_COMPLETED.wait(state).unwrap();
+    }
+}
+
+/// Wait until the helper thread has completed all the submitted requests. This is called before a
+/// GC, so the GC will not reclaim a buffer that the helper thread is still zeroing.
+pub(crate) fn wait_for_helper() {
+    let ticket = HELPER_STATE.lock().unwrap().submitted;
+    wait_for_helper_ticket(ticket);
+}
+
+/// How a bump pointer allocator zeroes its thread local buffers.
+pub(crate) struct NurseryZeroing {
+    mode: NurseryZeroingOptions,
+    /// The size of the buffers that the allocator usually acquires. With `Concurrent`, the helper
+    /// thread zeroes the next buffer of this size ahead of time.
+    buffer_bytes: usize,
+    /// With `Concurrent`, the next buffer and the ticket of its zeroing request.
+    next: Option<(Address, usize)>,
+}
+
+impl NurseryZeroing {
+    /// Zero the buffers in bulk. This is used by the allocators that do not allocate for mutators.
+    pub const BULK: Self = NurseryZeroing {
+        mode: NurseryZeroingOptions::Bulk,
+        buffer_bytes: 0,
+        next: None,
+    };
+
+    pub fn new(mode: NurseryZeroingOptions, buffer_bytes: usize) -> Self {
+        NurseryZeroing {
+            mode,
+            buffer_bytes,
+            next: None,
+        }
+    }
+
+    /// Zero an object that the allocator has just bump allocated, if the objects are zeroed when
+    /// they are allocated (`Temporal`). With the other modes, the buffer has been zeroed.
+    #[inline(always)]
+    pub fn zero_object(&self, start: Address, bytes: usize) {
+        if self.mode == NurseryZeroingOptions::Temporal {
+            memory::zero(start, bytes)
+        }
+    }
+
+    /// Zero a buffer that the allocator is about to allocate into.
+    pub fn zero_buffer(&self, start: Address, bytes: usize) {
+        match self.mode {
+            NurseryZeroingOptions::Temporal => {}
+            NurseryZeroingOptions::Bulk | NurseryZeroingOptions::Concurrent => {
+                memory::zero(start, bytes)
+            }
+            NurseryZeroingOptions::Nontemporal => memory::zero_nontemporal(start, bytes),
+            NurseryZeroingOptions::Adaptive => {
+                if bytes > ADAPTIVE_NONTEMPORAL_BYTES {
+                    memory::zero_nontemporal(start, bytes)
+                } else {
+                    memory::zero(start, bytes)
+                }
+            }
+        }
+    }
+
+    /// Acquire a buffer of `bytes`, and zero it if `zeroed` is true, i.e. the space guarantees
+    /// zeroed memory. `acquire` gets an unzeroed buffer from the space, and returns zero if it
+    /// fails (possibly after a GC). Return the start of the buffer, or zero if the acquisition
+    /// failed.
+    pub fn acquire_buffer(
+        &mut self,
+        bytes: usize,
+        zeroed: bool,
+        mut acquire: impl FnMut() -> Address,
+    ) -> Address {
+        if !zeroed {
+            return acquire();
+        }
+        if self.mode != NurseryZeroingOptions::Concurrent || bytes != self.buffer_bytes {
+            let start = acquire();
+            if !start.is_zero() {
+                self.zero_buffer(start, bytes);
+            }
+            return start;
+        }
+
+        // Acquire the buffer to zero ahead first. If this triggers a GC, the allocator is reset
+        // during the GC, and we will not use any buffer acquired before the GC.
+        let next = acquire();
+        if next.is_zero() {
+            return next;
+        }
+        // Keep the new buffer before we acquire again below. If that triggers a GC, the reset
+        // takes the buffer back. If that fails, the buffer is used by the next acquisition.
+        match self.next.replace((next, submit_to_helper(next, bytes))) {
+            Some((start, ticket)) => {
+                wait_for_helper_ticket(ticket);
+                start
+            }
+            None => {
+                // There is no buffer zeroed ahead. Acquire one and zero it ourselves.
+                let start = acquire();
+                if !start.is_zero() {
+                    memory::zero(start, bytes);
+                }
+                start
+            }
+        }
+    }
+
+    /// Take back the buffer zeroed ahead, and return its start. This is called when the allocator
+    /// is reset, as the buffer may be reclaimed by a GC. The allocator does not allocate into the
+    /// buffer, so it may return the buffer to its space.
+    pub fn reset(&mut self) -> Option<Address> {
+        self.next.take().map(|(start, ticket)| {
+            wait_for_helper_ticket(ticket);
+            start
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUFFER_BYTES: usize = 4096;
+    const BUFFERS: usize = 4;
+
+    fn acquire_all(mode: NurseryZeroingOptions) {
+        let mut memory = vec![0xffu8; BUFFER_BYTES * BUFFERS];
+        let base = Address::from_mut_ptr(memory.as_mut_ptr());
+        let mut zeroing = NurseryZeroing::new(mode, BUFFER_BYTES);
+        let mut acquired = 0;
+        let mut acquire = || {
+            if acquired == BUFFERS {
+                return Address::ZERO;
+            }
+            acquired += 1;
+            base + (acquired - 1) * BUFFER_BYTES
+        };
+        loop {
+            let start = zeroing.acquire_buffer(BUFFER_BYTES, true, &mut acquire);
+            if start.is_zero() {
+                break;
+            }
+            let offset = start - base;
+            let buffer = &memory[offset..offset + BUFFER_BYTES];
+            assert!(buffer.iter().all(|b| *b == 0), "{:?}", mode);
+        }
+        // The buffer zeroed ahead is the last one acquired.
+        let next = zeroing.reset();
+        if mode == NurseryZeroingOptions::Concurrent {
+            assert_eq!(next, Some(base + (BUFFERS - 1) * BUFFER_BYTES));
+        } else {
+            assert_eq!(next, None);
+        }
+    }
+
+    #[test]
+    fn buffers_are_zeroed() {
+        for mode in [
+            NurseryZeroingOptions::Bulk,
+            NurseryZeroingOptions::Nontemporal,
+            NurseryZeroingOptions::Concurrent,
+            NurseryZeroingOptions::Adaptive,
+        ] {
+            acquire_all(mode);
+        }
+    }
+
+    #[test]
+    fn concurrent_keeps_next_buffer_if_acquisition_fails() {
+        let mut memory = vec![0xffu8; BUFFER_BYTES * 2];
+        let base = Address::from_mut_ptr(memory.as_mut_ptr());
+        let mut zeroing = NurseryZeroing::new(NurseryZeroingOptions::Concurrent, BUFFER_BYTES);
+        // The first acquisition gets the buffer to zero ahead, and the second one fails.
+        let mut acquired = false;
+        let acquire_once = || {
+            if acquired {
+                return Address::ZERO;
+            }
+            acquired = true;
+            base
+        };
+        assert!(zeroing
+            .acquire_buffer(BUFFER_BYTES, true, acquire_once)
+            .is_zero());
+        // The buffer is used by the next acquisition.
+        let next = base + BUFFER_BYTES;
+        assert_eq!(zeroing.acquire_buffer(BUFFER_BYTES, true, || next), base);
+        assert!(memory[..BUFFER_BYTES].iter().all(|b| *b == 0));
+        assert_eq!(zeroing.reset(), Some(next));
+    }
+
+    #[test]
+    fn temporal_zeroes_objects() {
+        let mut memory = vec![0xffu8; BUFFER_BYTES];
+        let base = Address::from_mut_ptr(memory.as_mut_ptr());
+        let mut zeroing = NurseryZeroing::new(NurseryZeroingOptions::Temporal, BUFFER_BYTES);
+        assert_eq!(zeroing.acquire_buffer(BUFFER_BYTES, true, || base), base);
+        assert!(memory.iter().all(|b| *b == 0xff));
+        zeroing.zero_object(base, 16);
+        assert!(memory[..16].iter().all(|b| *b == 0));
+        assert!(memory[16..].iter().all(|b| *b == 0xff));
+    }
+}
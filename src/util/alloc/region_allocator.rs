@@ -0,0 +1,229 @@
+use super::allocated_bytes::AllocatedBytes;
+use super::allocation_sampler::AllocationSampler;
+use super::allocator::{align_allocation_no_fill, fill_alignment_gap};
+use crate::plan::Plan;
+use crate::policy::region::{HeapRegion, RegionSpace};
+use crate::policy::space::Space;
+use crate::util::alloc::Allocator;
+use crate::util::linear_scan::Region;
+use crate::util::opaque_pointer::VMThread;
+use crate::util::Address;
+use crate::vm::*;
+
+/// A bump pointer allocator that allocates into the regions of a [`RegionSpace`].
+#[repr(C)]
+pub struct RegionAllocator<VM: VMBinding> {
+    /// [`VMThread`] associated with this allocator instance
+    pub tls: VMThread,
+    /// Current cursor for bump pointer
+    cursor: Address,
+    /// Limit for bump pointer
+    limit: Address,
+    /// [`Space`](src/policy/space/Space) instance associated with this allocator instance.
+    space: &'static RegionSpace<VM>,
+    /// [`Plan`] instance that this allocator instance is associated with.
+    plan: &'static dyn Plan<VM = VM>,
+    /// Is this a copy allocator?
+    copy: bool,
+    /// Allocation sampling state. This is disabled unless the option `alloc_sample_interval` is set.
+    sampler: AllocationSampler,
+    /// The bytes allocated by this allocator.
+    allocated: AllocatedBytes,
+}
+
+impl<VM: VMBinding> RegionAllocator<VM> {
+    pub fn new(
+        tls: VMThread,
+        space: &'static RegionSpace<VM>,
+        plan: &'static dyn Plan<VM = VM>,
+        copy: bool,
+    ) -> Self {
+        RegionAllocator {
+            tls,
+            cursor: Address::ZERO,
+            limit: Address::ZERO,
+            space,
+            plan,
+            copy,
+            sampler: AllocationSampler::DISABLED,
+            allocated: AllocatedBytes::ZERO,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.sampler
+            .new_buffer(self.cursor, Address::ZERO, Address::ZERO);
+        self.allocated.new_buffer(self.cursor, Address::ZERO);
+        self.cursor = Address::ZERO;
+        self.limit = Address::ZERO;
+    }
+
+    pub fn region_space(&self) -> &'static RegionSpace<VM> {
+        self.space
+    }
+
+    /// Get a clean region from RegionSpace, and allocate into it.
+    fn acquire_clean_region(
+        &mut self,
+        size: usize,
+        align: usize,
+        offset: isize,
+        stress_test: bool,
+    ) -> Address {
+        match self.space.get_clean_region(self.tls, self.copy) {
+            None => Address::ZERO,
+            Some(region) => {
+                trace!(
+                    "{:?}: Acquired a new region {:?} -> {:?}",
+                    self.tls,
+                    region.start(),
+                    region.end()
+                );
+                self.allocated.new_buffer(self.cursor, region.start());
+                if !stress_test {
+                    self.limit = self
+                        .sampler
+                        .new_buffer(self.cursor, region.start(), region.end());
+                    self.cursor = region.start();
+                    self.alloc(size, align, offset)
+                } else {
+                    self.cursor = region.start();
+                    // For a stress test, we artificially make the fastpath fail by
+                    // manipulating the limit as below.
+                    self.limit = unsafe { Address::from_usize(HeapRegion::BYTES) };
+                    self.alloc_slow_once_precise_stress(size, align, offset, false)
+                }
+            }
+        }
+    }
+
+    /// The bump allocation failed because the limit is clamped to the next sample point. Restore
+    /// the real limit, and sample the allocation if it fits into the current region.
+    fn alloc_sampled(&mut self, size: usize, align: usize, offset: isize) -> Address {
+        self.limit = self.sampler.buffer_limit();
+        let result = align_allocation_no_fill::<VM>(self.cursor, align, offset);
+        let new_cursor = result + size;
+        if new_cursor > self.limit {
+            self.alloc_slow(size, align, offset)
+        } else {
+            fill_alignment_gap::<VM>(self.cursor, result);
+            self.cursor = new_cursor;
+            self.limit = self.sampler.sample_in_buffer(result, size, new_cursor);
+            self.sampler.report::<VM>(self.tls);
+            result
+        }
+    }
+
+    /// Get the byte offsets of the `cursor` and the `limit` fields in this struct. A VM compiler
+    /// can use them to inline the bump pointer fast-path. See [`crate::util::alloc::AllocatorFastPath`].
+    pub(crate) fn cursor_and_limit_offsets() -> (usize, usize) {
+        let allocator = std::mem::MaybeUninit::<Self>::uninit();
+        let base = allocator.as_ptr();
+        unsafe {
+            (
+                std::ptr::addr_of!((*base).cursor) as usize - base as usize,
+                std::ptr::addr_of!((*base).limit) as usize - base as usize,
+            )
+        }
+    }
+}
+
+impl<VM: VMBinding> Allocator<VM> for RegionAllocator<VM> {
+    fn get_space(&self) -> &'static dyn Space<VM> {
+        self.space as _
+    }
+
+    fn get_plan(&self) -> &'static dyn Plan<VM = VM> {
+        self.plan
+    }
+
+    fn does_thread_local_allocation(&self) -> bool {
+        true
+    }
+
+    fn get_thread_local_buffer_granularity(&self) -> usize {
+        HeapRegion::BYTES
+    }
+
+    fn alloc(&mut self, size: usize, align: usize, offset: isize) -> Address {
+        debug_assert!(
+            size <= HeapRegion::BYTES,
+            "Trying to allocate a {} bytes object, which is larger than a region",
+            size
+        );
+        let result = align_allocation_no_fill::<VM>(self.cursor, align, offset);
+        let new_cursor = result + size;
+
+        if new_cursor > self.limit {
+            if self.sampler.is_limit_clamped(self.limit) {
+                trace!("{:?}: Reached an allocation sample point", self.tls);
+                return self.alloc_sampled(size, align, offset);
+            }
+            trace!(
+                "{:?}: Thread local buffer used up, go to alloc slow path",
+                self.tls
+            );
+            self.alloc_slow(size, align, offset)
+        } else {
+            fill_alignment_gap::<VM>(self.cursor, result);
+            self.cursor = new_cursor;
+            trace!(
+                "{:?}: Bump allocation size: {}, result: {}, new_cursor: {}, limit: {}",
+                self.tls,
+                size,
+                result,
+                self.cursor,
+                self.limit
+            );
+            result
+        }
+    }
+
+    fn alloc_slow_once(&mut self, size: usize, align: usize, offset: isize) -> Address {
+        trace!("{:?}: alloc_slow_once", self.tls);
+        self.acquire_clean_region(size, align, offset, false)
+    }
+
+    /// Slow path for allocation if precise stress testing has been enabled.
+    /// It works by manipulating the limit to be always below the cursor,
+    /// in the same way as [`crate::util::alloc::BumpAllocator`].
+    fn alloc_slow_once_precise_stress(
+        &mut self,
+        size: usize,
+        align: usize,
+        offset: isize,
+        need_poll: bool,
+    ) -> Address {
+        if need_poll {
+            return self.acquire_clean_region(size, align, offset, true);
+        }
+
+        trace!("{:?}: alloc_slow_once_precise_stress", self.tls);
+        let result = align_allocation_no_fill::<VM>(self.cursor, align, offset);
+        let new_cursor = result + size;
+
+        // For stress test, limit is [0, region_size) to artificially make the
+        // check in the fastpath (alloc()) fail. The real limit is recovered by
+        // adding it to the current cursor.
+        if new_cursor > self.cursor + self.limit.as_usize() {
+            self.acquire_clean_region(size, align, offset, true)
+        } else {
+            fill_alignment_gap::<VM>(self.cursor, result);
+            self.limit -= new_cursor - self.cursor;
+            self.cursor = new_cursor;
+            result
+        }
+    }
+
+    fn get_tls(&self) -> VMThread {
+        self.tls
+    }
+
+    fn enable_allocation_sampling(&mut self, interval: usize) {
+        self.sampler = AllocationSampler::new(interval);
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.allocated.get(self.cursor)
+    }
+}
@@ -1,6 +1,9 @@
 use atomic::Ordering;
+use std::sync::atomic::AtomicU8;
 
-use crate::util::metadata::side_metadata::SideMetadataSpec;
+use crate::util::metadata::side_metadata::{
+    address_to_meta_address, meta_byte_lshift, SideMetadataSpec,
+};
 use crate::util::Address;
 use crate::util::ObjectReference;
 use crate::vm::VMBinding;
@@ -21,6 +24,42 @@ pub fn set_alloc_bit<VM: VMBinding>(object: ObjectReference) {
     ALLOC_SIDE_METADATA_SPEC.store_atomic::<u8>(object.to_address::<VM>(), 1, Ordering::SeqCst);
 }
 
+/// Atomically set the alloc bits for a number of objects, such as the objects allocated by
+/// `alloc_many()`. The alloc bits that are in the same metadata byte are set with one atomic
+/// operation, rather than one for each object.
+pub fn set_alloc_bits<VM: VMBinding>(objects: &[ObjectReference]) {
+    // The sanity checker needs to see each alloc bit that is set.
+    if cfg!(feature = "extreme_assertions") {
+        for object in objects {
+            set_alloc_bit::<VM>(*object);
+        }
+        return;
+    }
+    let set_bits = |meta_addr: Address, bits: u8| {
+        if bits != 0 {
+            unsafe { meta_addr.as_ref::<AtomicU8>() }.fetch_or(bits, Ordering::SeqCst);
+        }
+    };
+    let mut meta_addr = Address::ZERO;
+    let mut bits = 0u8;
+    for object in objects {
+        debug_assert!(
+            !is_alloced::<VM>(*object),
+            "{:x}: alloc bit already set",
+            object
+        );
+        let addr = object.to_address::<VM>();
+        let object_meta_addr = address_to_meta_address(&ALLOC_SIDE_METADATA_SPEC, addr);
+        if object_meta_addr != meta_addr {
+            set_bits(meta_addr, bits);
+            meta_addr = object_meta_addr;
+            bits = 0;
+        }
+        bits |= 1 << meta_byte_lshift(&ALLOC_SIDE_METADATA_SPEC, addr);
+    }
+    set_bits(meta_addr, bits);
+}
+
 /// Atomically unset the alloc bit for an object.
 pub fn unset_alloc_bit<VM: VMBinding>(object: ObjectReference) {
     debug_assert!(is_alloced::<VM>(object), "{:x}: alloc bit not set", object);
@@ -0,0 +1,74 @@
+//! A card table for the card table write barrier.
+//!
+//! The heap is divided into cards of `BYTES_IN_CARD` bytes, and each card has one byte of global
+//! side metadata. The barrier marks the card of the source object as dirty on every reference
+//! store, and a nursery GC scans the objects that start in the dirty cards, and cleans the cards.
+//!
+//! The card table only covers the chunks of the spaces that are created with the card table. Each
+//! such space records its chunks in a [`ChunkMap`] (see `CommonSpace::card_table_chunks`), so the
+//! GC does not need to look at the whole address range of a space to find the dirty cards.
+
+use atomic::Ordering;
+
+use crate::util::heap::chunk_map::{Chunk, ChunkMap, ChunkState};
+use crate::util::linear_scan::Region;
+use crate::util::metadata::side_metadata::SideMetadataSpec;
+use crate::util::Address;
+
+/// Log of the number of bytes in a card.
+pub const LOG_BYTES_IN_CARD: usize = 9;
+/// The number of bytes in a card.
+pub const BYTES_IN_CARD: usize = 1 << LOG_BYTES_IN_CARD;
+
+/// The card table value for a card that has no reference store since it was last scanned.
+pub const CARD_CLEAN: u8 = 0;
+/// The card table value for a card that may contain references into the nursery.
+pub const CARD_DIRTY: u8 = 1;
+
+/// The side metadata spec for the card table.
+pub const CARD_TABLE_SIDE_METADATA_SPEC: SideMetadataSpec =
+    crate::util::metadata::side_metadata::spec_defs::CARD_TABLE;
+
+/// The base address of the card table. A binding can mark the card for `addr` by storing
+/// `CARD_DIRTY` to `CARD_TABLE_SIDE_METADATA_ADDR + (addr >> LOG_BYTES_IN_CARD)`.
+pub const CARD_TABLE_SIDE_METADATA_ADDR: Address =
+    CARD_TABLE_SIDE_METADATA_SPEC.get_absolute_offset();
+
+/// The side metadata spec that records the chunks covered by the card table.
+pub const CARD_TABLE_CHUNK_MAP_SIDE_METADATA_SPEC: SideMetadataSpec =
+    crate::util::metadata::side_metadata::spec_defs::CARD_TABLE_CHUNK_MAP;
+
+/// Mark the card that covers the address as dirty.
+pub fn mark_card(addr: Address) {
+    CARD_TABLE_SIDE_METADATA_SPEC.store_atomic::<u8>(addr, CARD_DIRTY, Ordering::Relaxed);
+}
+
+/// Is the card that covers the address dirty?
+pub fn is_dirty(addr: Address) -> bool {
+    CARD_TABLE_SIDE_METADATA_SPEC.load_atomic::<u8>(addr, Ordering::Relaxed) == CARD_DIRTY
+}
+
+/// Clean the card that covers the address.
+pub fn clear(addr: Address) {
+    CARD_TABLE_SIDE_METADATA_SPEC.store_atomic::<u8>(addr, CARD_CLEAN, Ordering::Relaxed);
+}
+
+/// Create the chunk map for a space that is covered by the card table.
+pub(crate) fn new_chunk_map() -> ChunkMap {
+    ChunkMap::with_alloc_table(CARD_TABLE_CHUNK_MAP_SIDE_METADATA_SPEC)
+}
+
+/// Record the chunks in the memory range in the chunk map of a space, so the GC will look for dirty
+/// cards in them. The side metadata for the range should be mapped.
+pub(crate) fn register_chunks(chunk_map: &ChunkMap, start: Address, bytes: usize) {
+    let mut chunk = Chunk::from_unaligned_address(start);
+    while chunk.start() < start + bytes {
+        chunk_map.set(chunk, ChunkState::Allocated);
+        chunk = chunk.next();
+    }
+}
+
+/// Clean all the cards in a chunk.
+pub(crate) fn clear_chunk(chunk: Chunk) {
+    CARD_TABLE_SIDE_METADATA_SPEC.bzero_metadata(chunk.start(), Chunk::BYTES);
+}
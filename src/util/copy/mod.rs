@@ -7,6 +7,8 @@ use crate::policy::copyspace::CopySpace;
 use crate::policy::copyspace::CopySpaceCopyContext;
 use crate::policy::immix::ImmixSpace;
 use crate::policy::immix::{ImmixCopyContext, ImmixHybridCopyContext};
+use crate::policy::marksweepspace::native_ms::{MarkSweepCopyContext, MarkSweepSpace};
+use crate::policy::region::{RegionCopyContext, RegionSpace};
 use crate::policy::space::Space;
 use crate::util::object_forwarding;
 use crate::util::opaque_pointer::VMWorkerThread;
@@ -21,6 +23,8 @@ use enum_map::EnumMap;
 const MAX_COPYSPACE_COPY_ALLOCATORS: usize = 1;
 const MAX_IMMIX_COPY_ALLOCATORS: usize = 1;
 const MAX_IMMIX_HYBRID_COPY_ALLOCATORS: usize = 1;
+const MAX_MARKSWEEP_COPY_ALLOCATORS: usize = 1;
+const MAX_REGION_COPY_ALLOCATORS: usize = 1;
 
 type CopySpaceMapping<VM> = Vec<(CopySelector, &'static dyn Space<VM>)>;
 
@@ -56,6 +60,10 @@ pub struct GCWorkerCopyContext<VM: VMBinding> {
     pub immix: [MaybeUninit<ImmixCopyContext<VM>>; MAX_IMMIX_COPY_ALLOCATORS],
     /// Copy allocators for ImmixSpace
     pub immix_hybrid: [MaybeUninit<ImmixHybridCopyContext<VM>>; MAX_IMMIX_HYBRID_COPY_ALLOCATORS],
+    /// Copy allocators for MarkSweepSpace
+    pub marksweep: [MaybeUninit<MarkSweepCopyContext<VM>>; MAX_MARKSWEEP_COPY_ALLOCATORS],
+    /// Copy allocators for RegionSpace
+    pub region: [MaybeUninit<RegionCopyContext<VM>>; MAX_REGION_COPY_ALLOCATORS],
     /// The config for the plan
     config: CopyConfig<VM>,
 }
@@ -96,6 +104,12 @@ impl<VM: VMBinding> GCWorkerCopyContext<VM> {
                 unsafe { self.immix_hybrid[index as usize].assume_init_mut() }
                     .alloc_copy(original, bytes, align, offset)
             }
+            CopySelector::MarkSweep(index) => {
+                unsafe { self.marksweep[index as usize].assume_init_mut() }
+                    .alloc_copy(original, bytes, align, offset)
+            }
+            CopySelector::Region(index) => unsafe { self.region[index as usize].assume_init_mut() }
+                .alloc_copy(original, bytes, align, offset),
             CopySelector::Unused => unreachable!(),
         }
     }
@@ -127,6 +141,12 @@ impl<VM: VMBinding> GCWorkerCopyContext<VM> {
                 unsafe { self.immix_hybrid[index as usize].assume_init_mut() }
                     .post_copy(object, bytes)
             }
+            CopySelector::MarkSweep(index) => {
+                unsafe { self.marksweep[index as usize].assume_init_mut() }.post_copy(object, bytes)
+            }
+            CopySelector::Region(index) => {
+                unsafe { self.region[index as usize].assume_init_mut() }.post_copy(object, bytes)
+            }
             CopySelector::Unused => unreachable!(),
         }
     }
@@ -145,6 +165,12 @@ impl<VM: VMBinding> GCWorkerCopyContext<VM> {
                 CopySelector::ImmixHybrid(index) => {
                     unsafe { self.immix_hybrid[*index as usize].assume_init_mut() }.prepare()
                 }
+                CopySelector::MarkSweep(index) => {
+                    unsafe { self.marksweep[*index as usize].assume_init_mut() }.prepare()
+                }
+                CopySelector::Region(index) => {
+                    unsafe { self.region[*index as usize].assume_init_mut() }.prepare()
+                }
                 CopySelector::Unused => {}
             }
         }
@@ -164,6 +190,12 @@ impl<VM: VMBinding> GCWorkerCopyContext<VM> {
                 CopySelector::ImmixHybrid(index) => {
                     unsafe { self.immix_hybrid[*index as usize].assume_init_mut() }.release()
                 }
+                CopySelector::MarkSweep(index) => {
+                    unsafe { self.marksweep[*index as usize].assume_init_mut() }.release()
+                }
+                CopySelector::Region(index) => {
+                    unsafe { self.region[*index as usize].assume_init_mut() }.release()
+                }
                 CopySelector::Unused => {}
             }
         }
@@ -184,6 +216,8 @@ impl<VM: VMBinding> GCWorkerCopyContext<VM> {
             copy: unsafe { MaybeUninit::uninit().assume_init() },
             immix: unsafe { MaybeUninit::uninit().assume_init() },
             immix_hybrid: unsafe { MaybeUninit::uninit().assume_init() },
+            marksweep: unsafe { MaybeUninit::uninit().assume_init() },
+            region: unsafe { MaybeUninit::uninit().assume_init() },
             config,
         };
 
@@ -211,6 +245,20 @@ impl<VM: VMBinding> GCWorkerCopyContext<VM> {
                         space.downcast_ref::<ImmixSpace<VM>>().unwrap(),
                     ));
                 }
+                CopySelector::MarkSweep(index) => {
+                    ret.marksweep[index as usize].write(MarkSweepCopyContext::new(
+                        worker_tls,
+                        plan,
+                        space.downcast_ref::<MarkSweepSpace<VM>>().unwrap(),
+                    ));
+                }
+                CopySelector::Region(index) => {
+                    ret.region[index as usize].write(RegionCopyContext::new(
+                        worker_tls,
+                        plan,
+                        space.downcast_ref::<RegionSpace<VM>>().unwrap(),
+                    ));
+                }
                 CopySelector::Unused => unreachable!(),
             }
         }
@@ -224,6 +272,8 @@ impl<VM: VMBinding> GCWorkerCopyContext<VM> {
             copy: unsafe { MaybeUninit::uninit().assume_init() },
             immix: unsafe { MaybeUninit::uninit().assume_init() },
             immix_hybrid: unsafe { MaybeUninit::uninit().assume_init() },
+            marksweep: unsafe { MaybeUninit::uninit().assume_init() },
+            region: unsafe { MaybeUninit::uninit().assume_init() },
             config: CopyConfig::default(),
         }
     }
@@ -258,6 +308,8 @@ pub enum CopySelector {
     CopySpace(u8),
     Immix(u8),
     ImmixHybrid(u8),
+    MarkSweep(u8),
+    Region(u8),
     Unused,
 }
 
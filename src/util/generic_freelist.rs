@@ -60,6 +60,35 @@ pub trait GenericFreeList: Sized {
         FAILURE
     }
 
+    /// Grow a previously allocated contiguous lump of units to `size` units in place, by taking the
+    /// units from the free lump right after it. Return false if there are not enough free units
+    /// after the lump.
+    fn grow(&mut self, unit: i32, size: i32) -> bool {
+        debug_assert!(!self.get_free(unit));
+        let old_size = self.get_size(unit);
+        debug_assert!(size > old_size);
+        let right = self.get_right(unit);
+        if !self.is_coalescable(right) || self.alloc_from_unit(size - old_size, right) == FAILURE {
+            return false;
+        }
+        self.set_size(unit, size);
+        true
+    }
+
+    /// Shrink a previously allocated contiguous lump of units to `size` units in place, and free
+    /// the rest of the lump. Return the size of the freed lump after it is coalesced.
+    fn shrink(&mut self, unit: i32, size: i32) -> i32 {
+        debug_assert!(!self.get_free(unit));
+        let old_size = self.get_size(unit);
+        debug_assert!(size > 0 && size < old_size);
+        let tail = unit + size;
+        self.set_size(unit, size);
+        self.set_free(unit, false);
+        self.set_size(tail, old_size - size);
+        self.set_free(tail, false);
+        self.free(tail, true)
+    }
+
     /// Free a previously allocated contiguous lump of units
     fn free(&mut self, unit: i32, return_coalesced_size: bool) -> i32 {
         debug_assert!(!self.get_free(unit));
@@ -56,9 +56,12 @@ pub enum ChunkState {
 
 /// A byte-map to record all the allocated chunks.
 /// A plan can use this to maintain records for the chunks that they used, and the states of the chunks.
-/// Any plan that uses the chunk map should include the `ALLOC_TABLE` spec in their local sidemetadata specs
+/// Any plan that uses the chunk map should include the `ALLOC_TABLE` spec in their local sidemetadata specs,
+/// or the spec passed to `with_alloc_table()`.
 pub struct ChunkMap {
     chunk_range: Mutex<Range<Chunk>>,
+    /// The side metadata that records the state of each chunk.
+    alloc_table: SideMetadataSpec,
 }
 
 impl ChunkMap {
@@ -67,8 +70,15 @@ impl ChunkMap {
         crate::util::metadata::side_metadata::spec_defs::CHUNK_MARK;
 
     pub fn new() -> Self {
+        Self::with_alloc_table(Self::ALLOC_TABLE)
+    }
+
+    /// Create a chunk map that records the chunk states in the given side metadata. This allows
+    /// more than one chunk map to track the same chunk.
+    pub fn with_alloc_table(alloc_table: SideMetadataSpec) -> Self {
         Self {
             chunk_range: Mutex::new(Chunk::ZERO..Chunk::ZERO),
+            alloc_table,
         }
     }
 
@@ -79,7 +89,7 @@ impl ChunkMap {
             return;
         }
         // Update alloc byte
-        unsafe { Self::ALLOC_TABLE.store::<u8>(chunk.start(), state as u8) };
+        unsafe { self.alloc_table.store::<u8>(chunk.start(), state as u8) };
         // If this is a newly allcoated chunk, then expand the chunk range.
         if state == ChunkState::Allocated {
             debug_assert!(!chunk.start().is_zero());
@@ -98,7 +108,7 @@ impl ChunkMap {
 
     /// Get chunk state
     pub fn get(&self, chunk: Chunk) -> ChunkState {
-        let byte = unsafe { Self::ALLOC_TABLE.load::<u8>(chunk.start()) };
+        let byte = unsafe { self.alloc_table.load::<u8>(chunk.start()) };
         match byte {
             0 => ChunkState::Free,
             1 => ChunkState::Allocated,
@@ -337,6 +337,71 @@ impl<VM: VMBinding> FreeListPageResource<VM> {
         }
     }
 
+    /// Get the number of pages allocated at `first`.
+    pub fn get_allocated_pages(&self, first: Address) -> usize {
+        debug_assert!(conversions::is_page_aligned(first));
+        let page_offset = conversions::bytes_to_pages(first - self.start);
+        self.free_list.size(page_offset as _) as _
+    }
+
+    /// Grow the pages allocated at `first` to `pages` in place, if the pages after them are free.
+    /// `reserved_pages` have been reserved for the growth. Return true if the pages are grown.
+    pub fn grow_pages(
+        &self,
+        first: Address,
+        pages: usize,
+        reserved_pages: usize,
+        tls: VMThread,
+    ) -> bool {
+        debug_assert!(conversions::is_page_aligned(first));
+        let page_offset = conversions::bytes_to_pages(first - self.start);
+        let mut sync = self.sync.lock().unwrap();
+        // FIXME
+        #[allow(clippy::cast_ref_to_mut)]
+        let me = unsafe { &mut *(self as *const _ as *mut Self) };
+        let old_pages = self.free_list.size(page_offset as _) as usize;
+        debug_assert!(pages > old_pages);
+        if !me.free_list.grow(page_offset as _, pages as _) {
+            return false;
+        }
+        let grown_pages = pages - old_pages;
+        sync.pages_currently_on_freelist -= grown_pages;
+        drop(sync);
+
+        self.commit_pages(reserved_pages, grown_pages, tls);
+        if self.protect_memory_on_release {
+            // The pages were on the free list, so they have been mapped before.
+            self.munprotect(first + conversions::pages_to_bytes(old_pages), grown_pages);
+        }
+        true
+    }
+
+    /// Shrink the pages allocated at `first` to `pages` in place, and release the rest of them.
+    pub fn shrink_pages(&self, first: Address, pages: usize) {
+        debug_assert!(conversions::is_page_aligned(first));
+        let page_offset = conversions::bytes_to_pages(first - self.start);
+        let old_pages = self.free_list.size(page_offset as _) as usize;
+        debug_assert!(pages > 0 && pages < old_pages);
+        let released_pages = old_pages - pages;
+        let released = first + conversions::pages_to_bytes(pages);
+
+        if self.protect_memory_on_release {
+            self.mprotect(released, released_pages);
+        }
+
+        let mut sync = self.sync.lock().unwrap();
+        // FIXME
+        #[allow(clippy::cast_ref_to_mut)]
+        let me = unsafe { &mut *(self as *const _ as *mut Self) };
+        self.common.accounting.release(released_pages);
+        let freed = me.free_list.shrink(page_offset as _, pages as _);
+        sync.pages_currently_on_freelist += released_pages;
+        if !self.common.contiguous {
+            // only discontiguous spaces use chunks
+            me.release_free_chunks(released, freed as _, &mut sync);
+        }
+    }
+
     fn release_free_chunks(
         &mut self,
         freed_page: Address,
@@ -185,6 +185,37 @@ impl<VM: VMBinding> MonotonePageResource<VM> {
         }
     }
 
+    /// Get the memory allocated from this page resource, as a list of (start, bytes). The current
+    /// chunk is allocated up to the cursor, and the other discontiguous regions are fully allocated.
+    pub fn allocated_regions(&self) -> Vec<(Address, usize)> {
+        let sync = self.sync.lock().unwrap();
+        match sync.conditional {
+            MonotonePageResourceConditional::Contiguous { start, .. } => {
+                if sync.cursor > start {
+                    vec![(start, sync.cursor - start)]
+                } else {
+                    vec![]
+                }
+            }
+            MonotonePageResourceConditional::Discontiguous => {
+                let mut regions = vec![];
+                let mut region = self.common.get_head_discontiguous_region();
+                while !region.is_zero() {
+                    let end = if region == sync.current_chunk {
+                        sync.cursor
+                    } else {
+                        region + self.vm_map().get_contiguous_region_size(region)
+                    };
+                    if end > region {
+                        regions.push((region, end - region));
+                    }
+                    region = self.vm_map().get_next_contiguous_region(region);
+                }
+                regions
+            }
+        }
+    }
+
     /// Get highwater mark of current monotone space.
     pub fn cursor(&self) -> Address {
         self.sync.lock().unwrap().cursor
@@ -168,6 +168,29 @@ mod tests {
         assert_eq!(l.get_next(BOTTOM_SENTINEL), BOTTOM_SENTINEL);
     }
 
+    #[test]
+    fn grow_and_shrink_in_place() {
+        let mut l = IntArrayFreeList::new(LIST_SIZE, LIST_SIZE as _, 1);
+        assert_eq!(l.alloc(2), FIRST_UNIT);
+
+        // Take two units from the free lump after the allocation.
+        assert!(l.grow(FIRST_UNIT, 4));
+        assert_eq!(l.get_size(FIRST_UNIT), 4);
+        assert!(!l.is_free(FIRST_UNIT));
+        assert!(l.is_free(LAST_UNIT));
+        // Only one unit is left after the allocation.
+        assert!(!l.grow(FIRST_UNIT, 6));
+        assert_eq!(l.get_size(FIRST_UNIT), 4);
+
+        // The freed units are coalesced with the free unit after them.
+        assert_eq!(l.shrink(FIRST_UNIT, 1), 4);
+        assert_eq!(l.get_size(FIRST_UNIT), 1);
+        assert!(!l.is_free(FIRST_UNIT));
+        assert_eq!(l.get_size(1), 4);
+        assert!(l.is_free(1));
+        assert_eq!(l.alloc(4), 1);
+    }
+
     #[test]
     #[should_panic]
     fn free_list_access_out_of_bounds() {
@@ -2,9 +2,16 @@ use crate::util::alloc::AllocationError;
 use crate::util::opaque_pointer::*;
 use crate::util::Address;
 use crate::vm::{Collection, VMBinding};
-use libc::{PROT_EXEC, PROT_NONE, PROT_READ, PROT_WRITE};
+use libc::{PROT_NONE, PROT_READ, PROT_WRITE};
 use std::io::{Error, Result};
 
+/// The protection of the memory that MMTk maps. With the feature `code_space`, only the code space
+/// is executable (see [`crate::policy::codespace`]), and the other memory is not.
+#[cfg(not(feature = "code_space"))]
+const MMAP_PROT: libc::c_int = PROT_READ | PROT_WRITE | libc::PROT_EXEC;
+#[cfg(feature = "code_space")]
+const MMAP_PROT: libc::c_int = PROT_READ | PROT_WRITE;
+
 pub fn result_is_mapped(result: Result<()>) -> bool {
     match result {
         Ok(_) => false,
@@ -13,8 +20,46 @@ pub fn result_is_mapped(result: Result<()>) -> bool {
 }
 
 pub fn zero(start: Address, len: usize) {
+    set(start, 0, len);
+}
+
+/// Set every byte of the memory to `val`.
+pub fn set(start: Address, val: u8, len: usize) {
     let ptr = start.to_mut_ptr();
-    wrap_libc_call(&|| unsafe { libc::memset(ptr, 0, len) }, ptr).unwrap()
+    wrap_libc_call(&|| unsafe { libc::memset(ptr, val as i32, len) }, ptr).unwrap()
+}
+
+/// Zero memory with non-temporal stores, which write to memory without bringing it into the
+/// cache. This is useful for zeroing memory that will not be used soon. On architectures without
+/// non-temporal stores, this is the same as [`zero`].
+pub fn zero_nontemporal(start: Address, len: usize) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        use std::arch::x86_64::{__m128i, _mm_setzero_si128, _mm_sfence, _mm_stream_si128};
+        const STORE_BYTES: usize = std::mem::size_of::<__m128i>();
+
+        let end = start + len;
+        let aligned_start = start.align_up(STORE_BYTES);
+        let aligned_end = end.align_down(STORE_BYTES);
+        if aligned_start < aligned_end {
+            // Zero the unaligned head and tail with normal stores.
+            zero(start, aligned_start - start);
+            zero(aligned_end, end - aligned_end);
+            // SSE2 is always available on x86_64.
+            unsafe {
+                let zeros = _mm_setzero_si128();
+                let mut cursor = aligned_start;
+                while cursor < aligned_end {
+                    _mm_stream_si128(cursor.to_mut_ptr::<__m128i>(), zeros);
+                    cursor += STORE_BYTES;
+                }
+                // Non-temporal stores are weakly ordered. Make them visible before we return.
+                _mm_sfence();
+            }
+            return;
+        }
+    }
+    zero(start, len)
 }
 
 /// Demand-zero mmap:
@@ -28,7 +73,7 @@ pub fn zero(start: Address, len: usize) {
 /// may corrupt others' data.
 #[allow(clippy::let_and_return)] // Zeroing is not neceesary for some OS/s
 pub unsafe fn dzmmap(start: Address, size: usize) -> Result<()> {
-    let prot = PROT_READ | PROT_WRITE | PROT_EXEC;
+    let prot = MMAP_PROT;
     let flags = libc::MAP_ANON | libc::MAP_PRIVATE | libc::MAP_FIXED;
     let ret = mmap_fixed(start, size, prot, flags);
     // We do not need to explicitly zero for Linux (memory is guaranteed to be zeroed)
@@ -51,7 +96,7 @@ const MMAP_FLAGS: libc::c_int = libc::MAP_ANON | libc::MAP_PRIVATE | libc::MAP_F
 /// This function will not overwrite existing memory mapping, and it will result Err if there is an existing mapping.
 #[allow(clippy::let_and_return)] // Zeroing is not neceesary for some OS/s
 pub fn dzmmap_noreplace(start: Address, size: usize) -> Result<()> {
-    let prot = PROT_READ | PROT_WRITE | PROT_EXEC;
+    let prot = MMAP_PROT;
     let flags = MMAP_FLAGS;
     let ret = mmap_fixed(start, size, prot, flags);
     // We do not need to explicitly zero for Linux (memory is guaranteed to be zeroed)
@@ -85,6 +130,109 @@ pub fn mmap_fixed(
     )
 }
 
+/// Move the mapping of `old_size` bytes at `from` to `to`, and resize it to `new_size` bytes. This
+/// replaces any existing mapping at `to`, and leaves `from` unmapped.
+#[cfg(target_os = "linux")]
+pub fn mremap_fixed(from: Address, old_size: usize, to: Address, new_size: usize) -> Result<()> {
+    let ptr = to.to_mut_ptr();
+    wrap_libc_call(
+        &|| unsafe {
+            libc::mremap(
+                from.to_mut_ptr(),
+                old_size,
+                new_size,
+                libc::MREMAP_MAYMOVE | libc::MREMAP_FIXED,
+                to.to_mut_ptr::<libc::c_void>(),
+            )
+        },
+        ptr,
+    )
+}
+
+/// Map `size` bytes of a file privately, so the writes to the memory are not written back to the
+/// file. If `start` is given, the file is mapped at `start`, and it is an error if the memory at
+/// `start` is already mapped. Otherwise the OS chooses the address. Return the start address of
+/// the mapping.
+pub fn mmap_file(file: &std::fs::File, start: Option<Address>, size: usize) -> Result<Address> {
+    use std::os::unix::io::AsRawFd;
+    #[cfg(target_os = "linux")]
+    let fixed = libc::MAP_FIXED_NOREPLACE;
+    // MAP_FIXED would replace the existing mappings. Without it, `start` is a hint that the OS
+    // follows if the memory is free, and the mapping is placed elsewhere otherwise.
+    #[cfg(not(target_os = "linux"))]
+    let fixed = 0;
+    let (addr, flags) = match start {
+        Some(start) => (start, libc::MAP_PRIVATE | fixed),
+        None => (Address::ZERO, libc::MAP_PRIVATE),
+    };
+    let ret = unsafe {
+        libc::mmap(
+            addr.to_mut_ptr(),
+            size,
+            MMAP_PROT,
+            flags,
+            file.as_raw_fd(),
+            0,
+        )
+    };
+    if ret == libc::MAP_FAILED {
+        return Err(Error::last_os_error());
+    }
+    let mapped = Address::from_mut_ptr(ret);
+    // The memory at `start` is already mapped. Old kernels do not know MAP_FIXED_NOREPLACE, and
+    // treat it as a hint.
+    if start.map_or(false, |start| start != mapped) {
+        munmap(mapped, size)?;
+        return Err(Error::from_raw_os_error(libc::EEXIST));
+    }
+    Ok(mapped)
+}
+
+/// Create an anonymous file that lives in memory. The same memory can be mapped at more than one
+/// address by mapping the file with [`mmap_shared`].
+#[cfg(target_os = "linux")]
+pub fn memfd_create(name: &str) -> Result<std::fs::File> {
+    use std::os::unix::io::FromRawFd;
+    let name = std::ffi::CString::new(name).unwrap();
+    let fd = unsafe { libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC) };
+    if fd == -1 {
+        return Err(Error::last_os_error());
+    }
+    Ok(unsafe { std::fs::File::from_raw_fd(fd) })
+}
+
+/// Map `size` bytes of a file from `offset` as shared memory with the protection `prot`, so the
+/// writes to one mapping of the file are visible in the other mappings of it. If `start` is given,
+/// the file is mapped at `start`, replacing any existing mapping. Otherwise the OS chooses the
+/// address. Return the start address of the mapping.
+pub fn mmap_shared(
+    file: &std::fs::File,
+    offset: usize,
+    start: Option<Address>,
+    size: usize,
+    prot: libc::c_int,
+) -> Result<Address> {
+    use std::os::unix::io::AsRawFd;
+    let (addr, flags) = match start {
+        Some(start) => (start, libc::MAP_SHARED | libc::MAP_FIXED),
+        None => (Address::ZERO, libc::MAP_SHARED),
+    };
+    let ret = unsafe {
+        libc::mmap(
+            addr.to_mut_ptr(),
+            size,
+            prot,
+            flags,
+            file.as_raw_fd(),
+            offset as libc::off_t,
+        )
+    };
+    if ret == libc::MAP_FAILED {
+        return Err(Error::last_os_error());
+    }
+    Ok(Address::from_mut_ptr(ret))
+}
+
 pub fn munmap(start: Address, size: usize) -> Result<()> {
     wrap_libc_call(&|| unsafe { libc::munmap(start.to_mut_ptr(), size) }, 0)
 }
@@ -150,7 +298,7 @@ pub fn panic_if_unmapped(_start: Address, _size: usize) {
 
 pub fn munprotect(start: Address, size: usize) -> Result<()> {
     wrap_libc_call(
-        &|| unsafe { libc::mprotect(start.to_mut_ptr(), size, PROT_READ | PROT_WRITE | PROT_EXEC) },
+        &|| unsafe { libc::mprotect(start.to_mut_ptr(), size, MMAP_PROT) },
         0,
     )
 }
@@ -162,6 +310,33 @@ pub fn mprotect(start: Address, size: usize) -> Result<()> {
     )
 }
 
+/// Make the memory read-only. Writing to the memory will fault.
+pub fn mprotect_readonly(start: Address, size: usize) -> Result<()> {
+    wrap_libc_call(
+        &|| unsafe { libc::mprotect(start.to_mut_ptr(), size, PROT_READ) },
+        0,
+    )
+}
+
+/// Make the memory readable and executable. Writing to the memory will fault.
+pub fn mprotect_executable(start: Address, size: usize) -> Result<()> {
+    wrap_libc_call(
+        &|| unsafe { libc::mprotect(start.to_mut_ptr(), size, PROT_READ | libc::PROT_EXEC) },
+        0,
+    )
+}
+
+/// Free the memory of a shared writable mapping and its backing store, e.g. the pages of an
+/// in-memory file (see [`memfd_create`]). The memory reads as zeros afterwards in all the mappings
+/// of the file.
+#[cfg(target_os = "linux")]
+pub fn madvise_remove(start: Address, size: usize) -> Result<()> {
+    wrap_libc_call(
+        &|| unsafe { libc::madvise(start.to_mut_ptr(), size, libc::MADV_REMOVE) },
+        0,
+    )
+}
+
 fn wrap_libc_call<T: PartialEq>(f: &dyn Fn() -> T, expect: T) -> Result<()> {
     let ret = f();
     if ret == expect {
@@ -362,4 +537,15 @@ mod tests {
         let total = get_system_total_memory();
         println!("Total memory: {:?}", total);
     }
+
+    #[test]
+    fn test_zero_nontemporal() {
+        let mut buffer = vec![0xffu8; 256];
+        let base = Address::from_mut_ptr(buffer.as_mut_ptr());
+        // Use an unaligned range so both the normal stores and the non-temporal stores are used.
+        zero_nontemporal(base + 3usize, 200);
+        assert!(buffer[..3].iter().all(|b| *b == 0xff));
+        assert!(buffer[3..203].iter().all(|b| *b == 0));
+        assert!(buffer[203..].iter().all(|b| *b == 0xff));
+    }
 }
@@ -28,6 +28,34 @@ impl VMGlobalLogBitSpec {
         }
     }
 
+    /// Mark the objects of `bytes` bytes in the order they were allocated (e.g. by `alloc_many()`)
+    /// as unlogged. If the log bit is in the side metadata, the bits for each run of
+    /// contiguous objects are set in bulk, including the bits for the addresses inside the objects.
+    /// Those bits are never read, as the log bit is only checked for object references.
+    pub fn mark_objects_as_unlogged<VM: VMBinding>(
+        &self,
+        objects: &[ObjectReference],
+        bytes: usize,
+        order: Ordering,
+    ) {
+        match self.as_spec() {
+            MetadataSpec::InHeader(_) => {
+                for object in objects {
+                    self.mark_as_unlogged::<VM>(*object, order);
+                }
+            }
+            MetadataSpec::OnSide(spec) => {
+                crate::policy::sft::for_each_contiguous_run::<VM>(objects, bytes, |run| {
+                    let start = run[0].to_address::<VM>();
+                    // Include the bit for the last object.
+                    let end = run[run.len() - 1].to_address::<VM>()
+                        + (1usize << spec.log_bytes_in_region);
+                    spec.bset_metadata(start, end - start);
+                })
+            }
+        }
+    }
+
     pub fn is_unlogged<VM: VMBinding>(&self, object: ObjectReference, order: Ordering) -> bool {
         self.load_atomic::<VM, u8>(object, None, order) == 1
     }
@@ -60,6 +60,12 @@ define_side_metadata_specs!(
     MS_ACTIVE_CHUNK = (global: true, log_num_of_bits: 3, log_bytes_in_region: LOG_BYTES_IN_CHUNK),
     // Track the index in SFT map for a chunk (only used for SFT sparse chunk map)
     SFT_DENSE_CHUNK_MAP_INDEX   = (global: true, log_num_of_bits: 3, log_bytes_in_region: LOG_BYTES_IN_CHUNK),
+    // Reference counts for reference counting plans (2-bit sticky counts)
+    RC_COUNT        = (global: true, log_num_of_bits: 1, log_bytes_in_region: LOG_MIN_OBJECT_SIZE as usize),
+    // Card table for the card table write barrier (one byte per card)
+    CARD_TABLE      = (global: true, log_num_of_bits: 3, log_bytes_in_region: crate::util::card_table::LOG_BYTES_IN_CARD),
+    // The chunks covered by the card table (one byte per chunk)
+    CARD_TABLE_CHUNK_MAP = (global: true, log_num_of_bits: 3, log_bytes_in_region: LOG_BYTES_IN_CHUNK),
 );
 
 // This defines all LOCAL side metadata used by mmtk-core.
@@ -91,11 +97,21 @@ define_side_metadata_specs!(
     MS_BLOCK_TLS    = (global: false, log_num_of_bits: LOG_BITS_IN_ADDRESS, log_bytes_in_region: crate::policy::marksweepspace::native_ms::Block::LOG_BYTES),
     // First cell of free list in block for native mimalloc
     MS_FREE         = (global: false, log_num_of_bits: LOG_BITS_IN_ADDRESS, log_bytes_in_region: crate::policy::marksweepspace::native_ms::Block::LOG_BYTES),
+    // Live bytes in blocks, counted in the last GC for the defragmentation of native mimalloc
+    MS_BLOCK_LIVE_BYTES = (global: false, log_num_of_bits: 5, log_bytes_in_region: crate::policy::marksweepspace::native_ms::Block::LOG_BYTES),
     // The following specs are only used for manual malloc/free
     // First cell of local free list in block for native mimalloc
     MS_LOCAL_FREE   = (global: false, log_num_of_bits: LOG_BITS_IN_ADDRESS, log_bytes_in_region: crate::policy::marksweepspace::native_ms::Block::LOG_BYTES),
     // First cell of thread free list in block for native mimalloc
     MS_THREAD_FREE  = (global: false, log_num_of_bits: LOG_BITS_IN_ADDRESS, log_bytes_in_region: crate::policy::marksweepspace::native_ms::Block::LOG_BYTES),
+    // Region states for the region space
+    REGION_STATE    = (global: false, log_num_of_bits: 3, log_bytes_in_region: crate::policy::region::HeapRegion::LOG_BYTES),
+    // Live bytes in regions, counted by the last full heap mark in the region space
+    REGION_LIVE_BYTES = (global: false, log_num_of_bits: 5, log_bytes_in_region: crate::policy::region::HeapRegion::LOG_BYTES),
+    // Forwarding offset table for mark compact: the compacted address of the first live object in each forwarding block
+    MC_FORWARDING_TABLE = (global: false, log_num_of_bits: LOG_BITS_IN_ADDRESS, log_bytes_in_region: crate::policy::markcompactspace::LOG_BYTES_IN_FORWARDING_BLOCK),
+    // Pages of padding before a large object with a large alignment, recorded at the page of the object start
+    LOS_PADDING_PAGES = (global: false, log_num_of_bits: LOG_BITS_IN_ADDRESS, log_bytes_in_region: LOG_BYTES_IN_PAGE as usize),
 );
 
 #[cfg(test)]
@@ -11,6 +11,8 @@ pub mod address;
 /// Allocators
 // This module is made public so the binding could implement allocator slowpaths if they would like to.
 pub mod alloc;
+/// Card table for the card table write barrier.
+pub mod card_table;
 /// Constants used in MMTk
 pub mod constants;
 /// Calculation, conversion and rounding for memory related numbers.
@@ -53,6 +55,8 @@ pub mod malloc;
 pub mod metadata;
 /// Forwarding word in object copying.
 pub(crate) mod object_forwarding;
+/// Reference counts for reference counting plans.
+pub(crate) mod ref_count;
 /// Utilities funcitons for Rust
 pub(crate) mod rust_util;
 /// Sanity checker for GC.
@@ -6,11 +6,23 @@ use std::fmt::Debug;
 use std::str::FromStr;
 use strum_macros::EnumString;
 
-#[derive(Copy, Clone, EnumString, Debug)]
+#[derive(Copy, Clone, EnumString, Debug, PartialEq, Eq)]
+/// How the bump pointer allocators (including the Immix allocator) zero the memory they hand out
+/// to mutators.
 pub enum NurseryZeroingOptions {
+    /// Zero each object when it is allocated. MMTk zeroes the objects allocated in its allocation
+    /// functions. A binding that inlines the allocation fast-path must zero the objects allocated
+    /// in the fast-path itself (see `AllocatorFastPath::needs_zeroing`).
     Temporal,
+    /// Zero a thread local buffer in bulk when the allocator acquires it.
+    Bulk,
+    /// Like `Bulk`, but zero with non-temporal stores that do not bring the buffer into the cache.
     Nontemporal,
+    /// A helper thread zeroes the next thread local buffer while the mutator allocates in the
+    /// current one.
     Concurrent,
+    /// Like `Bulk`, but use non-temporal stores for large buffers, which are unlikely to stay in
+    /// the cache until the mutator uses them.
     Adaptive,
 }
 
@@ -20,11 +32,16 @@ pub enum PlanSelector {
     SemiSpace,
     GenCopy,
     GenImmix,
+    GenMarkSweep,
     MarkSweep,
     PageProtect,
     Immix,
     MarkCompact,
     StickyImmix,
+    StickyMarkSweep,
+    LXR,
+    ConcurrentImmix,
+    G1,
 }
 
 /// MMTk option for perf events
@@ -657,6 +674,11 @@ options! {
     // to have a Fixed nursery size of 8192 bytes
     nursery:               NurserySize          [env_var: true, command_line: true]  [|v: &NurserySize| v.min > 0 && v.max > 0 && v.max >= v.min]
         = NurserySize { kind: NurseryKind::Bounded, min: DEFAULT_MIN_NURSERY, max: DEFAULT_MAX_NURSERY },
+    // The pause time target in milliseconds for the plans that collect part of the heap, e.g. the
+    // generational plans and the sticky plans. They measure their pause times, and size the nursery and
+    // the Immix defragmentation to fit in the target, within the bounds of the nursery size. 0 means
+    // there is no target.
+    pause_target_ms:       usize                [env_var: true, command_line: true]  [always_valid] = 0,
     // Should a major GC be performed when a system GC is required?
     full_heap_system_gc:   bool                 [env_var: true, command_line: true]  [always_valid] = false,
     // Should we shrink/grow the heap to adjust to application working set? (not supported)
@@ -669,8 +691,8 @@ options! {
     // We disable weak reference processing by default, as we are still working on it. This will be changed to `false`
     // once weak reference processing is implemented properly.
     no_reference_types:    bool                 [env_var: true, command_line: true]  [always_valid] = true,
-    // The zeroing approach to use for new object allocations. Affects each plan differently. (not supported)
-    nursery_zeroing:       NurseryZeroingOptions[env_var: true, command_line: true]  [always_valid] = NurseryZeroingOptions::Temporal,
+    // The zeroing approach to use for new object allocations in the bump pointer and Immix allocators.
+    nursery_zeroing:       NurseryZeroingOptions[env_var: true, command_line: true]  [always_valid] = NurseryZeroingOptions::Bulk,
     // How frequent (every X bytes) should we do a stress GC?
     stress_factor:         usize                [env_var: true, command_line: true]  [always_valid] = DEFAULT_STRESS_FACTOR,
     // How frequent (every X bytes) should we run analysis (a STW event that collects data)
@@ -681,10 +703,25 @@ options! {
     // But this should have no obvious mutator overhead, and can be used to test GC performance along with a larger stress
     // factor (e.g. tens of metabytes).
     precise_stress:        bool                 [env_var: true, command_line: true]  [always_valid] = true,
-    // The size of vmspace.
+    // How frequent (every X bytes on average) should we sample an allocation for heap profiling? The distance between
+    // two samples is randomized, and each sampled allocation is reported to the binding with `Collection::sample_allocation()`.
+    // 0 means allocation sampling is disabled. Allocation sampling is also disabled for precise stress tests, which
+    // manipulate the bump pointer limit in the same way.
+    alloc_sample_interval: usize                [env_var: true, command_line: true]  [always_valid] = 0,
+    // How frequent (every X bytes) should a generational plan sample an allocation made with `alloc_with_site()` to
+    // track the survival rate of its allocation site? A sampled object is checked in the next nursery GC.
+    pretenure_sample_bytes: usize               [env_var: true, command_line: true]  [|v: &usize| *v > 0] = 4096,
+    // The survival rate (in percent) in nursery GCs above which an allocation site is pretenured, i.e. its objects
+    // are allocated in the mature space of a generational plan.
+    pretenure_threshold:   usize                [env_var: true, command_line: true]  [|v: &usize| *v <= 100] = 80,
+    // The start address of a region of vmspace outside the MMTk heap range, which is mapped and populated by the VM before
+    // MMTk is initialized. If this is 0, the region is an address range in the MMTk heap range instead. Other regions can
+    // be added with memory_manager::register_vm_space_region() or memory_manager::map_vm_space_image().
+    vm_space_start:        usize                [env_var: true, command_line: true]  [always_valid] = 0,
+    // The size of the region of vmspace specified by vm_space_start. There is no such region if this is 0.
     // FIXME: This value is set for JikesRVM. We need a proper way to set options.
     //   We need to set these values programmatically in VM specific code.
-    vm_space_size:         usize                [env_var: true, command_line: true] [|v: &usize| *v > 0]    = 0x7cc_cccc,
+    vm_space_size:         usize                [env_var: true, command_line: true]  [always_valid] = 0x7cc_cccc,
     // Perf events to measure
     // Semicolons are used to separate events
     // Each event is in the format of event_name,pid,cpu (see man perf_event_open for what pid and cpu mean).
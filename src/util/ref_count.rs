@@ -0,0 +1,67 @@
+use atomic::Ordering;
+
+use crate::util::metadata::side_metadata::SideMetadataSpec;
+use crate::util::ObjectReference;
+use crate::vm::VMBinding;
+
+/// A reference count is required per min-object-size aligned address, rather than per object, and can only exist as side metadata.
+pub(crate) const RC_SIDE_METADATA_SPEC: SideMetadataSpec =
+    crate::util::metadata::side_metadata::spec_defs::RC_COUNT;
+
+/// The maximum reference count. Reference counts are sticky: once a count reaches this value,
+/// it is no longer incremented or decremented, and the object can only be reclaimed by tracing.
+pub const MAX_REF_COUNT: u8 = 3;
+
+/// Get the reference count of an object.
+pub fn get_count<VM: VMBinding>(object: ObjectReference) -> u8 {
+    RC_SIDE_METADATA_SPEC.load_atomic::<u8>(object.to_address::<VM>(), Ordering::SeqCst)
+}
+
+/// Check if an object has a non-zero reference count.
+pub fn is_counted<VM: VMBinding>(object: ObjectReference) -> bool {
+    get_count::<VM>(object) != 0
+}
+
+/// Atomically set the reference count for an object.
+pub fn set_count<VM: VMBinding>(object: ObjectReference, count: u8) {
+    debug_assert!(count <= MAX_REF_COUNT);
+    RC_SIDE_METADATA_SPEC.store_atomic::<u8>(object.to_address::<VM>(), count, Ordering::SeqCst);
+}
+
+/// Atomically increment the reference count for an object, and return the old count.
+/// A stuck count is not changed.
+pub fn inc<VM: VMBinding>(object: ObjectReference) -> u8 {
+    RC_SIDE_METADATA_SPEC
+        .fetch_update_atomic::<u8, _>(
+            object.to_address::<VM>(),
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+            |count| {
+                if count == MAX_REF_COUNT {
+                    None
+                } else {
+                    Some(count + 1)
+                }
+            },
+        )
+        .unwrap_or_else(|count| count)
+}
+
+/// Atomically decrement the reference count for an object, and return the old count.
+/// A zero count or a stuck count is not changed.
+pub fn dec<VM: VMBinding>(object: ObjectReference) -> u8 {
+    RC_SIDE_METADATA_SPEC
+        .fetch_update_atomic::<u8, _>(
+            object.to_address::<VM>(),
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+            |count| {
+                if count == 0 || count == MAX_REF_COUNT {
+                    None
+                } else {
+                    Some(count - 1)
+                }
+            },
+        )
+        .unwrap_or_else(|count| count)
+}
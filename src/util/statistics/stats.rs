@@ -1,9 +1,12 @@
 use crate::mmtk::MMTK;
+use crate::plan::AllocationSemantics;
 use crate::util::options::Options;
 use crate::util::statistics::counter::*;
 use crate::util::statistics::Timer;
+use crate::util::VMMutatorThread;
 use crate::vm::VMBinding;
 
+use enum_map::EnumMap;
 #[cfg(feature = "perf_counter")]
 use pfm::Perfmon;
 use std::collections::HashMap;
@@ -55,8 +58,14 @@ pub struct Stats {
     pub shared: Arc<SharedStats>,
     counters: Mutex<Vec<Arc<Mutex<dyn Counter + Send>>>>,
     exceeded_phase_limit: AtomicBool,
+    /// The bytes allocated by each mutator for each allocation semantics, recorded when a GC
+    /// releases the mutator and when the mutator is destroyed. The flag tells if the mutator has
+    /// been destroyed.
+    allocated_bytes: Mutex<Vec<AllocatedBytesRecord>>,
 }
 
+type AllocatedBytesRecord = (VMMutatorThread, EnumMap<AllocationSemantics, usize>, bool);
+
 impl Stats {
     #[allow(unused)]
     pub fn new(options: &Options) -> Self {
@@ -103,6 +112,7 @@ impl Stats {
             shared,
             counters: Mutex::new(counters),
             exceeded_phase_limit: AtomicBool::new(false),
+            allocated_bytes: Mutex::new(vec![]),
         }
     }
 
@@ -211,9 +221,52 @@ impl Stats {
         print!("Total time: ");
         self.total_time.lock().unwrap().print_total(None);
         println!(" ms");
+        self.print_allocated_bytes();
         println!("------------------------------ End MMTk Statistics -----------------------------")
     }
 
+    /// Record the bytes allocated by a mutator. This is called when the mutator is stopped, or by
+    /// the mutator thread, so the counters of its allocators can be read.
+    pub(crate) fn record_allocated_bytes(
+        &self,
+        tls: VMMutatorThread,
+        bytes: EnumMap<AllocationSemantics, usize>,
+        destroyed: bool,
+    ) {
+        let mut records = self.allocated_bytes.lock().unwrap();
+        // A thread may be reused for another mutator after its mutator is destroyed.
+        match records.iter_mut().find(|(t, _, d)| *t == tls && !*d) {
+            Some(record) => *record = (tls, bytes, destroyed),
+            None => records.push((tls, bytes, destroyed)),
+        }
+    }
+
+    /// Print the bytes allocated by each mutator for each allocation semantics. We do not read the
+    /// counters of the running mutators. The bytes are recorded in the last GC, or when a mutator
+    /// was destroyed.
+    fn print_allocated_bytes(&self) {
+        println!("Allocated bytes per mutator (as of the last GC):");
+        print!("mutator\t");
+        let mut totals: EnumMap<AllocationSemantics, usize> = EnumMap::default();
+        for (semantics, _) in totals.iter() {
+            print!("{:?}\t", semantics);
+        }
+        println!();
+        for (tls, bytes, _) in self.allocated_bytes.lock().unwrap().iter() {
+            print!("{:?}\t", tls.0 .0);
+            for (semantics, total) in totals.iter_mut() {
+                *total += bytes[semantics];
+                print!("{}\t", bytes[semantics]);
+            }
+            println!();
+        }
+        print!("total\t");
+        for (_, total) in totals.iter() {
+            print!("{}\t", total);
+        }
+        println!();
+    }
+
     pub fn print_column_names(&self, scheduler_stat: &HashMap<String, String>) {
         print!("GC\t");
         let counter = self.counters.lock().unwrap();
@@ -79,6 +79,16 @@ impl TreadMill {
         self.to_space.lock().unwrap().insert(object);
     }
 
+    /// Remove an object that is freed outside a GC.
+    pub fn remove(&self, object: ObjectReference, is_in_nursery: bool) {
+        let removed = if is_in_nursery {
+            self.alloc_nursery.lock().unwrap().remove(&object)
+        } else {
+            self.to_space.lock().unwrap().remove(&object)
+        };
+        debug_assert!(removed, "object ({}) is not in the treadmill", object);
+    }
+
     pub fn is_to_space_empty(&self) -> bool {
         self.to_space.lock().unwrap().is_empty()
     }
@@ -1,6 +1,7 @@
 use crate::plan::MutatorContext;
 use crate::util::alloc::AllocationError;
 use crate::util::opaque_pointer::*;
+use crate::util::Address;
 use crate::vm::VMBinding;
 use crate::{scheduler::*, Mutator};
 
@@ -83,6 +84,9 @@ pub trait Collection<VM: VMBinding> {
     ///    application. MMTk expects the binding to notify the VM about this OOM. MMTk makes no
     ///    assumptions about whether the VM will continue executing or abort immediately.
     ///
+    /// MMTk also reports an allocation into the sealed read-only space through this method, in
+    /// which case the allocation returns a zero address.
+    ///
     /// See [`AllocationError`] for more information.
     ///
     /// Arguments:
@@ -120,4 +124,18 @@ pub trait Collection<VM: VMBinding> {
     /// Arguments:
     /// * `tls_worker`: The thread pointer for the worker thread performing this call.
     fn post_forwarding(_tls: VMWorkerThread) {}
+
+    /// Report an allocation that is sampled for heap profiling, similar to JVMTI
+    /// `SampledObjectAlloc`. MMTk samples one allocation every `alloc_sample_interval` bytes on
+    /// average (see [`crate::util::options::Options`]). This is never called if the option is 0.
+    ///
+    /// This is called by the allocator in the allocation that is sampled, before the allocation
+    /// returns to the binding, so the object is not initialized yet. The binding may record the
+    /// address, and inspect the object later. It must not allocate with the same mutator.
+    ///
+    /// Arguments:
+    /// * `tls`: The thread pointer for the mutator that allocated the object.
+    /// * `addr`: The address returned by the allocation.
+    /// * `size`: The size of the allocation in bytes.
+    fn sample_allocation(_tls: VMMutatorThread, _addr: Address, _size: usize) {}
 }
@@ -59,7 +59,8 @@ where
     const ALIGNMENT_VALUE: usize = 0xdead_beef;
     /// Allowed minimal alignment in bytes.
     const MIN_ALIGNMENT: usize = 1 << DEFAULT_LOG_MIN_ALIGNMENT;
-    /// Allowed maximum alignment in bytes.
+    /// Allowed maximum alignment in bytes for most objects. [`crate::memory_manager::alloc`] also
+    /// accepts larger alignments, which may cost more padding.
     const MAX_ALIGNMENT: usize = 1 << DEFAULT_LOG_MAX_ALIGNMENT;
     /// Does the binding use a non-zero allocation offset? If this is false, we expect the binding
     /// to always use offset === 0 for allocation, and we are able to do some optimization if we know
@@ -1,6 +1,7 @@
 use crate::plan::Mutator;
 use crate::scheduler::GCWorker;
 use crate::util::ObjectReference;
+use crate::util::VMMutatorThread;
 use crate::util::VMWorkerThread;
 use crate::vm::edge_shape::Edge;
 use crate::vm::VMBinding;
@@ -186,6 +187,26 @@ pub trait Scanning<VM: VMBinding> {
         unreachable!("scan_object_and_trace_edges() will not be called when support_edge_enqueue() is always true.")
     }
 
+    /// Delegated scanning of an object on behalf of a mutator thread, visiting each reference field
+    /// encountered. Barriers that take a snapshot of the fields of an object before the mutator
+    /// overwrites them call this method in the mutator thread. MMTk only loads from the edges.
+    ///
+    /// Only the plans that need such a snapshot call this method (currently LXR). By default, it
+    /// calls `scan_object` with the thread of the mutator. A binding should override it if
+    /// `scan_object` can only be called from a GC worker.
+    ///
+    /// Arguments:
+    /// * `tls`: The VM-specific thread-local storage for the mutator.
+    /// * `object`: The object to be scanned.
+    /// * `edge_visitor`: Called back for each edge.
+    fn scan_object_for_mutator<EV: EdgeVisitor<VM::VMEdge>>(
+        tls: VMMutatorThread,
+        object: ObjectReference,
+        edge_visitor: &mut EV,
+    ) {
+        Self::scan_object(VMWorkerThread(tls.0), object, edge_visitor)
+    }
+
     /// MMTk calls this method at the first time during a collection that thread's stacks
     /// have been scanned. This can be used (for example) to clean up
     /// obsolete compiled methods that are no longer being executed.
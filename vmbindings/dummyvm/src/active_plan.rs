@@ -2,9 +2,31 @@ use mmtk::Plan;
 use mmtk::vm::ActivePlan;
 use mmtk::util::opaque_pointer::*;
 use mmtk::Mutator;
+use std::sync::Mutex;
 use crate::DummyVM;
 use crate::SINGLETON;
 
+struct MutatorPtr(*mut Mutator<DummyVM>);
+
+unsafe impl Send for MutatorPtr {}
+
+lazy_static! {
+    /// The mutators that are bound and not destroyed yet.
+    static ref MUTATORS: Mutex<Vec<MutatorPtr>> = Mutex::new(vec![]);
+    /// The index of the next mutator returned by `get_next_mutator()`.
+    static ref MUTATOR_CURSOR: Mutex<usize> = Mutex::new(0);
+}
+
+/// Record a bound mutator, so GC can find it.
+pub fn register_mutator(mutator: *mut Mutator<DummyVM>) {
+    MUTATORS.lock().unwrap().push(MutatorPtr(mutator));
+}
+
+/// Forget a mutator before it is destroyed.
+pub fn unregister_mutator(mutator: *mut Mutator<DummyVM>) {
+    MUTATORS.lock().unwrap().retain(|m| m.0 != mutator);
+}
+
 pub struct VMActivePlan<> {}
 
 impl ActivePlan<DummyVM> for VMActivePlan {
@@ -13,23 +35,32 @@ impl ActivePlan<DummyVM> for VMActivePlan {
     }
 
     fn number_of_mutators() -> usize {
-        unimplemented!()
+        MUTATORS.lock().unwrap().len()
     }
 
     fn is_mutator(_tls: VMThread) -> bool {
-        // FIXME
-        true
+        // All the threads other than the GC threads are mutators.
+        !crate::collection::is_gc_thread()
     }
 
-    fn mutator(_tls: VMMutatorThread) -> &'static mut Mutator<DummyVM> {
-        unimplemented!()
+    fn mutator(tls: VMMutatorThread) -> &'static mut Mutator<DummyVM> {
+        let mutators = MUTATORS.lock().unwrap();
+        let mutator = mutators
+            .iter()
+            .find(|m| unsafe { (*m.0).mutator_tls } == tls)
+            .expect("The thread is not bound to a mutator");
+        unsafe { &mut *mutator.0 }
     }
 
     fn reset_mutator_iterator() {
-        unimplemented!()
+        *MUTATOR_CURSOR.lock().unwrap() = 0;
     }
 
     fn get_next_mutator() -> Option<&'static mut Mutator<DummyVM>> {
-        unimplemented!()
+        let mut cursor = MUTATOR_CURSOR.lock().unwrap();
+        let mutators = MUTATORS.lock().unwrap();
+        let mutator = mutators.get(*cursor)?;
+        *cursor += 1;
+        Some(unsafe { &mut *mutator.0 })
     }
 }
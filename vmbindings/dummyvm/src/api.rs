@@ -31,11 +31,14 @@ pub extern "C" fn mmtk_init(heap_size: usize) {
 
 #[no_mangle]
 pub extern "C" fn mmtk_bind_mutator(tls: VMMutatorThread) -> *mut Mutator<DummyVM> {
-    Box::into_raw(memory_manager::bind_mutator(&SINGLETON, tls))
+    let mutator = Box::into_raw(memory_manager::bind_mutator(&SINGLETON, tls));
+    crate::active_plan::register_mutator(mutator);
+    mutator
 }
 
 #[no_mangle]
 pub extern "C" fn mmtk_destroy_mutator(mutator: *mut Mutator<DummyVM>) {
+    crate::active_plan::unregister_mutator(mutator);
     // notify mmtk-core about destroyed mutator
     memory_manager::destroy_mutator(unsafe { &mut *mutator });
     // turn the ptr back to a box, and let Rust properly reclaim it
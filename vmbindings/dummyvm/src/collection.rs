@@ -1,35 +1,150 @@
 use crate::DummyVM;
+use crate::SINGLETON;
+use mmtk::memory_manager;
 use mmtk::util::opaque_pointer::*;
+use mmtk::util::Address;
+use mmtk::vm::ActivePlan;
 use mmtk::vm::Collection;
 use mmtk::vm::GCThreadContext;
 use mmtk::Mutator;
 use mmtk::MutatorContext;
+use std::cell::Cell;
+use std::sync::{Condvar, Mutex, MutexGuard};
 
 pub struct VMCollection {}
 
+/// The state for stopping and resuming the mutators. The dummy VM has one mutator thread (the test
+/// thread). It stops in `block_for_gc`, or at a `yieldpoint` if a GC thread requested a GC.
+struct SafepointState {
+    /// A GC is waiting for the mutator to stop.
+    stop_requested: bool,
+    /// The mutator is stopped.
+    mutator_stopped: bool,
+    /// The number of GCs that have finished.
+    gc_count: usize,
+    /// The message of a panic in a GC thread. The mutator waiting for the GC panics with it.
+    gc_thread_panic: Option<String>,
+}
+
+lazy_static! {
+    static ref SAFEPOINT: Mutex<SafepointState> = Mutex::new(SafepointState {
+        stop_requested: false,
+        mutator_stopped: false,
+        gc_count: 0,
+        gc_thread_panic: None,
+    });
+    static ref SAFEPOINT_CHANGED: Condvar = Condvar::new();
+    /// The allocations reported by `sample_allocation` that have not been taken yet.
+    static ref ALLOCATION_SAMPLES: Mutex<Vec<(Address, usize)>> = Mutex::new(vec![]);
+}
+
+thread_local! {
+    static IS_GC_THREAD: Cell<bool> = Cell::new(false);
+}
+
+/// Is the current thread a GC thread?
+pub fn is_gc_thread() -> bool {
+    IS_GC_THREAD.with(|is_gc_thread| is_gc_thread.get())
+}
+
+/// The number of GCs that have finished so far.
+pub fn gc_count() -> usize {
+    SAFEPOINT.lock().unwrap().gc_count
+}
+
+/// A yieldpoint for the mutator thread. If a GC thread requested a GC (e.g. a concurrent plan
+/// finished its concurrent work), stop the mutator until the GC finishes. The mutator should call
+/// this periodically if such a GC may happen.
+pub fn yieldpoint() {
+    let state = SAFEPOINT.lock().unwrap();
+    if state.stop_requested {
+        wait_for_gc(state);
+    }
+}
+
+/// Take the allocations that MMTk sampled so far (the address and the size of each allocation).
+pub fn take_allocation_samples() -> Vec<(Address, usize)> {
+    std::mem::take(&mut *ALLOCATION_SAMPLES.lock().unwrap())
+}
+
+fn wait_for_gc(mut state: MutexGuard<SafepointState>) {
+    let gc_count = state.gc_count;
+    state.mutator_stopped = true;
+    SAFEPOINT_CHANGED.notify_all();
+    while state.gc_count == gc_count {
+        if let Some(message) = &state.gc_thread_panic {
+            panic!("A GC thread panicked: {}", message);
+        }
+        state = SAFEPOINT_CHANGED.wait(state).unwrap();
+    }
+    state.mutator_stopped = false;
+}
+
 impl Collection<DummyVM> for VMCollection {
-    fn stop_all_mutators<F>(_tls: VMWorkerThread, _mutator_visitor: F)
+    fn stop_all_mutators<F>(_tls: VMWorkerThread, mut mutator_visitor: F)
     where
         F: FnMut(&'static mut Mutator<DummyVM>),
     {
-        unimplemented!()
+        {
+            let mut state = SAFEPOINT.lock().unwrap();
+            state.stop_requested = true;
+            SAFEPOINT_CHANGED.notify_all();
+            if crate::active_plan::VMActivePlan::number_of_mutators() != 0 {
+                while !state.mutator_stopped {
+                    state = SAFEPOINT_CHANGED.wait(state).unwrap();
+                }
+            }
+        }
+        for mutator in crate::active_plan::VMActivePlan::mutators() {
+            mutator_visitor(mutator);
+        }
     }
 
     fn resume_mutators(_tls: VMWorkerThread) {
-        unimplemented!()
+        let mut state = SAFEPOINT.lock().unwrap();
+        state.stop_requested = false;
+        state.gc_count += 1;
+        SAFEPOINT_CHANGED.notify_all();
     }
 
     fn block_for_gc(_tls: VMMutatorThread) {
-        panic!("block_for_gc is not implemented")
+        wait_for_gc(SAFEPOINT.lock().unwrap());
     }
 
-    fn spawn_gc_thread(_tls: VMThread, _ctx: GCThreadContext<DummyVM>) {}
+    fn spawn_gc_thread(_tls: VMThread, ctx: GCThreadContext<DummyVM>) {
+        std::thread::spawn(move || {
+            IS_GC_THREAD.with(|is_gc_thread| is_gc_thread.set(true));
+            let tls = VMWorkerThread(VMThread::UNINITIALIZED);
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| match ctx {
+                GCThreadContext::Controller(mut controller) => {
+                    memory_manager::start_control_collector(&SINGLETON, tls, &mut controller)
+                }
+                GCThreadContext::Worker(mut worker) => {
+                    memory_manager::start_worker(&SINGLETON, tls, &mut worker)
+                }
+            }));
+            if let Err(payload) = result {
+                // Forward the panic to the mutator. Otherwise the mutator would wait forever.
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                let mut state = SAFEPOINT.lock().unwrap();
+                state.gc_thread_panic = Some(message);
+                SAFEPOINT_CHANGED.notify_all();
+            }
+        });
+    }
 
     fn prepare_mutator<T: MutatorContext<DummyVM>>(
         _tls_w: VMWorkerThread,
         _tls_m: VMMutatorThread,
         _mutator: &T,
     ) {
-        unimplemented!()
+    }
+
+    fn sample_allocation(_tls: VMMutatorThread, addr: Address, size: usize) {
+        ALLOCATION_SAMPLES.lock().unwrap().push((addr, size));
     }
 }
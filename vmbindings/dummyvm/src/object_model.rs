@@ -9,29 +9,93 @@ pub struct VMObjectModel {}
 // Change this if you want to test other values.
 pub const OBJECT_REF_OFFSET: usize = 4;
 
+// An object starts with a header: the size of the object in bytes (4 bytes), followed by
+// the number of reference fields (4 bytes). The reference fields follow the header. Any
+// bytes after the reference fields are not scanned by the GC.
+pub const HEADER_BYTES: usize = 8;
+const NUM_REFS_OFFSET: usize = 4;
+
+/// Write the header of a new object that starts at `start`, and clear its reference fields.
+pub fn init_object(start: Address, size: usize, num_refs: usize) -> ObjectReference {
+    debug_assert!(HEADER_BYTES + num_refs * std::mem::size_of::<ObjectReference>() <= size);
+    unsafe {
+        start.store::<u32>(size as u32);
+        (start + NUM_REFS_OFFSET).store::<u32>(num_refs as u32);
+    }
+    let object = VMObjectModel::address_to_ref(start);
+    for i in 0..num_refs {
+        set_ref(object, i, ObjectReference::NULL);
+    }
+    object
+}
+
+/// The number of reference fields in an object.
+pub fn num_refs(object: ObjectReference) -> usize {
+    let start = VMObjectModel::ref_to_object_start(object);
+    unsafe { (start + NUM_REFS_OFFSET).load::<u32>() as usize }
+}
+
+/// The address of the `i`-th reference field of an object.
+pub fn ref_slot(object: ObjectReference, i: usize) -> Address {
+    debug_assert!(i < num_refs(object));
+    VMObjectModel::ref_to_object_start(object) + HEADER_BYTES + i * std::mem::size_of::<ObjectReference>()
+}
+
+/// Load the `i`-th reference field of an object without any barrier.
+pub fn get_ref(object: ObjectReference, i: usize) -> ObjectReference {
+    unsafe { ref_slot(object, i).load::<ObjectReference>() }
+}
+
+/// Store to the `i`-th reference field of an object without any barrier.
+pub fn set_ref(object: ObjectReference, i: usize, value: ObjectReference) {
+    unsafe { ref_slot(object, i).store::<ObjectReference>(value) }
+}
+
 impl ObjectModel<DummyVM> for VMObjectModel {
-    const GLOBAL_LOG_BIT_SPEC: VMGlobalLogBitSpec = VMGlobalLogBitSpec::in_header(0);
+    // The forwarding pointer overwrites the header of the old copy of an object. The other
+    // metadata is on the side, so it does not overlap with the header or the fields.
+    const GLOBAL_LOG_BIT_SPEC: VMGlobalLogBitSpec = VMGlobalLogBitSpec::side_first();
     const LOCAL_FORWARDING_POINTER_SPEC: VMLocalForwardingPointerSpec = VMLocalForwardingPointerSpec::in_header(0);
-    const LOCAL_FORWARDING_BITS_SPEC: VMLocalForwardingBitsSpec = VMLocalForwardingBitsSpec::in_header(0);
-    const LOCAL_MARK_BIT_SPEC: VMLocalMarkBitSpec = VMLocalMarkBitSpec::in_header(0);
-    const LOCAL_LOS_MARK_NURSERY_SPEC: VMLocalLOSMarkNurserySpec = VMLocalLOSMarkNurserySpec::in_header(0);
+    const LOCAL_FORWARDING_BITS_SPEC: VMLocalForwardingBitsSpec = VMLocalForwardingBitsSpec::side_first();
+    const LOCAL_MARK_BIT_SPEC: VMLocalMarkBitSpec = VMLocalMarkBitSpec::side_after(Self::LOCAL_FORWARDING_BITS_SPEC.as_spec());
+    const LOCAL_LOS_MARK_NURSERY_SPEC: VMLocalLOSMarkNurserySpec = VMLocalLOSMarkNurserySpec::side_after(Self::LOCAL_MARK_BIT_SPEC.as_spec());
 
     const OBJECT_REF_OFFSET_LOWER_BOUND: isize = OBJECT_REF_OFFSET as isize;
 
     fn copy(
-        _from: ObjectReference,
-        _semantics: CopySemantics,
-        _copy_context: &mut GCWorkerCopyContext<DummyVM>,
+        from: ObjectReference,
+        semantics: CopySemantics,
+        copy_context: &mut GCWorkerCopyContext<DummyVM>,
     ) -> ObjectReference {
-        unimplemented!()
+        let bytes = Self::get_size_when_copied(from);
+        let align = Self::get_align_when_copied(from);
+        let offset = Self::get_align_offset_when_copied(from);
+        let dst = copy_context.alloc_copy(from, bytes, align, offset, semantics);
+        let src = Self::ref_to_object_start(from);
+        unsafe {
+            std::ptr::copy_nonoverlapping::<u8>(src.to_ptr(), dst.to_mut_ptr(), bytes);
+        }
+        let to_obj = Self::address_to_ref(dst);
+        copy_context.post_copy(to_obj, bytes, semantics);
+        to_obj
     }
 
-    fn copy_to(_from: ObjectReference, _to: ObjectReference, _region: Address) -> Address {
-        unimplemented!()
+    fn copy_to(from: ObjectReference, to: ObjectReference, _region: Address) -> Address {
+        let bytes = Self::get_current_size(from);
+        let src = Self::ref_to_object_start(from);
+        let dst = Self::ref_to_object_start(to);
+        if src != dst {
+            // The old and the new copy may overlap when compacting.
+            unsafe {
+                std::ptr::copy::<u8>(src.to_ptr(), dst.to_mut_ptr(), bytes);
+            }
+        }
+        dst + bytes
     }
 
-    fn get_current_size(_object: ObjectReference) -> usize {
-        unimplemented!()
+    fn get_current_size(object: ObjectReference) -> usize {
+        let start = Self::ref_to_object_start(object);
+        unsafe { start.load::<u32>() as usize }
     }
 
     fn get_size_when_copied(object: ObjectReference) -> usize {
@@ -46,8 +110,8 @@ impl ObjectModel<DummyVM> for VMObjectModel {
         0
     }
 
-    fn get_reference_when_copied_to(_from: ObjectReference, _to: Address) -> ObjectReference {
-        unimplemented!()
+    fn get_reference_when_copied_to(_from: ObjectReference, to: Address) -> ObjectReference {
+        Self::address_to_ref(to)
     }
 
     fn get_type_descriptor(_reference: ObjectReference) -> &'static [i8] {
@@ -59,7 +123,7 @@ impl ObjectModel<DummyVM> for VMObjectModel {
     }
 
     fn ref_to_header(object: ObjectReference) -> Address {
-        object.to_raw_address()
+        Self::ref_to_object_start(object)
     }
 
     fn ref_to_address(object: ObjectReference) -> Address {
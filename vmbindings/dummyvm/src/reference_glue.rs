@@ -1,20 +1,23 @@
-use mmtk::vm::ReferenceGlue;
+use mmtk::vm::{ObjectModel, ReferenceGlue};
 use mmtk::util::ObjectReference;
 use mmtk::util::opaque_pointer::VMWorkerThread;
+use crate::object_model::{VMObjectModel, HEADER_BYTES};
 use crate::DummyVM;
 
 pub struct VMReferenceGlue {}
 
+// A reference object has no reference fields in its header, so the GC does not scan its referent.
+// The referent is the first word after the header.
 impl ReferenceGlue<DummyVM> for VMReferenceGlue {
     type FinalizableType = ObjectReference;
 
-    fn set_referent(_reference: ObjectReference, _referent: ObjectReference) {
-        unimplemented!()
+    fn set_referent(reference: ObjectReference, referent: ObjectReference) {
+        let slot = VMObjectModel::ref_to_object_start(reference) + HEADER_BYTES;
+        unsafe { slot.store::<ObjectReference>(referent) }
     }
-    fn get_referent(_object: ObjectReference) -> ObjectReference {
-        unimplemented!()
-    }
-    fn enqueue_references(_references: &[ObjectReference], _tls: VMWorkerThread) {
-        unimplemented!()
+    fn get_referent(object: ObjectReference) -> ObjectReference {
+        let slot = VMObjectModel::ref_to_object_start(object) + HEADER_BYTES;
+        unsafe { slot.load::<ObjectReference>() }
     }
+    fn enqueue_references(_references: &[ObjectReference], _tls: VMWorkerThread) {}
 }
@@ -1,42 +1,74 @@
 use crate::DummyVM;
 use crate::edges::DummyVMEdge;
+use crate::object_model;
 use mmtk::util::opaque_pointer::*;
-use mmtk::util::ObjectReference;
+use mmtk::util::{Address, ObjectReference};
+use mmtk::vm::edge_shape::{Edge, SimpleEdge};
 use mmtk::vm::EdgeVisitor;
 use mmtk::vm::RootsWorkFactory;
 use mmtk::vm::Scanning;
 use mmtk::Mutator;
+use std::sync::Mutex;
+
+lazy_static! {
+    /// The addresses of the slots that hold roots. GC updates the slots if it moves the objects.
+    static ref ROOTS: Mutex<Vec<Address>> = Mutex::new(vec![]);
+}
+
+/// Add a slot that holds a root. The slot must be valid until it is removed.
+pub fn add_root(slot: Address) {
+    ROOTS.lock().unwrap().push(slot);
+}
+
+/// Remove a slot added by `add_root`.
+pub fn remove_root(slot: Address) {
+    ROOTS.lock().unwrap().retain(|s| *s != slot);
+}
+
+fn visit_fields<EV: EdgeVisitor<DummyVMEdge>>(object: ObjectReference, edge_visitor: &mut EV) {
+    for i in 0..object_model::num_refs(object) {
+        let edge = DummyVMEdge::Simple(SimpleEdge::from_address(object_model::ref_slot(object, i)));
+        if !edge.load().is_null() {
+            edge_visitor.visit_edge(edge);
+        }
+    }
+}
 
 pub struct VMScanning {}
 
 impl Scanning<DummyVM> for VMScanning {
     fn scan_thread_roots(_tls: VMWorkerThread, _factory: impl RootsWorkFactory<DummyVMEdge>) {
-        unimplemented!()
+        // All the roots are VM specific roots.
     }
     fn scan_thread_root(
         _tls: VMWorkerThread,
         _mutator: &'static mut Mutator<DummyVM>,
         _factory: impl RootsWorkFactory<DummyVMEdge>,
     ) {
-        unimplemented!()
+        // All the roots are VM specific roots.
     }
-    fn scan_vm_specific_roots(_tls: VMWorkerThread, _factory: impl RootsWorkFactory<DummyVMEdge>) {
-        unimplemented!()
+    fn scan_vm_specific_roots(_tls: VMWorkerThread, mut factory: impl RootsWorkFactory<DummyVMEdge>) {
+        let edges: Vec<DummyVMEdge> = ROOTS
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|slot| DummyVMEdge::Simple(SimpleEdge::from_address(*slot)))
+            .filter(|edge| !edge.load().is_null())
+            .collect();
+        if !edges.is_empty() {
+            factory.create_process_edge_roots_work(edges);
+        }
     }
     fn scan_object<EV: EdgeVisitor<DummyVMEdge>>(
         _tls: VMWorkerThread,
-        _object: ObjectReference,
-        _edge_visitor: &mut EV,
+        object: ObjectReference,
+        edge_visitor: &mut EV,
     ) {
-        unimplemented!()
-    }
-    fn notify_initial_thread_scan_complete(_partial_scan: bool, _tls: VMWorkerThread) {
-        unimplemented!()
+        visit_fields(object, edge_visitor);
     }
+    fn notify_initial_thread_scan_complete(_partial_scan: bool, _tls: VMWorkerThread) {}
     fn supports_return_barrier() -> bool {
-        unimplemented!()
-    }
-    fn prepare_for_roots_re_scanning() {
-        unimplemented!()
+        false
     }
+    fn prepare_for_roots_re_scanning() {}
 }
@@ -0,0 +1,39 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api;
+use crate::object_model::OBJECT_REF_OFFSET;
+use crate::tests::fixtures::{MutatorFixture, SerialFixture};
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::plan::AllocationSemantics;
+use mmtk::util::constants::BYTES_IN_PAGE;
+use mmtk::util::ObjectReference;
+
+lazy_static! {
+    static ref MUTATOR: SerialFixture<MutatorFixture> = SerialFixture::new();
+}
+
+#[test]
+pub fn allocate_large_alignment() {
+    MUTATOR.with_fixture(|fixture| {
+        let mutator = unsafe { &mut *fixture.mutator };
+        // A page, and an alignment that makes the padding larger than most spaces allow.
+        for align in [BYTES_IN_PAGE, 64 * 1024] {
+            for semantics in [AllocationSemantics::Default, AllocationSemantics::NonMoving] {
+                for size in [16, 4000] {
+                    let addr = memory_manager::alloc(mutator, size, align, 0, semantics);
+                    assert!(!addr.is_zero());
+                    assert!(
+                        addr.is_aligned_to(align),
+                        "{} is not aligned to {}",
+                        addr,
+                        align
+                    );
+                    let object = ObjectReference::from_raw_address(addr.add(OBJECT_REF_OFFSET));
+                    memory_manager::post_alloc(mutator, object, size, semantics);
+                    assert!(api::mmtk_is_in_mmtk_spaces(object));
+                }
+            }
+        }
+    })
+}
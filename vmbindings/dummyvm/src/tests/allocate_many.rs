@@ -0,0 +1,54 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api;
+use crate::object_model::OBJECT_REF_OFFSET;
+use crate::tests::fixtures::{MutatorFixture, SerialFixture};
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::plan::AllocationSemantics;
+use mmtk::util::ObjectReference;
+
+lazy_static! {
+    static ref MUTATOR: SerialFixture<MutatorFixture> = SerialFixture::new();
+}
+
+#[test]
+pub fn allocate_many() {
+    MUTATOR.with_fixture(|fixture| {
+        let mutator = unsafe { &mut *fixture.mutator };
+        let size = 24;
+        let align = 8;
+        // Enough objects to use up more than one thread local buffer.
+        let count = 5000;
+        let mut addrs = memory_manager::alloc_many::<DummyVM>(
+            mutator,
+            size,
+            align,
+            0,
+            AllocationSemantics::Default,
+            count,
+        );
+        assert_eq!(addrs.len(), count);
+
+        // The objects are aligned, and do not overlap.
+        addrs.sort();
+        for pair in addrs.windows(2) {
+            assert!(pair[0].is_aligned_to(align));
+            assert!(pair[0] + size <= pair[1]);
+        }
+
+        let objects: Vec<ObjectReference> = addrs
+            .iter()
+            .map(|addr| ObjectReference::from_raw_address(addr.add(OBJECT_REF_OFFSET)))
+            .collect();
+        memory_manager::post_alloc_many::<DummyVM>(
+            mutator,
+            &objects,
+            size,
+            AllocationSemantics::Default,
+        );
+        for object in objects {
+            assert!(api::mmtk_is_in_mmtk_spaces(object));
+        }
+    })
+}
@@ -0,0 +1,41 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api;
+use crate::object_model::OBJECT_REF_OFFSET;
+use crate::tests::fixtures::{MutatorFixture, SerialFixture};
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::plan::AllocationSemantics;
+use mmtk::util::ObjectReference;
+
+lazy_static! {
+    static ref MUTATOR: SerialFixture<MutatorFixture> = SerialFixture::new();
+}
+
+#[test]
+pub fn allocate_non_moving() {
+    MUTATOR.with_fixture(|fixture| {
+        let mutator = unsafe { &mut *fixture.mutator };
+        // A small object, and an object that is too large for the non-moving space of any plan. We
+        // call MMTk directly rather than the API functions of the dummy VM, so MMTk decides to put
+        // the large object in the large object space.
+        for size in [40, 128 * 1024] {
+            let addr = memory_manager::alloc::<DummyVM>(
+                mutator,
+                size,
+                8,
+                0,
+                AllocationSemantics::NonMoving,
+            );
+            assert!(!addr.is_zero());
+            let objref = ObjectReference::from_raw_address(addr.add(OBJECT_REF_OFFSET));
+            memory_manager::post_alloc::<DummyVM>(
+                mutator,
+                objref,
+                size,
+                AllocationSemantics::NonMoving,
+            );
+            assert!(api::mmtk_is_in_mmtk_spaces(objref));
+        }
+    })
+}
@@ -3,9 +3,9 @@ use mmtk::util::opaque_pointer::*;
 use mmtk::AllocationSemantics;
 
 /// This test allocates after calling initialize_collection(). When we exceed the heap limit, MMTk will trigger a GC. And block_for_gc will be called.
-/// We havent implemented block_for_gc so it will panic. This test is similar to allocate_with_initialize_collection, except that we once disabled GC in the test.
+/// This test runs with NoGC, which panics when a GC is triggered. The dummy VM forwards the panic of the GC thread to the mutator. This test is similar to allocate_with_initialize_collection, except that we once disabled GC in the test.
 #[test]
-#[should_panic(expected = "block_for_gc is not implemented")]
+#[should_panic(expected = "GC triggered in nogc")]
 pub fn allocate_with_re_enable_collection() {
     const MB: usize = 1024 * 1024;
     // 1MB heap
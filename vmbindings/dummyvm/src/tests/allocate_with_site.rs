@@ -0,0 +1,46 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api;
+use crate::object_model::OBJECT_REF_OFFSET;
+use crate::tests::fixtures::{MutatorFixture, SerialFixture};
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::plan::{AllocationSemantics, AllocationSite};
+use mmtk::util::ObjectReference;
+
+lazy_static! {
+    static ref MUTATOR: SerialFixture<MutatorFixture> = SerialFixture::new();
+}
+
+#[test]
+pub fn allocate_with_site() {
+    MUTATOR.with_fixture(|fixture| {
+        let mutator = unsafe { &mut *fixture.mutator };
+        let site = AllocationSite(42);
+        // Sample a number of objects from the site. They are allocated in the nursery (if any) until
+        // a nursery GC finds out their survival rate.
+        for _ in 0..1000 {
+            let size = 64;
+            let addr = memory_manager::alloc_with_site(
+                mutator,
+                size,
+                8,
+                0,
+                AllocationSemantics::Default,
+                site,
+            );
+            assert!(!addr.is_zero());
+            let object = ObjectReference::from_raw_address(addr.add(OBJECT_REF_OFFSET));
+            memory_manager::post_alloc(mutator, object, size, AllocationSemantics::Default);
+            assert!(api::mmtk_is_in_mmtk_spaces(object));
+        }
+        assert_eq!(
+            memory_manager::get_allocation_site_survival_rate(fixture.mmtk, site),
+            None
+        );
+        assert!(!memory_manager::is_allocation_site_pretenured(
+            fixture.mmtk,
+            site
+        ));
+    })
+}
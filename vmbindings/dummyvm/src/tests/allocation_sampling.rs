@@ -0,0 +1,45 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api::*;
+use crate::collection::take_allocation_samples;
+use crate::tests::fixtures::init_with_gc;
+use crate::BUILDER;
+use mmtk::AllocationSemantics;
+use std::collections::HashSet;
+
+const MB: usize = 1024 * 1024;
+const SIZE: usize = 64;
+const INTERVAL: usize = 4096;
+const COUNT: usize = 10000;
+
+/// With `alloc_sample_interval`, the allocators report about one allocation every `INTERVAL` bytes
+/// to the binding, and each reported allocation is one that the mutator got.
+#[test]
+pub fn allocation_sampling() {
+    assert!(BUILDER
+        .lock()
+        .unwrap()
+        .options
+        .alloc_sample_interval
+        .set(INTERVAL));
+    let mutator = init_with_gc(32 * MB);
+    let mut allocated = HashSet::new();
+    for _ in 0..COUNT {
+        let addr = mmtk_alloc(mutator, SIZE, 8, 0, AllocationSemantics::Default);
+        assert!(!addr.is_zero());
+        allocated.insert(addr);
+    }
+
+    let samples = take_allocation_samples();
+    for (addr, size) in samples.iter() {
+        assert!(allocated.contains(addr), "{} was not allocated", addr);
+        assert_eq!(*size, SIZE);
+    }
+    // We expect about 156 samples. Use a loose bound so the test is not flaky.
+    let expected = COUNT * SIZE / INTERVAL;
+    assert!(
+        samples.len() > expected / 2 && samples.len() < expected * 2,
+        "samples = {}",
+        samples.len()
+    );
+}
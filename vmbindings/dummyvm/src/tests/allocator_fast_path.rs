@@ -0,0 +1,42 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api;
+use crate::tests::fixtures::{MutatorFixture, SerialFixture};
+use mmtk::memory_manager;
+use mmtk::plan::AllocationSemantics;
+use mmtk::util::Address;
+
+lazy_static! {
+    static ref MUTATOR: SerialFixture<MutatorFixture> = SerialFixture::new();
+}
+
+#[test]
+pub fn allocator_fast_path_offsets() {
+    MUTATOR.with_fixture(|fixture| {
+        let fast_path = match memory_manager::get_allocator_fast_path(
+            fixture.mmtk,
+            AllocationSemantics::Default,
+        ) {
+            Some(fast_path) => fast_path,
+            // The allocator for the plan does not have an inline-able fast-path.
+            None => return,
+        };
+        assert_eq!(
+            fast_path.selector,
+            memory_manager::get_allocator_mapping(fixture.mmtk, AllocationSemantics::Default)
+        );
+        // By default, the thread local buffers are zeroed in bulk, so the fast-path does not zero objects.
+        assert!(!fast_path.needs_zeroing);
+
+        let mutator = Address::from_mut_ptr(fixture.mutator);
+        let size = 16;
+        let addr = api::mmtk_alloc(fixture.mutator, size, 8, 0, AllocationSemantics::Default);
+        assert!(!addr.is_zero());
+
+        // The allocation is done in the thread local buffer, so the cursor is right after the object.
+        let cursor = unsafe { (mutator + fast_path.cursor_offset).load::<Address>() };
+        let limit = unsafe { (mutator + fast_path.limit_offset).load::<Address>() };
+        assert_eq!(cursor, addr + size);
+        assert!(cursor <= limit);
+    })
+}
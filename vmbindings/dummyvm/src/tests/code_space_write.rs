@@ -0,0 +1,89 @@
+// GITHUB-CI: MMTK_PLAN=all
+// GITHUB-CI: FEATURES=code_space
+
+use crate::api::*;
+use crate::object_model::{self, VMObjectModel, HEADER_BYTES};
+use crate::tests::fixtures::{init_with_gc, Roots};
+use crate::DummyVM;
+use crate::BUILDER;
+use crate::SINGLETON;
+use mmtk::memory_manager;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::options::PlanSelector;
+use mmtk::util::{Address, ObjectReference};
+use mmtk::vm::ObjectModel;
+use mmtk::plan::Mutator;
+use mmtk::AllocationSemantics;
+
+const SIZE: usize = 256;
+const SEMANTICS: [AllocationSemantics; 2] = [AllocationSemantics::Code, AllocationSemantics::LargeCode];
+
+fn code_bytes_are(object: Address, value: u8) -> bool {
+    (HEADER_BYTES..SIZE).all(|i| unsafe { (object + i).load::<u8>() } == value)
+}
+
+/// Write the code of an object between `code_space_begin_write()` and `code_space_end_write()`.
+fn write_code(object: Address, value: u8, init_header: bool) {
+    let writable = memory_manager::code_space_begin_write::<DummyVM>(&SINGLETON, object, SIZE);
+    if init_header {
+        object_model::init_object(writable, SIZE, 0);
+    }
+    unsafe { std::ptr::write_bytes((writable + HEADER_BYTES).to_mut_ptr::<u8>(), value, SIZE - HEADER_BYTES) };
+    memory_manager::code_space_end_write::<DummyVM>(&SINGLETON, object, SIZE);
+    assert!(code_bytes_are(object, value));
+}
+
+fn alloc_code(mutator: *mut Mutator<DummyVM>, semantics: AllocationSemantics, value: u8) -> ObjectReference {
+    let addr = mmtk_alloc(mutator, SIZE, 8, 0, semantics);
+    assert!(!addr.is_zero());
+    write_code(addr, value, true);
+    let object = VMObjectModel::address_to_ref(addr);
+    mmtk_post_alloc(mutator, object, SIZE, semantics);
+    object
+}
+
+/// Code objects are written between `code_space_begin_write()` and `code_space_end_write()`, and
+/// the writes are visible at their addresses. A GC does not change the protection of the code
+/// spaces, so the live code objects can still be patched after a GC, and it frees the memory of
+/// the dead code objects.
+#[test]
+pub fn code_space_write() {
+    let can_gc = {
+        let mut builder = BUILDER.lock().unwrap();
+        // User triggered GCs are full heap GCs, which reclaim dead code.
+        assert!(builder.options.full_heap_system_gc.set(true));
+        !matches!(*builder.options.plan, PlanSelector::NoGC)
+    };
+    const MB: usize = 1024 * 1024;
+    let mutator = init_with_gc(32 * MB);
+    let tls = VMMutatorThread(VMThread::UNINITIALIZED);
+
+    // The dead code objects are the only objects in their runs of pages, so the GC frees the runs,
+    // and the memory reads as zeros.
+    let dead: Vec<Address> = SEMANTICS
+        .into_iter()
+        .map(|semantics| VMObjectModel::ref_to_object_start(alloc_code(mutator, semantics, 0xcc)))
+        .collect();
+    if can_gc {
+        mmtk_handle_user_collection_request(tls);
+        for addr in dead {
+            assert!(code_bytes_are(addr, 0));
+        }
+    }
+
+    let roots = Roots::new(SEMANTICS.len());
+    for (i, semantics) in SEMANTICS.into_iter().enumerate() {
+        roots.set(i, alloc_code(mutator, semantics, 0xc3));
+    }
+    if can_gc {
+        mmtk_handle_user_collection_request(tls);
+    }
+    for i in 0..SEMANTICS.len() {
+        let object = roots.get(i);
+        assert!(memory_manager::is_live_object(object));
+        let addr = VMObjectModel::ref_to_object_start(object);
+        assert!(code_bytes_are(addr, 0xc3));
+        // Patch the code after the GC.
+        write_code(addr, 0x90, false);
+    }
+}
@@ -0,0 +1,84 @@
+// GITHUB-CI: MMTK_PLAN=ConcurrentImmix
+
+use crate::collection::{gc_count, yieldpoint};
+use crate::object_model::get_ref;
+use crate::tests::fixtures::{alloc_garbage, alloc_list, alloc_object, check_list, init_with_gc, list_tail, write_ref, Roots};
+use crate::reference_glue::VMReferenceGlue;
+use crate::DummyVM;
+use crate::SINGLETON;
+use mmtk::memory_manager;
+use mmtk::util::ObjectReference;
+use mmtk::vm::ReferenceGlue;
+use mmtk::AllocationSemantics;
+use mmtk::Mutator;
+
+const LEN: usize = 1000;
+const SIZE: usize = 32;
+
+/// Allocate garbage until MMTk triggers a GC, and return after the GC.
+fn alloc_until_gc(mutator: *mut Mutator<DummyVM>) {
+    let count = gc_count();
+    while gc_count() == count {
+        alloc_garbage(mutator, 1, SIZE);
+    }
+}
+
+/// Allocate garbage and stop at yieldpoints until the final mark pause, which a GC thread
+/// triggers when the concurrent marking finishes.
+fn alloc_until_final_mark(mutator: *mut Mutator<DummyVM>) {
+    let count = gc_count();
+    while gc_count() == count {
+        alloc_garbage(mutator, 1, SIZE);
+        yieldpoint();
+    }
+    assert!(SINGLETON.get_plan().last_collection_was_exhaustive());
+}
+
+/// An allocation triggered GC starts concurrent marking, and the marking ends with a final mark
+/// pause. Reference objects and finalizable objects are processed in the final mark pause.
+#[test]
+pub fn concurrent_immix_pauses() {
+    const MB: usize = 1024 * 1024;
+    let mutator = init_with_gc(16 * MB);
+    let roots = Roots::new(3);
+    roots.set(0, alloc_list(mutator, LEN, SIZE));
+    // The second list is reachable from a holder object.
+    let holder = alloc_object(mutator, SIZE, 1, AllocationSemantics::Default);
+    write_ref(mutator, holder, 0, alloc_list(mutator, LEN, SIZE));
+    roots.set(1, holder);
+
+    // The initial mark pause does not reclaim memory.
+    alloc_until_gc(mutator);
+    assert!(!SINGLETON.get_plan().last_collection_was_exhaustive());
+
+    // While marking, move the second list to the end of the first list. The barrier records the
+    // overwritten reference in the holder, so the second list stays alive.
+    let holder = roots.get(1);
+    let second = get_ref(holder, 0);
+    write_ref(mutator, list_tail(roots.get(0)), 0, second);
+    write_ref(mutator, holder, 0, ObjectReference::NULL);
+
+    alloc_until_final_mark(mutator);
+    alloc_garbage(mutator, 10000, SIZE);
+    check_list(roots.get(0), 2 * LEN, SIZE);
+
+    // A dead object with a finalizer, and a live weak reference to another dead object. They do
+    // not stop the next GC from starting concurrent marking.
+    let finalizable = alloc_object(mutator, SIZE, 0, AllocationSemantics::Default);
+    memory_manager::add_finalizer::<DummyVM>(&SINGLETON, finalizable);
+    let reference = alloc_object(mutator, SIZE, 0, AllocationSemantics::Default);
+    let referent = alloc_object(mutator, SIZE, 0, AllocationSemantics::Default);
+    VMReferenceGlue::set_referent(reference, referent);
+    memory_manager::add_weak_candidate::<DummyVM>(&SINGLETON, reference);
+    roots.set(2, reference);
+    alloc_until_gc(mutator);
+    assert!(!SINGLETON.get_plan().last_collection_was_exhaustive());
+    assert!(memory_manager::get_finalized_object::<DummyVM>(&SINGLETON).is_none());
+    assert_eq!(VMReferenceGlue::get_referent(roots.get(2)), referent);
+
+    // The final mark pause finds them dead.
+    alloc_until_final_mark(mutator);
+    assert!(memory_manager::get_finalized_object::<DummyVM>(&SINGLETON).is_some());
+    assert!(VMReferenceGlue::get_referent(roots.get(2)).is_null());
+    check_list(roots.get(0), 2 * LEN, SIZE);
+}
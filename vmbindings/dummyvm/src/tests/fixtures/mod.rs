@@ -1,15 +1,20 @@
 // Some tests are conditionally compiled. So not all the code in this module will be used. We simply allow dead code in this module.
 #![allow(dead_code)]
 
+use atomic::{Atomic, Ordering};
 use atomic_refcell::AtomicRefCell;
 use std::sync::Once;
 use std::sync::Mutex;
 
+use mmtk::plan::Mutator;
+use mmtk::util::{Address, ObjectReference, VMThread, VMMutatorThread};
+use mmtk::vm::edge_shape::SimpleEdge;
 use mmtk::AllocationSemantics;
 use mmtk::MMTK;
-use mmtk::util::{ObjectReference, VMThread, VMMutatorThread};
 
 use crate::api::*;
+use crate::edges::DummyVMEdge;
+use crate::object_model;
 use crate::object_model::OBJECT_REF_OFFSET;
 use crate::DummyVM;
 
@@ -141,8 +146,6 @@ impl FixtureContent for TwoObjects {
     }
 }
 
-use mmtk::plan::Mutator;
-
 pub struct MutatorFixture {
     pub mmtk: &'static MMTK<DummyVM>,
     pub mutator: *mut Mutator<DummyVM>,
@@ -166,3 +169,98 @@ impl FixtureContent for MutatorFixture {
 }
 
 unsafe impl Send for MutatorFixture {}
+
+/// Initialize MMTk with collection enabled, and bind a mutator. Unlike the fixtures above, GC may
+/// happen in the test. Objects should be allocated by `alloc_object`, and kept alive by `Roots`.
+pub fn init_with_gc(heap_size: usize) -> *mut Mutator<DummyVM> {
+    mmtk_init(heap_size);
+    mmtk_initialize_collection(VMThread::UNINITIALIZED);
+    mmtk_bind_mutator(VMMutatorThread(VMThread::UNINITIALIZED))
+}
+
+/// Allocate an object with `num_refs` reference fields, and initialize its header so GC can scan it.
+pub fn alloc_object(mutator: *mut Mutator<DummyVM>, size: usize, num_refs: usize, semantics: AllocationSemantics) -> ObjectReference {
+    let addr = mmtk_alloc(mutator, size, 8, 0, semantics);
+    assert!(!addr.is_zero());
+    let objref = object_model::init_object(addr, size, num_refs);
+    mmtk_post_alloc(mutator, objref, size, semantics);
+    objref
+}
+
+/// Store to the `i`-th reference field of an object with the write barrier.
+pub fn write_ref(mutator: *mut Mutator<DummyVM>, src: ObjectReference, i: usize, target: ObjectReference) {
+    let slot = DummyVMEdge::Simple(SimpleEdge::from_address(object_model::ref_slot(src, i)));
+    mmtk::memory_manager::object_reference_write::<DummyVM>(unsafe { &mut *mutator }, src, slot, target);
+}
+
+/// Slots that hold roots. GC updates the slots if it moves the objects.
+pub struct Roots {
+    slots: Box<[Atomic<ObjectReference>]>,
+}
+
+impl Roots {
+    pub fn new(len: usize) -> Self {
+        let slots: Box<[Atomic<ObjectReference>]> = (0..len).map(|_| Atomic::new(ObjectReference::NULL)).collect();
+        for slot in slots.iter() {
+            crate::scanning::add_root(Address::from_ref(slot));
+        }
+        Roots { slots }
+    }
+
+    pub fn get(&self, i: usize) -> ObjectReference {
+        self.slots[i].load(Ordering::SeqCst)
+    }
+
+    pub fn set(&self, i: usize, objref: ObjectReference) {
+        self.slots[i].store(objref, Ordering::SeqCst)
+    }
+}
+
+impl Drop for Roots {
+    fn drop(&mut self) {
+        for slot in self.slots.iter() {
+            crate::scanning::remove_root(Address::from_ref(slot));
+        }
+    }
+}
+
+/// Build a linked list of `len` objects of `size` bytes. Each object points to the next one
+/// with its first reference field. Return the head of the list.
+pub fn alloc_list(mutator: *mut Mutator<DummyVM>, len: usize, size: usize) -> ObjectReference {
+    let mut head = ObjectReference::NULL;
+    for _ in 0..len {
+        let objref = alloc_object(mutator, size, 1, AllocationSemantics::Default);
+        write_ref(mutator, objref, 0, head);
+        head = objref;
+    }
+    head
+}
+
+/// Check that a list built by `alloc_list` is intact.
+pub fn check_list(head: ObjectReference, len: usize, size: usize) {
+    let mut cursor = head;
+    for _ in 0..len {
+        assert!(!cursor.is_null());
+        assert!(mmtk::memory_manager::is_in_mmtk_spaces::<DummyVM>(cursor));
+        assert_eq!(<object_model::VMObjectModel as mmtk::vm::ObjectModel<DummyVM>>::get_current_size(cursor), size);
+        assert_eq!(object_model::num_refs(cursor), 1);
+        cursor = object_model::get_ref(cursor, 0);
+    }
+    assert!(cursor.is_null());
+}
+
+/// Return the last object of a list built by `alloc_list`.
+pub fn list_tail(head: ObjectReference) -> ObjectReference {
+    let mut cursor = head;
+    while !object_model::get_ref(cursor, 0).is_null() {
+        cursor = object_model::get_ref(cursor, 0);
+    }
+    cursor
+}
+
+/// Allocate objects that are not reachable from any root.
+pub fn alloc_garbage(mutator: *mut Mutator<DummyVM>, count: usize, size: usize) {
+    for _ in 0..count {
+        alloc_object(mutator, size, 0, AllocationSemantics::Default);
+    }
+}
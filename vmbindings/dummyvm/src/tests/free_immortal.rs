@@ -0,0 +1,70 @@
+// GITHUB-CI: MMTK_PLAN=all
+// GITHUB-CI: FEATURES=immortal_free_list
+
+use crate::api::*;
+use crate::object_model::OBJECT_REF_OFFSET;
+use crate::tests::fixtures::init_with_gc;
+use crate::DummyVM;
+use crate::BUILDER;
+use crate::SINGLETON;
+use mmtk::memory_manager;
+use mmtk::plan::AllocationSemantics;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::options::PlanSelector;
+use mmtk::util::{Address, ObjectReference};
+use mmtk::Mutator;
+
+const SIZE: usize = 24;
+const OBJECTS: usize = 100;
+
+fn object_at(addr: Address) -> ObjectReference {
+    ObjectReference::from_raw_address(addr.add(OBJECT_REF_OFFSET))
+}
+
+fn alloc_immortal(mutator: *mut Mutator<DummyVM>, size: usize) -> Address {
+    let mutator = unsafe { &mut *mutator };
+    let addr = memory_manager::alloc(mutator, size, 8, 0, AllocationSemantics::Immortal);
+    assert!(!addr.is_zero());
+    let object = object_at(addr);
+    memory_manager::post_alloc(mutator, object, size, AllocationSemantics::Immortal);
+    assert!(mmtk_is_in_mmtk_spaces(object));
+    addr
+}
+
+/// The cells of the freed immortal objects are reused after the next GC.
+#[test]
+pub fn free_immortal() {
+    {
+        // User triggered GCs are full heap GCs, so the immortal space is released.
+        let mut builder = BUILDER.lock().unwrap();
+        // NoGC does not use the immortal space of the common plan.
+        if matches!(*builder.options.plan, PlanSelector::NoGC) {
+            return;
+        }
+        assert!(builder.options.full_heap_system_gc.set(true));
+    }
+    const MB: usize = 1024 * 1024;
+    let mutator = init_with_gc(32 * MB);
+    let tls = VMMutatorThread(VMThread::UNINITIALIZED);
+
+    // Objects of different size classes can be freed.
+    for size in [16, 100, 1000] {
+        for _ in 0..OBJECTS {
+            let addr = alloc_immortal(mutator, size);
+            memory_manager::free_immortal::<DummyVM>(&SINGLETON, object_at(addr));
+        }
+    }
+
+    let freed: Vec<Address> = (0..OBJECTS).map(|_| alloc_immortal(mutator, SIZE)).collect();
+    for addr in freed.iter().copied() {
+        memory_manager::free_immortal::<DummyVM>(&SINGLETON, object_at(addr));
+    }
+
+    // The freed cells are put back to the free lists in the GC.
+    mmtk_handle_user_collection_request(tls);
+    let reused = (0..OBJECTS)
+        .map(|_| alloc_immortal(mutator, SIZE))
+        .filter(|addr| freed.contains(addr))
+        .count();
+    assert!(reused > 0);
+}
@@ -0,0 +1,91 @@
+// GITHUB-CI: MMTK_PLAN=G1
+
+use crate::api::*;
+use crate::collection::gc_count;
+use crate::reference_glue::VMReferenceGlue;
+use crate::tests::fixtures::{alloc_garbage, alloc_list, alloc_object, check_list, init_with_gc, list_tail, write_ref, Roots};
+use crate::DummyVM;
+use crate::BUILDER;
+use crate::SINGLETON;
+use mmtk::memory_manager;
+use mmtk::util::opaque_pointer::*;
+use mmtk::vm::ReferenceGlue;
+use mmtk::AllocationSemantics;
+use mmtk::Mutator;
+
+const LEN: usize = 1000;
+const SIZE: usize = 32;
+
+/// Allocate garbage until MMTk triggers a GC, and return after the GC.
+fn alloc_until_gc(mutator: *mut Mutator<DummyVM>) {
+    let count = gc_count();
+    while gc_count() == count {
+        alloc_garbage(mutator, 1, SIZE);
+    }
+}
+
+/// G1 evacuates the young regions in the pauses triggered by allocation. Reference objects and
+/// finalizable objects are processed in those pauses. After a marking pause, the following pauses
+/// also evacuate the old regions with few live objects.
+#[test]
+pub fn g1_pauses() {
+    const MB: usize = 1024 * 1024;
+    {
+        // User triggered GCs mark the heap.
+        let mut builder = BUILDER.lock().unwrap();
+        assert!(builder.options.full_heap_system_gc.set(true));
+    }
+    let mutator = init_with_gc(32 * MB);
+    let tls = VMMutatorThread(VMThread::UNINITIALIZED);
+    // The list, two reference objects, and the referent of the second reference object.
+    let roots = Roots::new(4);
+    roots.set(0, alloc_list(mutator, LEN, SIZE));
+
+    // An evacuating pause.
+    alloc_until_gc(mutator);
+    assert!(!SINGLETON.get_plan().last_collection_was_exhaustive());
+    check_list(roots.get(0), LEN, SIZE);
+
+    // A dead object with a finalizer, a weak reference to a dead object, and a weak reference to
+    // a live object. The next evacuating pause processes them.
+    let finalizable = alloc_object(mutator, SIZE, 0, AllocationSemantics::Default);
+    memory_manager::add_finalizer::<DummyVM>(&SINGLETON, finalizable);
+    for i in 1..3 {
+        let reference = alloc_object(mutator, SIZE, 0, AllocationSemantics::Default);
+        let referent = alloc_object(mutator, SIZE, 0, AllocationSemantics::Default);
+        VMReferenceGlue::set_referent(reference, referent);
+        memory_manager::add_weak_candidate::<DummyVM>(&SINGLETON, reference);
+        roots.set(i, reference);
+    }
+    roots.set(3, VMReferenceGlue::get_referent(roots.get(2)));
+    alloc_until_gc(mutator);
+    assert!(!SINGLETON.get_plan().last_collection_was_exhaustive());
+    assert!(memory_manager::get_finalized_object::<DummyVM>(&SINGLETON).is_some());
+    assert!(VMReferenceGlue::get_referent(roots.get(1)).is_null());
+    // The referent was evacuated, and the reference points to the new copy.
+    assert_eq!(VMReferenceGlue::get_referent(roots.get(2)), roots.get(3));
+    check_list(roots.get(0), LEN, SIZE);
+
+    // A marking pause finds the old regions with few live objects.
+    mmtk_handle_user_collection_request(tls);
+    assert!(SINGLETON.get_plan().last_collection_was_exhaustive());
+    assert_eq!(VMReferenceGlue::get_referent(roots.get(2)), roots.get(3));
+    check_list(roots.get(0), LEN, SIZE);
+
+    // An old object points to a young object. The remembered set keeps the young object alive.
+    let young = alloc_object(mutator, SIZE, 1, AllocationSemantics::Default);
+    write_ref(mutator, list_tail(roots.get(0)), 0, young);
+
+    // The list is in an old region with few live objects. A mixed pause evacuates it.
+    let old_head = roots.get(0);
+    for _ in 0..16 {
+        alloc_until_gc(mutator);
+        assert!(!SINGLETON.get_plan().last_collection_was_exhaustive());
+        check_list(roots.get(0), LEN + 1, SIZE);
+        assert_eq!(VMReferenceGlue::get_referent(roots.get(2)), roots.get(3));
+        if roots.get(0) != old_head {
+            break;
+        }
+    }
+    assert_ne!(roots.get(0), old_head);
+}
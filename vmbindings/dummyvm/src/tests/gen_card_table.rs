@@ -0,0 +1,49 @@
+// GITHUB-CI: MMTK_PLAN=GenCopy
+// GITHUB-CI: MMTK_PLAN=GenImmix
+// GITHUB-CI: MMTK_PLAN=GenMarkSweep
+// GITHUB-CI: FEATURES=card_table_barrier
+
+use crate::api::*;
+use crate::object_model::{get_ref, num_refs};
+use crate::tests::fixtures::{alloc_object, init_with_gc, write_ref, Roots};
+use crate::DummyVM;
+use crate::SINGLETON;
+use mmtk::memory_manager;
+use mmtk::plan::BarrierSelector;
+use mmtk::util::card_table;
+use mmtk::util::opaque_pointer::*;
+use mmtk::AllocationSemantics;
+
+const MB: usize = 1024 * 1024;
+const SIZE: usize = 32;
+
+/// With the card table barrier, a nursery GC finds the young objects that are only reachable from
+/// the mature objects through the dirty cards, and cleans the cards.
+#[test]
+pub fn gen_card_table() {
+    let mutator = init_with_gc(32 * MB);
+    let tls = VMMutatorThread(VMThread::UNINITIALIZED);
+    assert_eq!(SINGLETON.get_plan().constraints().barrier, BarrierSelector::CardTableBarrier);
+    let roots = Roots::new(1);
+    let mature = alloc_object(mutator, SIZE, 1, AllocationSemantics::Default);
+    roots.set(0, mature);
+
+    // User triggered GCs are nursery GCs. The object is promoted to the mature space.
+    mmtk_handle_user_collection_request(tls);
+    assert!(!SINGLETON.get_plan().last_collection_was_exhaustive());
+    let mature = roots.get(0);
+    assert!(!card_table::is_dirty(mature.to_address::<DummyVM>()));
+
+    // The young object is only reachable from the mature object.
+    let young = alloc_object(mutator, SIZE, 0, AllocationSemantics::Default);
+    write_ref(mutator, mature, 0, young);
+    assert!(card_table::is_dirty(mature.to_address::<DummyVM>()));
+
+    mmtk_handle_user_collection_request(tls);
+    assert!(!SINGLETON.get_plan().last_collection_was_exhaustive());
+    assert_eq!(roots.get(0), mature);
+    assert!(!card_table::is_dirty(mature.to_address::<DummyVM>()));
+    let young = get_ref(mature, 0);
+    assert!(memory_manager::is_live_object(young));
+    assert_eq!(num_refs(young), 0);
+}
@@ -0,0 +1,99 @@
+// GITHUB-CI: MMTK_PLAN=Immix
+// GITHUB-CI: FEATURES=immix_lazy_sweeping,is_mmtk_object
+
+use crate::api::*;
+use crate::object_model::{get_ref, VMObjectModel};
+use crate::tests::fixtures::{alloc_garbage, alloc_list, alloc_object, check_list, init_with_gc, list_tail, write_ref, Roots};
+use crate::DummyVM;
+use crate::SINGLETON;
+use mmtk::memory_manager;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::ObjectReference;
+use mmtk::vm::ObjectModel;
+use mmtk::AllocationSemantics;
+
+const SIZE: usize = 48;
+/// The number of objects in each list.
+const LIVE: usize = 1024;
+/// The number of dead objects allocated after each object of the first list, so its blocks have
+/// free lines after a GC.
+const DEAD_PER_LIVE: usize = 15;
+/// The dead objects that fill whole blocks.
+const DEAD_BYTES: usize = 4 * 1024 * 1024;
+
+/// Does the object still have its alloc bit? The alloc bits of the dead objects in the free lines
+/// of a block are cleared when the block is swept.
+fn has_alloc_bit(object: ObjectReference) -> bool {
+    memory_manager::is_mmtk_object(object.to_raw_address())
+}
+
+/// A GC releases the blocks without live objects, and leaves the other blocks unswept. An allocator
+/// sweeps the unswept blocks when it acquires them, and allocates into the lines of the dead
+/// objects without overwriting the survivors.
+#[test]
+pub fn immix_lazy_sweeping() {
+    const MB: usize = 1024 * 1024;
+    let mutator = init_with_gc(32 * MB);
+    let tls = VMMutatorThread(VMThread::UNINITIALIZED);
+    let roots = Roots::new(2);
+
+    // A list whose objects are spread over blocks that are mostly free after a GC.
+    let mut head = ObjectReference::NULL;
+    let mut dead = vec![];
+    for _ in 0..LIVE {
+        let objref = alloc_object(mutator, SIZE, 1, AllocationSemantics::Default);
+        write_ref(mutator, objref, 0, head);
+        head = objref;
+        for _ in 0..DEAD_PER_LIVE {
+            dead.push(alloc_object(mutator, SIZE, 0, AllocationSemantics::Default));
+        }
+    }
+    roots.set(0, head);
+    // The range of the blocks of the first list, which all have survivors.
+    let starts = || dead.iter().copied().chain([list_tail(head), head]).map(VMObjectModel::ref_to_object_start);
+    let start = starts().min().unwrap();
+    let end = starts().max().unwrap() + SIZE;
+    alloc_garbage(mutator, DEAD_BYTES / SIZE, SIZE);
+
+    let used_before = memory_manager::used_bytes::<DummyVM>(&SINGLETON);
+    mmtk_handle_user_collection_request(tls);
+    check_list(roots.get(0), LIVE, SIZE);
+    // The blocks of the dead objects are released in the GC, not when they are swept.
+    let used_after = memory_manager::used_bytes::<DummyVM>(&SINGLETON);
+    assert!(
+        used_after + DEAD_BYTES / 2 <= used_before,
+        "Used {} bytes before the GC, and {} bytes after it",
+        used_before,
+        used_after
+    );
+    // The blocks with survivors are not swept in the GC.
+    assert!(dead.iter().all(|o| has_alloc_bit(*o)));
+
+    // The allocator sweeps the blocks of the first list when it acquires them, and allocates into
+    // the lines of the dead objects.
+    roots.set(1, alloc_list(mutator, LIVE, SIZE));
+    check_list(roots.get(0), LIVE, SIZE);
+    check_list(roots.get(1), LIVE, SIZE);
+    let swept = dead.iter().filter(|o| !has_alloc_bit(**o)).count();
+    assert!(swept > 0, "No block of the first list is swept");
+    let mut reused = 0;
+    let mut cursor = roots.get(1);
+    while !cursor.is_null() {
+        let addr = VMObjectModel::ref_to_object_start(cursor);
+        if start <= addr && addr < end {
+            reused += 1;
+        }
+        cursor = get_ref(cursor, 0);
+    }
+    assert!(
+        reused >= LIVE / 2,
+        "Only {} of {} new objects are allocated into the free lines",
+        reused,
+        LIVE
+    );
+
+    // The blocks that are not swept yet are swept in the next GC.
+    mmtk_handle_user_collection_request(tls);
+    check_list(roots.get(0), LIVE, SIZE);
+    check_list(roots.get(1), LIVE, SIZE);
+}
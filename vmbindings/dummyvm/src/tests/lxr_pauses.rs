@@ -0,0 +1,66 @@
+// GITHUB-CI: MMTK_PLAN=LXR
+
+use crate::api::*;
+use crate::tests::fixtures::{alloc_garbage, alloc_list, alloc_object, check_list, init_with_gc, Roots};
+use crate::DummyVM;
+use crate::SINGLETON;
+use mmtk::memory_manager;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::ObjectReference;
+use mmtk::AllocationSemantics;
+
+/// LXR uses reference counting pauses even if the binding registered finalizable objects. A
+/// reference counting pause finds the finalizable objects that are not counted, and keeps them and
+/// their referents alive for finalization.
+#[test]
+pub fn lxr_pauses() {
+    const MB: usize = 1024 * 1024;
+    const LEN: usize = 1000;
+    const SIZE: usize = 32;
+    let mutator = init_with_gc(32 * MB);
+    let tls = VMMutatorThread(VMThread::UNINITIALIZED);
+    let roots = Roots::new(2);
+    roots.set(0, alloc_list(mutator, LEN, SIZE));
+    alloc_garbage(mutator, 10000, SIZE);
+
+    // No finalizer is registered. This is a reference counting pause.
+    mmtk_handle_user_collection_request(tls);
+    assert!(!SINGLETON.get_plan().last_collection_was_exhaustive());
+    alloc_garbage(mutator, 10000, SIZE);
+    check_list(roots.get(0), LEN, SIZE);
+
+    // A young dead object with a finalizer. It is never counted, and a reference counting pause
+    // finds it.
+    let finalizable = alloc_object(mutator, SIZE, 0, AllocationSemantics::Default);
+    memory_manager::add_finalizer::<DummyVM>(&SINGLETON, finalizable);
+    mmtk_handle_user_collection_request(tls);
+    assert!(!SINGLETON.get_plan().last_collection_was_exhaustive());
+    assert_eq!(
+        memory_manager::get_finalized_object::<DummyVM>(&SINGLETON),
+        Some(finalizable)
+    );
+    alloc_garbage(mutator, 10000, SIZE);
+    check_list(roots.get(0), LEN, SIZE);
+
+    // A counted object with a finalizer. Its count drops to zero when the root is cleared, and it
+    // is kept alive for finalization with the object it points to.
+    let old = alloc_list(mutator, 2, SIZE);
+    roots.set(1, old);
+    memory_manager::add_finalizer::<DummyVM>(&SINGLETON, old);
+    mmtk_handle_user_collection_request(tls);
+    assert!(memory_manager::get_finalized_object::<DummyVM>(&SINGLETON).is_none());
+    roots.set(1, ObjectReference::NULL);
+    mmtk_handle_user_collection_request(tls);
+    assert!(!SINGLETON.get_plan().last_collection_was_exhaustive());
+    assert_eq!(
+        memory_manager::get_finalized_object::<DummyVM>(&SINGLETON),
+        Some(old)
+    );
+    // The binding keeps the object alive while it runs the finalizer.
+    roots.set(1, old);
+    alloc_garbage(mutator, 10000, SIZE);
+    mmtk_handle_user_collection_request(tls);
+    alloc_garbage(mutator, 10000, SIZE);
+    check_list(roots.get(0), LEN, SIZE);
+    check_list(roots.get(1), 2, SIZE);
+}
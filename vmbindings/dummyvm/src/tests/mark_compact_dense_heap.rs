@@ -0,0 +1,35 @@
+// GITHUB-CI: MMTK_PLAN=MarkCompact
+
+use crate::api::*;
+use crate::tests::fixtures::{alloc_garbage, alloc_list, check_list, init_with_gc, Roots};
+use mmtk::util::opaque_pointer::*;
+
+/// The size is not a multiple of the alignment of the objects (8 bytes), so the objects do not
+/// end at the boundaries of the compaction regions.
+const SIZE: usize = 20;
+/// The live objects take more than a few compaction regions (256KB each).
+const LEN: usize = 50000;
+
+/// Mark compact compacts each region right after the live objects of the previous region. In a
+/// dense heap, a region starts with an object that straddles the boundary with the previous
+/// region, and the live objects of the region are not moved.
+#[test]
+pub fn mark_compact_dense_heap() {
+    const MB: usize = 1024 * 1024;
+    let mutator = init_with_gc(32 * MB);
+    let tls = VMMutatorThread(VMThread::UNINITIALIZED);
+    let roots = Roots::new(2);
+
+    // Every object is live.
+    roots.set(0, alloc_list(mutator, LEN, SIZE));
+    mmtk_handle_user_collection_request(tls);
+    check_list(roots.get(0), LEN, SIZE);
+
+    // A few dead objects between the two lists. The second list is slid over them, and its
+    // regions are compacted to addresses with a different offset modulo the alignment.
+    alloc_garbage(mutator, 100, SIZE);
+    roots.set(1, alloc_list(mutator, LEN, SIZE));
+    mmtk_handle_user_collection_request(tls);
+    check_list(roots.get(0), LEN, SIZE);
+    check_list(roots.get(1), LEN, SIZE);
+}
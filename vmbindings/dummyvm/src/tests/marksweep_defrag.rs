@@ -0,0 +1,97 @@
+// GITHUB-CI: MMTK_PLAN=MarkSweep
+// GITHUB-CI: FEATURES=marksweep_defrag
+
+use crate::api::*;
+use crate::object_model::get_ref;
+use crate::tests::fixtures::{alloc_garbage, alloc_object, check_list, init_with_gc, write_ref, Roots};
+use crate::DummyVM;
+use crate::BUILDER;
+use crate::SINGLETON;
+use mmtk::memory_manager;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::ObjectReference;
+use mmtk::AllocationSemantics;
+use std::collections::HashSet;
+
+const SIZE: usize = 64;
+/// The number of objects that are kept alive.
+const LIVE: usize = 2048;
+/// The number of dead objects allocated after each live one, so every block of the size class
+/// is sparse after a GC.
+const DEAD_PER_LIVE: usize = 9;
+/// The number of list objects that are also referenced by the non-moving holder.
+const HELD: usize = 4;
+
+fn list_objects(head: ObjectReference) -> Vec<ObjectReference> {
+    let mut objects = vec![];
+    let mut cursor = head;
+    while !cursor.is_null() {
+        objects.push(cursor);
+        cursor = get_ref(cursor, 0);
+    }
+    objects
+}
+
+/// A defrag GC copies the live objects out of the sparse blocks of a size class, updates the
+/// references to them, and releases the blocks.
+#[test]
+pub fn marksweep_defrag() {
+    {
+        // User triggered GCs are defrag GCs.
+        let mut builder = BUILDER.lock().unwrap();
+        assert!(builder.options.full_heap_system_gc.set(true));
+    }
+    const MB: usize = 1024 * 1024;
+    let mutator = init_with_gc(32 * MB);
+    let tls = VMMutatorThread(VMThread::UNINITIALIZED);
+    let roots = Roots::new(2);
+
+    // The objects in the mark sweep space may be evacuated, and non-moving objects are not
+    // allocated in the mark sweep space.
+    let holder = alloc_object(mutator, SIZE, HELD, AllocationSemantics::NonMoving);
+    assert!(!holder.is_movable());
+    roots.set(1, holder);
+
+    // Fragment the size class with a list whose objects are spread over all its blocks.
+    let mut head = ObjectReference::NULL;
+    for _ in 0..LIVE {
+        let objref = alloc_object(mutator, SIZE, 1, AllocationSemantics::Default);
+        assert!(objref.is_movable());
+        write_ref(mutator, objref, 0, head);
+        head = objref;
+        alloc_garbage(mutator, DEAD_PER_LIVE, SIZE);
+    }
+    roots.set(0, head);
+    for (i, objref) in list_objects(head).into_iter().take(HELD).enumerate() {
+        write_ref(mutator, holder, i, objref);
+    }
+
+    // The blocks are not evacuated until a GC has counted their live bytes.
+    mmtk_handle_user_collection_request(tls);
+    check_list(roots.get(0), LIVE, SIZE);
+    let before: HashSet<ObjectReference> = list_objects(roots.get(0)).into_iter().collect();
+    let used_before = memory_manager::used_bytes::<DummyVM>(&SINGLETON);
+
+    mmtk_handle_user_collection_request(tls);
+    // The root and the links of the list refer to the copies.
+    check_list(roots.get(0), LIVE, SIZE);
+    let after = list_objects(roots.get(0));
+    // Every block holding the list is a sparse block, so every live object is copied out of it.
+    let copied = after.iter().filter(|objref| !before.contains(objref)).count();
+    assert_eq!(copied, LIVE, "Only {} of {} live objects were evacuated", copied, LIVE);
+    // The non-moving holder stays in place, and its fields refer to the copies.
+    assert_eq!(roots.get(1), holder);
+    for (i, objref) in after.iter().take(HELD).enumerate() {
+        assert_eq!(get_ref(holder, i), *objref);
+    }
+    // The sparse blocks are released, so at least half of the memory used by the size class is
+    // returned to the heap.
+    let used_after = memory_manager::used_bytes::<DummyVM>(&SINGLETON);
+    let allocated = LIVE * (1 + DEAD_PER_LIVE) * SIZE;
+    assert!(
+        used_after + allocated / 2 <= used_before,
+        "Used {} bytes before the defrag GC, and {} bytes after it",
+        used_before,
+        used_after
+    );
+}
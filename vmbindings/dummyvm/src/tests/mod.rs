@@ -13,6 +13,38 @@ mod allocate_without_initialize_collection;
 mod allocate_with_initialize_collection;
 mod allocate_with_disable_collection;
 mod allocate_with_re_enable_collection;
+mod allocator_fast_path;
+mod allocate_non_moving;
+mod non_moving_gc;
+mod allocate_many;
+mod allocate_large_alignment;
+mod resize_large_object;
+mod allocate_with_site;
+mod pretenure_with_site;
+mod mutator_allocated_bytes;
+mod allocation_sampling;
+mod lxr_pauses;
+mod concurrent_immix_pauses;
+mod g1_pauses;
+mod sticky_marksweep;
+mod mark_compact_dense_heap;
+mod weak_reference_read;
+#[cfg(feature = "ro_space")]
+mod seal_readonly_space;
+#[cfg(feature = "ro_space")]
+mod seal_readonly_space_roots;
+#[cfg(feature = "code_space")]
+mod code_space_write;
+#[cfg(feature = "vm_space")]
+mod vm_space_region;
+#[cfg(feature = "immortal_free_list")]
+mod free_immortal;
+#[cfg(feature = "marksweep_defrag")]
+mod marksweep_defrag;
+#[cfg(all(feature = "immix_lazy_sweeping", feature = "is_mmtk_object"))]
+mod immix_lazy_sweeping;
+#[cfg(feature = "card_table_barrier")]
+mod gen_card_table;
 #[cfg(not(feature = "malloc_counted_size"))]
 mod malloc_api;
 #[cfg(feature = "malloc_counted_size")]
@@ -0,0 +1,69 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::object_model::OBJECT_REF_OFFSET;
+use crate::tests::fixtures::{MutatorFixture, SerialFixture};
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::plan::AllocationSemantics;
+use mmtk::util::ObjectReference;
+
+lazy_static! {
+    static ref MUTATOR: SerialFixture<MutatorFixture> = SerialFixture::new();
+}
+
+#[test]
+pub fn mutator_allocated_bytes() {
+    MUTATOR.with_fixture(|fixture| {
+        let mutator = unsafe { &mut *fixture.mutator };
+        let default_before =
+            memory_manager::mutator_allocated_bytes(mutator, AllocationSemantics::Default);
+        let immortal_before =
+            memory_manager::mutator_allocated_bytes(mutator, AllocationSemantics::Immortal);
+
+        let size = 32;
+        for _ in 0..10 {
+            let addr =
+                memory_manager::alloc::<DummyVM>(mutator, size, 8, 0, AllocationSemantics::Default);
+            assert!(!addr.is_zero());
+            let object = ObjectReference::from_raw_address(addr.add(OBJECT_REF_OFFSET));
+            memory_manager::post_alloc::<DummyVM>(
+                mutator,
+                object,
+                size,
+                AllocationSemantics::Default,
+            );
+        }
+        assert_eq!(
+            memory_manager::mutator_allocated_bytes(mutator, AllocationSemantics::Default),
+            default_before + 10 * size
+        );
+
+        let addrs = memory_manager::alloc_many::<DummyVM>(
+            mutator,
+            size,
+            8,
+            0,
+            AllocationSemantics::Immortal,
+            100,
+        );
+        let objects: Vec<ObjectReference> = addrs
+            .iter()
+            .map(|addr| ObjectReference::from_raw_address(addr.add(OBJECT_REF_OFFSET)))
+            .collect();
+        memory_manager::post_alloc_many::<DummyVM>(
+            mutator,
+            &objects,
+            size,
+            AllocationSemantics::Immortal,
+        );
+        assert_eq!(
+            memory_manager::mutator_allocated_bytes(mutator, AllocationSemantics::Immortal),
+            immortal_before + 100 * size
+        );
+        // Allocations with other semantics are counted separately.
+        assert_eq!(
+            memory_manager::mutator_allocated_bytes(mutator, AllocationSemantics::Default),
+            default_before + 10 * size
+        );
+    })
+}
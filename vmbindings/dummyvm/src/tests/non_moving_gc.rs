@@ -0,0 +1,40 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api::*;
+use crate::tests::fixtures::{alloc_object, init_with_gc, Roots};
+use crate::BUILDER;
+use mmtk::memory_manager;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::options::PlanSelector;
+use mmtk::AllocationSemantics;
+
+const SIZE: usize = 40;
+
+/// A GC does not move the reachable non-moving objects, and reclaims the unreachable ones.
+#[test]
+pub fn non_moving_gc() {
+    {
+        // User triggered GCs are full heap GCs, so the non-moving space is collected.
+        let mut builder = BUILDER.lock().unwrap();
+        // NoGC cannot collect.
+        if matches!(*builder.options.plan, PlanSelector::NoGC) {
+            return;
+        }
+        assert!(builder.options.full_heap_system_gc.set(true));
+    }
+    const MB: usize = 1024 * 1024;
+    let mutator = init_with_gc(32 * MB);
+    let tls = VMMutatorThread(VMThread::UNINITIALIZED);
+    let roots = Roots::new(1);
+    let live = alloc_object(mutator, SIZE, 0, AllocationSemantics::NonMoving);
+    let dead = alloc_object(mutator, SIZE, 0, AllocationSemantics::NonMoving);
+    roots.set(0, live);
+
+    for _ in 0..2 {
+        mmtk_handle_user_collection_request(tls);
+        // The root is updated if the object is moved.
+        assert_eq!(roots.get(0), live);
+        assert!(memory_manager::is_live_object(live));
+        assert!(!memory_manager::is_live_object(dead));
+    }
+}
@@ -0,0 +1,75 @@
+// GITHUB-CI: MMTK_PLAN=GenCopy
+// GITHUB-CI: MMTK_PLAN=GenImmix
+// GITHUB-CI: MMTK_PLAN=GenMarkSweep
+
+use crate::api::*;
+use crate::object_model::{get_ref, init_object, num_refs, set_ref};
+use crate::tests::fixtures::{alloc_object, init_with_gc, write_ref, Roots};
+use crate::DummyVM;
+use crate::BUILDER;
+use crate::SINGLETON;
+use mmtk::memory_manager;
+use mmtk::plan::AllocationSite;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::ObjectReference;
+use mmtk::AllocationSemantics;
+use mmtk::Mutator;
+
+const MB: usize = 1024 * 1024;
+const SIZE: usize = 64;
+/// More than the samples needed to pretenure a site.
+const OBJECTS: usize = 100;
+
+fn alloc_with_site(mutator: *mut Mutator<DummyVM>, num_refs: usize, site: AllocationSite) -> ObjectReference {
+    let mutator = unsafe { &mut *mutator };
+    let addr = memory_manager::alloc_with_site(mutator, SIZE, 8, 0, AllocationSemantics::Default, site);
+    assert!(!addr.is_zero());
+    let object = init_object(addr, SIZE, num_refs);
+    memory_manager::post_alloc(mutator, object, SIZE, AllocationSemantics::Default);
+    object
+}
+
+/// A site whose objects survive a nursery GC is pretenured. Its objects are allocated in the mature
+/// space, and a nursery GC finds the young objects that they point to, even if their fields are
+/// initialized without the write barrier.
+#[test]
+pub fn pretenure_with_site() {
+    {
+        // Sample every object from the site.
+        let mut builder = BUILDER.lock().unwrap();
+        assert!(builder.options.pretenure_sample_bytes.set(SIZE));
+    }
+    let mutator = init_with_gc(32 * MB);
+    let tls = VMMutatorThread(VMThread::UNINITIALIZED);
+    let site = AllocationSite(7);
+    let roots = Roots::new(2);
+
+    // All the objects from the site are reachable.
+    let mut head = ObjectReference::NULL;
+    for _ in 0..OBJECTS {
+        let object = alloc_with_site(mutator, 1, site);
+        write_ref(mutator, object, 0, head);
+        head = object;
+    }
+    roots.set(0, head);
+
+    // User triggered GCs are nursery GCs.
+    mmtk_handle_user_collection_request(tls);
+    assert!(!SINGLETON.get_plan().last_collection_was_exhaustive());
+    assert_eq!(memory_manager::get_allocation_site_survival_rate::<DummyVM>(&SINGLETON, site), Some(1.0));
+    assert!(memory_manager::is_allocation_site_pretenured::<DummyVM>(&SINGLETON, site));
+
+    // The field of the pretenured object is initialized without the write barrier.
+    let pretenured = alloc_with_site(mutator, 1, site);
+    let young = alloc_object(mutator, SIZE, 0, AllocationSemantics::Default);
+    set_ref(pretenured, 0, young);
+    roots.set(1, pretenured);
+
+    // The pretenured object is not moved, and the young object is kept alive through it.
+    mmtk_handle_user_collection_request(tls);
+    assert!(!SINGLETON.get_plan().last_collection_was_exhaustive());
+    assert_eq!(roots.get(1), pretenured);
+    let young = get_ref(pretenured, 0);
+    assert!(memory_manager::is_live_object(young));
+    assert_eq!(num_refs(young), 0);
+}
@@ -0,0 +1,65 @@
+// GITHUB-CI: MMTK_PLAN=all
+
+use crate::api;
+use crate::object_model::OBJECT_REF_OFFSET;
+use crate::tests::fixtures::{MutatorFixture, SerialFixture};
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::plan::AllocationSemantics;
+use mmtk::util::alloc::AllocatorSelector;
+use mmtk::util::{Address, ObjectReference};
+
+lazy_static! {
+    static ref MUTATOR: SerialFixture<MutatorFixture> = SerialFixture::new();
+}
+
+fn start_of(object: ObjectReference) -> Address {
+    object.to_raw_address().sub(OBJECT_REF_OFFSET)
+}
+
+#[test]
+pub fn resize_large_object() {
+    MUTATOR.with_fixture(|fixture| {
+        let mutator = unsafe { &mut *fixture.mutator };
+        let size = 16 * 1024;
+        let addr = memory_manager::alloc::<DummyVM>(mutator, size, 8, 0, AllocationSemantics::Los);
+        assert!(!addr.is_zero());
+        let object = ObjectReference::from_raw_address(addr.add(OBJECT_REF_OFFSET));
+        memory_manager::post_alloc::<DummyVM>(mutator, object, size, AllocationSemantics::Los);
+        unsafe { std::ptr::write_bytes(addr.to_mut_ptr::<u8>(), 0xab, size) };
+
+        let grown_size = 40 * 1024;
+        let grown = match memory_manager::resize_large_object(mutator, object, grown_size) {
+            Some(grown) => grown,
+            None => {
+                // Only a plan without a large object space cannot resize the object.
+                assert!(!matches!(
+                    memory_manager::get_allocator_mapping(fixture.mmtk, AllocationSemantics::Los),
+                    AllocatorSelector::LargeObject(_)
+                ));
+                return;
+            }
+        };
+        assert!(api::mmtk_is_in_mmtk_spaces(grown));
+        // The bytes of the object are kept, whether it is grown in place or moved.
+        let start = start_of(grown);
+        for i in 0..size {
+            assert_eq!(unsafe { (start + i).load::<u8>() }, 0xab);
+        }
+        // The grown part is usable.
+        unsafe {
+            std::ptr::write_bytes((start + size).to_mut_ptr::<u8>(), 0xcd, grown_size - size)
+        };
+
+        // Shrinking is always done in place.
+        let shrunk = memory_manager::resize_large_object(mutator, grown, 8 * 1024);
+        assert_eq!(shrunk, Some(grown));
+        assert!(api::mmtk_is_in_mmtk_spaces(grown));
+
+        // The object keeps the page where it starts, even if it is shrunk to nothing.
+        let emptied = memory_manager::resize_large_object(mutator, grown, 0);
+        assert_eq!(emptied, Some(grown));
+        assert!(api::mmtk_is_in_mmtk_spaces(grown));
+        unsafe { start.store::<u8>(0xef) };
+    })
+}
@@ -0,0 +1,33 @@
+// GITHUB-CI: MMTK_PLAN=all
+// GITHUB-CI: FEATURES=ro_space
+
+use crate::tests::fixtures::{MutatorFixture, SerialFixture};
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::plan::AllocationSemantics;
+
+lazy_static! {
+    static ref MUTATOR: SerialFixture<MutatorFixture> = SerialFixture::new();
+}
+
+#[test]
+#[should_panic(expected = "ReadOnlySpaceSealed")]
+pub fn seal_readonly_space() {
+    MUTATOR.with_fixture(|fixture| {
+        let mutator = unsafe { &mut *fixture.mutator };
+        let size = 64;
+        let addr =
+            memory_manager::alloc::<DummyVM>(mutator, size, 8, 0, AllocationSemantics::ReadOnly);
+        assert!(!addr.is_zero());
+        unsafe { std::ptr::write_bytes(addr.to_mut_ptr::<u8>(), 0xab, size) };
+
+        memory_manager::seal_readonly_space(fixture.mmtk);
+        // The object can still be read.
+        for i in 0..size {
+            assert_eq!(unsafe { (addr + i).load::<u8>() }, 0xab);
+        }
+        // Allocating into the sealed space is reported with `Collection::out_of_memory()`, which
+        // panics in DummyVM.
+        memory_manager::alloc::<DummyVM>(mutator, size, 8, 0, AllocationSemantics::ReadOnly);
+    })
+}
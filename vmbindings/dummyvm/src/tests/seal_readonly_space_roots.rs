@@ -0,0 +1,48 @@
+// GITHUB-CI: MMTK_PLAN=all
+// GITHUB-CI: FEATURES=ro_space
+
+use crate::api::*;
+use crate::tests::fixtures::{alloc_object, init_with_gc, write_ref};
+use crate::DummyVM;
+use crate::BUILDER;
+use crate::SINGLETON;
+use mmtk::memory_manager;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::options::PlanSelector;
+use mmtk::AllocationSemantics;
+
+const SIZE: usize = 64;
+const LARGE_SIZE: usize = 16 * 1024;
+
+/// The sealed objects are roots. An object that is only reachable from a sealed object is kept
+/// alive, and the fields of the sealed object are not changed by GCs.
+#[test]
+pub fn seal_readonly_space_roots() {
+    {
+        // User triggered GCs are full heap GCs, so the large object space is collected.
+        let mut builder = BUILDER.lock().unwrap();
+        // NoGC cannot collect.
+        if matches!(*builder.options.plan, PlanSelector::NoGC) {
+            return;
+        }
+        assert!(builder.options.full_heap_system_gc.set(true));
+    }
+    const MB: usize = 1024 * 1024;
+    let mutator = init_with_gc(32 * MB);
+    let tls = VMMutatorThread(VMThread::UNINITIALIZED);
+
+    // Large objects are never moved, so a sealed object can refer to them.
+    let referent = alloc_object(mutator, LARGE_SIZE, 0, AllocationSemantics::Los);
+    let dead = alloc_object(mutator, LARGE_SIZE, 0, AllocationSemantics::Los);
+    let sealed = alloc_object(mutator, SIZE, 1, AllocationSemantics::ReadOnly);
+    write_ref(mutator, sealed, 0, referent);
+    memory_manager::seal_readonly_space::<DummyVM>(&SINGLETON);
+
+    for _ in 0..2 {
+        mmtk_handle_user_collection_request(tls);
+        assert!(memory_manager::is_live_object(sealed));
+        assert!(memory_manager::is_live_object(referent));
+        assert!(!memory_manager::is_live_object(dead));
+        assert_eq!(crate::object_model::get_ref(sealed, 0), referent);
+    }
+}
@@ -0,0 +1,88 @@
+// GITHUB-CI: MMTK_PLAN=StickyMarkSweep
+
+use crate::api::*;
+use crate::collection::gc_count;
+use crate::object_model::VMObjectModel;
+use crate::tests::fixtures::{alloc_garbage, alloc_object, init_with_gc, write_ref, Roots};
+use crate::DummyVM;
+use crate::BUILDER;
+use crate::SINGLETON;
+use mmtk::memory_manager;
+use mmtk::util::opaque_pointer::*;
+use mmtk::util::options::{NurseryKind, NurserySize};
+use mmtk::util::ObjectReference;
+use mmtk::vm::ObjectModel;
+use mmtk::AllocationSemantics;
+use mmtk::Mutator;
+use std::sync::atomic::Ordering;
+
+const MB: usize = 1024 * 1024;
+const SIZE: usize = 32;
+
+fn is_unlogged(object: ObjectReference) -> bool {
+    VMObjectModel::GLOBAL_LOG_BIT_SPEC.is_unlogged::<DummyVM>(object, Ordering::SeqCst)
+}
+
+/// Allocate garbage until the nursery is full and a GC happens.
+fn alloc_until_nursery_gc(mutator: *mut Mutator<DummyVM>) {
+    let count = gc_count();
+    while gc_count() == count {
+        alloc_garbage(mutator, 100, SIZE);
+    }
+    assert!(!SINGLETON.get_plan().last_collection_was_exhaustive());
+}
+
+/// A nursery GC keeps the mature objects whether they are reachable or not, and reclaims the young
+/// objects that are not reachable. A major GC reclaims the mature objects, and resets their log bits.
+#[test]
+pub fn sticky_marksweep() {
+    {
+        // User triggered GCs are major GCs. Nursery GCs are triggered by allocation.
+        let mut builder = BUILDER.lock().unwrap();
+        assert!(builder.options.full_heap_system_gc.set(true));
+        assert!(builder.options.nursery.set(NurserySize::new(NurseryKind::Fixed, 2 * MB)));
+    }
+    let mutator = init_with_gc(32 * MB);
+    let tls = VMMutatorThread(VMThread::UNINITIALIZED);
+    let roots = Roots::new(2);
+    let mature = alloc_object(mutator, SIZE, 1, AllocationSemantics::Default);
+    let dead_mature = alloc_object(mutator, SIZE, 0, AllocationSemantics::Default);
+    roots.set(0, mature);
+    roots.set(1, dead_mature);
+
+    // The objects survive the nursery GC, and become mature.
+    alloc_until_nursery_gc(mutator);
+    assert!(memory_manager::is_live_object(mature));
+    assert!(memory_manager::is_live_object(dead_mature));
+    assert!(is_unlogged(mature));
+    assert!(is_unlogged(dead_mature));
+
+    // A young object that is only reachable from a mature object, and a young object that is not
+    // reachable.
+    roots.set(1, ObjectReference::NULL);
+    let young = alloc_object(mutator, SIZE, 0, AllocationSemantics::Default);
+    write_ref(mutator, mature, 0, young);
+    assert!(!is_unlogged(mature));
+    let dead_young = alloc_object(mutator, SIZE, 0, AllocationSemantics::Default);
+
+    // The nursery GC does not trace the mature objects, so the unreachable mature object is kept.
+    alloc_until_nursery_gc(mutator);
+    assert!(memory_manager::is_live_object(mature));
+    assert!(memory_manager::is_live_object(dead_mature));
+    assert!(memory_manager::is_live_object(young));
+    assert!(!memory_manager::is_live_object(dead_young));
+    assert!(is_unlogged(mature));
+    assert!(is_unlogged(young));
+
+    // The major GC reclaims the unreachable mature object, and its log bit is reset, so a young
+    // object allocated in its cell is not taken as a mature object.
+    mmtk_handle_user_collection_request(tls);
+    assert!(SINGLETON.get_plan().last_collection_was_exhaustive());
+    assert!(memory_manager::is_live_object(mature));
+    assert!(memory_manager::is_live_object(young));
+    assert!(!memory_manager::is_live_object(dead_mature));
+    assert!(!is_unlogged(dead_mature));
+    assert!(is_unlogged(mature));
+    assert!(is_unlogged(young));
+    assert_eq!(crate::object_model::get_ref(mature, 0), young);
+}
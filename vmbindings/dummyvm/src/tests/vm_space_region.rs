@@ -0,0 +1,76 @@
+// GITHUB-CI: MMTK_PLAN=all
+// GITHUB-CI: FEATURES=vm_space
+
+use crate::tests::fixtures::{Fixture, MMTKSingleton};
+use crate::DummyVM;
+use mmtk::memory_manager;
+use mmtk::util::*;
+
+lazy_static! {
+    static ref MMTK_SINGLETON: Fixture<MMTKSingleton> = Fixture::new();
+}
+
+const REGION_SIZE: usize = 64 * 1024;
+
+fn in_vm_space(addr: Address) -> bool {
+    memory_manager::is_in_mmtk_spaces::<DummyVM>(ObjectReference::from_raw_address(addr))
+}
+
+/// With the default options, the VM space has a fixed address range in the heap range, which the VM
+/// maps and populates itself.
+#[test]
+pub fn fixed_region() {
+    MMTK_SINGLETON.with_fixture(|fixture| {
+        // The fixed region is added when MMTk is initialized, before any region is registered.
+        let (start, end) = fixture.mmtk.get_plan().base().vm_space.regions()[0];
+        assert!(in_vm_space(start));
+        assert!(in_vm_space(end - 8));
+    });
+}
+
+#[test]
+pub fn register_region() {
+    MMTK_SINGLETON.with_fixture(|fixture| {
+        // The region is mapped and populated by the VM. Map two chunks, so the region can start at a
+        // chunk, and nothing else is in the chunk after it.
+        let chunk_bytes = conversions::chunk_index_to_address(1).as_usize();
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                2 * chunk_bytes,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_ANON | libc::MAP_PRIVATE,
+                -1,
+                0,
+            )
+        };
+        assert_ne!(ptr, libc::MAP_FAILED);
+        let start = conversions::chunk_align_up(Address::from_mut_ptr(ptr));
+        memory_manager::register_vm_space_region(fixture.mmtk, start, REGION_SIZE);
+
+        assert!(in_vm_space(start));
+        assert!(in_vm_space(start + REGION_SIZE - 8));
+        // The VM space is known in whole chunks.
+        assert!(in_vm_space(start + REGION_SIZE));
+        assert!(!in_vm_space(start + chunk_bytes));
+    });
+}
+
+#[test]
+pub fn map_image() {
+    MMTK_SINGLETON.with_fixture(|fixture| {
+        let path = std::env::temp_dir().join(format!("mmtk-vm-space-{}.img", std::process::id()));
+        std::fs::write(&path, vec![0xabu8; REGION_SIZE]).unwrap();
+        let (start, size) = memory_manager::map_vm_space_image(fixture.mmtk, &path, None).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(size, REGION_SIZE);
+        assert_eq!(unsafe { start.load::<u8>() }, 0xab);
+        // The image is mapped privately, so it can be written.
+        unsafe { start.store::<u8>(0xcd) };
+        assert_eq!(unsafe { start.load::<u8>() }, 0xcd);
+
+        assert!(in_vm_space(start));
+        assert!(in_vm_space(start + size - 8));
+    });
+}
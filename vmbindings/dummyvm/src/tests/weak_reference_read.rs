@@ -0,0 +1,54 @@
+// GITHUB-CI: MMTK_PLAN=ConcurrentImmix
+
+use crate::collection::{gc_count, yieldpoint};
+use crate::tests::fixtures::{alloc_garbage, alloc_object, init_with_gc};
+use crate::DummyVM;
+use crate::SINGLETON;
+use mmtk::memory_manager;
+use mmtk::util::ObjectReference;
+use mmtk::AllocationSemantics;
+use mmtk::Mutator;
+
+const SIZE: usize = 32;
+
+fn read_referent(mutator: *mut Mutator<DummyVM>, referent: ObjectReference) {
+    let read = memory_manager::weak_reference_read::<DummyVM>(unsafe { &mut *mutator }, referent);
+    assert_eq!(read, referent);
+}
+
+/// With the SATB barrier, reading a weak referent during concurrent marking keeps the referent
+/// alive, as the mutator may store it in an object that has been scanned. Reading it when the
+/// marking is not in progress does nothing.
+#[test]
+pub fn weak_reference_read() {
+    const MB: usize = 1024 * 1024;
+    let mutator = init_with_gc(16 * MB);
+    assert!(SINGLETON
+        .get_plan()
+        .constraints()
+        .barrier
+        .needs_weak_reference_read_barrier());
+
+    // Neither referent is strongly reachable.
+    let read_before_marking = alloc_object(mutator, SIZE, 0, AllocationSemantics::Default);
+    let read_during_marking = alloc_object(mutator, SIZE, 0, AllocationSemantics::Default);
+    read_referent(mutator, read_before_marking);
+
+    // The initial mark pause starts the marking.
+    let count = gc_count();
+    while gc_count() == count {
+        alloc_garbage(mutator, 1, SIZE);
+    }
+    assert!(!SINGLETON.get_plan().last_collection_was_exhaustive());
+    read_referent(mutator, read_during_marking);
+
+    // The final mark pause.
+    let count = gc_count();
+    while gc_count() == count {
+        alloc_garbage(mutator, 1, SIZE);
+        yieldpoint();
+    }
+    assert!(SINGLETON.get_plan().last_collection_was_exhaustive());
+    assert!(memory_manager::is_live_object(read_during_marking));
+    assert!(!memory_manager::is_live_object(read_before_marking));
+}